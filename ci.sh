@@ -4,4 +4,6 @@ set -euo pipefail
 cargo fmt --all --check
 cargo clippy --all-targets --all-features -- -D warnings
 cargo test --all
+cargo clippy --all-targets --no-default-features -- -D warnings
+cargo test --all --no-default-features
 cargo doc --no-deps
@@ -108,6 +108,7 @@ fn handle(state: &Arc<Mutex<State>>, msg: &mk2::Message) -> Result<(), Box<dyn s
     match *msg {
         mk2::Message::Press {
             button: button @ mk2::Button::GridButton { x, y },
+            ..
         } => {
             let press_time = std::time::Instant::now();
             state.lock().currently_pressed.insert((x, y), press_time);
@@ -134,6 +135,7 @@ fn handle(state: &Arc<Mutex<State>>, msg: &mk2::Message) -> Result<(), Box<dyn s
 
         mk2::Message::Release {
             button: mk2::Button::GridButton { x, y },
+            ..
         } => {
             let mut state = state.lock();
             let state = &mut *state;
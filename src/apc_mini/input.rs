@@ -0,0 +1,75 @@
+use super::{Button, Fader};
+
+/// An APC mini input message. The `timestamp` fields hold the MIDI timestamp of the message in
+/// microseconds, see [`MidiClock`](crate::MidiClock).
+///
+/// ```
+/// use launchy::apc_mini::{Button, Fader, Input, Message};
+/// use launchy::InputDevice as _;
+///
+/// assert_eq!(
+///     Input::decode_message(0, &[0x90, 56, 127]),
+///     Message::Press { button: Button::grid(0, 0), timestamp: 0 },
+/// );
+/// assert_eq!(
+///     Input::decode_message(0, &[0x80, 82, 127]),
+///     Message::Release { button: Button::scene(0), timestamp: 0 },
+/// );
+/// assert_eq!(
+///     Input::decode_message(0, &[0xB0, 56, 100]),
+///     Message::FaderChange { fader: Fader::MASTER, value: 100, timestamp: 0 },
+/// );
+/// ```
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub enum Message {
+    /// A button was pressed
+    Press { button: Button, timestamp: u64 },
+    /// A button was released
+    Release { button: Button, timestamp: u64 },
+    /// A fader was moved
+    FaderChange {
+        fader: Fader,
+        value: u8,
+        timestamp: u64,
+    },
+    /// A message that doesn't match the APC mini protocol, with its raw bytes
+    Verbatim { bytes: Vec<u8> },
+}
+
+/// The APC mini input connection creator.
+pub struct Input;
+
+impl crate::InputDevice for Input {
+    const MIDI_CONNECTION_NAME: &'static str = "Launchy APC mini input";
+    const MIDI_DEVICE_KEYWORD: &'static str = "APC MINI";
+    type Message = Message;
+
+    fn verbatim(bytes: Vec<u8>) -> Message {
+        Message::Verbatim { bytes }
+    }
+
+    fn try_decode_message(timestamp: u64, data: &[u8]) -> Result<Message, crate::DecodeError> {
+        let decode_button = |note: u8| {
+            Button::from_note(note)
+                .ok_or_else(|| crate::DecodeError::new(data, format!("unexpected button {}", note)))
+        };
+
+        let msg = match *data {
+            [0x90, note, 1..=127] => Message::Press {
+                button: decode_button(note)?,
+                timestamp,
+            },
+            [0x80 | 0x90, note, _] => Message::Release {
+                button: decode_button(note)?,
+                timestamp,
+            },
+            [0xB0, number @ 48..=56, value] => Message::FaderChange {
+                fader: Fader::new(number - 48),
+                value,
+                timestamp,
+            },
+            _ => return Err(crate::DecodeError::new(data, "unexpected message")),
+        };
+        Ok(msg)
+    }
+}
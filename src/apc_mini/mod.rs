@@ -0,0 +1,199 @@
+/*!
+# Akai APC mini low-level API
+
+The Akai APC mini isn't a Novation device, but it's close enough to a Launchpad: an 8x8 grid of
+buttons, with 8 round scene launch buttons in a column to the right and 8 track buttons in a row
+below, plus the Shift button in the bottom right corner. Below those are 9 faders.
+
+Every button is a note, and its LED is set with a note-on message whose velocity selects one of
+the colors. The grid buttons can be green, red or yellow; the scene launch buttons are green only
+and the track buttons are red only. All LEDs can blink.
+
+The canvas is 9x9 and contains every button except the Shift button, which has no LED.
+*/
+
+mod input;
+pub use input::*;
+
+mod output;
+pub use output::*;
+
+/// A button of the APC mini
+///
+/// The grid is indexed from the top left, the scene launch buttons from top to bottom and the
+/// track buttons from left to right.
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum Button {
+    GridButton {
+        x: u8,
+        y: u8,
+    },
+    /// One of the buttons in the column to the right of the grid
+    SceneButton {
+        index: u8,
+    },
+    /// One of the buttons in the row below the grid
+    TrackButton {
+        index: u8,
+    },
+    Shift,
+}
+
+impl Button {
+    /// Creates a new GridButton coordinate
+    pub fn grid(x: u8, y: u8) -> Self {
+        Self::GridButton { x, y }
+    }
+
+    /// Creates the scene launch button (the right column) with the given index
+    pub fn scene(index: u8) -> Self {
+        Self::SceneButton { index }
+    }
+
+    /// Creates the track button (the row below the grid) with the given index
+    pub fn track(index: u8) -> Self {
+        Self::TrackButton { index }
+    }
+
+    /// Creates a new button out of absolute coordinates, like the ones returned by `abs_x()` and
+    /// `abs_y()`.
+    pub fn from_abs(x: u8, y: u8) -> Self {
+        match (x, y) {
+            (0..=7, 0..=7) => Self::grid(x, y),
+            (8, 0..=7) => Self::scene(y),
+            (0..=7, 8) => Self::track(x),
+            (8, 8) => Self::Shift,
+            _ => panic!("Unexpected coordinates: ({}, {})", x, y),
+        }
+    }
+
+    /// Returns x coordinate assuming coordinate origin in the top left grid button
+    pub fn abs_x(&self) -> u8 {
+        match *self {
+            Self::GridButton { x, .. } => x,
+            Self::TrackButton { index } => index,
+            Self::SceneButton { .. } | Self::Shift => 8,
+        }
+    }
+
+    /// Returns y coordinate assuming coordinate origin in the top left grid button
+    pub fn abs_y(&self) -> u8 {
+        match *self {
+            Self::GridButton { y, .. } => y,
+            Self::SceneButton { index } => index,
+            Self::TrackButton { .. } | Self::Shift => 8,
+        }
+    }
+
+    /// The note number of this button. The grid starts at 0 in the bottom left and goes up by 1
+    /// to the right and by 8 upwards.
+    pub(crate) fn note(self) -> u8 {
+        match self {
+            Self::GridButton { x, y } => {
+                assert!(x <= 7 && y <= 7);
+                (7 - y) * 8 + x
+            }
+            Self::TrackButton { index } => {
+                assert!(index <= 7);
+                64 + index
+            }
+            Self::SceneButton { index } => {
+                assert!(index <= 7);
+                82 + index
+            }
+            Self::Shift => 98,
+        }
+    }
+
+    pub(crate) fn from_note(note: u8) -> Option<Self> {
+        Some(match note {
+            0..=63 => Self::grid(note % 8, 7 - note / 8),
+            64..=71 => Self::track(note - 64),
+            82..=89 => Self::scene(note - 82),
+            98 => Self::Shift,
+            _ => return None,
+        })
+    }
+}
+
+/// One of the 9 faders of the APC mini. The rightmost fader is the master fader.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Fader(pub(crate) u8);
+
+impl Fader {
+    pub const MASTER: Self = Self(8);
+
+    /// Create a new fader from the given index, counting from the left. `index` must be below 9
+    pub fn new(index: u8) -> Self {
+        assert!(index < 9);
+        Self(index)
+    }
+
+    pub fn index(self) -> u8 {
+        self.0
+    }
+}
+
+#[doc(hidden)]
+pub struct Spec;
+
+impl crate::DeviceSpec for Spec {
+    const BOUNDING_BOX_WIDTH: u32 = 9;
+    const BOUNDING_BOX_HEIGHT: u32 = 9;
+    const COLOR_PRECISION: u16 = 2;
+
+    type Input = Input;
+    type Output<S: crate::MidiSink> = Output<S>;
+
+    fn is_valid(x: u32, y: u32) -> bool {
+        // The Shift button in the bottom right corner has no LED
+        x <= 8 && y <= 8 && !(x == 8 && y == 8)
+    }
+
+    fn flush<T: crate::MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
+        changes: &[(u32, u32, (u8, u8, u8))],
+    ) -> Result<(), crate::MidiError> {
+        for &(x, y, (r, g, _b)) in changes {
+            let button = Button::from_abs(x as u8, y as u8);
+            // The scene launch and track buttons only have a single color each
+            let color = match (button, r, g) {
+                (_, 0, 0) => Color::Off,
+                (Button::SceneButton { .. }, _, _) => Color::Green,
+                (Button::TrackButton { .. }, _, _) => Color::Red,
+                (_, 0, _) => Color::Green,
+                (_, _, 0) => Color::Red,
+                _ => Color::Yellow,
+            };
+            canvas.output.light(button, color)?;
+        }
+        Ok(())
+    }
+
+    fn convert_message(msg: Message) -> Option<crate::CanvasMessage> {
+        match msg {
+            // The Shift button isn't part of the canvas
+            Message::Press {
+                button: Button::Shift,
+                ..
+            }
+            | Message::Release {
+                button: Button::Shift,
+                ..
+            } => None,
+            Message::Press { button, timestamp } => Some(crate::CanvasMessage::Press {
+                x: button.abs_x() as u32,
+                y: button.abs_y() as u32,
+                timestamp,
+            }),
+            Message::Release { button, timestamp } => Some(crate::CanvasMessage::Release {
+                x: button.abs_x() as u32,
+                y: button.abs_y() as u32,
+                timestamp,
+            }),
+            Message::FaderChange { .. } | Message::Verbatim { .. } => None,
+        }
+    }
+}
+
+pub type Canvas<'a> = crate::DeviceCanvas<Spec>;
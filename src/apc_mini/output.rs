@@ -0,0 +1,132 @@
+use super::Button;
+use crate::midi_io::DefaultSink;
+use crate::{MidiSink, OutputDevice};
+
+/// One of the colors of the APC mini LEDs. The scene launch buttons can only be green and the
+/// track buttons can only be red; any other color than [`Color::Off`] lights them in their color.
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum Color {
+    Off,
+    Green,
+    Red,
+    Yellow,
+}
+
+/// Whether an LED is lit steadily or blinks
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum LightMode {
+    Plain,
+    Blink,
+}
+
+/// The note-on velocity that selects the given color and light mode
+fn velocity(button: Button, color: Color, light_mode: LightMode) -> u8 {
+    let base = match (button, color) {
+        (_, Color::Off) => return 0,
+        // The single color LEDs are just on or off
+        (Button::SceneButton { .. } | Button::TrackButton { .. }, _) => 1,
+        (_, Color::Green) => 1,
+        (_, Color::Red) => 3,
+        (_, Color::Yellow) => 5,
+    };
+    match light_mode {
+        LightMode::Plain => base,
+        LightMode::Blink => base + 1,
+    }
+}
+
+/// The APC mini output connection handler.
+///
+/// ```
+/// use launchy::apc_mini::{Button, Color, Output};
+/// use launchy::OutputDevice as _;
+///
+/// let (sink, sent) = launchy::ChannelSink::new();
+/// let mut output = Output::from_connection(sink)?;
+///
+/// output.light(Button::grid(0, 7), Color::Yellow)?;
+/// output.blink(Button::track(2), Color::Red)?;
+/// assert_eq!(sent.try_recv()?, [0x90, 0, 5]);
+/// assert_eq!(sent.try_recv()?, [0x90, 66, 2]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct Output<S: MidiSink = DefaultSink> {
+    connection: S,
+}
+
+impl<S: MidiSink> crate::OutputDevice for Output<S> {
+    const MIDI_CONNECTION_NAME: &'static str = "Launchy APC mini output";
+    const MIDI_DEVICE_KEYWORD: &'static str = "APC MINI";
+
+    type Sink = S;
+
+    fn from_connection(connection: S) -> Result<Self, crate::MidiError> {
+        Ok(Self { connection })
+    }
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), crate::MidiError> {
+        self.connection.send(bytes)
+    }
+
+    /// LED messages only depend on the latest color
+    fn coalescing_key(bytes: &[u8]) -> Option<u32> {
+        match *bytes {
+            [0x90, note, _] => Some(u32::from_be_bytes([0, 0, 0x90, note])),
+            _ => None,
+        }
+    }
+}
+
+impl<S: MidiSink> Output<S> {
+    /// Set a `button` to a certain `color` with a certain `light_mode`. The Shift button has no
+    /// LED.
+    pub fn set_button(
+        &mut self,
+        button: Button,
+        color: Color,
+        light_mode: LightMode,
+    ) -> Result<(), crate::MidiError> {
+        assert_ne!(button, Button::Shift, "the Shift button has no LED");
+        self.send(&[0x90, button.note(), velocity(button, color, light_mode)])
+    }
+
+    /// Light a button steadily. Identical to `set_button(<button>, <color>, LightMode::Plain)`.
+    pub fn light(&mut self, button: Button, color: Color) -> Result<(), crate::MidiError> {
+        self.set_button(button, color, LightMode::Plain)
+    }
+
+    /// Let a button blink. Identical to `set_button(<button>, <color>, LightMode::Blink)`.
+    pub fn blink(&mut self, button: Button, color: Color) -> Result<(), crate::MidiError> {
+        self.set_button(button, color, LightMode::Blink)
+    }
+
+    /// Light multiple buttons with varying colors
+    pub fn light_multiple(
+        &mut self,
+        buttons: impl IntoIterator<Item = impl std::borrow::Borrow<(Button, Color)>>,
+    ) -> Result<(), crate::MidiError> {
+        for pair in buttons {
+            let (button, color) = *pair.borrow();
+            self.light(button, color)?;
+        }
+        Ok(())
+    }
+
+    /// Light all buttons with the same color. The scene launch and track buttons are lit green
+    /// and red respectively.
+    pub fn light_all(&mut self, color: Color) -> Result<(), crate::MidiError> {
+        for y in 0..9 {
+            for x in 0..9 {
+                if (x, y) != (8, 8) {
+                    self.light(Button::from_abs(x, y), color)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Turn off all LEDs
+    pub fn clear(&mut self) -> Result<(), crate::MidiError> {
+        self.light_all(Color::Off)
+    }
+}
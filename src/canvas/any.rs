@@ -0,0 +1,90 @@
+use super::*;
+
+/// A canvas for any of the models that [`discover`](crate::discover) can find, as returned by
+/// [`DiscoveredDevice::canvas`](crate::DiscoveredDevice::canvas)
+///
+/// This lets you handle all discovered devices uniformly, for example by adding them all into a
+/// [`CanvasLayout`]. If you need access to the device-specific functionality, match on the variant.
+pub enum AnyCanvas {
+    S(DeviceCanvas<crate::s::Spec>),
+    Mini(DeviceCanvas<crate::mini::Spec>),
+    Mk2(DeviceCanvas<crate::mk2::Spec>),
+    MiniMk3(DeviceCanvas<crate::mini_mk3::Spec>),
+    X(DeviceCanvas<crate::x::Spec>),
+    Pro(DeviceCanvas<crate::pro::Spec>),
+    ProMk3(DeviceCanvas<crate::pro_mk3::Spec>),
+}
+
+impl AnyCanvas {
+    /// The model of the device behind this canvas
+    pub fn model(&self) -> crate::Model {
+        match self {
+            Self::S(_) => crate::Model::S,
+            Self::Mini(_) => crate::Model::Mini,
+            Self::Mk2(_) => crate::Model::Mk2,
+            Self::MiniMk3(_) => crate::Model::MiniMk3,
+            Self::X(_) => crate::Model::X,
+            Self::Pro(_) => crate::Model::Pro,
+            Self::ProMk3(_) => crate::Model::ProMk3,
+        }
+    }
+}
+
+macro_rules! delegate {
+    ($self:ident, $canvas:ident => $expr:expr) => {
+        match $self {
+            AnyCanvas::S($canvas) => $expr,
+            AnyCanvas::Mini($canvas) => $expr,
+            AnyCanvas::Mk2($canvas) => $expr,
+            AnyCanvas::MiniMk3($canvas) => $expr,
+            AnyCanvas::X($canvas) => $expr,
+            AnyCanvas::Pro($canvas) => $expr,
+            AnyCanvas::ProMk3($canvas) => $expr,
+        }
+    };
+}
+
+impl_traits_for_canvas!(AnyCanvas[]);
+
+impl crate::Canvas for AnyCanvas {
+    fn bounding_box(&self) -> (u32, u32) {
+        delegate!(self, canvas => canvas.bounding_box())
+    }
+
+    fn low_level_get(&self, x: u32, y: u32) -> Option<&Color> {
+        delegate!(self, canvas => canvas.low_level_get(x, y))
+    }
+
+    fn low_level_get_pending(&self, x: u32, y: u32) -> Option<&Color> {
+        delegate!(self, canvas => canvas.low_level_get_pending(x, y))
+    }
+
+    fn low_level_get_pending_mut(&mut self, x: u32, y: u32) -> Option<&mut Color> {
+        delegate!(self, canvas => canvas.low_level_get_pending_mut(x, y))
+    }
+
+    fn low_level_get_pending_effect(&self, x: u32, y: u32) -> Option<PadEffect> {
+        delegate!(self, canvas => canvas.low_level_get_pending_effect(x, y))
+    }
+
+    fn low_level_set_pending_effect(&mut self, x: u32, y: u32, effect: PadEffect) -> Option<()> {
+        delegate!(self, canvas => canvas.low_level_set_pending_effect(x, y, effect))
+    }
+
+    fn effect_refresh_interval(&self) -> Option<std::time::Duration> {
+        delegate!(self, canvas => canvas.effect_refresh_interval())
+    }
+
+    fn flush(&mut self) -> Result<(), crate::MidiError> {
+        delegate!(self, canvas => canvas.flush())
+    }
+
+    #[cfg(feature = "async")]
+    fn flush_async(&mut self) -> FlushFuture {
+        delegate!(self, canvas => canvas.flush_async())
+    }
+
+    fn lowest_visible_brightness(&self) -> f32 {
+        delegate!(self, canvas => canvas.lowest_visible_brightness())
+    }
+}
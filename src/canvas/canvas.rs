@@ -1,5 +1,11 @@
 use super::*;
 
+/// The future returned by [`Canvas::flush_async`]. Requires the `async` feature.
+#[cfg(feature = "async")]
+pub type FlushFuture = std::pin::Pin<
+    Box<dyn std::future::Future<Output = Result<(), crate::MidiError>> + Send + 'static>,
+>;
+
 /// A trait that abstracts over the specifics of a Launchpad and allows generic access and
 /// manipulation of a Launchpad's LEDs.
 ///
@@ -14,6 +20,7 @@ use super::*;
 ///
 /// Example:
 /// ```no_run
+/// # #[cfg(feature = "midir")] {
 /// # use launchy::{Canvas, Color};
 /// fn light_white(canvas: &mut impl Canvas) {
 ///     // Iterate through all buttons in the canvas. See the documentation on [`CanvasIterator`] for
@@ -29,6 +36,7 @@ use super::*;
 /// // Light a connected Launchpad S and Launchpad Mk2 completely white
 /// light_white(&mut launchy::s::Canvas::guess(|_| {})?);
 /// light_white(&mut launchy::mk2::Canvas::guess(|_| {})?);
+/// # }
 /// # Ok::<(), launchy::MidiError>(())
 /// ```
 pub trait Canvas:
@@ -53,7 +61,10 @@ pub trait Canvas:
     fn low_level_get(&self, x: u32, y: u32) -> Option<&Color>;
     /// Returns a reference to the in-buffer/unflushed color at the given position
     fn low_level_get_pending(&self, x: u32, y: u32) -> Option<&Color>;
-    /// Returns a mutable reference to the in-buffer/unflushed color at the given position
+    /// Returns a mutable reference to the in-buffer/unflushed color at the given position.
+    ///
+    /// Implementations may treat every call as a write, and only look at the pads that were
+    /// accessed this way on the next flush.
     fn low_level_get_pending_mut(&mut self, x: u32, y: u32) -> Option<&mut Color>;
 
     /// Flush the accumulated changes to the underlying device
@@ -71,11 +82,100 @@ pub trait Canvas:
     /// canvas.flush()?;
     /// # Ok::<(), launchy::MidiError>(())
     /// ```
+    ///
+    /// Flushing blocks until the messages are handed to the MIDI driver, which doesn't wait for
+    /// the device and only takes a moment. So it's fine to flush from async code as well, for
+    /// example alongside awaiting input messages with the `async` feature. To wait for the device
+    /// without blocking, use a throttled canvas and `Canvas::flush_async`.
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "async")]
+    /// # async fn run() -> Result<(), launchy::MidiError> {
+    /// use futures::StreamExt as _;
+    /// use launchy::{Canvas as _, Color};
+    ///
+    /// let (mut canvas, mut input) = launchy::mk2::Canvas::guess_polling()?;
+    /// while let Some(msg) = input.next().await {
+    ///     canvas[msg.pad()] = if msg.is_press() { Color::WHITE } else { Color::BLACK };
+    ///     canvas.flush()?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
     fn flush(&mut self) -> Result<(), crate::MidiError>;
+    /// Like [`Self::flush`], but returns a future that resolves once the device received the
+    /// changes. Requires the `async` feature.
+    ///
+    /// The changes are handed over right away, like with [`Self::flush`]. Only throttled canvases
+    /// send them in the background, see
+    /// [`DeviceCanvas::guess_throttled`](crate::DeviceCanvas::guess_throttled). For all other
+    /// canvases, the future is ready immediately.
+    ///
+    /// ```
+    /// # #[cfg(feature = "async")]
+    /// # fn main() -> Result<(), launchy::MidiError> {
+    /// # use launchy::{Pad, Color, Canvas as _};
+    /// # let mut canvas = launchy::MockCanvas::new(9, 9);
+    /// canvas[Pad { x: 0, y: 0 }] = Color::RED;
+    /// futures::executor::block_on(canvas.flush_async())?;
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "async"))]
+    /// # fn main() {}
+    /// ```
+    #[cfg(feature = "async")]
+    fn flush_async(&mut self) -> FlushFuture {
+        Box::pin(std::future::ready(self.flush()))
+    }
     /// The lowest visible brightness on this canvas. Used to calibrate brightness across
     /// Launchpads; users of the library probably don't need to worry about this
     fn lowest_visible_brightness(&self) -> f32;
 
+    // These are the methods for pad effects. Canvases that don't support effects can keep the
+    // default implementations
+
+    /// Returns the in-buffer/unflushed effect at the given position, or None if out of bounds.
+    /// Canvases that don't support effects report [`PadEffect::Steady`] for every pad.
+    fn low_level_get_pending_effect(&self, x: u32, y: u32) -> Option<PadEffect> {
+        self.low_level_get_pending(x, y).map(|_| PadEffect::Steady)
+    }
+
+    /// Sets the in-buffer/unflushed effect at the given position. Returns None if out of bounds,
+    /// or if this canvas doesn't support effects.
+    fn low_level_set_pending_effect(&mut self, x: u32, y: u32, effect: PadEffect) -> Option<()> {
+        let _ = (x, y, effect);
+        None
+    }
+
+    /// If this canvas emulates effects in software, returns how soon it should be flushed again
+    /// for the effects to animate. Returns None if no effects need to be animated.
+    ///
+    /// [`Self::run_effects_for`] and [`EffectDriver`] do these flushes for you. To do them in your
+    /// own event loop instead, for example to pulse a pad on the Launchpad S, which can't pulse by
+    /// itself:
+    /// ```no_run
+    /// # #[cfg(feature = "midir")] {
+    /// # use launchy::{Canvas as _, Color, MsgPollingWrapper as _, Pad, PadEffect};
+    /// let (mut canvas, poller) = launchy::s::Canvas::guess_polling()?;
+    ///
+    /// canvas[Pad { x: 0, y: 1 }] = Color::GREEN;
+    /// canvas.set_effect(Pad { x: 0, y: 1 }, PadEffect::Pulse);
+    /// canvas.flush()?;
+    ///
+    /// loop {
+    ///     let timeout = canvas.effect_refresh_interval().unwrap_or(std::time::Duration::MAX);
+    ///     if let Some(msg) = poller.recv_timeout(timeout) {
+    ///         println!("{:?}", msg);
+    ///     }
+    ///     canvas.flush()?;
+    /// }
+    /// # }
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    fn effect_refresh_interval(&self) -> Option<std::time::Duration> {
+        None
+    }
+
     // These are defaut implementations that you get for free
 
     /// Returns the currently displayed color at the given position, or None if out of bounds
@@ -126,6 +226,70 @@ pub trait Canvas:
         Some(())
     }
 
+    /// Returns the buffered/unflushed effect at the given position, or None if out of bounds
+    fn get_effect(&self, pad: Pad) -> Option<PadEffect> {
+        let (x, y) = pad.to_u32()?;
+        self.low_level_get_pending_effect(x, y)
+    }
+
+    /// Sets the effect at the given position, see [`PadEffect`]. The effect stays until it's
+    /// replaced, also when the color of the pad changes. Returns None if out of bounds, or if this
+    /// canvas doesn't support effects.
+    ///
+    /// ```
+    /// # use launchy::{Pad, Color, Canvas as _, PadEffect};
+    /// # let mut canvas = launchy::MockCanvas::new(9, 9);
+    /// canvas[Pad { x: 5, y: 5 }] = Color::RED;
+    /// canvas.set_effect(Pad { x: 5, y: 5 }, PadEffect::Pulse);
+    /// canvas.flush()?;
+    ///
+    /// // Stop pulsing
+    /// canvas.set_effect(Pad { x: 5, y: 5 }, PadEffect::Steady);
+    /// canvas.flush()?;
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    fn set_effect(&mut self, pad: Pad, effect: PadEffect) -> Option<()> {
+        let (x, y) = pad.to_u32()?;
+        self.low_level_set_pending_effect(x, y, effect)
+    }
+
+    /// Blocks for `duration`, flushing whenever the software effects need to animate. Returns
+    /// early if a flush fails.
+    ///
+    /// This drives the effects of a canvas that is used from a single thread, for example with
+    /// the input handled in a callback:
+    /// ```no_run
+    /// # #[cfg(feature = "midir")] {
+    /// # use launchy::{Canvas as _, Color, Pad, PadEffect};
+    /// let mut canvas = launchy::s::Canvas::guess(|msg| println!("{:?}", msg))?;
+    ///
+    /// canvas[Pad { x: 0, y: 1 }] = Color::GREEN;
+    /// canvas.set_effect(Pad { x: 0, y: 1 }, PadEffect::Pulse);
+    /// canvas.flush()?;
+    ///
+    /// // Pulse for ten seconds
+    /// canvas.run_effects_for(std::time::Duration::from_secs(10))?;
+    /// # }
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    fn run_effects_for(&mut self, duration: std::time::Duration) -> Result<(), crate::MidiError> {
+        let deadline = std::time::Instant::now() + duration;
+        loop {
+            let now = std::time::Instant::now();
+            if now >= deadline {
+                return Ok(());
+            }
+
+            match self.effect_refresh_interval() {
+                Some(interval) => {
+                    std::thread::sleep(interval.min(deadline - now));
+                    self.flush()?;
+                }
+                None => std::thread::sleep(deadline - now),
+            }
+        }
+    }
+
     /// An iterator over the buttons of a given Canvas. Create an iterator by calling `.iter()` on a
     /// [`Canvas`].
     ///
@@ -166,6 +330,7 @@ pub trait Canvas:
     /// For example, if you were to make a paint program for the Launchpad MK2 where you can toggle
     /// pixels by pressing:
     /// ```no_run
+    /// # #[cfg(feature = "midir")] {
     /// # use launchy::{Color, CanvasMessage, Canvas as _, MsgPollingWrapper as _};
     /// let (mut canvas, poller) = launchy::mk2::Canvas::guess_polling()?;
     ///
@@ -175,6 +340,7 @@ pub trait Canvas:
     ///         canvas.flush()?;
     ///     }
     /// }
+    /// # }
     /// # Ok::<(), launchy::MidiError>(())
     /// ```
     fn toggle(&mut self, pad: Pad, color: Color) -> Option<()> {
@@ -190,7 +356,7 @@ pub trait Canvas:
         Some(())
     }
 
-    /// Clear the entire canvas by setting all buttons to black.
+    /// Clear the entire canvas by setting all buttons to black, without effects.
     ///
     /// ```
     /// # use launchy::{Color, Pad, Canvas as _};
@@ -217,6 +383,7 @@ pub trait Canvas:
     {
         for pad in self.iter() {
             self[pad] = Color::BLACK;
+            let _ = self.set_effect(pad, PadEffect::Steady);
         }
     }
 
@@ -236,10 +403,12 @@ pub trait Canvas:
     }
 }
 
+/// A button press or release on a [`Canvas`]. The `timestamp` is the MIDI timestamp of the
+/// underlying device message in microseconds, see [`MidiClock`](crate::MidiClock).
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
 pub enum CanvasMessage {
-    Press { x: u32, y: u32 },
-    Release { x: u32, y: u32 },
+    Press { x: u32, y: u32, timestamp: u64 },
+    Release { x: u32, y: u32, timestamp: u64 },
 }
 
 impl CanvasMessage {
@@ -247,8 +416,8 @@ impl CanvasMessage {
     /// message
     pub fn x(&self) -> u32 {
         match *self {
-            Self::Press { x, y: _ } => x,
-            Self::Release { x, y: _ } => x,
+            Self::Press { x, .. } => x,
+            Self::Release { x, .. } => x,
         }
     }
 
@@ -256,8 +425,8 @@ impl CanvasMessage {
     /// message
     pub fn y(&self) -> u32 {
         match *self {
-            Self::Press { x: _, y } => y,
-            Self::Release { x: _, y } => y,
+            Self::Press { y, .. } => y,
+            Self::Release { y, .. } => y,
         }
     }
 
@@ -268,6 +437,14 @@ impl CanvasMessage {
         }
     }
 
+    /// Retrieves the MIDI timestamp of this message, in microseconds
+    pub fn timestamp(&self) -> u64 {
+        match *self {
+            Self::Press { timestamp, .. } => timestamp,
+            Self::Release { timestamp, .. } => timestamp,
+        }
+    }
+
     /// Returns whether this is a press message
     pub fn is_press(&self) -> bool {
         matches!(self, Self::Press { .. })
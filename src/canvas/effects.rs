@@ -0,0 +1,214 @@
+use super::*;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+/// The duration of one flash cycle of a software flash: the first half shows the pad color, the
+/// second half the other color. That's one beat at 120 BPM, the default tempo of the devices.
+pub const FLASH_PERIOD: Duration = Duration::from_millis(500);
+
+/// The duration of one pulse of a software pulse, from black to the pad color and back
+pub const PULSE_PERIOD: Duration = Duration::from_millis(1000);
+
+/// How often a canvas with software effects should be flushed for the effects to animate
+/// smoothly, see [`Canvas::effect_refresh_interval`]
+pub const EFFECT_FRAME_INTERVAL: Duration = Duration::from_millis(25);
+
+/// The time since the software effects started. All canvases share the same clock, so that their
+/// effects are in sync, for example across the devices of a [`CanvasLayout`].
+pub(crate) fn effect_time() -> Duration {
+    static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+    START.get_or_init(std::time::Instant::now).elapsed()
+}
+
+/// How a pad shows its color.
+///
+/// Devices that have the effect built in show it by themselves, for example the flashing and
+/// pulsing of the Launchpad MK2 and the MK3 generation, or the double buffering flash of the
+/// Launchpad S, Mini and MIDI 1. Effects that use palette colors on the device are shown with the
+/// nearest palette color. On all other devices, the canvas emulates the effect in software and
+/// has to be flushed regularly, which [`Canvas::run_effects_for`] or an [`EffectDriver`] do.
+///
+/// ```
+/// # use launchy::{Canvas as _, Color, PadEffect, Pad};
+/// # let mut canvas = launchy::MockCanvas::new(9, 9);
+/// let pad = Pad { x: 3, y: 3 };
+///
+/// // Flash between red and blue
+/// canvas[pad] = Color::RED;
+/// canvas.set_effect(pad, PadEffect::Flash { other: Color::BLUE });
+/// canvas.flush()?;
+/// assert_eq!(canvas.get_effect(pad), Some(PadEffect::Flash { other: Color::BLUE }));
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+///
+/// The Launchpad MK2 flashes by itself, with the nearest palette color for the other color. The
+/// Launchpad S can't pulse, so the canvas asks to be flushed regularly instead:
+/// ```
+/// # use launchy::{Canvas as _, Color, DeviceCanvas, PadEffect, Pad};
+/// use launchy::emulator::{Classic, Emulator, EmulatorTransport, Led, LedColor, Mk2};
+///
+/// let emulator = Emulator::new(Mk2::new());
+/// let mut canvas = DeviceCanvas::<launchy::mk2::Spec, EmulatorTransport>::from_connections(
+///     emulator.source(),
+///     emulator.sink(),
+///     |_msg| {},
+/// )?;
+/// canvas[Pad { x: 0, y: 1 }] = Color::RED;
+/// canvas.set_effect(Pad { x: 0, y: 1 }, PadEffect::Flash { other: Color::BLUE });
+/// canvas.flush()?;
+/// assert_eq!(
+///     emulator.device().led(launchy::mk2::Button::grid(0, 0)),
+///     Led::Flash { base: LedColor::Rgb(63, 0, 0), color: 45 },
+/// );
+/// assert_eq!(canvas.effect_refresh_interval(), None);
+///
+/// let emulator = Emulator::new(Classic::launchpad_s());
+/// let mut canvas = DeviceCanvas::<launchy::s::Spec, EmulatorTransport>::from_connections(
+///     emulator.source(),
+///     emulator.sink(),
+///     |_msg| {},
+/// )?;
+/// canvas[Pad { x: 0, y: 1 }] = Color::RED;
+/// canvas.set_effect(Pad { x: 0, y: 1 }, PadEffect::Pulse);
+/// canvas.flush()?;
+/// assert!(canvas.effect_refresh_interval().is_some());
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PadEffect {
+    /// The pad shows its color
+    #[default]
+    Steady,
+    /// The pad alternates between its color and `other`
+    Flash { other: Color },
+    /// The brightness of the pad rises and falls rhythmically
+    Pulse,
+}
+
+impl PadEffect {
+    /// The color that a pad with this effect shows, if its color is `color` and `time` has passed
+    /// since the effects were started
+    ///
+    /// ```
+    /// # use launchy::{Color, PadEffect, FLASH_PERIOD};
+    /// let effect = PadEffect::Flash { other: Color::BLUE };
+    ///
+    /// assert_eq!(effect.frame(Color::RED, FLASH_PERIOD / 4), Color::RED);
+    /// assert_eq!(effect.frame(Color::RED, FLASH_PERIOD * 3 / 4), Color::BLUE);
+    /// ```
+    pub fn frame(self, color: Color, time: Duration) -> Color {
+        match self {
+            Self::Steady => color,
+            Self::Flash { other } => {
+                match time.as_millis() % FLASH_PERIOD.as_millis() < FLASH_PERIOD.as_millis() / 2 {
+                    true => color,
+                    false => other,
+                }
+            }
+            Self::Pulse => {
+                let phase = (time.as_secs_f32() / PULSE_PERIOD.as_secs_f32()).fract();
+                color * (1.0 - (phase * 2.0 - 1.0).abs())
+            }
+        }
+    }
+}
+
+/// Flushes a shared canvas in the background whenever its software effects need to animate, so
+/// that they keep moving without flushing on a timer yourself. Dropping the driver stops it.
+///
+/// The canvas is flushed while nobody else holds its lock. Draw each frame within a single lock,
+/// so that it's never flushed half-drawn. For a canvas that isn't [`Send`], like a
+/// [`CanvasLayout`], use [`Canvas::run_effects_for`] instead.
+///
+/// ```
+/// # use launchy::{Canvas as _, ChannelSink, ChannelSource, ChannelTransport, Color, Pad};
+/// use launchy::{DeviceCanvas, EffectDriver, PadEffect, FLASH_PERIOD};
+/// use std::sync::{Arc, Mutex};
+///
+/// let (sink, sent) = ChannelSink::new();
+/// let (source, _device) = ChannelSource::new();
+/// let mut canvas =
+///     DeviceCanvas::<launchy::s::Spec, ChannelTransport>::from_connections(source, sink, |_| {})?;
+/// // Tear-free flushing can't flash natively, so the flash is emulated in software
+/// canvas.set_tear_free(true);
+/// canvas[Pad { x: 0, y: 1 }] = Color::RED;
+/// canvas.set_effect(Pad { x: 0, y: 1 }, PadEffect::Flash { other: Color::GREEN });
+/// canvas.flush()?;
+///
+/// let canvas = Arc::new(Mutex::new(canvas));
+/// let driver = EffectDriver::spawn(canvas.clone());
+/// # sent.try_iter().count(); // discard the messages so far
+///
+/// std::thread::sleep(FLASH_PERIOD);
+/// driver.stop()?;
+/// assert!(sent.try_iter().count() > 0);
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+pub struct EffectDriver {
+    stopped: Arc<(Mutex<bool>, Condvar)>,
+    worker: Option<std::thread::JoinHandle<Result<(), crate::MidiError>>>,
+}
+
+impl EffectDriver {
+    /// Start flushing `canvas` in the background. The driver stops by itself if a flush fails.
+    pub fn spawn<C: Canvas + Send + 'static>(canvas: Arc<Mutex<C>>) -> Self {
+        let stopped = Arc::new((Mutex::new(false), Condvar::new()));
+        let worker_stopped = stopped.clone();
+        let worker = std::thread::spawn(move || run_driver(&canvas, &worker_stopped));
+
+        Self {
+            stopped,
+            worker: Some(worker),
+        }
+    }
+
+    /// Stop the driver. Returns the error of the flush that stopped it early, if any.
+    pub fn stop(mut self) -> Result<(), crate::MidiError> {
+        self.stop_worker()
+    }
+
+    fn stop_worker(&mut self) -> Result<(), crate::MidiError> {
+        let (stopped, changed) = &*self.stopped;
+        *stopped.lock().unwrap() = true;
+        changed.notify_all();
+
+        match self.worker.take() {
+            Some(worker) => worker.join().expect("effect driver panicked"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for EffectDriver {
+    fn drop(&mut self) {
+        let _ = self.stop_worker();
+    }
+}
+
+fn run_driver<C: Canvas>(
+    canvas: &Mutex<C>,
+    (stopped, changed): &(Mutex<bool>, Condvar),
+) -> Result<(), crate::MidiError> {
+    loop {
+        // Without software effects, check again after a frame whether some were added
+        let interval = {
+            let mut canvas = canvas.lock().unwrap();
+            match canvas.effect_refresh_interval() {
+                Some(interval) => {
+                    canvas.flush()?;
+                    interval
+                }
+                None => EFFECT_FRAME_INTERVAL,
+            }
+        };
+
+        let stopped = stopped.lock().unwrap();
+        let (stopped, _) = changed
+            .wait_timeout_while(stopped, interval, |stopped| !*stopped)
+            .unwrap();
+        if *stopped {
+            return Ok(());
+        }
+    }
+}
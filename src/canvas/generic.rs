@@ -1,4 +1,8 @@
 use super::*;
+use crate::MidiTransport;
+
+/// A pad to flush with an effect: the x and y coordinates, the quantized color and the effect
+pub type EffectChange = (u32, u32, (u8, u8, u8), PadEffect);
 
 /// Launchpad's implement this trait to signify how they can be used as a [`Canvas`]. Based on this
 /// specification, [`DeviceCanvas`] provides a generic [`Canvas`] implemention that can be used for
@@ -16,8 +20,8 @@ pub trait DeviceSpec {
 
     /// The input handler type
     type Input: crate::InputDevice;
-    /// The output handler type
-    type Output: crate::OutputDevice;
+    /// The output handler type, for a given [`MidiSink`](crate::MidiSink)
+    type Output<S: crate::MidiSink>: crate::OutputDevice<Sink = S>;
 
     /// Returns whether the point at the given `x` and `y` coordinates are in bounds
     fn is_valid(x: u32, y: u32) -> bool;
@@ -27,35 +31,92 @@ pub trait DeviceSpec {
     /// `changes` is a slice of tuples `(u32, u32, (u8, u8, u8))`, where the first element is the x
     /// coordinate, the second element is the y coordinate, and the third element is an RGB color
     /// tuple, according to `COLOR_PRECISION`.
-    fn flush(
-        canvas: &mut crate::DeviceCanvas<Self>,
+    fn flush<T: MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
         changes: &[(u32, u32, (u8, u8, u8))],
     ) -> Result<(), crate::MidiError>
     where
         Self: Sized;
 
+    /// Whether the device shows the given effect by itself. All other effects are emulated in
+    /// software by the canvas. Never called with [`PadEffect::Steady`].
+    fn supports_effect<T: MidiTransport>(
+        canvas: &crate::DeviceCanvas<Self, T>,
+        effect: PadEffect,
+    ) -> bool
+    where
+        Self: Sized,
+    {
+        let _ = (canvas, effect);
+        false
+    }
+
+    /// Flush the pads with effects that the device shows by itself, as specified by `changes`.
+    ///
+    /// `changes` contains the same tuples as in [`Self::flush`], plus the effect. It only contains
+    /// effects that [`Self::supports_effect`] accepted. This is called after [`Self::flush`].
+    fn flush_effects<T: MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
+        changes: &[EffectChange],
+    ) -> Result<(), crate::MidiError>
+    where
+        Self: Sized,
+    {
+        let _ = (canvas, changes);
+        Ok(())
+    }
+
     /// Convert a message from the underlying input handler into an abstract CanvasMessage. If the
     /// low-level message has no CanvasMessage equivalent, i.e. if it's irrelevant in a canvas
     /// context, None is returned.
     fn convert_message(msg: <Self::Input as crate::InputDevice>::Message) -> Option<CanvasMessage>;
 
     /// Optional code to setup this device for canvas usage
-    fn setup(output: &mut Self::Output) -> Result<(), crate::MidiError> {
+    fn setup<S: crate::MidiSink>(output: &mut Self::Output<S>) -> Result<(), crate::MidiError> {
         let _ = output;
         Ok(())
     }
 }
 
+/// How a [`DeviceCanvas`] turns the changed pads into device messages, see
+/// [`DeviceCanvas::set_flush_strategy`]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum FlushStrategy {
+    /// Send exactly the changed pads, in the regular lighting message of the device
+    #[default]
+    PerPad,
+    /// Plan the sequence of messages with the fewest bytes. Depending on the device, that means
+    /// filling the whole display or uniform rows and columns in one message, using the shorter
+    /// palette messages for colors that a palette entry shows exactly, or rewriting the display
+    /// in rapid update mode when most pads changed. Pads that didn't change may be sent again.
+    ///
+    /// The palette tables are measured approximations of the device colors, so a color that a
+    /// palette entry supposedly shows exactly may look slightly different than its RGB message.
+    Minimal,
+}
+
 /// Utility to be able to process messages from a CanvasLayout by polling
 pub struct DeviceCanvasPoller {
-    receiver: std::sync::mpsc::Receiver<CanvasMessage>,
+    pub(crate) receiver: crate::midi_io::PollerReceiver<CanvasMessage>,
 }
 
 impl crate::MsgPollingWrapper for DeviceCanvasPoller {
     type Message = CanvasMessage;
 
     fn receiver(&self) -> &std::sync::mpsc::Receiver<Self::Message> {
-        &self.receiver
+        self.receiver.receiver()
+    }
+}
+
+#[cfg(feature = "async")]
+impl futures_core::Stream for DeviceCanvasPoller {
+    type Item = CanvasMessage;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<CanvasMessage>> {
+        self.receiver.poll_next(cx)
     }
 }
 
@@ -63,28 +124,103 @@ impl crate::MsgPollingWrapper for DeviceCanvasPoller {
 /// a user of the library don't need to access this struct directly. Use the "Canvas" type aliases
 /// that each launchpad module provides, for example `launchy::mk2::Canvas` or
 /// `launchy::s::Canvas`.
-pub struct DeviceCanvas<Spec: DeviceSpec> {
-    _input: crate::InputDeviceHandler,
-    pub(crate) output: Spec::Output,
+///
+/// The second generic parameter specifies the [`MidiTransport`] that is used
+/// to talk to the device. By default, that's real OS MIDI ports, or in-memory channels without the
+/// `midir` feature. To drive a canvas without any
+/// hardware attached, use [`ChannelTransport`](crate::ChannelTransport):
+///
+/// ```
+/// # use launchy::{Canvas as _, ChannelSink, ChannelSource, ChannelTransport, Color, Pad};
+/// let (sink, sent) = ChannelSink::new();
+/// let (source, _device) = ChannelSource::new();
+/// let mut canvas =
+///     launchy::DeviceCanvas::<launchy::mk2::Spec, ChannelTransport>::from_connections(
+///         source,
+///         sink,
+///         |_msg| {},
+///     )?;
+/// # sent.try_iter().count(); // discard setup messages
+///
+/// canvas[Pad { x: 0, y: 1 }] = Color::RED;
+/// canvas.flush()?;
+/// assert_eq!(sent.try_recv().unwrap(), [240, 0, 32, 41, 2, 24, 11, 81, 63, 0, 0, 247]);
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+pub struct DeviceCanvas<Spec: DeviceSpec, T: MidiTransport = crate::midi_io::DefaultTransport> {
+    _input: crate::InputDeviceHandler<<T::Source as crate::MidiSource>::Connection>,
+    pub(crate) output: Spec::Output<T::Sink>,
     curr_state: crate::util::Array2d<crate::Color>,
     new_state: crate::util::Array2d<crate::Color>,
+    /// The pads that were written to since the last flush
+    dirty: crate::util::DirtySet,
+    /// The change list of the last flush, kept to reuse its allocation
+    changes: Vec<(u32, u32, (u8, u8, u8))>,
+    flush_strategy: FlushStrategy,
+    tear_free: bool,
+    new_effects: crate::util::Array2d<PadEffect>,
+    /// The effects that the device currently shows by itself. `None` for pads that show a plain
+    /// color, including pads with software effects
+    native_effects: crate::util::Array2d<Option<PadEffect>>,
+    /// How many pads of `native_effects` show an effect
+    num_native_effects: usize,
+    /// The pads with an effect that's emulated in software
+    animated: Vec<(u32, u32)>,
+    /// The effect change list of the last flush, kept to reuse its allocation
+    effect_changes: Vec<EffectChange>,
+    /// The effect time of the last flush
+    effect_time: std::time::Duration,
     // This is a debug variable to be able to see how many messages I'm actually spewing out.
     num_sent_changes: usize,
+    /// The queue of the output sink, if it sends in the background. Awaited by `flush_async`
+    #[cfg(feature = "async")]
+    queue: Option<crate::QueueMonitor>,
 }
 
-impl<Spec: DeviceSpec> DeviceCanvas<Spec> {
-    /// Create a new canvas by guessing both input and output MIDI connection by their name.
-    pub fn guess(
+impl<Spec: DeviceSpec, T: MidiTransport> DeviceCanvas<Spec, T> {
+    /// Create a new canvas from an already established input source and output sink
+    pub fn from_connections(
+        source: T::Source,
+        sink: T::Sink,
         mut callback: impl FnMut(CanvasMessage) + Send + 'static,
     ) -> Result<Self, crate::MidiError> {
         use crate::midi_io::{InputDevice, OutputDevice};
 
-        let _input = Spec::Input::guess(move |msg| {
+        let _input = Spec::Input::from_source(source, move |msg| {
             if let Some(msg) = Spec::convert_message(msg) {
                 (callback)(msg);
             }
         })?;
-        let mut output = Spec::Output::guess()?;
+        #[cfg(feature = "async")]
+        let queue = crate::MidiSink::queue_monitor(&sink);
+        let output = Spec::Output::<T::Sink>::from_connection(sink)?;
+
+        #[allow(unused_mut)]
+        let mut canvas = Self::from_parts(_input, output)?;
+        #[cfg(feature = "async")]
+        {
+            canvas.queue = queue;
+        }
+        Ok(canvas)
+    }
+
+    /// Like [`Self::from_connections`], but returns a poller instead of taking a callback
+    pub fn from_connections_polling(
+        source: T::Source,
+        sink: T::Sink,
+    ) -> Result<(Self, DeviceCanvasPoller), crate::MidiError> {
+        let (sender, receiver) = crate::midi_io::poller_channel(None);
+        let canvas = Self::from_connections(source, sink, move |msg| sender.send(msg))?;
+
+        let poller = DeviceCanvasPoller { receiver };
+
+        Ok((canvas, poller))
+    }
+
+    fn from_parts(
+        _input: crate::InputDeviceHandler<<T::Source as crate::MidiSource>::Connection>,
+        mut output: Spec::Output<T::Sink>,
+    ) -> Result<Self, crate::MidiError> {
         Spec::setup(&mut output)?;
 
         let curr_state =
@@ -97,22 +233,443 @@ impl<Spec: DeviceSpec> DeviceCanvas<Spec> {
             output,
             curr_state,
             new_state,
+            dirty: crate::util::DirtySet::new(Spec::BOUNDING_BOX_WIDTH, Spec::BOUNDING_BOX_HEIGHT),
+            changes: Vec::new(),
+            flush_strategy: FlushStrategy::default(),
+            tear_free: false,
+            new_effects: crate::util::Array2d::new(
+                Spec::BOUNDING_BOX_WIDTH,
+                Spec::BOUNDING_BOX_HEIGHT,
+            ),
+            native_effects: crate::util::Array2d::new(
+                Spec::BOUNDING_BOX_WIDTH,
+                Spec::BOUNDING_BOX_HEIGHT,
+            ),
+            num_native_effects: 0,
+            animated: Vec::new(),
+            effect_changes: Vec::new(),
+            effect_time: std::time::Duration::ZERO,
             num_sent_changes: 0,
+            #[cfg(feature = "async")]
+            queue: None,
         })
     }
 
-    pub fn guess_polling() -> Result<(Self, DeviceCanvasPoller), crate::MidiError> {
-        let (sender, receiver) = std::sync::mpsc::channel();
-        let canvas = Self::guess(move |msg| {
-            sender
-                .send(msg)
-                .expect("Message receiver has hung up (this shouldn't happen)")
+    /// How changes are turned into device messages when flushing. The default is
+    /// [`FlushStrategy::PerPad`].
+    pub fn flush_strategy(&self) -> FlushStrategy {
+        self.flush_strategy
+    }
+
+    /// Choose how changes are turned into device messages when flushing.
+    ///
+    /// ```
+    /// # use launchy::{Canvas as _, ChannelSink, ChannelSource, ChannelTransport, Color, FlushStrategy, Pad};
+    /// let (sink, sent) = ChannelSink::new();
+    /// let (source, _device) = ChannelSource::new();
+    /// let mut canvas =
+    ///     launchy::DeviceCanvas::<launchy::mk2::Spec, ChannelTransport>::from_connections(
+    ///         source,
+    ///         sink,
+    ///         |_msg| {},
+    ///     )?;
+    /// # sent.try_iter().count(); // discard setup messages
+    ///
+    /// // Light everything, then turn it off again. By default, every changed pad is sent as RGB,
+    /// // which takes 4 bytes per pad
+    /// for pad in canvas.iter() {
+    ///     canvas[pad] = Color::WHITE;
+    /// }
+    /// canvas.flush()?;
+    /// # sent.try_iter().count();
+    /// canvas.clear();
+    /// canvas.flush()?;
+    /// assert_eq!(sent.try_recv().unwrap().len(), 8 + 80 * 4);
+    ///
+    /// // The minimal flush clears the display in one short message instead
+    /// canvas.set_flush_strategy(FlushStrategy::Minimal);
+    /// for pad in canvas.iter() {
+    ///     canvas[pad] = Color::WHITE;
+    /// }
+    /// canvas.flush()?;
+    /// # sent.try_iter().count();
+    /// canvas.clear();
+    /// canvas.flush()?;
+    /// assert_eq!(sent.try_iter().collect::<Vec<_>>(), [[240, 0, 32, 41, 2, 24, 14, 0, 247]]);
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    pub fn set_flush_strategy(&mut self, strategy: FlushStrategy) {
+        self.flush_strategy = strategy;
+    }
+
+    /// Whether flushes are presented atomically using hardware double buffering. Initially
+    /// disabled.
+    pub fn tear_free(&self) -> bool {
+        self.tear_free
+    }
+
+    /// Present every flush atomically, so that large updates don't visibly wipe across the grid.
+    ///
+    /// This uses the hardware double buffering of the Launchpad S, Mini and MIDI 1: the changes
+    /// are written into the hidden buffer, which is then displayed with a single message and
+    /// copied into the other buffer, so both buffers are consistent after every flush. This costs
+    /// two extra messages per flush, and can't be combined with flashing via
+    /// `control_double_buffering`, so [`PadEffect::Flash`] is emulated in software instead. Other
+    /// devices ignore this setting.
+    ///
+    /// ```
+    /// # use launchy::{Canvas as _, Color, DeviceCanvas, Pad};
+    /// use launchy::emulator::{Classic, Emulator, EmulatorTransport};
+    /// use launchy::s::{Buffer, Button};
+    ///
+    /// let emulator = Emulator::new(Classic::launchpad_s());
+    /// let mut canvas = DeviceCanvas::<launchy::s::Spec, EmulatorTransport>::from_connections(
+    ///     emulator.source(),
+    ///     emulator.sink(),
+    ///     |_msg| {},
+    /// )?;
+    /// canvas.set_tear_free(true);
+    ///
+    /// for pad in canvas.iter() {
+    ///     canvas[pad] = Color::RED;
+    /// }
+    /// canvas.flush()?;
+    ///
+    /// let device = emulator.device();
+    /// assert_eq!(device.led(Button::grid(3, 4)), launchy::s::Color::RED);
+    /// assert_eq!(device.buffered_led(Buffer::A, Button::UP), launchy::s::Color::RED);
+    /// assert_eq!(device.buffered_led(Buffer::B, Button::UP), launchy::s::Color::RED);
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    pub fn set_tear_free(&mut self, tear_free: bool) {
+        self.tear_free = tear_free;
+
+        // Whether the device shows an effect by itself may depend on this setting
+        for y in 0..Spec::BOUNDING_BOX_HEIGHT {
+            for x in 0..Spec::BOUNDING_BOX_WIDTH {
+                if self.new_effects.get(x, y) != Some(&PadEffect::Steady) {
+                    self.dirty.mark(x, y);
+                }
+            }
+        }
+    }
+
+    /// Whether the device shows the given effect by itself
+    fn is_native_effect(&self, effect: PadEffect) -> bool {
+        effect != PadEffect::Steady && Spec::supports_effect(self, effect)
+    }
+
+    /// Whether the device shows an effect by itself on any pad. Flush implementations must not
+    /// rewrite pads that didn't change in that case, because that would stop their effects. Pads
+    /// that get a native effect in the ongoing flush are sent after it, so they don't count yet.
+    pub(crate) fn has_native_effects(&self) -> bool {
+        self.num_native_effects > 0
+    }
+
+    /// The quantized color that the pad shows after the ongoing flush, or None if out of bounds.
+    /// Flush implementations use this to rewrite pads that didn't change. For pads with a software
+    /// effect, that's the current frame of the effect.
+    pub(crate) fn target_color(&self, x: u32, y: u32) -> Option<(u8, u8, u8)> {
+        if !Spec::is_valid(x, y) {
+            return None;
+        }
+
+        let color = *self.new_state.get(x, y)?;
+        let effect = *self.new_effects.get(x, y)?;
+        let color = match self.is_native_effect(effect) {
+            true => color,
+            false => effect.frame(color, self.effect_time),
+        };
+        Some(color.quantize(Spec::COLOR_PRECISION as u8))
+    }
+
+    /// Like [`Canvas::flush`], but sends every pad regardless of whether it changed. Used to
+    /// restore the display after the device was reconnected.
+    pub(crate) fn flush_all(&mut self) -> Result<(), crate::MidiError> {
+        self.flush_changes(true)
+    }
+
+    /// Send all changed pads to the device, or all pads if `resend_all` is set
+    fn flush_changes(&mut self, resend_all: bool) -> Result<(), crate::MidiError> {
+        if resend_all {
+            for y in 0..Spec::BOUNDING_BOX_HEIGHT {
+                for x in 0..Spec::BOUNDING_BOX_WIDTH {
+                    if Spec::is_valid(x, y) {
+                        self.dirty.mark(x, y);
+                    }
+                }
+            }
+        }
+
+        // Pads with software effects change over time, so they're checked on every flush
+        for &(x, y) in &self.animated {
+            self.dirty.mark(x, y);
+        }
+        self.effect_time = effect_time();
+
+        // Reuse the buffers of the last flush. They're taken out of self, because Spec::flush
+        // needs the canvas mutably
+        let mut changes = std::mem::take(&mut self.changes);
+        let mut effect_changes = std::mem::take(&mut self.effect_changes);
+        changes.clear();
+        effect_changes.clear();
+        for &(x, y) in self.dirty.cells() {
+            let color = *self.new_state.get(x, y).unwrap();
+            let effect = *self.new_effects.get(x, y).unwrap();
+            let native_effect = *self.native_effects.get(x, y).unwrap();
+            let old = self.curr_state.get(x, y).unwrap();
+
+            let old = old.quantize(Spec::COLOR_PRECISION as u8);
+            if self.is_native_effect(effect) {
+                let new = color.quantize(Spec::COLOR_PRECISION as u8);
+                if resend_all || new != old || native_effect != Some(effect) {
+                    effect_changes.push((x, y, new, effect));
+                }
+            } else {
+                let new = effect.frame(color, self.effect_time);
+                let new = new.quantize(Spec::COLOR_PRECISION as u8);
+                // The effect that the device showed by itself is stopped by sending the color
+                if resend_all || new != old || native_effect.is_some() {
+                    changes.push((x, y, new));
+                }
+            }
+        }
+
+        let result = self.send_changes(&changes, &effect_changes);
+        self.changes = changes;
+        self.effect_changes = effect_changes;
+        result?;
+
+        // Only now the device shows the pending state. If sending failed, the pads stay dirty and
+        // are sent again on the next flush
+        self.animated.clear();
+        for &(x, y) in self.dirty.cells() {
+            let color = *self.new_state.get(x, y).unwrap();
+            let effect = *self.new_effects.get(x, y).unwrap();
+
+            let is_native = self.is_native_effect(effect);
+            *self.curr_state.get_mut(x, y).unwrap() = match is_native {
+                true => color,
+                false => effect.frame(color, self.effect_time),
+            };
+            let native_effect = self.native_effects.get_mut(x, y).unwrap();
+            match (native_effect.is_some(), is_native) {
+                (false, true) => self.num_native_effects += 1,
+                (true, false) => self.num_native_effects -= 1,
+                _ => {}
+            }
+            *native_effect = is_native.then_some(effect);
+            if effect != PadEffect::Steady && !is_native {
+                self.animated.push((x, y));
+            }
+        }
+        self.dirty.clear();
+
+        Ok(())
+    }
+
+    fn send_changes(
+        &mut self,
+        changes: &[(u32, u32, (u8, u8, u8))],
+        effect_changes: &[EffectChange],
+    ) -> Result<(), crate::MidiError> {
+        let num_changes = changes.len() + effect_changes.len();
+        if num_changes > 0 {
+            use crate::midi_io::OutputDevice;
+            self.num_sent_changes += num_changes;
+
+            if self.num_sent_changes / 1000 != (self.num_sent_changes - num_changes) / 1000 {
+                log::debug!(
+                    "{}: we're at {} total transmitted changes now",
+                    Spec::Output::<T::Sink>::MIDI_DEVICE_KEYWORD,
+                    self.num_sent_changes,
+                );
+            }
+        }
+
+        if !changes.is_empty() {
+            Spec::flush(self, changes)?;
+        }
+        if !effect_changes.is_empty() {
+            Spec::flush_effects(self, effect_changes)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "midir")]
+impl<Spec: DeviceSpec> DeviceCanvas<Spec, crate::Midir> {
+    /// Create a new canvas by guessing both input and output MIDI connection by their name.
+    pub fn guess(
+        mut callback: impl FnMut(CanvasMessage) + Send + 'static,
+    ) -> Result<Self, crate::MidiError> {
+        use crate::midi_io::{InputDevice, OutputDevice};
+
+        let _input = Spec::Input::guess(move |msg| {
+            if let Some(msg) = Spec::convert_message(msg) {
+                (callback)(msg);
+            }
         })?;
+        let output = Spec::Output::<midir::MidiOutputConnection>::guess()?;
+
+        Self::from_parts(_input, output)
+    }
+
+    pub fn guess_polling() -> Result<(Self, DeviceCanvasPoller), crate::MidiError> {
+        let (sender, receiver) = crate::midi_io::poller_channel(None);
+        let canvas = Self::guess(move |msg| sender.send(msg))?;
 
         let poller = DeviceCanvasPoller { receiver };
 
         Ok((canvas, poller))
     }
+
+    /// Like [`Self::guess`], but the output goes through a [`ThrottledSink`](crate::ThrottledSink)
+    /// with the rate limit of the device. Flushing faster than the device can keep up lowers the
+    /// frame rate instead of corrupting the display. The returned
+    /// [`QueueMonitor`](crate::QueueMonitor) tells how far behind the device is.
+    ///
+    /// ```no_run
+    /// # use launchy::{Canvas as _, Color, Pad};
+    /// let (mut canvas, queue) = launchy::s::Canvas::guess_throttled(|_msg| {})?;
+    ///
+    /// for frame in 0.. {
+    ///     canvas[Pad { x: frame % 8, y: 1 }] = Color::WHITE;
+    ///     canvas.flush()?;
+    ///     println!("{} messages are waiting to be sent", queue.len());
+    /// }
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    pub fn guess_throttled(
+        mut callback: impl FnMut(CanvasMessage) + Send + 'static,
+    ) -> Result<(DeviceCanvas<Spec, crate::Throttled>, crate::QueueMonitor), crate::MidiError> {
+        use crate::midi_io::{InputDevice, OutputDevice};
+        type ThrottledOutput<Spec> = <Spec as DeviceSpec>::Output<crate::ThrottledSink>;
+
+        let _input = Spec::Input::guess(move |msg| {
+            if let Some(msg) = Spec::convert_message(msg) {
+                (callback)(msg);
+            }
+        })?;
+        let connection = crate::midi_io::guess_output_connection::<ThrottledOutput<Spec>>()?;
+        let (sink, queue) = crate::ThrottledSink::for_device::<ThrottledOutput<Spec>>(connection);
+        let output = ThrottledOutput::<Spec>::from_connection(sink)?;
+
+        #[allow(unused_mut)]
+        let mut canvas = DeviceCanvas::from_parts(_input, output)?;
+        #[cfg(feature = "async")]
+        {
+            canvas.queue = Some(queue.clone());
+        }
+        Ok((canvas, queue))
+    }
+
+    /// Create a new canvas on explicitly chosen input and output ports. See
+    /// [`PortSelector`](crate::PortSelector) for the ways to choose a port.
+    pub fn select(
+        input: crate::PortSelector<'_>,
+        output: crate::PortSelector<'_>,
+        mut callback: impl FnMut(CanvasMessage) + Send + 'static,
+    ) -> Result<Self, crate::MidiError> {
+        use crate::midi_io::{InputDevice, OutputDevice};
+
+        let _input = Spec::Input::select(input, move |msg| {
+            if let Some(msg) = Spec::convert_message(msg) {
+                (callback)(msg);
+            }
+        })?;
+        let output = Spec::Output::<midir::MidiOutputConnection>::select(output)?;
+
+        Self::from_parts(_input, output)
+    }
+
+    /// Like [`Self::guess`], but connects to the `n`th device (zero-indexed) of this type instead
+    /// of the first one.
+    ///
+    /// The input and output port of a device are paired by their names, which the operating system
+    /// chooses alike for both ports of a device, rather than by their position in the port lists.
+    pub fn guess_nth(
+        n: usize,
+        callback: impl FnMut(CanvasMessage) + Send + 'static,
+    ) -> Result<Self, crate::MidiError> {
+        let (input, output) = Self::nth_port_names(n)?;
+        Self::select_ports(&input, &output, callback)
+    }
+
+    /// Create a canvas for every connected device of this type. The callback receives the index of
+    /// the canvas that a message belongs to.
+    ///
+    /// ```no_run
+    /// # use launchy::{Canvas as _, Color};
+    /// let mut canvases = launchy::mk2::Canvas::guess_all(|index, msg| {
+    ///     println!("Launchpad number {} sent {:?}", index, msg);
+    /// })?;
+    ///
+    /// for canvas in &mut canvases {
+    ///     canvas[launchy::Pad { x: 0, y: 0 }] = Color::RED;
+    ///     canvas.flush()?;
+    /// }
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    pub fn guess_all(
+        callback: impl Fn(usize, CanvasMessage) + Send + Sync + 'static,
+    ) -> Result<Vec<Self>, crate::MidiError> {
+        let callback = std::sync::Arc::new(callback);
+        Self::port_names()?
+            .iter()
+            .enumerate()
+            .map(|(n, (input, output))| {
+                let callback = callback.clone();
+                Self::select_ports(input, output, move |msg| (callback)(n, msg))
+            })
+            .collect()
+    }
+
+    /// Like [`Self::guess_all`], but returns a poller for each canvas instead of taking a callback
+    pub fn guess_all_polling() -> Result<Vec<(Self, DeviceCanvasPoller)>, crate::MidiError> {
+        Self::port_names()?
+            .iter()
+            .map(|(input, output)| {
+                let (sender, receiver) = crate::midi_io::poller_channel(None);
+                let canvas = Self::select_ports(input, output, move |msg| sender.send(msg))?;
+
+                Ok((canvas, DeviceCanvasPoller { receiver }))
+            })
+            .collect()
+    }
+
+    /// Create a canvas on the input and output port with the given names
+    pub(crate) fn select_ports(
+        input: &str,
+        output: &str,
+        callback: impl FnMut(CanvasMessage) + Send + 'static,
+    ) -> Result<Self, crate::MidiError> {
+        use crate::PortSelector::Name;
+
+        Self::select(Name(input), Name(output), callback)
+    }
+
+    /// The input and output port names of every connected device of this type
+    pub(crate) fn port_names() -> Result<Vec<(String, String)>, crate::MidiError> {
+        crate::midi_io::device_port_names::<Spec::Input, Spec::Output<midir::MidiOutputConnection>>(
+        )
+    }
+
+    /// The input and output port names of the `n`th connected device of this type
+    pub(crate) fn nth_port_names(n: usize) -> Result<(String, String), crate::MidiError> {
+        Self::port_names()?
+            .into_iter()
+            .nth(n)
+            .ok_or_else(|| crate::MidiError::NoMatchingPort {
+                selector: format!("ports of device number {}", n),
+            })
+    }
+
+    /// Number of connected devices of this type, judging by the port names
+    pub(crate) fn count() -> usize {
+        Self::port_names().map_or(0, |port_names| port_names.len())
+    }
 }
 
 #[doc(hidden)] // this is crap workaround and shouldn't be seen by user directly
@@ -120,13 +677,13 @@ pub trait DeviceCanvasTrait {
     type Spec: DeviceSpec;
 }
 
-impl<S: DeviceSpec> DeviceCanvasTrait for DeviceCanvas<S> {
+impl<S: DeviceSpec, T: MidiTransport> DeviceCanvasTrait for DeviceCanvas<S, T> {
     type Spec = S;
 }
 
-impl_traits_for_canvas!(DeviceCanvas[S: DeviceSpec]);
+impl_traits_for_canvas!(DeviceCanvas[S: DeviceSpec, T: MidiTransport]);
 
-impl<Spec: DeviceSpec> crate::Canvas for DeviceCanvas<Spec> {
+impl<Spec: DeviceSpec, T: MidiTransport> crate::Canvas for DeviceCanvas<Spec, T> {
     fn bounding_box(&self) -> (u32, u32) {
         (Spec::BOUNDING_BOX_WIDTH, Spec::BOUNDING_BOX_HEIGHT)
     }
@@ -147,6 +704,8 @@ impl<Spec: DeviceSpec> crate::Canvas for DeviceCanvas<Spec> {
             return None;
         }
 
+        // The caller may write to the pad, so it has to be checked on the next flush
+        self.dirty.mark(x, y);
         self.new_state.get_mut(x, y)
     }
 
@@ -158,38 +717,84 @@ impl<Spec: DeviceSpec> crate::Canvas for DeviceCanvas<Spec> {
         self.new_state.get(x, y)
     }
 
-    fn flush(&mut self) -> Result<(), crate::MidiError> {
-        let mut changes: Vec<(u32, u32, (u8, u8, u8))> = Vec::with_capacity(9 * 9);
-
-        for pad in self.iter() {
-            let old = self.get(pad).unwrap();
-            let new = self.get_pending(pad).unwrap();
+    fn low_level_get_pending_effect(&self, x: u32, y: u32) -> Option<PadEffect> {
+        if !Spec::is_valid(x, y) {
+            return None;
+        }
 
-            let old = old.quantize(Spec::COLOR_PRECISION as u8);
-            let new = new.quantize(Spec::COLOR_PRECISION as u8);
+        self.new_effects.get(x, y).copied()
+    }
 
-            if new != old {
-                changes.push((pad.x as u32, pad.y as u32, new));
-            }
+    fn low_level_set_pending_effect(&mut self, x: u32, y: u32, effect: PadEffect) -> Option<()> {
+        if !Spec::is_valid(x, y) {
+            return None;
         }
 
-        if !changes.is_empty() {
-            use crate::midi_io::OutputDevice;
-            self.num_sent_changes += changes.len();
-
-            if self.num_sent_changes / 1000 != (self.num_sent_changes - changes.len()) / 1000 {
-                println!(
-                    "{}: we're at {} total transmitted changes now",
-                    Spec::Output::MIDI_DEVICE_KEYWORD,
-                    self.num_sent_changes,
-                );
-            }
+        self.dirty.mark(x, y);
+        *self.new_effects.get_mut(x, y)? = effect;
+        Some(())
+    }
 
-            Spec::flush(self, &changes)?;
+    fn effect_refresh_interval(&self) -> Option<std::time::Duration> {
+        match self.animated.is_empty() {
+            true => None,
+            false => Some(EFFECT_FRAME_INTERVAL),
         }
+    }
 
-        self.curr_state = self.new_state.clone();
+    /// Only the pads that were written to since the last flush, and the pads with software
+    /// effects, are checked for changes
+    fn flush(&mut self) -> Result<(), crate::MidiError> {
+        self.flush_changes(false)
+    }
 
-        Ok(())
+    /// The output of a throttled canvas only queues the messages, and the
+    /// [`ThrottledSink`](crate::ThrottledSink) sends them on its own thread. So flushing doesn't
+    /// block, and the returned future resolves once the queue was sent or a send failed. The
+    /// future doesn't borrow the canvas, so it can be awaited in another task. For canvases that
+    /// aren't throttled, the future is ready immediately.
+    ///
+    /// ```
+    /// # #[cfg(feature = "async")]
+    /// # fn main() -> Result<(), launchy::MidiError> {
+    /// use launchy::{Canvas as _, ChannelSink, ChannelSource, ChannelTransport, Color, Pad};
+    /// use launchy::{DeviceCanvas, OutputDevice as _, Throttled, ThrottledSink};
+    ///
+    /// type Output = launchy::mk2::Output<ThrottledSink<ChannelSink>>;
+    ///
+    /// let (sink, sent) = ChannelSink::new();
+    /// let (sink, queue) = ThrottledSink::for_device::<Output>(sink);
+    /// let (source, _device) = ChannelSource::new();
+    /// let mut canvas =
+    ///     DeviceCanvas::<launchy::mk2::Spec, Throttled<ChannelTransport>>::from_connections(
+    ///         source,
+    ///         sink,
+    ///         |_msg| {},
+    ///     )?;
+    ///
+    /// canvas[Pad { x: 0, y: 1 }] = Color::RED;
+    /// futures::executor::block_on(canvas.flush_async())?;
+    /// assert!(queue.is_empty());
+    /// assert!(sent.try_iter().count() > 0);
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "async"))]
+    /// # fn main() {}
+    /// ```
+    #[cfg(feature = "async")]
+    fn flush_async(&mut self) -> FlushFuture {
+        let result = self.flush_changes(false);
+        let queue = self.queue.clone();
+
+        Box::pin(async move {
+            result?;
+            if let Some(queue) = queue {
+                queue.until_empty().await;
+                if let Some(error) = queue.take_error() {
+                    return Err(error);
+                }
+            }
+            Ok(())
+        })
     }
 }
@@ -74,14 +74,26 @@ impl LayoutDevice<'_> {
 
 /// Utility to be able to process messages from a CanvasLayout by polling
 pub struct CanvasLayoutPoller {
-    receiver: std::sync::mpsc::Receiver<CanvasMessage>,
+    receiver: crate::midi_io::PollerReceiver<CanvasMessage>,
 }
 
 impl crate::MsgPollingWrapper for CanvasLayoutPoller {
     type Message = CanvasMessage;
 
     fn receiver(&self) -> &std::sync::mpsc::Receiver<Self::Message> {
-        &self.receiver
+        self.receiver.receiver()
+    }
+}
+
+#[cfg(feature = "async")]
+impl futures_core::Stream for CanvasLayoutPoller {
+    type Item = CanvasMessage;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<CanvasMessage>> {
+        self.receiver.poll_next(cx)
     }
 }
 
@@ -89,6 +101,10 @@ struct Pixel {
     device_index: usize,
     color_new: Color,
     color_old: Color,
+    effect: PadEffect,
+    /// Whether the pixel was written to since the last flush, i.e. whether it's in
+    /// `CanvasLayout::dirty`
+    is_dirty: bool,
 }
 
 fn transform_color(color: Color, source: f32, target: f32) -> Color {
@@ -105,6 +121,7 @@ fn transform_color(color: Color, source: f32, target: f32) -> Color {
 ///
 /// Example:
 /// ```no_run
+/// # #[cfg(feature = "midir")] {
 /// # use launchy::{CanvasLayout, Canvas as _};
 /// let mut canvas_layout = CanvasLayout::new(|msg| println!("Got a message: {:?}", msg));
 ///
@@ -117,11 +134,14 @@ fn transform_color(color: Color, source: f32, target: f32) -> Color {
 ///     canvas_layout[pad] = launchy::Color::RED;
 /// }
 /// canvas_layout.flush()?;
+/// # }
 /// # Ok::<(), launchy::MidiError>(())
 /// ```
 pub struct CanvasLayout<'a> {
     devices: Vec<LayoutDevice<'a>>,
     coordinate_map: HashMap<(u32, u32), Pixel>, // we need to store some stuff for each pixel
+    /// The pixels that were written to since the last flush
+    dirty: Vec<(u32, u32)>,
     callback: std::sync::Arc<dyn Fn(CanvasMessage) + Send + Sync + 'static>,
     light_threshold: f32,
 }
@@ -133,6 +153,7 @@ impl<'a> CanvasLayout<'a> {
         Self {
             devices: Vec::new(),
             coordinate_map: HashMap::new(),
+            dirty: Vec::new(),
             callback: std::sync::Arc::new(callback),
             light_threshold: 1.0 / 4.0, // good default value? I have, like, no idea
         }
@@ -140,12 +161,8 @@ impl<'a> CanvasLayout<'a> {
 
     /// Create a new CanvasLayout, plus an input handler object that you can use to poll messages.
     pub fn new_polling() -> (Self, CanvasLayoutPoller) {
-        let (sender, receiver) = std::sync::mpsc::sync_channel(50);
-        let canvas = Self::new(move |msg| {
-            sender
-                .send(msg)
-                .expect("Message receiver has hung up (this shouldn't happen)")
-        });
+        let (sender, receiver) = crate::midi_io::poller_channel(Some(50));
+        let canvas = Self::new(move |msg| sender.send(msg));
 
         let poller = CanvasLayoutPoller { receiver };
 
@@ -156,7 +173,15 @@ impl<'a> CanvasLayout<'a> {
         self.light_threshold
     }
     pub fn set_light_threshold(&mut self, value: f32) {
-        self.light_threshold = value
+        self.light_threshold = value;
+
+        // The threshold affects the color of every pixel on the devices
+        for (&coordinates, pixel) in &mut self.coordinate_map {
+            if !pixel.is_dirty {
+                pixel.is_dirty = true;
+                self.dirty.push(coordinates);
+            }
+        }
     }
 
     /// Add a new device to this canvas layout, at the specified `x` and `y` coordinate.
@@ -169,6 +194,7 @@ impl<'a> CanvasLayout<'a> {
     ///
     /// Example:
     /// ```no_run
+    /// # #[cfg(feature = "midir")] {
     /// # use launchy::{CanvasLayout, Rotation};
     /// # let mut canvas_layout = launchy::CanvasLayout::new(|_| {});
     /// canvas_layout.add(0, 0, Rotation::None, |callback| launchy::mk2::Canvas::guess(callback))?;
@@ -179,6 +205,7 @@ impl<'a> CanvasLayout<'a> {
     ///     canvas_layout.add(0, 0, Rotation::None, |callback| launchy::mk2::Canvas::guess(callback))?;
     ///     Ok::<_, launchy::MidiError>(canvas_layout)
     /// })?;
+    /// # }
     ///
     /// # Ok::<(), launchy::MidiError>(())
     /// ```
@@ -198,8 +225,12 @@ impl<'a> CanvasLayout<'a> {
         let canvas = (creator)(Box::new(move |msg| {
             let (x, y) = to_global(msg.x(), msg.y(), rotation, x_offset, y_offset);
             match msg {
-                CanvasMessage::Press { .. } => (callback)(CanvasMessage::Press { x, y }),
-                CanvasMessage::Release { .. } => (callback)(CanvasMessage::Release { x, y }),
+                CanvasMessage::Press { timestamp, .. } => {
+                    (callback)(CanvasMessage::Press { x, y, timestamp })
+                }
+                CanvasMessage::Release { timestamp, .. } => {
+                    (callback)(CanvasMessage::Release { x, y, timestamp })
+                }
             }
         }))?;
 
@@ -214,6 +245,8 @@ impl<'a> CanvasLayout<'a> {
                     device_index: index,
                     color_new: canvas.get_pending(pad).unwrap(),
                     color_old: canvas[pad],
+                    effect: canvas.get_effect(pad).unwrap(),
+                    is_dirty: false,
                 },
             );
 
@@ -255,6 +288,7 @@ impl<'a> CanvasLayout<'a> {
     /// canvas_layout.add_by_guess::<launchy::s::Canvas>(9, 0)?;
     /// # Ok::<(), launchy::MidiError>(())
     /// ```
+    #[cfg(feature = "midir")]
     pub fn add_by_guess<E: 'a + DeviceCanvasTrait>(
         &mut self,
         x: u32,
@@ -264,6 +298,7 @@ impl<'a> CanvasLayout<'a> {
     }
 
     /// Like `add_by_guess`, but with a parameter for the rotation of the Launchpad.
+    #[cfg(feature = "midir")]
     pub fn add_by_guess_rotated<E: 'a + DeviceCanvasTrait>(
         &mut self,
         x: u32,
@@ -272,6 +307,42 @@ impl<'a> CanvasLayout<'a> {
     ) -> Result<(), crate::MidiError> {
         self.add(x, y, rotation, DeviceCanvas::<E::Spec>::guess)
     }
+
+    /// Like `add_by_guess`, but adds the `n`th device (zero-indexed) of the given type. Use this
+    /// if you have multiple devices of the same type.
+    ///
+    /// Example
+    /// ```no_run
+    /// # let mut canvas_layout = launchy::CanvasLayout::new(|_| {});
+    /// // Assuming three Launchpad MK2s next to each other:
+    /// canvas_layout.add_nth_by_guess::<launchy::mk2::Canvas>(0, 0, 0)?;
+    /// canvas_layout.add_nth_by_guess::<launchy::mk2::Canvas>(1, 9, 0)?;
+    /// canvas_layout.add_nth_by_guess::<launchy::mk2::Canvas>(2, 18, 0)?;
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    #[cfg(feature = "midir")]
+    pub fn add_nth_by_guess<E: 'a + DeviceCanvasTrait>(
+        &mut self,
+        n: usize,
+        x: u32,
+        y: u32,
+    ) -> Result<(), crate::MidiError> {
+        self.add_nth_by_guess_rotated::<E>(n, x, y, Rotation::None)
+    }
+
+    /// Like `add_nth_by_guess`, but with a parameter for the rotation of the Launchpad.
+    #[cfg(feature = "midir")]
+    pub fn add_nth_by_guess_rotated<E: 'a + DeviceCanvasTrait>(
+        &mut self,
+        n: usize,
+        x: u32,
+        y: u32,
+        rotation: Rotation,
+    ) -> Result<(), crate::MidiError> {
+        self.add(x, y, rotation, |callback| {
+            DeviceCanvas::<E::Spec>::guess_nth(n, callback)
+        })
+    }
 }
 
 impl Canvas for CanvasLayout<'_> {
@@ -301,23 +372,90 @@ impl Canvas for CanvasLayout<'_> {
     fn low_level_get_pending_mut(&mut self, x: u32, y: u32) -> Option<&mut Color> {
         // store the actual pixel color for possible retrieval later
         let pixel = self.coordinate_map.get_mut(&(x, y))?;
+        if !pixel.is_dirty {
+            pixel.is_dirty = true;
+            self.dirty.push((x, y));
+        }
         Some(&mut pixel.color_new)
     }
 
+    fn low_level_get_pending_effect(&self, x: u32, y: u32) -> Option<PadEffect> {
+        let pixel = self.coordinate_map.get(&(x, y))?;
+        Some(pixel.effect)
+    }
+
+    fn low_level_set_pending_effect(&mut self, x: u32, y: u32, effect: PadEffect) -> Option<()> {
+        let pixel = self.coordinate_map.get_mut(&(x, y))?;
+        if !pixel.is_dirty {
+            pixel.is_dirty = true;
+            self.dirty.push((x, y));
+        }
+        pixel.effect = effect;
+        Some(())
+    }
+
+    /// The shortest refresh interval of all devices
+    fn effect_refresh_interval(&self) -> Option<std::time::Duration> {
+        self.devices
+            .iter()
+            .filter_map(|device| device.canvas.effect_refresh_interval())
+            .min()
+    }
+
     fn low_level_get(&self, x: u32, y: u32) -> Option<&Color> {
         let pixel = self.coordinate_map.get(&(x, y))?;
         Some(&pixel.color_old)
     }
 
+    /// Only the pixels that were written to since the last flush are passed on to the devices
     fn flush(&mut self) -> Result<(), crate::MidiError> {
-        for (&(global_x, global_y), pixel) in self.coordinate_map.iter_mut() {
+        self.pass_on_dirty_pixels();
+        for device in &mut self.devices {
+            device.canvas.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Resolves once every device received its changes
+    #[cfg(feature = "async")]
+    fn flush_async(&mut self) -> FlushFuture {
+        self.pass_on_dirty_pixels();
+        let flushes: Vec<_> = self
+            .devices
+            .iter_mut()
+            .map(|device| device.canvas.flush_async())
+            .collect();
+
+        Box::pin(async move {
+            for flush in flushes {
+                flush.await?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl CanvasLayout<'_> {
+    /// Write the pixels that were written to since the last flush into the device canvases
+    fn pass_on_dirty_pixels(&mut self) {
+        for (global_x, global_y) in self.dirty.drain(..) {
+            let pixel = self.coordinate_map.get_mut(&(global_x, global_y)).unwrap();
+            pixel.is_dirty = false;
             let device = &mut self.devices[pixel.device_index];
 
-            let transformed_color = transform_color(
-                pixel.color_new,
+            let (source, target) = (
                 self.light_threshold,
                 device.canvas.lowest_visible_brightness(),
             );
+            let transform = |color| transform_color(color, source, target);
+            let transformed_color = transform(pixel.color_new);
+            let transformed_effect = match pixel.effect {
+                PadEffect::Flash { other } => PadEffect::Flash {
+                    other: transform(other),
+                },
+                effect => effect,
+            };
 
             let (local_x, local_y) = device.to_local(global_x, global_y);
 
@@ -325,15 +463,12 @@ impl Canvas for CanvasLayout<'_> {
                 .canvas
                 .low_level_get_pending_mut(local_x, local_y)
                 .unwrap() = transformed_color;
+            device
+                .canvas
+                .low_level_set_pending_effect(local_x, local_y, transformed_effect);
 
             pixel.color_old = pixel.color_new;
         }
-
-        for device in &mut self.devices {
-            device.canvas.flush()?;
-        }
-
-        Ok(())
     }
 }
 
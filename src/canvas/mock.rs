@@ -4,6 +4,7 @@ use crate::util::Array2d;
 pub struct MockCanvas {
     curr_buf: Array2d<Color>,
     new_buf: Array2d<Color>,
+    effects: Array2d<PadEffect>,
 }
 
 impl MockCanvas {
@@ -11,6 +12,7 @@ impl MockCanvas {
         Self {
             curr_buf: Array2d::new(width, height),
             new_buf: Array2d::new(width, height),
+            effects: Array2d::new(width, height),
         }
     }
 }
@@ -32,6 +34,15 @@ impl crate::Canvas for MockCanvas {
         self.new_buf.get_mut(x, y)
     }
 
+    fn low_level_get_pending_effect(&self, x: u32, y: u32) -> Option<PadEffect> {
+        self.effects.get(x, y).copied()
+    }
+
+    fn low_level_set_pending_effect(&mut self, x: u32, y: u32, effect: PadEffect) -> Option<()> {
+        *self.effects.get_mut(x, y)? = effect;
+        Some(())
+    }
+
     fn flush(&mut self) -> Result<(), crate::MidiError> {
         self.curr_buf = self.new_buf.clone();
         Ok(())
@@ -85,6 +85,9 @@ pub use generic::*;
 mod color;
 pub use color::*;
 
+mod effects;
+pub use effects::*;
+
 // the outer module is for "everything canvas", and the inner module is the core Canvas
 // functionality. There is reason behind this module inception
 #[allow(clippy::module_inception)]
@@ -99,3 +102,9 @@ pub use pad::*;
 
 mod mock;
 pub use mock::*;
+
+mod any;
+pub use any::*;
+
+mod reconnecting;
+pub use reconnecting::*;
@@ -19,6 +19,7 @@ use crate::util::Array2d;
 ///
 /// Example:
 /// ```no_run
+/// # #[cfg(feature = "midir")] {
 /// # use launchy::{Pad, Canvas as _};
 /// // Create the base canvas
 /// let mut canvas = launchy::mk2::Canvas::guess(|msg| {})?;
@@ -32,12 +33,15 @@ use crate::util::Array2d;
 ///         canvas[Pad { x, y }] = launchy::Color::WHITE;
 ///     }
 /// }
+/// # }
 /// # Ok::<(), launchy::MidiError>(())
 /// ```
 pub struct PaddingCanvas<C: Canvas> {
     inner: C,
     curr_buf: Array2d<Color>,
     new_buf: Array2d<Color>,
+    /// The effects of the virtual pixels
+    effects: Array2d<PadEffect>,
 }
 
 impl<C: Canvas> PaddingCanvas<C> {
@@ -49,6 +53,7 @@ impl<C: Canvas> PaddingCanvas<C> {
             inner,
             curr_buf: Array2d::new(width, height),
             new_buf: Array2d::new(width, height),
+            effects: Array2d::new(width, height),
         }
     }
 }
@@ -85,10 +90,37 @@ impl<C: Canvas> Canvas for PaddingCanvas<C> {
         }
     }
 
+    fn low_level_get_pending_effect(&self, x: u32, y: u32) -> Option<PadEffect> {
+        if let Some(effect) = self.inner.low_level_get_pending_effect(x, y) {
+            Some(effect)
+        } else {
+            self.effects.get(x, y).copied()
+        }
+    }
+
+    fn low_level_set_pending_effect(&mut self, x: u32, y: u32, effect: PadEffect) -> Option<()> {
+        if self.inner.low_level_get_pending(x, y).is_some() {
+            self.inner.low_level_set_pending_effect(x, y, effect)
+        } else {
+            *self.effects.get_mut(x, y)? = effect;
+            Some(())
+        }
+    }
+
+    fn effect_refresh_interval(&self) -> Option<std::time::Duration> {
+        self.inner.effect_refresh_interval()
+    }
+
     fn flush(&mut self) -> Result<(), crate::MidiError> {
         self.curr_buf = self.new_buf.clone();
         self.inner.flush()
     }
+
+    #[cfg(feature = "async")]
+    fn flush_async(&mut self) -> FlushFuture {
+        self.curr_buf = self.new_buf.clone();
+        self.inner.flush_async()
+    }
 }
 
 impl_traits_for_canvas!(PaddingCanvas[C: Canvas]);
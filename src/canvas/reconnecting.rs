@@ -0,0 +1,354 @@
+use super::*;
+use crate::util::{Array2d, DirtySet};
+use crate::MidiTransport;
+use std::time::{Duration, Instant};
+
+/// An event of a [`ReconnectingCanvas`]: either a regular message from the device, or a change of
+/// the connection state
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum CanvasEvent {
+    Message(CanvasMessage),
+    /// The device was connected, or reconnected after a disconnect
+    Connected,
+    /// The device was unplugged, or its connection failed otherwise
+    Disconnected,
+}
+
+type EventCallback = std::sync::Arc<dyn Fn(CanvasEvent) + Send + Sync + 'static>;
+type MessageCallback = Box<dyn FnMut(CanvasMessage) + Send + 'static>;
+type Connector<Spec, T> =
+    Box<dyn FnMut(MessageCallback) -> Result<DeviceCanvas<Spec, T>, crate::MidiError> + Send>;
+type PresenceCheck = Box<dyn FnMut() -> bool + Send>;
+
+/// A [`DeviceCanvas`] that survives the device being unplugged and plugged back in.
+///
+/// The canvas keeps its own copy of the display state. While the device is disconnected, you can
+/// continue to draw and flush as usual; flushing doesn't fail because of the missing device.
+/// Every now and then (see [`Self::set_rescan_interval`]), the canvas checks whether the device is
+/// present again. In that case it reconnects, runs the device setup again and sends the complete
+/// display state, so that the device shows what it's supposed to show.
+///
+/// Connection checks happen during [`flush`](Canvas::flush) and
+/// [`poll_connection`](Self::poll_connection). If you don't flush regularly, call the latter
+/// periodically, or input from a reconnected device won't arrive.
+///
+/// ```
+/// # use launchy::{Canvas as _, CanvasEvent, Color, DeviceCanvas, MidiError, Pad, ReconnectingCanvas};
+/// use launchy::emulator::{Emulator, EmulatorTransport, Led, LedColor, Mk2};
+/// use std::sync::{mpsc, Arc, Mutex};
+///
+/// // An emulated device that can be unplugged by setting it to None
+/// let device = Arc::new(Mutex::new(Some(Emulator::new(Mk2::new()))));
+/// let (sender, events) = mpsc::channel();
+///
+/// let connect_device = device.clone();
+/// let check_device = device.clone();
+/// let mut canvas = ReconnectingCanvas::<launchy::mk2::Spec, EmulatorTransport>::from_connector(
+///     move |callback| match &*connect_device.lock().unwrap() {
+///         Some(emulator) => {
+///             DeviceCanvas::from_connections(emulator.source(), emulator.sink(), callback)
+///         }
+///         None => Err(MidiError::NoPortFound { keyword: "Emulator" }),
+///     },
+///     move || check_device.lock().unwrap().is_some(),
+///     move |event| sender.send(event).unwrap(),
+/// );
+/// canvas.set_rescan_interval(std::time::Duration::ZERO);
+/// assert_eq!(events.try_recv(), Ok(CanvasEvent::Connected));
+///
+/// // Unplug the device. Flushing continues to work
+/// *device.lock().unwrap() = None;
+/// canvas[Pad { x: 0, y: 1 }] = Color::GREEN;
+/// canvas.flush()?;
+/// assert_eq!(events.try_recv(), Ok(CanvasEvent::Disconnected));
+///
+/// // Plug it back in: the display state is restored
+/// *device.lock().unwrap() = Some(Emulator::new(Mk2::new()));
+/// canvas.poll_connection();
+/// assert_eq!(events.try_recv(), Ok(CanvasEvent::Connected));
+/// assert_eq!(
+///     device.lock().unwrap().as_ref().unwrap().device().led(launchy::mk2::Button::grid(0, 0)),
+///     Led::Plain(LedColor::Rgb(0, 63, 0)),
+/// );
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+///
+/// To use a reconnecting canvas in a [`CanvasLayout`], add it with [`CanvasLayout::add`] and
+/// forward the [`CanvasEvent::Message`]s to the layout callback.
+pub struct ReconnectingCanvas<Spec: DeviceSpec, T: MidiTransport = crate::midi_io::DefaultTransport>
+{
+    canvas: Option<DeviceCanvas<Spec, T>>,
+    connector: Connector<Spec, T>,
+    is_present: PresenceCheck,
+    callback: EventCallback,
+    curr_state: Array2d<Color>,
+    new_state: Array2d<Color>,
+    curr_effects: Array2d<PadEffect>,
+    new_effects: Array2d<PadEffect>,
+    /// The pads that were written to since the last flush
+    dirty: DirtySet,
+    rescan_interval: Duration,
+    last_check: Option<Instant>,
+}
+
+impl<Spec: DeviceSpec, T: MidiTransport> ReconnectingCanvas<Spec, T> {
+    /// Create a reconnecting canvas with a custom way to connect to the device.
+    ///
+    /// `connector` is called with a message callback and should return a canvas connected to the
+    /// device. `is_present` should return whether the device is currently available. If the
+    /// device can't be connected to right away, the canvas starts out disconnected.
+    pub fn from_connector(
+        connector: impl FnMut(MessageCallback) -> Result<DeviceCanvas<Spec, T>, crate::MidiError>
+            + Send
+            + 'static,
+        is_present: impl FnMut() -> bool + Send + 'static,
+        callback: impl Fn(CanvasEvent) + Send + Sync + 'static,
+    ) -> Self {
+        let mut canvas = Self {
+            canvas: None,
+            connector: Box::new(connector),
+            is_present: Box::new(is_present),
+            callback: std::sync::Arc::new(callback),
+            curr_state: Array2d::new(Spec::BOUNDING_BOX_WIDTH, Spec::BOUNDING_BOX_HEIGHT),
+            new_state: Array2d::new(Spec::BOUNDING_BOX_WIDTH, Spec::BOUNDING_BOX_HEIGHT),
+            curr_effects: Array2d::new(Spec::BOUNDING_BOX_WIDTH, Spec::BOUNDING_BOX_HEIGHT),
+            new_effects: Array2d::new(Spec::BOUNDING_BOX_WIDTH, Spec::BOUNDING_BOX_HEIGHT),
+            dirty: DirtySet::new(Spec::BOUNDING_BOX_WIDTH, Spec::BOUNDING_BOX_HEIGHT),
+            rescan_interval: Duration::from_secs(1),
+            last_check: None,
+        };
+        canvas.poll_connection();
+        canvas
+    }
+
+    /// How often the presence of the device is checked. The default is one second.
+    pub fn rescan_interval(&self) -> Duration {
+        self.rescan_interval
+    }
+
+    pub fn set_rescan_interval(&mut self, interval: Duration) {
+        self.rescan_interval = interval;
+    }
+
+    /// Returns whether the device is currently connected
+    pub fn is_connected(&self) -> bool {
+        self.canvas.is_some()
+    }
+
+    /// Check whether the device was unplugged or has become available, and reconnect if
+    /// possible. Does nothing if the last check was less than the rescan interval ago. Returns
+    /// whether the device is connected.
+    pub fn poll_connection(&mut self) -> bool {
+        let now = Instant::now();
+        if let Some(last_check) = self.last_check {
+            if now.duration_since(last_check) < self.rescan_interval {
+                return self.is_connected();
+            }
+        }
+        self.last_check = Some(now);
+
+        let is_present = (self.is_present)();
+        match (&self.canvas, is_present) {
+            (Some(_), false) => self.disconnect(),
+            (None, true) => self.connect(),
+            _ => {}
+        }
+
+        self.is_connected()
+    }
+
+    fn connect(&mut self) {
+        let callback = self.callback.clone();
+        let mut canvas =
+            match (self.connector)(Box::new(move |msg| (callback)(CanvasEvent::Message(msg)))) {
+                Ok(canvas) => canvas,
+                Err(e) => {
+                    log::debug!("Reconnecting failed: {}", e);
+                    return;
+                }
+            };
+
+        // Restore what was displayed before the device went away
+        for pad in self.iter() {
+            let (x, y) = (pad.x as u32, pad.y as u32);
+            *canvas.low_level_get_pending_mut(x, y).unwrap() = *self.curr_state.get(x, y).unwrap();
+            canvas.low_level_set_pending_effect(x, y, *self.curr_effects.get(x, y).unwrap());
+        }
+        if let Err(e) = canvas.flush_all() {
+            log::debug!(
+                "Restoring the display state after reconnecting failed: {}",
+                e
+            );
+            return;
+        }
+
+        self.canvas = Some(canvas);
+        (self.callback)(CanvasEvent::Connected);
+    }
+
+    fn disconnect(&mut self) {
+        self.canvas = None;
+        (self.callback)(CanvasEvent::Disconnected);
+    }
+}
+
+#[cfg(feature = "midir")]
+impl<Spec: DeviceSpec> ReconnectingCanvas<Spec, crate::Midir> {
+    /// Create a reconnecting canvas for the first device of this type, found by guessing the MIDI
+    /// ports by their name
+    pub fn guess(callback: impl Fn(CanvasEvent) + Send + Sync + 'static) -> Self {
+        Self::guess_nth(0, callback)
+    }
+
+    /// Like [`Self::guess`], but for the `n`th device (zero-indexed) of this type.
+    ///
+    /// The canvas remembers the ports of the device that it connected to first, and only
+    /// reconnects to ports with the same names. So when devices are plugged in again in a
+    /// different order, it doesn't restore its state onto another device of the same type.
+    pub fn guess_nth(n: usize, callback: impl Fn(CanvasEvent) + Send + Sync + 'static) -> Self {
+        let ports = std::sync::Arc::new(std::sync::Mutex::new(None::<(String, String)>));
+        let connect_ports = ports.clone();
+
+        Self::from_connector(
+            move |callback| {
+                let mut ports = connect_ports.lock().unwrap();
+                let (input, output) = match &*ports {
+                    Some(ports) => ports.clone(),
+                    None => DeviceCanvas::<Spec>::nth_port_names(n)?,
+                };
+                let canvas = DeviceCanvas::select_ports(&input, &output, callback)?;
+                *ports = Some((input, output));
+                Ok(canvas)
+            },
+            move || match &*ports.lock().unwrap() {
+                Some(ports) => DeviceCanvas::<Spec>::port_names()
+                    .is_ok_and(|port_names| port_names.contains(ports)),
+                None => DeviceCanvas::<Spec>::count() > n,
+            },
+            callback,
+        )
+    }
+
+    /// Like [`Self::guess`], but returns a poller instead of taking a callback
+    pub fn guess_polling() -> (Self, ReconnectingCanvasPoller) {
+        let (sender, receiver) = crate::midi_io::poller_channel(None);
+        let canvas = Self::guess(move |event| sender.send(event));
+
+        (canvas, ReconnectingCanvasPoller { receiver })
+    }
+}
+
+/// Utility to be able to process events from a [`ReconnectingCanvas`] by polling
+pub struct ReconnectingCanvasPoller {
+    receiver: crate::midi_io::PollerReceiver<CanvasEvent>,
+}
+
+impl crate::MsgPollingWrapper for ReconnectingCanvasPoller {
+    type Message = CanvasEvent;
+
+    fn receiver(&self) -> &std::sync::mpsc::Receiver<Self::Message> {
+        self.receiver.receiver()
+    }
+}
+
+#[cfg(feature = "async")]
+impl futures_core::Stream for ReconnectingCanvasPoller {
+    type Item = CanvasEvent;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<CanvasEvent>> {
+        self.receiver.poll_next(cx)
+    }
+}
+
+impl_traits_for_canvas!(ReconnectingCanvas[S: DeviceSpec, T: MidiTransport]);
+
+impl<Spec: DeviceSpec, T: MidiTransport> Canvas for ReconnectingCanvas<Spec, T> {
+    fn bounding_box(&self) -> (u32, u32) {
+        (Spec::BOUNDING_BOX_WIDTH, Spec::BOUNDING_BOX_HEIGHT)
+    }
+
+    fn lowest_visible_brightness(&self) -> f32 {
+        1.0 / Spec::COLOR_PRECISION as f32
+    }
+
+    fn low_level_get(&self, x: u32, y: u32) -> Option<&Color> {
+        if !Spec::is_valid(x, y) {
+            return None;
+        }
+
+        self.curr_state.get(x, y)
+    }
+
+    fn low_level_get_pending(&self, x: u32, y: u32) -> Option<&Color> {
+        if !Spec::is_valid(x, y) {
+            return None;
+        }
+
+        self.new_state.get(x, y)
+    }
+
+    fn low_level_get_pending_mut(&mut self, x: u32, y: u32) -> Option<&mut Color> {
+        if !Spec::is_valid(x, y) {
+            return None;
+        }
+
+        self.dirty.mark(x, y);
+        self.new_state.get_mut(x, y)
+    }
+
+    fn low_level_get_pending_effect(&self, x: u32, y: u32) -> Option<PadEffect> {
+        if !Spec::is_valid(x, y) {
+            return None;
+        }
+
+        self.new_effects.get(x, y).copied()
+    }
+
+    fn low_level_set_pending_effect(&mut self, x: u32, y: u32, effect: PadEffect) -> Option<()> {
+        if !Spec::is_valid(x, y) {
+            return None;
+        }
+
+        self.dirty.mark(x, y);
+        *self.new_effects.get_mut(x, y)? = effect;
+        Some(())
+    }
+
+    /// The refresh interval of the device canvas. While disconnected, there's nothing to refresh
+    fn effect_refresh_interval(&self) -> Option<Duration> {
+        self.canvas.as_ref()?.effect_refresh_interval()
+    }
+
+    /// Flushes the changes to the device if it's connected. Never fails; a failed flush is
+    /// treated as a disconnect.
+    fn flush(&mut self) -> Result<(), crate::MidiError> {
+        self.poll_connection();
+
+        if let Some(canvas) = &mut self.canvas {
+            for &(x, y) in self.dirty.cells() {
+                *canvas.low_level_get_pending_mut(x, y).unwrap() =
+                    *self.new_state.get(x, y).unwrap();
+                canvas.low_level_set_pending_effect(x, y, *self.new_effects.get(x, y).unwrap());
+            }
+
+            if let Err(e) = canvas.flush() {
+                log::debug!(
+                    "Flushing failed, treating the device as disconnected: {}",
+                    e
+                );
+                self.disconnect();
+            }
+        }
+
+        for &(x, y) in self.dirty.cells() {
+            *self.curr_state.get_mut(x, y).unwrap() = *self.new_state.get(x, y).unwrap();
+            *self.curr_effects.get_mut(x, y).unwrap() = *self.new_effects.get(x, y).unwrap();
+        }
+        self.dirty.clear();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::midi_io::{poller_channel, PollerReceiver};
+use crate::{InputDevice, MidiSource};
+
+/// How far off the hardware position may be from the value of a control for it to be picked up,
+/// when the previous position of the hardware control isn't known
+const PICKUP_TOLERANCE: u8 = 2;
+
+/// Identifies a continuous control, like a knob or a fader.
+///
+/// `index` is the number of the control on the device: the knob index for the Launch Control (0-7
+/// in the upper row, 8-15 in the lower row), the fader index for the Launchpad MK2 in fader mode
+/// and the CC number for the DAW faders of the Launchpad Mini MK3. `template` is the template the
+/// control belongs to, for the Launch Control. It's always 0 for devices without templates.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ControlId {
+    pub template: u8,
+    pub index: u8,
+}
+
+impl ControlId {
+    /// The control with the given index, on a device without templates
+    pub fn new(index: u8) -> Self {
+        Self { template: 0, index }
+    }
+
+    /// The control with the given index on the given template
+    pub fn on_template(template: u8, index: u8) -> Self {
+        Self { template, index }
+    }
+}
+
+/// A raw change of a continuous control, as reported by the device
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ControlChange {
+    pub control: ControlId,
+    /// The position of the hardware control, from 0 to 127
+    pub value: u8,
+    pub timestamp: u64,
+}
+
+/// Input devices with continuous controls, like knobs or faders, that can be used in a
+/// [`ControlSurface`]
+pub trait ControlInput: InputDevice {
+    /// Extract the control change from an input message, or return `None` if the message isn't
+    /// about a continuous control
+    fn control_change(msg: &Self::Message) -> Option<ControlChange>;
+}
+
+/// How hardware movements of a continuous control change its value
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Default)]
+pub enum ControlMode {
+    /// The value follows the position of the hardware control
+    #[default]
+    Absolute,
+    /// The value changes by as much as the hardware control is moved. Values set with
+    /// [`ControlSurface::set_value`] are never overwritten by a jump to the hardware position.
+    Relative,
+}
+
+/// The value of a continuous control changed
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ControlEvent {
+    pub control: ControlId,
+    /// The new value, from 0 to 127
+    pub value: u8,
+    pub timestamp: u64,
+}
+
+impl ControlEvent {
+    /// The new value as a fraction from 0.0 to 1.0
+    pub fn fraction(&self) -> f32 {
+        self.value as f32 / 127.0
+    }
+}
+
+#[derive(Default)]
+struct ControlState {
+    values: HashMap<ControlId, u8>,
+    /// The last known hardware position of each control index. Templates share the same hardware
+    /// controls, so this isn't tracked per template
+    positions: HashMap<u8, u8>,
+    modes: HashMap<ControlId, ControlMode>,
+    default_mode: ControlMode,
+    pickup: bool,
+}
+
+impl ControlState {
+    fn mode(&self, control: ControlId) -> ControlMode {
+        self.modes
+            .get(&control)
+            .copied()
+            .unwrap_or(self.default_mode)
+    }
+
+    /// Apply a hardware change, and return the event if the value of the control changed
+    fn process(&mut self, change: ControlChange) -> Option<ControlEvent> {
+        let ControlChange {
+            control,
+            value: position,
+            timestamp,
+        } = change;
+        let prev_position = self.positions.insert(control.index, position);
+        let prev_value = self.values.get(&control).copied();
+
+        let new_value = match (self.mode(control), prev_value) {
+            (_, None) => position,
+            (ControlMode::Absolute, Some(value)) => {
+                let is_picked_up = match prev_position {
+                    // The hardware control moved onto or across the value
+                    Some(prev_position) => {
+                        let (low, high) = match prev_position < position {
+                            true => (prev_position, position),
+                            false => (position, prev_position),
+                        };
+                        (low..=high).contains(&value)
+                    }
+                    None => position.abs_diff(value) <= PICKUP_TOLERANCE,
+                };
+                match !self.pickup || is_picked_up {
+                    true => position,
+                    false => return None,
+                }
+            }
+            (ControlMode::Relative, Some(value)) => {
+                let delta = position as i16 - prev_position? as i16;
+                (value as i16 + delta).clamp(0, 127) as u8
+            }
+        };
+
+        if prev_value == Some(new_value) {
+            return None;
+        }
+        self.values.insert(control, new_value);
+        Some(ControlEvent {
+            control,
+            value: new_value,
+            timestamp,
+        })
+    }
+}
+
+/// A companion to [`Canvas`](crate::Canvas) for the continuous controls of a device: the knobs of
+/// the Launch Control, the faders of the Launchpad MK2 in fader mode and the DAW faders of the
+/// Launchpad Mini MK3.
+///
+/// The surface keeps track of the value of every control, separately for every template, and
+/// reports value changes as [`ControlEvent`]s. Hardware movements are interpreted according to
+/// the [`ControlMode`] of the control.
+///
+/// With pickup (also called soft takeover) enabled, a control in absolute mode only follows the
+/// hardware once the hardware control has been moved onto its value. That way, values don't jump
+/// after they were changed with [`Self::set_value`], or after switching to a template in which the
+/// knob has a different value.
+///
+/// The surface can open its own input connection, e.g. with [`Self::guess`]. Some platforms,
+/// Windows included, only allow one connection to a MIDI input port, though. To use the surface
+/// next to a canvas or another input of the same device there, create it with [`Self::new`]
+/// instead and feed it the messages of the existing connection with [`Self::process`]. For a
+/// canvas, that's done by wrapping the [`MidiSource`] of the canvas in one that passes every
+/// message to [`InputDevice::decode_message`] and the surface as well.
+///
+/// ```
+/// use launchy::{ChannelSource, ControlEvent, ControlId, ControlSurface, MsgPollingWrapper as _};
+///
+/// let (source, device) = ChannelSource::new();
+/// let (surface, poller) =
+///     ControlSurface::<launchy::launch_control::Input>::from_source_polling(source)?;
+/// surface.set_pickup(true);
+///
+/// // Turn the first knob of the first template
+/// device.send(0, &[0xB0, 21, 100]);
+/// let event = poller.try_recv().unwrap();
+/// assert_eq!((event.control, event.value), (ControlId::new(0), 100));
+///
+/// // After setting the value in software, the knob has to be turned past it to pick it up
+/// surface.set_value(ControlId::new(0), 20);
+/// device.send(0, &[0xB0, 21, 60]);
+/// assert_eq!(poller.try_recv(), None);
+/// device.send(0, &[0xB0, 21, 10]);
+/// assert_eq!(poller.try_recv().unwrap().value, 10);
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+pub struct ControlSurface<I: ControlInput> {
+    /// The input connection, if the surface opened one. Behind a mutex so that the surface can be
+    /// shared with the callback of another connection
+    _input: Option<Mutex<Box<dyn std::any::Any + Send>>>,
+    state: Arc<Mutex<ControlState>>,
+    _device: std::marker::PhantomData<I>,
+}
+
+impl<I> ControlSurface<I>
+where
+    I: ControlInput,
+    I::Message: 'static,
+{
+    /// Create a control surface without an input connection. Pass the messages of the device to
+    /// [`Self::process`] to update it.
+    ///
+    /// ```
+    /// use launchy::{ChannelSource, ControlId, ControlSurface, InputDevice as _};
+    /// use launchy::launch_control::Input;
+    /// use std::sync::Arc;
+    ///
+    /// let surface = Arc::new(ControlSurface::<Input>::new());
+    ///
+    /// // The one input connection to the device, which could also handle the buttons
+    /// let (source, device) = ChannelSource::new();
+    /// let (sender, events) = std::sync::mpsc::channel();
+    /// let callback_surface = surface.clone();
+    /// let _input = Input::from_source(source, move |msg| {
+    ///     if let Some(event) = callback_surface.process(&msg) {
+    ///         sender.send(event).unwrap();
+    ///     }
+    /// })?;
+    ///
+    /// device.send(0, &[0xB0, 21, 100]);
+    /// assert_eq!(events.try_recv().unwrap().value, 100);
+    /// assert_eq!(surface.value(ControlId::new(0)), Some(100));
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            _input: None,
+            state: Arc::new(Mutex::new(ControlState::default())),
+            _device: std::marker::PhantomData,
+        }
+    }
+
+    /// Update the surface with an input message of the device. Returns the event if the value of a
+    /// control changed, or `None` if it didn't or if the message isn't about a continuous control.
+    ///
+    /// This is only needed for surfaces created with [`Self::new`]; the other constructors feed
+    /// their own connection into the surface.
+    pub fn process(&self, msg: &I::Message) -> Option<ControlEvent> {
+        let change = I::control_change(msg)?;
+        self.state.lock().unwrap().process(change)
+    }
+
+    /// Create a control surface from an already established input source
+    pub fn from_source<S: MidiSource>(
+        source: S,
+        callback: impl FnMut(ControlEvent) + Send + 'static,
+    ) -> Result<Self, crate::MidiError>
+    where
+        S::Connection: Send + 'static,
+    {
+        Self::with_input(callback, |callback| I::from_source(source, callback))
+    }
+
+    /// Like [`Self::from_source`], but returns a poller instead of taking a callback
+    pub fn from_source_polling<S: MidiSource>(
+        source: S,
+    ) -> Result<(Self, ControlSurfacePoller), crate::MidiError>
+    where
+        S::Connection: Send + 'static,
+    {
+        let (sender, receiver) = poller_channel(None);
+        let surface = Self::from_source(source, move |event| sender.send(event))?;
+
+        Ok((surface, ControlSurfacePoller { receiver }))
+    }
+
+    /// Create a control surface by guessing the MIDI input port by its name
+    #[cfg(feature = "midir")]
+    pub fn guess(
+        callback: impl FnMut(ControlEvent) + Send + 'static,
+    ) -> Result<Self, crate::MidiError> {
+        Self::with_input(callback, I::guess)
+    }
+
+    /// Like [`Self::guess`], but returns a poller instead of taking a callback
+    #[cfg(feature = "midir")]
+    pub fn guess_polling() -> Result<(Self, ControlSurfacePoller), crate::MidiError> {
+        let (sender, receiver) = poller_channel(None);
+        let surface = Self::guess(move |event| sender.send(event))?;
+
+        Ok((surface, ControlSurfacePoller { receiver }))
+    }
+
+    fn with_input<H: Send + 'static>(
+        mut callback: impl FnMut(ControlEvent) + Send + 'static,
+        connect: impl FnOnce(Box<dyn FnMut(I::Message) + Send>) -> Result<H, crate::MidiError>,
+    ) -> Result<Self, crate::MidiError> {
+        let state = Arc::new(Mutex::new(ControlState::default()));
+
+        let callback_state = state.clone();
+        let input = connect(Box::new(move |msg| {
+            let Some(change) = I::control_change(&msg) else {
+                return;
+            };
+            // Don't hold the lock while running the user callback
+            let event = callback_state.lock().unwrap().process(change);
+            if let Some(event) = event {
+                (callback)(event);
+            }
+        }))?;
+
+        Ok(Self {
+            _input: Some(Mutex::new(Box::new(input))),
+            state,
+            _device: std::marker::PhantomData,
+        })
+    }
+
+    /// The current value of a control, or `None` if it hasn't been moved or set yet
+    pub fn value(&self, control: ControlId) -> Option<u8> {
+        self.state.lock().unwrap().values.get(&control).copied()
+    }
+
+    /// Set the value of a control in software, for example when loading a preset. No
+    /// [`ControlEvent`] is emitted for this change.
+    pub fn set_value(&self, control: ControlId, value: u8) {
+        assert!(value <= 127);
+        self.state.lock().unwrap().values.insert(control, value);
+    }
+
+    /// The mode of a control. Unless set with [`Self::set_mode`], that's the default mode.
+    pub fn mode(&self, control: ControlId) -> ControlMode {
+        self.state.lock().unwrap().mode(control)
+    }
+
+    pub fn set_mode(&self, control: ControlId, mode: ControlMode) {
+        self.state.lock().unwrap().modes.insert(control, mode);
+    }
+
+    /// The mode of all controls whose mode hasn't been set with [`Self::set_mode`]. Initially
+    /// [`ControlMode::Absolute`].
+    pub fn default_mode(&self) -> ControlMode {
+        self.state.lock().unwrap().default_mode
+    }
+
+    pub fn set_default_mode(&self, mode: ControlMode) {
+        self.state.lock().unwrap().default_mode = mode;
+    }
+
+    /// Whether pickup (soft takeover) is enabled for controls in absolute mode. Initially
+    /// disabled.
+    pub fn pickup(&self) -> bool {
+        self.state.lock().unwrap().pickup
+    }
+
+    pub fn set_pickup(&self, pickup: bool) {
+        self.state.lock().unwrap().pickup = pickup;
+    }
+}
+
+impl<I> Default for ControlSurface<I>
+where
+    I: ControlInput,
+    I::Message: 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Utility to be able to process events from a [`ControlSurface`] by polling
+pub struct ControlSurfacePoller {
+    receiver: PollerReceiver<ControlEvent>,
+}
+
+impl crate::MsgPollingWrapper for ControlSurfacePoller {
+    type Message = ControlEvent;
+
+    fn receiver(&self) -> &std::sync::mpsc::Receiver<Self::Message> {
+        self.receiver.receiver()
+    }
+}
+
+#[cfg(feature = "async")]
+impl futures_core::Stream for ControlSurfacePoller {
+    type Item = ControlEvent;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<ControlEvent>> {
+        self.receiver.poll_next(cx)
+    }
+}
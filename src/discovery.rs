@@ -0,0 +1,262 @@
+use log::debug;
+use midir::{MidiInput, MidiInputPort, MidiOutput, MidiOutputConnection, MidiOutputPort};
+use std::time::{Duration, Instant};
+
+use crate::protocols::query::{parse_device_query, DeviceInquiry};
+use crate::{AnyCanvas, CanvasMessage, DeviceCanvas, DeviceSpec, MidirSource, Model};
+
+/// Connection name of the temporary connections that are used to identify devices
+const DISCOVERY_CONNECTION_NAME: &str = "Launchy Discovery";
+
+/// A device that was found by [`discover`], together with the MIDI ports it's reachable on
+#[derive(Clone)]
+pub struct DiscoveredDevice {
+    model: Model,
+    device_inquiry: DeviceInquiry,
+    input_port: MidiInputPort,
+    input_port_name: String,
+    output_port: MidiOutputPort,
+    output_port_name: String,
+}
+
+impl std::fmt::Debug for DiscoveredDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DiscoveredDevice")
+            .field("model", &self.model)
+            .field("device_inquiry", &self.device_inquiry)
+            .field("input_port_name", &self.input_port_name)
+            .field("output_port_name", &self.output_port_name)
+            .finish()
+    }
+}
+
+impl DiscoveredDevice {
+    pub fn model(&self) -> Model {
+        self.model
+    }
+
+    /// The response of the device to the device inquiry, which contains e.g. the firmware revision
+    pub fn device_inquiry(&self) -> &DeviceInquiry {
+        &self.device_inquiry
+    }
+
+    pub fn input_port(&self) -> &MidiInputPort {
+        &self.input_port
+    }
+
+    pub fn input_port_name(&self) -> &str {
+        &self.input_port_name
+    }
+
+    pub fn output_port(&self) -> &MidiOutputPort {
+        &self.output_port
+    }
+
+    pub fn output_port_name(&self) -> &str {
+        &self.output_port_name
+    }
+
+    /// Connect to the output port of this device. It's up to you to choose the `Output` type that
+    /// matches [`Self::model`].
+    pub fn output<O>(&self) -> Result<O, crate::MidiError>
+    where
+        O: crate::OutputDevice<Sink = MidiOutputConnection>,
+    {
+        O::from_port(MidiOutput::new(crate::APPLICATION_NAME)?, &self.output_port)
+    }
+
+    /// Connect to the input port of this device. It's up to you to choose the `Input` type that
+    /// matches [`Self::model`].
+    #[must_use = "If not saved, the connection will be immediately dropped"]
+    pub fn input<I, F>(
+        &self,
+        user_callback: F,
+    ) -> Result<crate::InputDeviceHandler, crate::MidiError>
+    where
+        I: crate::InputDevice,
+        F: FnMut(I::Message) + Send + 'static,
+    {
+        let midi_input = MidiInput::new(crate::APPLICATION_NAME)?;
+        I::from_port(midi_input, &self.input_port, user_callback)
+    }
+
+    /// Like [`Self::input`], but returns a poller instead of taking a callback
+    #[must_use = "If not saved, the connection will be immediately dropped"]
+    pub fn input_polling<I>(
+        &self,
+    ) -> Result<crate::InputDeviceHandlerPolling<I::Message>, crate::MidiError>
+    where
+        I: crate::InputDevice,
+        I::Message: Send + 'static,
+    {
+        let midi_input = MidiInput::new(crate::APPLICATION_NAME)?;
+        I::from_port_polling(midi_input, &self.input_port)
+    }
+
+    /// Open a canvas of the matching type on this device
+    pub fn canvas(
+        &self,
+        callback: impl FnMut(CanvasMessage) + Send + 'static,
+    ) -> Result<AnyCanvas, crate::MidiError> {
+        Ok(match self.model {
+            Model::S => AnyCanvas::S(self.device_canvas(callback)?),
+            Model::Mini => AnyCanvas::Mini(self.device_canvas(callback)?),
+            Model::Mk2 => AnyCanvas::Mk2(self.device_canvas(callback)?),
+            Model::MiniMk3 => AnyCanvas::MiniMk3(self.device_canvas(callback)?),
+            Model::X => AnyCanvas::X(self.device_canvas(callback)?),
+            Model::Pro => AnyCanvas::Pro(self.device_canvas(callback)?),
+            Model::ProMk3 => AnyCanvas::ProMk3(self.device_canvas(callback)?),
+        })
+    }
+
+    /// Like [`Self::canvas`], but returns a poller instead of taking a callback
+    pub fn canvas_polling(
+        &self,
+    ) -> Result<(AnyCanvas, crate::DeviceCanvasPoller), crate::MidiError> {
+        let (sender, receiver) = crate::midi_io::poller_channel(None);
+        let canvas = self.canvas(move |msg| sender.send(msg))?;
+
+        Ok((canvas, crate::DeviceCanvasPoller { receiver }))
+    }
+
+    fn device_canvas<Spec: DeviceSpec>(
+        &self,
+        callback: impl FnMut(CanvasMessage) + Send + 'static,
+    ) -> Result<DeviceCanvas<Spec>, crate::MidiError> {
+        use crate::OutputDevice as _;
+
+        let source = MidirSource::new(
+            MidiInput::new(crate::APPLICATION_NAME)?,
+            self.input_port.clone(),
+        );
+        let sink = MidiOutput::new(crate::APPLICATION_NAME)?.connect(
+            &self.output_port,
+            Spec::Output::<MidiOutputConnection>::MIDI_CONNECTION_NAME,
+        )?;
+        DeviceCanvas::from_connections(source, sink, callback)
+    }
+}
+
+/// Find all connected Launchpads, by sending a device inquiry to every MIDI output port and
+/// matching the responses to the input ports they arrive on.
+///
+/// Unlike the `guess` constructors, this finds every device, including multiple devices of the
+/// same model. Devices that don't respond to device inquiries, i.e. the Launchpad MIDI 1 and the
+/// Launch Control, can't be discovered this way.
+///
+/// ```no_run
+/// use launchy::{Canvas as _, Color};
+///
+/// for device in launchy::discover()? {
+///     println!("Found a {:?} on {}", device.model(), device.output_port_name());
+///
+///     let mut canvas = device.canvas(|_msg| {})?;
+///     for pad in canvas.iter() {
+///         canvas[pad] = Color::GREEN;
+///     }
+///     canvas.flush()?;
+/// }
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+pub fn discover() -> Result<Vec<DiscoveredDevice>, crate::MidiError> {
+    discover_with_timeout(Duration::from_millis(200))
+}
+
+/// Like [`discover`], but with a custom duration to wait for each output port to respond
+pub fn discover_with_timeout(timeout: Duration) -> Result<Vec<DiscoveredDevice>, crate::MidiError> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+
+    // Listen on all input ports at once, tagging every message with the index of its port
+    let input_ports = named_ports(&MidiInput::new(crate::APPLICATION_NAME)?);
+    let mut input_connections = Vec::new();
+    for (index, (port, name)) in input_ports.iter().enumerate() {
+        let sender = sender.clone();
+        let midi_input = MidiInput::new(crate::APPLICATION_NAME)?;
+        let callback = move |_timestamp: u64, data: &[u8], _: &mut ()| {
+            let _ = sender.send((index, data.to_vec()));
+        };
+        match midi_input.connect(port, DISCOVERY_CONNECTION_NAME, callback, ()) {
+            Ok(connection) => input_connections.push(connection),
+            Err(e) => debug!("Couldn't listen on MIDI input port '{}': {}", name, e),
+        }
+    }
+
+    let mut devices = Vec::new();
+    for (output_port, output_port_name) in named_ports(&MidiOutput::new(crate::APPLICATION_NAME)?) {
+        let midi_output = MidiOutput::new(crate::APPLICATION_NAME)?;
+        let mut connection = match midi_output.connect(&output_port, DISCOVERY_CONNECTION_NAME) {
+            Ok(connection) => connection,
+            Err(e) => {
+                debug!(
+                    "Couldn't connect to MIDI output port '{}': {}",
+                    output_port_name, e
+                );
+                continue;
+            }
+        };
+
+        // Discard any leftover responses to the previous inquiry
+        while receiver.try_recv().is_ok() {}
+
+        debug!("Sending device inquiry to '{}'", output_port_name);
+        if let Err(e) = connection.send(&[240, 126, 127, 6, 1, 247]) {
+            debug!(
+                "Couldn't send to MIDI output port '{}': {}",
+                output_port_name, e
+            );
+            continue;
+        }
+
+        let deadline = Instant::now() + timeout;
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            let (input_index, data) = match receiver.recv_timeout(remaining) {
+                Ok(message) => message,
+                Err(_) => break,
+            };
+            let device_inquiry = match parse_device_query(&data) {
+                Some(device_inquiry) => device_inquiry,
+                None => continue,
+            };
+            let model = match Model::from_device_inquiry(&device_inquiry) {
+                Some(model) => model,
+                None => continue,
+            };
+            // Further ports like the DAW ports of the MK3 generation answer device inquiries too
+            if !model.is_device_port(&output_port_name) {
+                debug!(
+                    "Skipping secondary port '{}' of a {:?}",
+                    output_port_name, model
+                );
+                break;
+            }
+
+            let (input_port, input_port_name) = input_ports[input_index].clone();
+            debug!(
+                "Found {:?} on '{}' / '{}'",
+                model, input_port_name, output_port_name
+            );
+            devices.push(DiscoveredDevice {
+                model,
+                device_inquiry,
+                input_port,
+                input_port_name,
+                output_port,
+                output_port_name,
+            });
+            break;
+        }
+    }
+
+    Ok(devices)
+}
+
+fn named_ports<T: midir::MidiIO>(midi_io: &T) -> Vec<(T::Port, String)> {
+    midi_io
+        .ports()
+        .into_iter()
+        .filter_map(|port| {
+            let name = midi_io.port_name(&port).ok()?;
+            Some((port, name))
+        })
+        .collect()
+}
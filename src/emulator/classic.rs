@@ -0,0 +1,464 @@
+use super::{EmulatedDevice, Emulator, TextScroll};
+use crate::protocols::double_buffering::{Brightness, Buffer, Color};
+use crate::protocols::Button80 as Button;
+
+/// LED state that is split into two buffers, as found on the Launchpad S, Launchpad Mini,
+/// Launchpad MIDI 1 and Launch Control. See [`crate::s::Output`] for an explanation of double
+/// buffering.
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct DoubleBufferedLeds {
+    buffers: [Vec<Color>; 2],
+    edited_buffer: Buffer,
+    displayed_buffer: Buffer,
+    flashing: bool,
+}
+
+impl DoubleBufferedLeds {
+    pub(super) fn new(num_leds: usize) -> Self {
+        Self {
+            buffers: [vec![Color::OFF; num_leds], vec![Color::OFF; num_leds]],
+            edited_buffer: Buffer::A,
+            displayed_buffer: Buffer::A,
+            flashing: false,
+        }
+    }
+
+    /// The buffer that incoming LED data is written to
+    pub fn edited_buffer(&self) -> Buffer {
+        self.edited_buffer
+    }
+
+    /// The buffer that is currently visible
+    pub fn displayed_buffer(&self) -> Buffer {
+        self.displayed_buffer
+    }
+
+    /// Whether the displayed buffer is flipped continually to make LEDs flash
+    pub fn is_flashing(&self) -> bool {
+        self.flashing
+    }
+
+    /// Flip the displayed buffer if flashing is enabled. A real device does this every 280ms.
+    pub fn advance_flash(&mut self) {
+        if self.flashing {
+            self.displayed_buffer = other_buffer(self.displayed_buffer);
+        }
+    }
+
+    pub(super) fn get(&self, buffer: Buffer, index: usize) -> Color {
+        self.buffers[buffer as usize][index]
+    }
+
+    pub(super) fn displayed(&self, index: usize) -> Color {
+        self.get(self.displayed_buffer, index)
+    }
+
+    /// Write a color code, as produced by `make_color_code`
+    pub(super) fn write(&mut self, index: usize, code: u8) {
+        let color = Color::new(code & 0b11, (code >> 4) & 0b11);
+        let copy = code & 0b0100 != 0;
+        let clear = code & 0b1000 != 0;
+
+        let edited = self.edited_buffer as usize;
+        let other = other_buffer(self.edited_buffer) as usize;
+
+        self.buffers[edited][index] = color;
+        if copy {
+            self.buffers[other][index] = color;
+        } else if clear {
+            self.buffers[other][index] = Color::OFF;
+        }
+    }
+
+    /// Apply a double buffering control byte (`0b001CFUDD`)
+    pub(super) fn control(&mut self, byte: u8) {
+        self.displayed_buffer = buffer_from_bit(byte & 0b1);
+        self.edited_buffer = buffer_from_bit((byte >> 2) & 0b1);
+        self.flashing = byte & 0b1000 != 0;
+
+        if byte & 0b10000 != 0 {
+            let displayed = self.buffers[self.displayed_buffer as usize].clone();
+            self.buffers[self.edited_buffer as usize] = displayed;
+        }
+    }
+
+    pub(super) fn reset(&mut self) {
+        *self = Self::new(self.buffers[0].len());
+    }
+}
+
+fn other_buffer(buffer: Buffer) -> Buffer {
+    match buffer {
+        Buffer::A => Buffer::B,
+        Buffer::B => Buffer::A,
+    }
+}
+
+fn buffer_from_bit(bit: u8) -> Buffer {
+    match bit {
+        0 => Buffer::A,
+        _ => Buffer::B,
+    }
+}
+
+/// How the grid buttons are mapped to MIDI notes
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum GridMappingMode {
+    XY,
+    DrumRack,
+}
+
+impl GridMappingMode {
+    /// The note of a grid button. In XY mode, the high nibble is the row and the low nibble the
+    /// column. In drum rack mode, the left and the right half of the grid each count upwards from
+    /// the bottom left in rows of four, followed by the scene launch buttons from top to bottom.
+    fn note(self, x: u8, y: u8) -> u8 {
+        match (self, x) {
+            (Self::XY, _) => y * 16 + x,
+            (Self::DrumRack, 8) => 100 + y,
+            (Self::DrumRack, 0..=3) => 36 + (7 - y) * 4 + x,
+            (Self::DrumRack, _) => 68 + (7 - y) * 4 + (x - 4),
+        }
+    }
+
+    /// The grid button with the given note, as `(x, y)`. The inverse of [`Self::note`].
+    fn button(self, note: u8) -> Option<(u8, u8)> {
+        match self {
+            Self::XY if note % 16 <= 8 && note / 16 <= 7 => Some((note % 16, note / 16)),
+            Self::XY => None,
+            Self::DrumRack => match note {
+                36..=67 => Some(((note - 36) % 4, 7 - (note - 36) / 4)),
+                68..=99 => Some((4 + (note - 68) % 4, 7 - (note - 68) / 4)),
+                100..=107 => Some((8, note - 100)),
+                _ => None,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+enum ClassicModel {
+    S,
+    Mini,
+    Midi1,
+}
+
+/// An emulated Launchpad S, Launchpad Mini or Launchpad MIDI 1. These devices share a single
+/// protocol, with red-green LEDs and double buffering.
+///
+/// ```
+/// use launchy::emulator::{Classic, Emulator};
+/// use launchy::s::{Button, Color, DoubleBufferingBehavior, Output};
+/// use launchy::OutputDevice as _;
+///
+/// let emulator = Emulator::new(Classic::launchpad_s());
+/// let mut output = Output::from_connection(emulator.sink())?;
+///
+/// // Write to the invisible buffer B, while displaying buffer A
+/// output.control_double_buffering(launchy::s::DoubleBuffering {
+///     copy: false,
+///     flash: false,
+///     edited_buffer: launchy::s::Buffer::B,
+///     displayed_buffer: launchy::s::Buffer::A,
+/// })?;
+/// output.set_button(Button::UP, Color::RED, DoubleBufferingBehavior::None)?;
+/// assert_eq!(emulator.device().led(Button::UP), Color::OFF);
+///
+/// // Rapid updates start at the top left of the grid
+/// output.set_button_rapid(
+///     Color::GREEN,
+///     DoubleBufferingBehavior::Copy,
+///     Color::AMBER,
+///     DoubleBufferingBehavior::Copy,
+/// )?;
+/// assert_eq!(emulator.device().led(Button::grid(1, 0)), Color::AMBER);
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct Classic {
+    model: ClassicModel,
+    leds: DoubleBufferedLeds,
+    rapid_update_cursor: Option<usize>,
+    duty_cycle: (u8, u8),
+    all_leds_brightness: Option<Brightness>,
+    grid_mapping_mode: GridMappingMode,
+    text_scroll: Option<TextScroll<Color>>,
+    unrecognized: Vec<Vec<u8>>,
+}
+
+impl Classic {
+    fn new(model: ClassicModel) -> Self {
+        Self {
+            model,
+            leds: DoubleBufferedLeds::new(81),
+            rapid_update_cursor: None,
+            duty_cycle: (1, 5),
+            all_leds_brightness: None,
+            grid_mapping_mode: GridMappingMode::XY,
+            text_scroll: None,
+            unrecognized: Vec::new(),
+        }
+    }
+
+    pub fn launchpad_s() -> Self {
+        Self::new(ClassicModel::S)
+    }
+
+    pub fn launchpad_mini() -> Self {
+        Self::new(ClassicModel::Mini)
+    }
+
+    pub fn launchpad_midi_1() -> Self {
+        Self::new(ClassicModel::Midi1)
+    }
+
+    /// The color that is currently displayed on `button`
+    pub fn led(&self, button: Button) -> Color {
+        self.leds.displayed(led_index(button))
+    }
+
+    /// The color of `button` in a specific buffer
+    pub fn buffered_led(&self, buffer: Buffer, button: Button) -> Color {
+        self.leds.get(buffer, led_index(button))
+    }
+
+    pub fn leds(&self) -> &DoubleBufferedLeds {
+        &self.leds
+    }
+
+    pub fn leds_mut(&mut self) -> &mut DoubleBufferedLeds {
+        &mut self.leds
+    }
+
+    /// The current duty cycle, as `(numerator, denominator)`
+    pub fn duty_cycle(&self) -> (u8, u8) {
+        self.duty_cycle
+    }
+
+    /// The brightness at which all LEDs were turned on, if the device is in that diagnostic state
+    pub fn all_leds_brightness(&self) -> Option<Brightness> {
+        self.all_leds_brightness
+    }
+
+    pub fn grid_mapping_mode(&self) -> GridMappingMode {
+        self.grid_mapping_mode
+    }
+
+    pub fn text_scroll(&self) -> Option<&TextScroll<Color>> {
+        self.text_scroll.as_ref()
+    }
+
+    /// Messages that couldn't be interpreted
+    pub fn unrecognized(&self) -> &[Vec<u8>] {
+        &self.unrecognized
+    }
+
+    fn reset(&mut self) {
+        self.leds.reset();
+        self.duty_cycle = (1, 5);
+        self.all_leds_brightness = None;
+        self.grid_mapping_mode = GridMappingMode::XY;
+    }
+
+    fn write_rapid(&mut self, code: u8) {
+        let cursor = self.rapid_update_cursor.get_or_insert(0);
+        let index = *cursor;
+        *cursor += 1;
+
+        // Grid in left-to-right, top-to-bottom order, then scene launch buttons, then the top
+        // control buttons. Overflowing data is ignored
+        let button = match index {
+            0..=63 => Button::grid(index as u8 % 8, index as u8 / 8),
+            64..=71 => Button::grid(8, index as u8 - 64),
+            72..=79 => Button::control(index as u8 - 72),
+            _ => return,
+        };
+        self.leds.write(led_index(button), code);
+    }
+
+    fn receive_control(&mut self, value: u8) -> bool {
+        match value {
+            0 => self.reset(),
+            1 => self.grid_mapping_mode = GridMappingMode::XY,
+            2 => self.grid_mapping_mode = GridMappingMode::DrumRack,
+            32..=63 => self.leds.control(value),
+            125..=127 => {
+                self.reset();
+                self.all_leds_brightness = Some(match value {
+                    125 => Brightness::Low,
+                    126 => Brightness::Medium,
+                    _ => Brightness::Full,
+                });
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn inquiry_code(&self) -> Option<[u8; 4]> {
+        match self.model {
+            ClassicModel::S => Some(crate::Model::S.device_inquiry_code()),
+            ClassicModel::Mini => Some(crate::Model::Mini.device_inquiry_code()),
+            // The original Launchpad doesn't know about SysEx inquiries
+            ClassicModel::Midi1 => None,
+        }
+    }
+}
+
+/// Position of a button in the LED buffers
+fn led_index(button: Button) -> usize {
+    button.abs_y() as usize * 9 + button.abs_x() as usize
+}
+
+impl EmulatedDevice for Classic {
+    type Button = Button;
+
+    fn receive(&mut self, bytes: &[u8], replies: &mut Vec<Vec<u8>>) {
+        if let [0x92, code1, code2] = *bytes {
+            self.write_rapid(code1);
+            self.write_rapid(code2);
+            return;
+        }
+        self.rapid_update_cursor = None;
+
+        if let Some(code) = self.inquiry_code() {
+            if let Some(reply) = super::device_inquiry_reply(bytes, code, [0, 0, 6, 4]) {
+                replies.push(reply);
+                return;
+            }
+            if bytes == [240, 0, 32, 41, 0, 112, 247] {
+                replies.push(super::version_inquiry_reply(
+                    [0, 0, 0, 1, 4],
+                    [0, 0, 0, 6, 4],
+                    2,
+                ));
+                return;
+            }
+        }
+
+        let recognized = match *bytes {
+            [0x90, key, code] => match self.grid_mapping_mode.button(key) {
+                Some((x, y)) => {
+                    self.leds.write(led_index(Button::grid(x, y)), code);
+                    true
+                }
+                None => false,
+            },
+            [0xB0, index @ 104..=111, code] => {
+                self.leds
+                    .write(led_index(Button::control(index - 104)), code);
+                true
+            }
+            [0xB0, 0, value] => self.receive_control(value),
+            [0xB0, 30, value] => {
+                self.duty_cycle = (value / 16 + 1, value % 16 + 3);
+                true
+            }
+            [0xB0, 31, value] => {
+                self.duty_cycle = (value / 16 + 9, value % 16 + 3);
+                true
+            }
+            [240, 0, 32, 41, 9, code, ref text @ .., 247] => {
+                self.text_scroll = match text {
+                    [] => None,
+                    _ => Some(TextScroll {
+                        text: text.to_vec(),
+                        color: Color::new(code & 0b11, (code >> 4) & 0b11),
+                        looping: code & 0b100_0000 != 0,
+                    }),
+                };
+                true
+            }
+            [240, 0, 32, 41, 2, 24, 34, mode @ 0..=1, 247] => {
+                self.grid_mapping_mode = match mode {
+                    0 => GridMappingMode::XY,
+                    _ => GridMappingMode::DrumRack,
+                };
+                true
+            }
+            _ => false,
+        };
+
+        if !recognized {
+            self.unrecognized.push(bytes.to_vec());
+        }
+    }
+
+    fn button_message(&self, button: Button, pressed: bool) -> Vec<u8> {
+        let velocity = if pressed { 127 } else { 0 };
+        match button {
+            Button::GridButton { x, y } => {
+                vec![0x90, self.grid_mapping_mode.note(x, y), velocity]
+            }
+            Button::ControlButton { index } => vec![0xB0, 104 + index, velocity],
+        }
+    }
+}
+
+impl Emulator<Classic> {
+    /// Finish the ongoing text scroll, or complete one loop of it. Returns whether a text scroll
+    /// was in progress.
+    pub fn finish_text_scroll(&self) -> bool {
+        let was_scrolling = {
+            let mut device = self.device();
+            match &device.text_scroll {
+                Some(scroll) if scroll.looping => true,
+                Some(_) => device.text_scroll.take().is_some(),
+                None => false,
+            }
+        };
+
+        if was_scrolling {
+            self.emit(&[0xB0, 0, 3]);
+        }
+        was_scrolling
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_notes_follow_the_mapping_mode() {
+        let mut device = Classic::launchpad_s();
+        let mut replies = Vec::new();
+        let top_left = Button::grid(0, 0);
+        let bottom_right = Button::grid(7, 7);
+        let scene = Button::grid(8, 1);
+
+        assert_eq!(device.button_message(top_left, true), [0x90, 0, 127]);
+        assert_eq!(device.button_message(scene, true), [0x90, 24, 127]);
+
+        device.receive(&[0xB0, 0, 2], &mut replies);
+        assert_eq!(device.grid_mapping_mode(), GridMappingMode::DrumRack);
+        assert_eq!(device.button_message(top_left, true), [0x90, 64, 127]);
+        assert_eq!(device.button_message(bottom_right, false), [0x90, 71, 0]);
+        assert_eq!(
+            device.button_message(Button::grid(4, 7), true),
+            [0x90, 68, 127]
+        );
+        assert_eq!(device.button_message(scene, true), [0x90, 101, 127]);
+
+        // LED writes are mapped the same way
+        device.receive(&[0x90, 64, 0b11], &mut replies);
+        device.receive(&[0x90, 71, 0b11_0000], &mut replies);
+        device.receive(&[0x90, 101, 0b11_0011], &mut replies);
+        assert_eq!(device.led(top_left), Color::RED);
+        assert_eq!(device.led(bottom_right), Color::GREEN);
+        assert_eq!(device.led(scene), Color::AMBER);
+        assert!(device.unrecognized().is_empty());
+
+        // Every grid button maps to a note and back in both modes
+        for mode in [GridMappingMode::XY, GridMappingMode::DrumRack] {
+            for x in 0..=8 {
+                for y in 0..=7 {
+                    assert_eq!(mode.button(mode.note(x, y)), Some((x, y)));
+                }
+            }
+        }
+
+        device.receive(&[0xB0, 0, 1], &mut replies);
+        assert_eq!(device.button_message(top_left, true), [0x90, 0, 127]);
+    }
+}
@@ -0,0 +1,205 @@
+use super::{DoubleBufferedLeds, EmulatedDevice, Emulator};
+use crate::launch_control::{Button, Knob, Template};
+use crate::protocols::double_buffering::{Brightness, Buffer, Color};
+
+/// An emulated Launch Control
+///
+/// Like on the real device, LED messages sent through note and control change messages only take
+/// effect if they are addressed to the currently selected template, while the SysEx
+/// [`light_multiple`](crate::launch_control::Output::light_multiple) works on any template.
+///
+/// ```
+/// use launchy::emulator::{Emulator, LaunchControl};
+/// use launchy::launch_control::{Button, Color, DoubleBufferingBehavior, Input, Message, Output};
+/// use launchy::{InputDevice as _, MsgPollingWrapper as _, OutputDevice as _};
+///
+/// let emulator = Emulator::new(LaunchControl::new());
+/// let mut output = Output::from_connection(emulator.sink())?;
+/// let input = Input::from_source_polling(emulator.source())?;
+///
+/// output.light(3, Button::Up, Color::GREEN, DoubleBufferingBehavior::Copy)?;
+/// assert_eq!(emulator.device().led(3, Button::Up), Color::GREEN);
+///
+/// emulator.switch_template(3);
+/// emulator.press(Button::pad(5));
+/// assert!(matches!(
+///     input.try_recv(),
+///     Some(Message::TemplateChanged { template, .. }) if template == 3.into(),
+/// ));
+/// assert!(matches!(
+///     input.try_recv(),
+///     Some(Message::Press { template, button, .. }) if template == 3.into() && button == Button::pad(5),
+/// ));
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct LaunchControl {
+    template: Template,
+    leds: Vec<DoubleBufferedLeds>,
+    all_leds_brightness: [Option<Brightness>; 16],
+    unrecognized: Vec<Vec<u8>>,
+}
+
+impl Default for LaunchControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LaunchControl {
+    pub fn new() -> Self {
+        Self {
+            template: Template(0),
+            leds: vec![DoubleBufferedLeds::new(12); 16],
+            all_leds_brightness: [None; 16],
+            unrecognized: Vec::new(),
+        }
+    }
+
+    /// The currently selected template
+    pub fn template(&self) -> Template {
+        self.template
+    }
+
+    /// The color that is currently displayed on `button` in `template`
+    pub fn led(&self, template: impl Into<Template>, button: Button) -> Color {
+        self.leds[template.into().0 as usize].displayed(button.as_index() as usize)
+    }
+
+    /// The color of `button` in `template`, in a specific buffer
+    pub fn buffered_led(
+        &self,
+        template: impl Into<Template>,
+        buffer: Buffer,
+        button: Button,
+    ) -> Color {
+        self.leds[template.into().0 as usize].get(buffer, button.as_index() as usize)
+    }
+
+    pub fn leds(&self, template: impl Into<Template>) -> &DoubleBufferedLeds {
+        &self.leds[template.into().0 as usize]
+    }
+
+    pub fn leds_mut(&mut self, template: impl Into<Template>) -> &mut DoubleBufferedLeds {
+        &mut self.leds[template.into().0 as usize]
+    }
+
+    /// The brightness at which all LEDs of `template` were turned on, if the template is in that
+    /// diagnostic state
+    pub fn all_leds_brightness(&self, template: impl Into<Template>) -> Option<Brightness> {
+        self.all_leds_brightness[template.into().0 as usize]
+    }
+
+    /// Messages that couldn't be interpreted
+    pub fn unrecognized(&self) -> &[Vec<u8>] {
+        &self.unrecognized
+    }
+
+    fn receive_short(&mut self, status: u8, number: u8, value: u8) -> bool {
+        let template = status as usize & 0x0F;
+        if template != self.template.0 as usize {
+            // Silently ignored by the device
+            return true;
+        }
+
+        let leds = &mut self.leds[template];
+        match (status & 0xF0, number) {
+            (0x90, _) => match decode_pad(number) {
+                Some(index) => leds.write(index, value),
+                None => return false,
+            },
+            (0x80, _) => match decode_pad(number) {
+                Some(index) => leds.write(index, 0),
+                None => return false,
+            },
+            (0xB0, 114..=117) => leds.write(number as usize - 114 + 8, value),
+            (0xB0, 0) => match value {
+                0 => {
+                    leds.reset();
+                    self.all_leds_brightness[template] = None;
+                }
+                32..=63 => leds.control(value),
+                125..=127 => {
+                    leds.reset();
+                    self.all_leds_brightness[template] = Some(match value {
+                        125 => Brightness::Low,
+                        126 => Brightness::Medium,
+                        _ => Brightness::Full,
+                    });
+                }
+                _ => return false,
+            },
+            _ => return false,
+        }
+        true
+    }
+}
+
+fn decode_pad(code: u8) -> Option<usize> {
+    match code {
+        9..=12 => Some(code as usize - 9),
+        25..=28 => Some(code as usize - 25 + 4),
+        _ => None,
+    }
+}
+
+impl EmulatedDevice for LaunchControl {
+    type Button = Button;
+
+    fn receive(&mut self, bytes: &[u8], _replies: &mut Vec<Vec<u8>>) {
+        let recognized = match *bytes {
+            [status @ (0x80..=0x9F | 0xB0..=0xBF), number, value] => {
+                self.receive_short(status, number, value)
+            }
+            [240, 0, 32, 41, 2, 10, 120, template @ 0..=15, ref payload @ .., 247] => {
+                let leds = &mut self.leds[template as usize];
+                for chunk in payload.chunks_exact(2).filter(|chunk| chunk[0] < 12) {
+                    leds.write(chunk[0] as usize, chunk[1]);
+                }
+                payload.len() % 2 == 0
+            }
+            [240, 0, 32, 41, 2, 10, 119, template @ 0..=15, 247] => {
+                self.template = Template(template);
+                true
+            }
+            _ => false,
+        };
+
+        if !recognized {
+            self.unrecognized.push(bytes.to_vec());
+        }
+    }
+
+    fn button_message(&self, button: Button, pressed: bool) -> Vec<u8> {
+        let template = self.template.0;
+        match (button, pressed) {
+            (Button::Pad(_), true) => vec![0x90 + template, button.code(), 127],
+            (Button::Pad(_), false) => vec![0x80 + template, button.code(), 0],
+            (_, pressed) => vec![
+                0xB0 + template,
+                button.code(),
+                if pressed { 127 } else { 0 },
+            ],
+        }
+    }
+}
+
+impl Emulator<LaunchControl> {
+    /// Simulate the user selecting a different template. Returns whether the message was
+    /// delivered.
+    pub fn switch_template(&self, template: impl Into<Template>) -> bool {
+        let template = template.into();
+        self.device().template = template;
+        self.emit(&[240, 0, 32, 41, 2, 10, 119, template.0, 247])
+    }
+
+    /// Simulate the user turning a knob. Returns whether the message was delivered.
+    pub fn turn_knob(&self, knob: Knob, value: u8) -> bool {
+        let template = self.device().template.0;
+        let number = match knob.0 {
+            index @ 0..=7 => 21 + index,
+            index => 41 + index - 8,
+        };
+        self.emit(&[0xB0 + template, number, value])
+    }
+}
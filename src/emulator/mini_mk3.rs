@@ -0,0 +1,249 @@
+use super::{EmulatedDevice, Led, LedColor, TextScroll};
+use crate::mini_mk3::{Layout, SleepMode};
+use crate::protocols::Button80 as Button;
+
+/// A text scroll on the Launchpad Mini MK3, which additionally has a speed
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct MiniMk3TextScroll {
+    pub scroll: TextScroll<LedColor>,
+    pub speed: u8,
+}
+
+/// An emulated Launchpad Mini MK3
+///
+/// ```
+/// use launchy::emulator::{Emulator, Led, LedColor, MiniMk3};
+/// use launchy::mini_mk3::{Button, ButtonStyle, Input, Message, Output, PaletteColor};
+/// use launchy::{InputDevice as _, MsgPollingWrapper as _, OutputDevice as _};
+///
+/// let emulator = Emulator::new(MiniMk3::new());
+/// let mut output = Output::from_connection(emulator.sink())?;
+/// let input = Input::from_source_polling(emulator.source())?;
+///
+/// output.set_buttons(&[(
+///     Button::grid(3, 3),
+///     ButtonStyle::flash2(PaletteColor::RED, PaletteColor::BLUE),
+/// )])?;
+/// assert_eq!(
+///     emulator.device().led(Button::grid(3, 3)),
+///     Led::Flash { base: LedColor::Palette(45), color: 5 },
+/// );
+///
+/// output.request_brightness()?;
+/// assert_eq!(input.try_recv(), Some(Message::Brightness(127)));
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct MiniMk3 {
+    /// Includes the logo LED at the position of the missing top right button
+    leds: [Led; 81],
+    layout: Layout,
+    brightness: u8,
+    sleep_mode: SleepMode,
+    text_scroll: Option<MiniMk3TextScroll>,
+    clock_ticks: u64,
+    unrecognized: Vec<Vec<u8>>,
+}
+
+impl Default for MiniMk3 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MiniMk3 {
+    pub fn new() -> Self {
+        Self {
+            leds: [Led::default(); 81],
+            layout: Layout::Live,
+            brightness: 127,
+            sleep_mode: SleepMode::Wake,
+            text_scroll: None,
+            clock_ticks: 0,
+            unrecognized: Vec::new(),
+        }
+    }
+
+    pub fn led(&self, button: Button) -> Led {
+        let (x, y) = abs_position(button);
+        self.leds[y * 9 + x]
+    }
+
+    /// The Novation logo LED in the top right corner
+    pub fn logo(&self) -> Led {
+        self.leds[8]
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    pub fn brightness(&self) -> u8 {
+        self.brightness
+    }
+
+    pub fn sleep_mode(&self) -> SleepMode {
+        self.sleep_mode
+    }
+
+    pub fn text_scroll(&self) -> Option<&MiniMk3TextScroll> {
+        self.text_scroll.as_ref()
+    }
+
+    /// Number of clock ticks that were received
+    pub fn clock_ticks(&self) -> u64 {
+        self.clock_ticks
+    }
+
+    /// Messages that couldn't be interpreted
+    pub fn unrecognized(&self) -> &[Vec<u8>] {
+        &self.unrecognized
+    }
+
+    fn led_mut(&mut self, key: u8) -> Option<&mut Led> {
+        let (row, column) = (key / 10, key % 10);
+        if !(1..=9).contains(&row) || !(1..=9).contains(&column) {
+            return None;
+        }
+        Some(&mut self.leds[(9 - row as usize) * 9 + (column as usize - 1)])
+    }
+
+    /// Light a button, `channel` being the light mode (plain, flash, pulse)
+    fn set_with_mode(&mut self, key: u8, channel: u8, color: u8) -> bool {
+        match self.led_mut(key) {
+            Some(led) => {
+                *led = match channel {
+                    0 => Led::Plain(LedColor::Palette(color)),
+                    1 => Led::flash(*led, color),
+                    _ => Led::Pulse(color),
+                };
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Parse the LED specifications of a "set buttons" message
+    fn set_buttons(&mut self, mut payload: &[u8]) -> bool {
+        while let [kind, key, ref rest @ ..] = *payload {
+            let (led, rest) = match (kind, rest) {
+                (0, &[color, ref rest @ ..]) => (Led::Plain(LedColor::Palette(color)), rest),
+                (1, &[color_b, color_a, ref rest @ ..]) => (
+                    Led::Flash {
+                        base: LedColor::Palette(color_b),
+                        color: color_a,
+                    },
+                    rest,
+                ),
+                (2, &[color, ref rest @ ..]) => (Led::Pulse(color), rest),
+                (3, &[r, g, b, ref rest @ ..]) => (Led::Plain(LedColor::Rgb(r, g, b)), rest),
+                _ => return false,
+            };
+            if let Some(target) = self.led_mut(key) {
+                *target = led;
+            }
+            payload = rest;
+        }
+        payload.is_empty()
+    }
+
+    fn scroll_text(&mut self, payload: &[u8]) -> bool {
+        self.text_scroll = match *payload {
+            [] => None,
+            [looping, speed, 0, color, ref text @ ..] => {
+                Some((looping, speed, LedColor::Palette(color), text))
+            }
+            [looping, speed, 1, r, g, b, ref text @ ..] => {
+                Some((looping, speed, LedColor::Rgb(r, g, b), text))
+            }
+            _ => return false,
+        }
+        .map(|(looping, speed, color, text)| MiniMk3TextScroll {
+            scroll: TextScroll {
+                text: text.to_vec(),
+                color,
+                looping: looping != 0,
+            },
+            speed,
+        });
+        true
+    }
+
+    fn receive_sysex(&mut self, command: u8, payload: &[u8], replies: &mut Vec<Vec<u8>>) -> bool {
+        let reply = |value: u8| vec![240, 0, 32, 41, 2, 13, command, value, 247];
+
+        match (command, payload) {
+            (3, _) => return self.set_buttons(payload),
+            (7, _) => return self.scroll_text(payload),
+            (8, []) => replies.push(reply(self.brightness)),
+            (8, &[brightness]) => self.brightness = brightness,
+            (9, []) => replies.push(reply(self.sleep_mode as u8)),
+            (9, &[sleep_mode]) => {
+                self.sleep_mode = match sleep_mode {
+                    0 => SleepMode::Sleep,
+                    _ => SleepMode::Wake,
+                }
+            }
+            (14, []) => replies.push(reply(self.layout as u8)),
+            (14, &[layout @ 0..=1]) => self.layout = layout.into(),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Absolute position of a button, where the right-hand side buttons are in column 8
+fn abs_position(button: Button) -> (usize, usize) {
+    match button {
+        Button::ControlButton { index } if index >= 8 => (8, index as usize - 7),
+        button => (button.abs_x() as usize, button.abs_y() as usize),
+    }
+}
+
+impl EmulatedDevice for MiniMk3 {
+    type Button = Button;
+
+    fn receive(&mut self, bytes: &[u8], replies: &mut Vec<Vec<u8>>) {
+        if let Some(reply) = super::device_inquiry_reply(
+            bytes,
+            crate::Model::MiniMk3.device_inquiry_code(),
+            [0, 4, 0, 7],
+        ) {
+            // According to the documentation, the device answers with both the application and
+            // the bootloader version
+            replies.push(reply);
+            replies.push(vec![
+                240, 126, 0, 6, 2, 0, 32, 41, 19, 17, 0, 0, 0, 1, 7, 0, 247,
+            ]);
+            return;
+        }
+
+        let recognized = match *bytes {
+            [status @ (0x90..=0x92 | 0xB0..=0xB2), key, color] => {
+                self.set_with_mode(key, status & 0x0F, color)
+            }
+            [248, 0, 0] => {
+                self.clock_ticks += 1;
+                true
+            }
+            [240, 0, 32, 41, 2, 13, command, ref payload @ .., 247] => {
+                self.receive_sysex(command, payload, replies)
+            }
+            _ => false,
+        };
+
+        if !recognized {
+            self.unrecognized.push(bytes.to_vec());
+        }
+    }
+
+    fn button_message(&self, button: Button, pressed: bool) -> Vec<u8> {
+        let velocity = if pressed { 127 } else { 0 };
+        match abs_position(button) {
+            // Grid buttons send notes, and are released with a note off message
+            (x @ 0..=7, y @ 1..=8) if pressed => vec![0x90, 10 * (9 - y as u8) + x as u8 + 1, 127],
+            (x @ 0..=7, y @ 1..=8) => vec![0x80, 10 * (9 - y as u8) + x as u8 + 1, 0x40],
+            (x, y) => vec![0xB0, 10 * (9 - y as u8) + x as u8 + 1, velocity],
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use super::{EmulatedDevice, Emulator, Led, LedColor, TextScroll};
+use crate::mk2::FaderType;
+use crate::protocols::Button80 as Button;
+
+/// The layouts of the Launchpad MK2
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum Mk2Layout {
+    Session,
+    User1,
+    User2,
+    Reserved,
+    Volume,
+    Pan,
+}
+
+/// A fader placed in [fader mode](crate::mk2::Output::enter_fader_mode)
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub struct EmulatedFader {
+    pub fader_type: FaderType,
+    pub color: u8,
+    pub value: u8,
+}
+
+/// An emulated Launchpad MK2
+///
+/// ```
+/// use launchy::emulator::{Emulator, Mk2, Mk2Layout};
+/// use launchy::mk2::{Fader, FaderType, Input, Message, Output, PaletteColor};
+/// use launchy::{InputDevice as _, MsgPollingWrapper as _, OutputDevice as _};
+///
+/// let emulator = Emulator::new(Mk2::new());
+/// let output = Output::from_connection(emulator.sink())?;
+/// let input = Input::from_source_polling(emulator.source())?;
+///
+/// let mut faders = output.enter_fader_mode(FaderType::Volume)?;
+/// faders.designate_faders(&[Fader::new(2, PaletteColor::GREEN, 0)])?;
+/// assert_eq!(emulator.device().layout(), Mk2Layout::Volume);
+///
+/// emulator.move_fader(2, 100);
+/// assert!(matches!(
+///     input.try_recv(),
+///     Some(Message::FaderChange { index: 2, value: 100, .. }),
+/// ));
+/// assert_eq!(emulator.device().fader(2).unwrap().value, 100);
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct Mk2 {
+    leds: [Led; 81],
+    layout: Mk2Layout,
+    faders: [Option<EmulatedFader>; 8],
+    text_scroll: Option<TextScroll<u8>>,
+    clock_ticks: u64,
+    in_bootloader: bool,
+    unrecognized: Vec<Vec<u8>>,
+}
+
+impl Default for Mk2 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mk2 {
+    pub fn new() -> Self {
+        Self {
+            leds: [Led::default(); 81],
+            layout: Mk2Layout::Session,
+            faders: [None; 8],
+            text_scroll: None,
+            clock_ticks: 0,
+            in_bootloader: false,
+            unrecognized: Vec::new(),
+        }
+    }
+
+    pub fn led(&self, button: Button) -> Led {
+        self.leds[led_index(button)]
+    }
+
+    pub fn layout(&self) -> Mk2Layout {
+        self.layout
+    }
+
+    /// The fader at `index`, if one was placed there in the current fader layout
+    pub fn fader(&self, index: u8) -> Option<EmulatedFader> {
+        self.faders[index as usize]
+    }
+
+    pub fn text_scroll(&self) -> Option<&TextScroll<u8>> {
+        self.text_scroll.as_ref()
+    }
+
+    /// Number of clock ticks that were received
+    pub fn clock_ticks(&self) -> u64 {
+        self.clock_ticks
+    }
+
+    pub fn is_in_bootloader(&self) -> bool {
+        self.in_bootloader
+    }
+
+    /// Messages that couldn't be interpreted
+    pub fn unrecognized(&self) -> &[Vec<u8>] {
+        &self.unrecognized
+    }
+
+    fn set(&mut self, button: Button, led: Led) {
+        self.leds[led_index(button)] = led;
+    }
+
+    /// Light a button, `channel` being the light mode (plain, flash, pulse)
+    fn set_with_mode(&mut self, button: Button, channel: u8, color: u8) {
+        let led = match channel {
+            0 => Led::Plain(LedColor::Palette(color)),
+            1 => Led::flash(self.led(button), color),
+            _ => Led::Pulse(color),
+        };
+        self.set(button, led);
+    }
+
+    fn receive_sysex(&mut self, command: u8, payload: &[u8]) -> bool {
+        match command {
+            10 | 35 | 40 => {
+                let (channel, chunk_size) = match command {
+                    10 => (0, 2),
+                    35 => (1, 3),
+                    _ => (2, 3),
+                };
+                // Flash and pulse entries are prefixed by a null byte
+                for chunk in payload.chunks_exact(chunk_size) {
+                    let (key, color) = (chunk[chunk_size - 2], chunk[chunk_size - 1]);
+                    if let Some(button) = decode_key(key) {
+                        self.set_with_mode(button, channel, color);
+                    }
+                }
+            }
+            11 => {
+                for chunk in payload.chunks_exact(4) {
+                    if let Some(button) = decode_key(chunk[0]) {
+                        let color = LedColor::Rgb(chunk[1], chunk[2], chunk[3]);
+                        self.set(button, Led::Plain(color));
+                    }
+                }
+            }
+            12 => {
+                for chunk in payload.chunks_exact(2).filter(|chunk| chunk[0] <= 8) {
+                    let led = Led::Plain(LedColor::Palette(chunk[1]));
+                    for y in 0..8 {
+                        self.set(Button::grid(chunk[0], y), led);
+                    }
+                }
+            }
+            13 => {
+                for chunk in payload.chunks_exact(2) {
+                    let led = Led::Plain(LedColor::Palette(chunk[1]));
+                    // Rows are counted from the bottom
+                    match 8u8.wrapping_sub(chunk[0]) {
+                        0 => (0..8).for_each(|index| self.set(Button::control(index), led)),
+                        row @ 1..=8 => (0..9).for_each(|x| self.set(Button::grid(x, row - 1), led)),
+                        _ => {}
+                    }
+                }
+            }
+            14 => match *payload {
+                [color] => self.leds = [Led::Plain(LedColor::Palette(color)); 81],
+                _ => return false,
+            },
+            20 => match *payload {
+                [color, looping, ref text @ ..] => {
+                    self.text_scroll = match text {
+                        [] => None,
+                        _ => Some(TextScroll {
+                            text: text.to_vec(),
+                            color,
+                            looping: looping != 0,
+                        }),
+                    };
+                }
+                _ => return false,
+            },
+            34 => match *payload {
+                [layout @ 0..=5] => {
+                    self.layout = match layout {
+                        0 => Mk2Layout::Session,
+                        1 => Mk2Layout::User1,
+                        2 => Mk2Layout::User2,
+                        3 => Mk2Layout::Reserved,
+                        4 => Mk2Layout::Volume,
+                        _ => Mk2Layout::Pan,
+                    };
+                    self.faders = [None; 8];
+                }
+                _ => return false,
+            },
+            43 => {
+                for chunk in payload.chunks_exact(4) {
+                    if let Some(fader) = self.faders.get_mut(chunk[0] as usize) {
+                        *fader = Some(EmulatedFader {
+                            fader_type: match chunk[1] {
+                                0 => FaderType::Volume,
+                                _ => FaderType::Pan,
+                            },
+                            color: chunk[2],
+                            value: chunk[3],
+                        });
+                    }
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Position of a button in the LED array
+fn led_index(button: Button) -> usize {
+    button.abs_y() as usize * 9 + button.abs_x() as usize
+}
+
+fn decode_key(key: u8) -> Option<Button> {
+    match key {
+        104..=111 => Some(Button::control(key - 104)),
+        11..=89 if key % 10 >= 1 => Some(Button::grid(key % 10 - 1, 8 - key / 10)),
+        _ => None,
+    }
+}
+
+impl EmulatedDevice for Mk2 {
+    type Button = Button;
+
+    fn receive(&mut self, bytes: &[u8], replies: &mut Vec<Vec<u8>>) {
+        if let Some(reply) = super::device_inquiry_reply(
+            bytes,
+            crate::Model::Mk2.device_inquiry_code(),
+            [0, 1, 7, 8],
+        ) {
+            replies.push(reply);
+            return;
+        }
+
+        let recognized = match *bytes {
+            [240, 0, 32, 41, 0, 112, 247] => {
+                replies.push(super::version_inquiry_reply(
+                    [0, 0, 1, 6, 4],
+                    [0, 0, 1, 7, 8],
+                    3,
+                ));
+                true
+            }
+            [status @ 0x90..=0x92, key, color] => match decode_key(key) {
+                Some(button @ Button::GridButton { .. }) => {
+                    self.set_with_mode(button, status - 0x90, color);
+                    true
+                }
+                _ => false,
+            },
+            [status @ 0xB0..=0xB2, key @ 104..=111, color] => {
+                self.set_with_mode(Button::control(key - 104), status - 0xB0, color);
+                true
+            }
+            [0xB0, number @ 21..=28, value] => match &mut self.faders[number as usize - 21] {
+                Some(fader) => {
+                    fader.value = value;
+                    true
+                }
+                None => false,
+            },
+            [248, 0, 0] => {
+                self.clock_ticks += 1;
+                true
+            }
+            [240, 0, 32, 41, 0, 113, 0, 105, 247] => {
+                self.in_bootloader = true;
+                true
+            }
+            [240, 0, 32, 41, 2, 24, command, ref payload @ .., 247] => {
+                self.receive_sysex(command, payload)
+            }
+            _ => false,
+        };
+
+        if !recognized {
+            self.unrecognized.push(bytes.to_vec());
+        }
+    }
+
+    fn button_message(&self, button: Button, pressed: bool) -> Vec<u8> {
+        let velocity = if pressed { 127 } else { 0 };
+        match button {
+            Button::GridButton { x, y } => vec![0x90, 10 * (8 - y) + x + 1, velocity],
+            Button::ControlButton { index } => vec![0xB0, 104 + index, velocity],
+        }
+    }
+}
+
+impl Emulator<Mk2> {
+    /// Simulate the user moving the fader at `index` to `value`. Returns whether the message was
+    /// delivered.
+    pub fn move_fader(&self, index: u8, value: u8) -> bool {
+        if let Some(fader) = &mut self.device().faders[index as usize] {
+            fader.value = value;
+        }
+        self.emit(&[0xB0, 21 + index, value])
+    }
+
+    /// Finish the ongoing text scroll, or complete one loop of it. Returns whether a text scroll
+    /// was in progress.
+    pub fn finish_text_scroll(&self) -> bool {
+        let was_scrolling = {
+            let mut device = self.device();
+            match &device.text_scroll {
+                Some(scroll) if scroll.looping => true,
+                Some(_) => device.text_scroll.take().is_some(),
+                None => false,
+            }
+        };
+
+        if was_scrolling {
+            self.emit(&[240, 0, 32, 41, 2, 24, 21, 247]);
+        }
+        was_scrolling
+    }
+}
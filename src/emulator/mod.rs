@@ -0,0 +1,256 @@
+/*!
+Software emulators for the supported devices.
+
+An emulator understands the exact bytes that the device `Output` structs send, and maintains the
+LED state that a real device would display. It can also produce the messages a real device would
+send, for example on a button press, so that input code can be exercised end to end.
+
+```
+use launchy::emulator::{Emulator, Led, LedColor, Mk2};
+use launchy::mk2::{Button, Input, Message, Output, PaletteColor};
+use launchy::{InputDevice as _, MsgPollingWrapper as _, OutputDevice as _};
+
+let emulator = Emulator::new(Mk2::new());
+let mut output = Output::from_connection(emulator.sink())?;
+let input = Input::from_source_polling(emulator.source())?;
+
+output.pulse(Button::UP, PaletteColor::RED)?;
+assert_eq!(emulator.device().led(Button::UP), Led::Pulse(5));
+
+emulator.press(Button::UP);
+assert!(matches!(input.try_recv(), Some(Message::Press { button: Button::UP, .. })));
+# Ok::<(), launchy::MidiError>(())
+```
+
+Emulators also implement [`MidiTransport`](crate::MidiTransport) via [`EmulatorTransport`], so
+that a [`DeviceCanvas`](crate::DeviceCanvas) can be run against them.
+*/
+
+mod classic;
+pub use classic::*;
+
+mod mk2;
+pub use mk2::*;
+
+mod mini_mk3;
+pub use mini_mk3::*;
+
+mod control;
+pub use control::*;
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A device whose MIDI protocol can be emulated
+pub trait EmulatedDevice: Send + 'static {
+    /// The button type of the emulated device
+    type Button: Copy;
+
+    /// Process a single MIDI message that was sent to the device. Any messages that the device
+    /// sends in response are pushed into `replies`.
+    fn receive(&mut self, bytes: &[u8], replies: &mut Vec<Vec<u8>>);
+
+    /// The message that the device sends when `button` is pressed or released
+    fn button_message(&self, button: Self::Button, pressed: bool) -> Vec<u8>;
+}
+
+type SharedInput = Arc<Mutex<Option<crate::ChannelSourceSender>>>;
+type ReceiveFn = Box<dyn FnMut(&[u8]) + Send>;
+
+/// Drives an [`EmulatedDevice`]. See the [module documentation](self) for an example.
+pub struct Emulator<D: EmulatedDevice> {
+    device: Arc<Mutex<D>>,
+    input: SharedInput,
+    start: std::time::Instant,
+}
+
+impl<D: EmulatedDevice> Emulator<D> {
+    pub fn new(device: D) -> Self {
+        Self {
+            device: Arc::new(Mutex::new(device)),
+            input: SharedInput::default(),
+            start: std::time::Instant::now(),
+        }
+    }
+
+    /// Returns a new output connection to this emulator. Pass it to the `from_connection` method
+    /// of the emulated device's `Output`.
+    pub fn sink(&self) -> EmulatorSink {
+        let device = self.device.clone();
+        let input = self.input.clone();
+        let start = self.start;
+
+        EmulatorSink {
+            receive: Box::new(move |bytes| {
+                let mut replies = Vec::new();
+                device.lock().unwrap().receive(bytes, &mut replies);
+
+                // The device lock is released at this point, so the input callback may safely
+                // send messages to the emulator in turn
+                for reply in replies {
+                    emit(&input, start, &reply);
+                }
+            }),
+        }
+    }
+
+    /// Returns a new input connection to this emulator. Any previously returned input connection
+    /// stops receiving messages, just like after a reconnect to a real device.
+    pub fn source(&self) -> crate::ChannelSource {
+        let (source, sender) = crate::ChannelSource::new();
+        *self.input.lock().unwrap() = Some(sender);
+        source
+    }
+
+    /// Access the emulated device state
+    pub fn device(&self) -> MutexGuard<'_, D> {
+        self.device.lock().unwrap()
+    }
+
+    /// Send a raw MIDI message from the device to the connected input, if any. Returns whether the
+    /// message was delivered.
+    pub fn emit(&self, bytes: &[u8]) -> bool {
+        emit(&self.input, self.start, bytes)
+    }
+
+    /// Simulate a button press. Returns whether the message was delivered.
+    pub fn press(&self, button: D::Button) -> bool {
+        let message = self.device().button_message(button, true);
+        self.emit(&message)
+    }
+
+    /// Simulate a button release. Returns whether the message was delivered.
+    pub fn release(&self, button: D::Button) -> bool {
+        let message = self.device().button_message(button, false);
+        self.emit(&message)
+    }
+}
+
+fn emit(input: &SharedInput, start: std::time::Instant, bytes: &[u8]) -> bool {
+    // Clone the sender so that the lock isn't held while the input callback runs
+    let sender = input.lock().unwrap().clone();
+    match sender {
+        Some(sender) => sender.send(start.elapsed().as_micros() as u64, bytes),
+        None => false,
+    }
+}
+
+/// The [`MidiSink`](crate::MidiSink) of an [`Emulator`]. Every sent message is processed by the
+/// emulated device.
+pub struct EmulatorSink {
+    receive: ReceiveFn,
+}
+
+impl crate::MidiSink for EmulatorSink {
+    fn send(&mut self, bytes: &[u8]) -> Result<(), crate::MidiError> {
+        (self.receive)(bytes);
+        Ok(())
+    }
+}
+
+/// The [`MidiTransport`](crate::MidiTransport) of an [`Emulator`]
+///
+/// ```
+/// # use launchy::{Canvas as _, Color, DeviceCanvas, Pad};
+/// use launchy::emulator::{Emulator, EmulatorTransport, Mk2, Led, LedColor};
+///
+/// let emulator = Emulator::new(Mk2::new());
+/// let mut canvas = DeviceCanvas::<launchy::mk2::Spec, EmulatorTransport>::from_connections(
+///     emulator.source(),
+///     emulator.sink(),
+///     |_msg| {},
+/// )?;
+///
+/// canvas[Pad { x: 0, y: 0 }] = Color::GREEN;
+/// canvas.flush()?;
+/// assert_eq!(
+///     emulator.device().led(launchy::mk2::Button::UP),
+///     Led::Plain(LedColor::Rgb(0, 63, 0)),
+/// );
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+pub struct EmulatorTransport;
+
+impl crate::MidiTransport for EmulatorTransport {
+    type Sink = EmulatorSink;
+    type Source = crate::ChannelSource;
+}
+
+/// A color as displayed by the RGB devices
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum LedColor {
+    /// A color from the device palette
+    Palette(u8),
+    /// A custom RGB color, in the device's color precision
+    Rgb(u8, u8, u8),
+}
+
+/// The state of a single LED on the RGB devices
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum Led {
+    /// A constant color
+    Plain(LedColor),
+    /// Flashing between `base` and the palette color `color`
+    Flash { base: LedColor, color: u8 },
+    /// Pulsing the palette color
+    Pulse(u8),
+}
+
+impl Default for Led {
+    fn default() -> Self {
+        Self::Plain(LedColor::Palette(0))
+    }
+}
+
+impl Led {
+    /// The color that is shown while the LED isn't flashing or pulsing
+    pub fn base_color(&self) -> LedColor {
+        match *self {
+            Self::Plain(color) => color,
+            Self::Flash { base, .. } => base,
+            Self::Pulse(color) => LedColor::Palette(color),
+        }
+    }
+
+    fn flash(previous: Led, color: u8) -> Self {
+        Self::Flash {
+            base: previous.base_color(),
+            color,
+        }
+    }
+}
+
+/// The reply of a device to a device inquiry request, if the request is addressed to it
+fn device_inquiry_reply(request: &[u8], code: [u8; 4], firmware: [u8; 4]) -> Option<Vec<u8>> {
+    match *request {
+        [240, 126, 127 | 0, 6, 1, 247] => Some(
+            [
+                &[240, 126, 0, 6, 2, 0, 32, 41][..],
+                &code,
+                &firmware,
+                &[247],
+            ]
+            .concat(),
+        ),
+        _ => None,
+    }
+}
+
+/// The reply of a device to a version inquiry request
+fn version_inquiry_reply(bootloader: [u8; 5], firmware: [u8; 5], size: u16) -> Vec<u8> {
+    [
+        &[240, 0, 32, 41, 0, 112][..],
+        &bootloader,
+        &firmware,
+        &size.to_be_bytes(),
+        &[247],
+    ]
+    .concat()
+}
+
+/// An ongoing text scroll
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct TextScroll<C> {
+    pub text: Vec<u8>,
+    pub color: C,
+    pub looping: bool,
+}
@@ -1,25 +1,52 @@
 #[derive(Debug)]
 pub enum MidiError {
+    #[cfg(feature = "midir")]
     InputConnectError(midir::ConnectError<()>),
+    #[cfg(feature = "midir")]
     OutputConnectError(midir::ConnectError<()>),
+    #[cfg(feature = "midir")]
     InitError(midir::InitError),
+    #[cfg(feature = "midir")]
     PortInfoError(midir::PortInfoError),
+    #[cfg(feature = "midir")]
     SendError(midir::SendError),
     NoPortFound {
         // The keyword that was searched for
         keyword: &'static str,
     },
+    NoMatchingPort {
+        // Description of the port that was searched for
+        selector: String,
+    },
+    /// The device didn't respond to a query in time
+    NoResponse,
+    /// The receiver of a [`ChannelSink`](crate::ChannelSink) was dropped
+    ChannelClosed,
+    /// A button was lit in a custom mode which has no note for it
+    NoNote {
+        // Debug representation of the button
+        button: String,
+    },
 }
 
 impl std::fmt::Display for MidiError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            #[cfg(feature = "midir")]
             Self::InputConnectError(_) => f.write_str("connecting to MIDI input port failed"),
+            #[cfg(feature = "midir")]
             Self::OutputConnectError(_) => f.write_str("connecting to MIDI output port failed"),
+            #[cfg(feature = "midir")]
             Self::InitError(_) => f.write_str("MIDI context initialization failed"),
+            #[cfg(feature = "midir")]
             Self::PortInfoError(_) => f.write_str("MIDI Port retrieval failed"),
+            #[cfg(feature = "midir")]
             Self::SendError(_) => f.write_str("sending MIDI message failed"),
             Self::NoPortFound { keyword } => write!(f, "couldn't find a port for {:?}", keyword),
+            Self::NoMatchingPort { selector } => write!(f, "couldn't find the {}", selector),
+            Self::NoResponse => f.write_str("the device didn't respond in time"),
+            Self::ChannelClosed => f.write_str("the message receiver has hung up"),
+            Self::NoNote { button } => write!(f, "{} has no note in this layout", button),
         }
     }
 }
@@ -27,16 +54,26 @@ impl std::fmt::Display for MidiError {
 impl std::error::Error for MidiError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
+            #[cfg(feature = "midir")]
             Self::InputConnectError(e) => Some(e),
+            #[cfg(feature = "midir")]
             Self::OutputConnectError(e) => Some(e),
+            #[cfg(feature = "midir")]
             Self::InitError(e) => Some(e),
+            #[cfg(feature = "midir")]
             Self::PortInfoError(e) => Some(e),
+            #[cfg(feature = "midir")]
             Self::SendError(e) => Some(e),
             Self::NoPortFound { keyword: _ } => None,
+            Self::NoMatchingPort { selector: _ } => None,
+            Self::NoResponse => None,
+            Self::ChannelClosed => None,
+            Self::NoNote { button: _ } => None,
         }
     }
 }
 
+#[cfg(feature = "midir")]
 impl From<midir::ConnectError<midir::MidiInput>> for MidiError {
     fn from(e: midir::ConnectError<midir::MidiInput>) -> Self {
         // Strip contained MidiInput from error because it's not Sync
@@ -44,6 +81,7 @@ impl From<midir::ConnectError<midir::MidiInput>> for MidiError {
     }
 }
 
+#[cfg(feature = "midir")]
 impl From<midir::ConnectError<midir::MidiOutput>> for MidiError {
     fn from(e: midir::ConnectError<midir::MidiOutput>) -> Self {
         // Strip contained MidiOutput from error because it's not Sync
@@ -51,20 +89,53 @@ impl From<midir::ConnectError<midir::MidiOutput>> for MidiError {
     }
 }
 
+#[cfg(feature = "midir")]
 impl From<midir::InitError> for MidiError {
     fn from(e: midir::InitError) -> Self {
         Self::InitError(e)
     }
 }
 
+#[cfg(feature = "midir")]
 impl From<midir::PortInfoError> for MidiError {
     fn from(e: midir::PortInfoError) -> Self {
         Self::PortInfoError(e)
     }
 }
 
+#[cfg(feature = "midir")]
 impl From<midir::SendError> for MidiError {
     fn from(e: midir::SendError) -> Self {
         Self::SendError(e)
     }
 }
+
+/// Returned when a message sent by a device doesn't match the device's protocol
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct DecodeError {
+    /// The raw bytes of the message
+    pub bytes: Vec<u8>,
+    /// Why the message couldn't be decoded
+    pub reason: String,
+}
+
+impl DecodeError {
+    pub(crate) fn new(bytes: &[u8], reason: impl Into<String>) -> Self {
+        Self {
+            bytes: bytes.to_vec(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "couldn't decode MIDI message {:?}: {}",
+            self.bytes, self.reason
+        )
+    }
+}
+
+impl std::error::Error for DecodeError {}
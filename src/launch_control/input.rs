@@ -2,7 +2,7 @@ use super::{Button, Template};
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
 /// One of the 16 knobs on the Launch COntrol
-pub struct Knob(u8);
+pub struct Knob(pub(crate) u8);
 
 impl Knob {
     /// Create a new knob from the given index, where indices 0-7 correspond to the top row and
@@ -22,40 +22,70 @@ impl Knob {
     }
 }
 
+/// A Launch Control input message. The `timestamp` fields hold the MIDI timestamp of the message in
+/// microseconds, see [`MidiClock`](crate::MidiClock).
 #[derive(Debug, Eq, PartialEq, Hash, Clone)]
 pub enum Message {
     /// When a button is pressed
-    Press { template: Template, button: Button },
+    Press {
+        template: Template,
+        button: Button,
+        timestamp: u64,
+    },
 
     /// When a button is released
-    Release { template: Template, button: Button },
+    Release {
+        template: Template,
+        button: Button,
+        timestamp: u64,
+    },
 
     /// When the user presses a pad button, then changes the template, and then releases the
     /// button, this message will be fired on release. The Launch Control provides no
     /// information which button has been released, nor the template it was pressed or released
     /// in
-    StalePadRelease,
+    StalePadRelease { timestamp: u64 },
 
     /// Same meaning as StalePadRelease, but for control buttons instead of pads
-    StaleControlButtonRelease,
+    StaleControlButtonRelease { timestamp: u64 },
 
     /// When a template is changed using the non-programmatically-accessible template buttons on
     /// the Launch Control
-    TemplateChanged { template: Template },
+    TemplateChanged { template: Template, timestamp: u64 },
 
     /// When a knob has been moved
     KnobChanged {
         template: Template,
         knob: Knob,
         value: u8,
+        timestamp: u64,
     },
+
+    /// A message that doesn't match the Launch Control protocol, with its raw bytes
+    Verbatim { bytes: Vec<u8> },
+}
+
+impl Message {
+    /// The template that this message was sent in. Every message from a button or knob reveals the
+    /// template that's currently selected on the device, as does [`Message::TemplateChanged`].
+    pub fn template(&self) -> Option<Template> {
+        match *self {
+            Message::Press { template, .. }
+            | Message::Release { template, .. }
+            | Message::TemplateChanged { template, .. }
+            | Message::KnobChanged { template, .. } => Some(template),
+            Message::StalePadRelease { .. }
+            | Message::StaleControlButtonRelease { .. }
+            | Message::Verbatim { .. } => None,
+        }
+    }
 }
 
 /// The Launch Control input connection creator.
 pub struct Input;
 
 impl Input {
-    fn decode_short_message(data: &[u8]) -> Message {
+    fn decode_short_message(timestamp: u64, data: &[u8]) -> Result<Message, crate::DecodeError> {
         let status = data[0] & 0xF0;
         let template = Template(data[0] & 0x0F);
         let note = data[1];
@@ -65,59 +95,69 @@ impl Input {
         // doesn't provide it. the lower 4 bits are always zero on those Stale message, so I can't
         // put it into the Message
 
-        match [status, note, velocity] {
+        let msg = match [status, note, velocity] {
             // Pad buttons press + release
             [0x90, button @ 9..=12, 127] => Message::Press {
                 template,
                 button: Button::pad(button - 9),
+                timestamp,
             },
             [0x80, button @ 9..=12, 0] => Message::Release {
                 template,
                 button: Button::pad(button - 9),
+                timestamp,
             },
             [0x90, button @ 25..=28, 127] => Message::Press {
                 template,
                 button: Button::pad(button - 25 + 4),
+                timestamp,
             },
             [0x80, button @ 25..=28, 0] => Message::Release {
                 template,
                 button: Button::pad(button - 25 + 4),
+                timestamp,
             },
-            [0x80, 0, 0] => Message::StalePadRelease,
+            [0x80, 0, 0] => Message::StalePadRelease { timestamp },
 
             // Control buttons press + release
             [0xB0, button @ 114..=117, 127] => Message::Press {
                 template,
                 button: Button::control(button - 114),
+                timestamp,
             },
             [0xB0, button @ 114..=117, 0] => Message::Release {
                 template,
                 button: Button::control(button - 114),
+                timestamp,
             },
-            [0xB0, 0, 0] => Message::StaleControlButtonRelease,
+            [0xB0, 0, 0] => Message::StaleControlButtonRelease { timestamp },
 
             // Knob changes
             [0xB0, knob @ 21..=28, value] => Message::KnobChanged {
                 template,
                 knob: Knob::upper(knob - 21),
                 value,
+                timestamp,
             },
             [0xB0, knob @ 41..=48, value] => Message::KnobChanged {
                 template,
                 knob: Knob::lower(knob - 41),
                 value,
+                timestamp,
             },
 
-            _ => panic!("Unexpected short message {:?}", data),
-        }
+            _ => return Err(crate::DecodeError::new(data, "unexpected short message")),
+        };
+        Ok(msg)
     }
 
-    fn decode_sysex_message(data: &[u8]) -> Message {
+    fn decode_sysex_message(timestamp: u64, data: &[u8]) -> Result<Message, crate::DecodeError> {
         match *data {
-            [240, 0, 32, 41, 2, 10, 119, template, 247] => Message::TemplateChanged {
+            [240, 0, 32, 41, 2, 10, 119, template @ 0..=15, 247] => Ok(Message::TemplateChanged {
                 template: Template(template),
-            },
-            _ => panic!("Unexpected sysex message {:?}", data),
+                timestamp,
+            }),
+            _ => Err(crate::DecodeError::new(data, "unexpected sysex message")),
         }
     }
 }
@@ -127,10 +167,37 @@ impl crate::InputDevice for Input {
     const MIDI_DEVICE_KEYWORD: &'static str = "Launch Control";
     type Message = Message;
 
-    fn decode_message(_timestamp: u64, data: &[u8]) -> Message {
+    fn verbatim(bytes: Vec<u8>) -> Message {
+        Message::Verbatim { bytes }
+    }
+
+    fn is_device_port(name: &str) -> bool {
+        super::is_device_port(name)
+    }
+
+    fn try_decode_message(timestamp: u64, data: &[u8]) -> Result<Message, crate::DecodeError> {
         match data.len() {
-            3 => Self::decode_short_message(data),
-            _ => Self::decode_sysex_message(data),
+            3 => Self::decode_short_message(timestamp, data),
+            _ => Self::decode_sysex_message(timestamp, data),
+        }
+    }
+}
+
+/// The knobs are controls 0 to 15, see [`Knob::new`], on the template they were turned in
+impl crate::ControlInput for Input {
+    fn control_change(msg: &Message) -> Option<crate::ControlChange> {
+        match *msg {
+            Message::KnobChanged {
+                template,
+                knob,
+                value,
+                timestamp,
+            } => Some(crate::ControlChange {
+                control: crate::ControlId::on_template(template.0, knob.0),
+                value,
+                timestamp,
+            }),
+            _ => None,
         }
     }
 }
@@ -10,8 +10,17 @@ pub use input::*;
 mod output;
 pub use output::*;
 
+mod templates;
+pub use templates::*;
+
+/// Whether a port belongs to a Launch Control. The ports of the Launch Control XL contain "Launch
+/// Control" as well
+fn is_device_port(name: &str) -> bool {
+    name.contains("Launch Control") && !name.contains("Launch Control XL")
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
-pub struct Template(u8);
+pub struct Template(pub(crate) u8);
 
 impl Template {
     pub fn from_byte(byte: u8) -> Self {
@@ -79,7 +88,7 @@ impl Button {
         }
     }
 
-    fn code(self) -> u8 {
+    pub(crate) fn code(self) -> u8 {
         match self {
             Button::Pad(index @ 0..=3) => index + 9,
             Button::Pad(index @ 4..=7) => index + 21,
@@ -92,6 +101,18 @@ impl Button {
     }
 }
 
+/// The button at the given canvas coordinates
+fn xy_to_button(x: u32, y: u32) -> Button {
+    match (x, y) {
+        (8, 0) => Button::Up,
+        (9, 0) => Button::Down,
+        (8, 1) => Button::Left,
+        (9, 1) => Button::Right,
+        (index, 1) => Button::pad(index as u8),
+        _ => panic!("Unexpected coordinates ({}|{})", x, y),
+    }
+}
+
 #[doc(hidden)]
 pub struct Spec;
 
@@ -101,7 +122,7 @@ impl crate::DeviceSpec for Spec {
     const COLOR_PRECISION: u16 = 4;
 
     type Input = Input;
-    type Output = Output;
+    type Output<S: crate::MidiSink> = Output<S>;
 
     fn is_valid(x: u32, y: u32) -> bool {
         if y == 0 && x <= 7 {
@@ -110,27 +131,22 @@ impl crate::DeviceSpec for Spec {
         true
     }
 
-    fn setup(output: &mut Self::Output) -> Result<(), crate::MidiError> {
+    fn setup<S: crate::MidiSink>(output: &mut Self::Output<S>) -> Result<(), crate::MidiError> {
         output.change_template(0)
     }
 
-    fn flush(
-        canvas: &mut crate::DeviceCanvas<Self>,
+    fn flush<T: crate::MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
         changes: &[(u32, u32, (u8, u8, u8))],
     ) -> Result<(), crate::MidiError> {
         canvas.output.light_multiple(
             0,
             changes.iter().map(|&(x, y, (r, g, _b))| {
-                let button = match (x, y) {
-                    (8, 0) => Button::Up,
-                    (9, 0) => Button::Down,
-                    (8, 1) => Button::Left,
-                    (9, 1) => Button::Right,
-                    (index, 1) => Button::pad(index as u8),
-                    _ => panic!("Unexpected coordinates ({}|{})", x, y),
-                };
-
-                (button, Color::new(r, g), DoubleBufferingBehavior::Copy)
+                (
+                    xy_to_button(x, y),
+                    Color::new(r, g),
+                    DoubleBufferingBehavior::Copy,
+                )
             }),
         )
     }
@@ -150,23 +166,43 @@ impl crate::DeviceSpec for Spec {
             Message::Press {
                 template: _,
                 button,
+                timestamp,
             } => {
                 let (x, y) = button_to_xy(button);
-                Some(crate::CanvasMessage::Press { x, y })
+                Some(crate::CanvasMessage::Press { x, y, timestamp })
             }
             Message::Release {
                 template: _,
                 button,
+                timestamp,
             } => {
                 let (x, y) = button_to_xy(button);
-                Some(crate::CanvasMessage::Release { x, y })
+                Some(crate::CanvasMessage::Release { x, y, timestamp })
             }
-            Message::StalePadRelease
-            | Message::StaleControlButtonRelease
+            Message::StalePadRelease { .. }
+            | Message::StaleControlButtonRelease { .. }
             | Message::TemplateChanged { .. }
-            | Message::KnobChanged { .. } => None,
+            | Message::KnobChanged { .. }
+            | Message::Verbatim { .. } => None,
         }
     }
 }
 
 pub type Canvas<'a> = crate::DeviceCanvas<Spec>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn launch_control_xl_ports_are_excluded() {
+        assert!(is_device_port("Launch Control"));
+        assert!(is_device_port("Launch Control:Launch Control MIDI 1 20:0"));
+        assert!(is_device_port("2- Launch Control"));
+        assert!(!is_device_port("Launch Control XL"));
+        assert!(!is_device_port(
+            "Launch Control XL:Launch Control XL MIDI 1 20:0"
+        ));
+        assert!(!is_device_port("Launchpad MK2"));
+    }
+}
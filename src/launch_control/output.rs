@@ -1,36 +1,40 @@
-use midir::MidiOutputConnection;
-
 use super::{Button, Template};
-use crate::OutputDevice;
+use crate::midi_io::DefaultSink;
+use crate::{MidiSink, OutputDevice};
 
 #[doc(inline)]
 pub use crate::protocols::double_buffering::*;
 
 /// The Launch Control output connection handler.
-pub struct Output {
-    connection: MidiOutputConnection,
+pub struct Output<S: MidiSink = DefaultSink> {
+    connection: S,
 }
 
-impl crate::OutputDevice for Output {
+impl<S: MidiSink> crate::OutputDevice for Output<S> {
     const MIDI_CONNECTION_NAME: &'static str = "Launchy Launch Control output";
     const MIDI_DEVICE_KEYWORD: &'static str = "Launch Control";
 
-    fn from_connection(connection: MidiOutputConnection) -> Result<Self, crate::MidiError> {
+    type Sink = S;
+
+    fn from_connection(connection: S) -> Result<Self, crate::MidiError> {
         Ok(Self { connection })
     }
 
+    fn is_device_port(name: &str) -> bool {
+        super::is_device_port(name)
+    }
+
     fn send(&mut self, bytes: &[u8]) -> Result<(), crate::MidiError> {
-        self.connection.send(bytes)?;
-        Ok(())
+        self.connection.send(bytes)
     }
 }
 
-impl Output {
+impl<S: MidiSink> Output<S> {
     /// Updates the state for a single LED, specified by `button`. The color, as well as the double
     /// buffering attributes, are specified in `light_state`.
     ///
     /// The given `template` must match the currently selected template on the Launch Control, or
-    /// nothing will happen.
+    /// nothing will happen. Use [`Self::light`] to set LEDs on other templates.
     pub fn set_button(
         &mut self,
         template: impl Into<Template>,
@@ -70,8 +74,26 @@ impl Output {
 
     /// Light multiple buttons with varying colors and double buffering behavior.
     ///
-    /// The given `template` must match the currently selected template on the Launch Control, or
-    /// nothing will happen.
+    /// This uses SysEx, so it works on any `template`, not just the one that's currently selected
+    /// on the Launch Control. The LEDs of other templates show up when switching to them.
+    ///
+    /// ```
+    /// use launchy::launch_control::{Button, Color, DoubleBufferingBehavior, Output, Template};
+    /// use launchy::OutputDevice as _;
+    ///
+    /// let (sink, sent) = launchy::ChannelSink::new();
+    /// let mut output = Output::from_connection(sink)?;
+    ///
+    /// output.light_multiple(
+    ///     Template::factory(2),
+    ///     [
+    ///         (Button::pad(0), Color::RED, DoubleBufferingBehavior::Copy),
+    ///         (Button::Up, Color::GREEN, DoubleBufferingBehavior::Copy),
+    ///     ],
+    /// )?;
+    /// assert_eq!(sent.try_recv()?, [240, 0, 32, 41, 2, 10, 120, 10, 0, 7, 8, 52, 247]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
     pub fn light_multiple(
         &mut self,
         template: impl Into<Template>,
@@ -91,11 +113,31 @@ impl Output {
         self.send(&bytes)
     }
 
-    // this doesn't seem to do ANYTHING at all /shrug
-    // pub fn toggle(&mut self, template: impl Into<Template>, button: Button, on: bool) ->
-    // anyhow::Result<()> {     let value = if on { 127 } else { 0 };
-    //     self.send(&[240, 0, 32, 41, 2, 10, 123, template.into().0, button.as_index(), value,
-    // 247]) }
+    /// Set the state of a button that's configured as a toggle button in the given `template`.
+    ///
+    /// In the factory templates, all buttons are momentary buttons, for which this has no visible
+    /// effect. Buttons can be made toggle buttons in user templates with Novation Components.
+    pub fn toggle(
+        &mut self,
+        template: impl Into<Template>,
+        button: Button,
+        on: bool,
+    ) -> Result<(), crate::MidiError> {
+        let value = if on { 127 } else { 0 };
+        self.send(&[
+            240,
+            0,
+            32,
+            41,
+            2,
+            10,
+            123,
+            template.into().0,
+            button.as_index(),
+            value,
+            247,
+        ])
+    }
 
     /// Change the template that will be displayed and edited on
     pub fn change_template(
@@ -168,10 +210,8 @@ impl Output {
         self.turn_on_all_leds(template, Brightness::Off)
     }
 
-    /// Light a button with a certain color.
-    ///
-    /// The given `template` must match the currently selected template on the Launch Control, or
-    /// nothing will happen.
+    /// Light a button with a certain color. Like [`Self::light_multiple`], this works on any
+    /// `template`.
     pub fn light(
         &mut self,
         template: impl Into<Template>,
@@ -0,0 +1,411 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+use super::{DoubleBufferingBehavior, Message, Output, Spec, Template};
+use crate::util::{Array2d, DirtySet};
+use crate::{Canvas, CanvasMessage, Color, DeviceSpec as _, MidiTransport, Pad, PadEffect};
+
+/// Keeps track of the template that's currently selected on a Launch Control.
+///
+/// The Launch Control can't be asked for its current template, but it reports every template
+/// change done with the template buttons as [`Message::TemplateChanged`], and every button and
+/// knob message carries the template it was sent in. Feed the input messages into
+/// [`Self::update`] to stay up to date. The tracker is cheap to clone, and all clones share the
+/// same state, so it can be updated from the input callback and read elsewhere.
+///
+/// ```
+/// use launchy::launch_control::{Input, Template, TemplateTracker};
+/// use launchy::InputDevice as _;
+///
+/// let tracker = TemplateTracker::new(Template::user(0));
+/// assert!(tracker.update(&Input::decode_message(0, &[240, 0, 32, 41, 2, 10, 119, 9, 247])));
+/// assert_eq!(tracker.get(), Template::factory(1));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TemplateTracker(Arc<AtomicU8>);
+
+impl TemplateTracker {
+    pub fn new(template: impl Into<Template>) -> Self {
+        Self(Arc::new(AtomicU8::new(template.into().0)))
+    }
+
+    /// The template that's currently selected
+    pub fn get(&self) -> Template {
+        Template(self.0.load(Ordering::Relaxed))
+    }
+
+    /// Override the tracked template, for example after changing the template with
+    /// [`Output::change_template`]
+    pub fn set(&self, template: impl Into<Template>) {
+        self.0.store(template.into().0, Ordering::Relaxed);
+    }
+
+    /// Update the tracked template from an input message. Returns whether the template changed.
+    pub fn update(&self, message: &Message) -> bool {
+        match message.template() {
+            Some(template) => self.0.swap(template.0, Ordering::Relaxed) != template.0,
+            None => false,
+        }
+    }
+}
+
+/// A Launch Control [`Canvas`] that keeps a separate display state for every template.
+///
+/// Unlike [`launch_control::Canvas`](super::Canvas), which always draws on the first user
+/// template, this canvas follows the template that's selected on the device. Drawing on the canvas
+/// and reading from it affects the current template. When the user switches templates on the
+/// hardware, the next [`flush`](Canvas::flush) restores the LEDs of the newly selected template.
+///
+/// Templates other than the current one can be drawn on with [`Self::set_on_template`]. Their
+/// LEDs are updated via SysEx without switching templates.
+///
+/// ```
+/// # use launchy::{Canvas as _, ChannelSink, ChannelSource, ChannelTransport, Color, Pad};
+/// use launchy::launch_control::{Template, TemplateCanvas};
+///
+/// let (sink, sent) = ChannelSink::new();
+/// let (source, device) = ChannelSource::new();
+/// let mut canvas =
+///     TemplateCanvas::<ChannelTransport>::from_connections(source, sink, |_msg| {})?;
+/// # sent.try_iter().count(); // discard setup messages
+///
+/// canvas[Pad { x: 0, y: 1 }] = Color::RED;
+/// canvas.set_on_template(Template::user(1), Pad { x: 1, y: 1 }, Color::GREEN);
+/// canvas.flush()?;
+/// assert_eq!(sent.try_recv()?, [240, 0, 32, 41, 2, 10, 120, 0, 0, 7, 247]);
+/// assert_eq!(sent.try_recv()?, [240, 0, 32, 41, 2, 10, 120, 1, 1, 52, 247]);
+///
+/// // The user switches to the second user template on the device
+/// device.send(0, &[240, 0, 32, 41, 2, 10, 119, 1, 247]);
+/// assert_eq!(canvas.template(), Template::user(1));
+/// assert_eq!(canvas[Pad { x: 1, y: 1 }], Color::GREEN);
+///
+/// // Flushing restores all 12 LEDs of that template
+/// canvas.flush()?;
+/// assert_eq!(sent.try_recv()?.len(), 8 + 12 * 2 + 1);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct TemplateCanvas<T: MidiTransport = crate::midi_io::DefaultTransport> {
+    _input: crate::InputDeviceHandler<<T::Source as crate::MidiSource>::Connection>,
+    output: Output<T::Sink>,
+    tracker: TemplateTracker,
+    /// The template that was selected during the last flush
+    flushed_template: Template,
+    states: Vec<TemplateState>,
+}
+
+/// The display state of a single template
+struct TemplateState {
+    curr: Array2d<Color>,
+    new: Array2d<Color>,
+    /// The Launch Control has no effects of its own, so they're all emulated in software
+    effects: Array2d<PadEffect>,
+    /// The pads with an effect other than [`PadEffect::Steady`]
+    animated: Vec<(u32, u32)>,
+    /// The pads that were written to since the last flush
+    dirty: DirtySet,
+}
+
+impl TemplateState {
+    fn new() -> Self {
+        let (width, height) = (Spec::BOUNDING_BOX_WIDTH, Spec::BOUNDING_BOX_HEIGHT);
+        Self {
+            curr: Array2d::new(width, height),
+            new: Array2d::new(width, height),
+            effects: Array2d::new(width, height),
+            animated: Vec::new(),
+            dirty: DirtySet::new(width, height),
+        }
+    }
+
+    fn pending_mut(&mut self, x: u32, y: u32) -> Option<&mut Color> {
+        if !Spec::is_valid(x, y) {
+            return None;
+        }
+
+        self.dirty.mark(x, y);
+        self.new.get_mut(x, y)
+    }
+
+    fn set_effect(&mut self, x: u32, y: u32, effect: PadEffect) -> Option<()> {
+        if !Spec::is_valid(x, y) {
+            return None;
+        }
+
+        self.dirty.mark(x, y);
+        *self.effects.get_mut(x, y)? = effect;
+        Some(())
+    }
+}
+
+impl<T: MidiTransport> TemplateCanvas<T> {
+    /// Create a new canvas from an already established input source and output sink
+    pub fn from_connections(
+        source: T::Source,
+        sink: T::Sink,
+        callback: impl FnMut(CanvasMessage) + Send + 'static,
+    ) -> Result<Self, crate::MidiError> {
+        use crate::midi_io::{InputDevice, OutputDevice};
+
+        let tracker = TemplateTracker::default();
+        let _input = super::Input::from_source(source, Self::wrap_callback(&tracker, callback))?;
+        let output = Output::from_connection(sink)?;
+
+        Self::from_parts(_input, output, tracker)
+    }
+
+    /// Like [`Self::from_connections`], but returns a poller instead of taking a callback
+    pub fn from_connections_polling(
+        source: T::Source,
+        sink: T::Sink,
+    ) -> Result<(Self, crate::DeviceCanvasPoller), crate::MidiError> {
+        let (sender, receiver) = crate::midi_io::poller_channel(None);
+        let canvas = Self::from_connections(source, sink, move |msg| sender.send(msg))?;
+
+        Ok((canvas, crate::DeviceCanvasPoller { receiver }))
+    }
+
+    fn wrap_callback(
+        tracker: &TemplateTracker,
+        mut callback: impl FnMut(CanvasMessage) + Send + 'static,
+    ) -> impl FnMut(Message) + Send + 'static {
+        let tracker = tracker.clone();
+        move |msg| {
+            tracker.update(&msg);
+            if let Some(msg) = Spec::convert_message(msg) {
+                (callback)(msg);
+            }
+        }
+    }
+
+    fn from_parts(
+        _input: crate::InputDeviceHandler<<T::Source as crate::MidiSource>::Connection>,
+        mut output: Output<T::Sink>,
+        tracker: TemplateTracker,
+    ) -> Result<Self, crate::MidiError> {
+        Spec::setup(&mut output)?;
+        tracker.set(0);
+
+        Ok(Self {
+            _input,
+            output,
+            tracker,
+            flushed_template: Template(0),
+            states: (0..16).map(|_| TemplateState::new()).collect(),
+        })
+    }
+
+    /// The template that's currently selected on the device, and that the canvas draws on
+    pub fn template(&self) -> Template {
+        self.tracker.get()
+    }
+
+    /// The tracker of the current template. It's updated by the input callback of this canvas.
+    pub fn tracker(&self) -> &TemplateTracker {
+        &self.tracker
+    }
+
+    /// Switch the device to another template. The next flush shows the display state of that
+    /// template.
+    pub fn change_template(
+        &mut self,
+        template: impl Into<Template>,
+    ) -> Result<(), crate::MidiError> {
+        let template = template.into();
+        self.output.change_template(template)?;
+        self.tracker.set(template);
+        Ok(())
+    }
+
+    /// Returns the pending color at the given pad of the given template, or `None` if out of
+    /// bounds
+    pub fn get_on_template(&self, template: impl Into<Template>, pad: Pad) -> Option<Color> {
+        let (x, y) = pad.to_u32()?;
+        if !Spec::is_valid(x, y) {
+            return None;
+        }
+        self.states[template.into().0 as usize]
+            .new
+            .get(x, y)
+            .copied()
+    }
+
+    /// Sets the color at the given pad of the given template, regardless of which template is
+    /// currently selected. Returns `None` if out of bounds. Like all canvas changes, this is only
+    /// sent on the next flush.
+    pub fn set_on_template(
+        &mut self,
+        template: impl Into<Template>,
+        pad: Pad,
+        color: Color,
+    ) -> Option<()> {
+        let (x, y) = pad.to_u32()?;
+        *self.states[template.into().0 as usize].pending_mut(x, y)? = color;
+        Some(())
+    }
+
+    /// Sends the changes of a single template, or all of its LEDs if `resend_all` is set. Effects
+    /// are animated if `animate` is set.
+    fn flush_template(
+        &mut self,
+        template: u8,
+        resend_all: bool,
+        animate: bool,
+    ) -> Result<(), crate::MidiError> {
+        let state = &mut self.states[template as usize];
+        if animate {
+            for &(x, y) in &state.animated {
+                state.dirty.mark(x, y);
+            }
+        }
+        if resend_all {
+            for y in 0..Spec::BOUNDING_BOX_HEIGHT {
+                for x in 0..Spec::BOUNDING_BOX_WIDTH {
+                    if Spec::is_valid(x, y) {
+                        state.dirty.mark(x, y);
+                    }
+                }
+            }
+        }
+        if state.dirty.cells().is_empty() {
+            return Ok(());
+        }
+
+        let time = crate::canvas::effect_time();
+        let frame = |state: &TemplateState, x, y| {
+            let effect = state.effects.get(x, y).unwrap();
+            effect.frame(*state.new.get(x, y).unwrap(), time)
+        };
+
+        let mut changes = Vec::new();
+        for &(x, y) in state.dirty.cells() {
+            let old = state.curr.get(x, y).unwrap();
+            let new = frame(state, x, y);
+
+            let old = old.quantize(Spec::COLOR_PRECISION as u8);
+            let new = new.quantize(Spec::COLOR_PRECISION as u8);
+
+            if resend_all || new != old {
+                let (r, g, _b) = new;
+                changes.push((
+                    super::xy_to_button(x, y),
+                    super::Color::new(r, g),
+                    DoubleBufferingBehavior::Copy,
+                ));
+            }
+        }
+
+        if !changes.is_empty() {
+            self.output.light_multiple(template, changes)?;
+        }
+
+        let state = &mut self.states[template as usize];
+        for &(x, y) in state.dirty.cells() {
+            *state.curr.get_mut(x, y).unwrap() = frame(state, x, y);
+            let is_animated = state.effects.get(x, y) != Some(&PadEffect::Steady);
+            if !is_animated {
+                state.animated.retain(|&pad| pad != (x, y));
+            } else if !state.animated.contains(&(x, y)) {
+                state.animated.push((x, y));
+            }
+        }
+        state.dirty.clear();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "midir")]
+impl TemplateCanvas<crate::Midir> {
+    /// Create a new canvas by guessing both input and output MIDI connection by their name
+    pub fn guess(
+        callback: impl FnMut(CanvasMessage) + Send + 'static,
+    ) -> Result<Self, crate::MidiError> {
+        use crate::midi_io::{InputDevice, OutputDevice};
+
+        let tracker = TemplateTracker::default();
+        let _input = super::Input::guess(Self::wrap_callback(&tracker, callback))?;
+        let output = Output::guess()?;
+
+        Self::from_parts(_input, output, tracker)
+    }
+
+    /// Like [`Self::guess`], but returns a poller instead of taking a callback
+    pub fn guess_polling() -> Result<(Self, crate::DeviceCanvasPoller), crate::MidiError> {
+        let (sender, receiver) = crate::midi_io::poller_channel(None);
+        let canvas = Self::guess(move |msg| sender.send(msg))?;
+
+        Ok((canvas, crate::DeviceCanvasPoller { receiver }))
+    }
+}
+
+impl_traits_for_canvas!(TemplateCanvas[T: MidiTransport]);
+
+impl<T: MidiTransport> Canvas for TemplateCanvas<T> {
+    fn bounding_box(&self) -> (u32, u32) {
+        (Spec::BOUNDING_BOX_WIDTH, Spec::BOUNDING_BOX_HEIGHT)
+    }
+
+    fn lowest_visible_brightness(&self) -> f32 {
+        1.0 / Spec::COLOR_PRECISION as f32
+    }
+
+    fn low_level_get(&self, x: u32, y: u32) -> Option<&Color> {
+        if !Spec::is_valid(x, y) {
+            return None;
+        }
+
+        self.states[self.template().0 as usize].curr.get(x, y)
+    }
+
+    fn low_level_get_pending(&self, x: u32, y: u32) -> Option<&Color> {
+        if !Spec::is_valid(x, y) {
+            return None;
+        }
+
+        self.states[self.template().0 as usize].new.get(x, y)
+    }
+
+    fn low_level_get_pending_mut(&mut self, x: u32, y: u32) -> Option<&mut Color> {
+        let template = self.template().0 as usize;
+        self.states[template].pending_mut(x, y)
+    }
+
+    fn low_level_get_pending_effect(&self, x: u32, y: u32) -> Option<PadEffect> {
+        if !Spec::is_valid(x, y) {
+            return None;
+        }
+
+        self.states[self.template().0 as usize]
+            .effects
+            .get(x, y)
+            .copied()
+    }
+
+    fn low_level_set_pending_effect(&mut self, x: u32, y: u32, effect: PadEffect) -> Option<()> {
+        let template = self.template().0 as usize;
+        self.states[template].set_effect(x, y, effect)
+    }
+
+    /// Only the effects of the current template are animated. The other templates show theirs
+    /// once they're selected.
+    fn effect_refresh_interval(&self) -> Option<std::time::Duration> {
+        match self.states[self.template().0 as usize].animated.is_empty() {
+            true => None,
+            false => Some(crate::EFFECT_FRAME_INTERVAL),
+        }
+    }
+
+    /// Sends the changes of all templates. If the user has switched templates since the last
+    /// flush, all LEDs of the newly selected template are sent, to restore its display state.
+    fn flush(&mut self) -> Result<(), crate::MidiError> {
+        let template = self.template();
+        let switched = template != self.flushed_template;
+        self.flushed_template = template;
+
+        for index in 0..16 {
+            let is_current = index == template.0;
+            self.flush_template(index, switched && is_current, is_current)?;
+        }
+        Ok(())
+    }
+}
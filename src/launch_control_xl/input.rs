@@ -0,0 +1,195 @@
+use super::{Button, Fader, Knob, Template};
+
+/// A Launch Control XL input message. The `timestamp` fields hold the MIDI timestamp of the
+/// message in microseconds, see [`MidiClock`](crate::MidiClock).
+///
+/// ```
+/// use launchy::launch_control_xl::{Button, Fader, Input, Knob, Message, Template};
+/// use launchy::InputDevice as _;
+///
+/// assert_eq!(
+///     Input::decode_message(0, &[0xB8, 50, 64]),
+///     Message::KnobChanged {
+///         template: Template::factory(0),
+///         knob: Knob::pan(1),
+///         value: 64,
+///         timestamp: 0,
+///     },
+/// );
+/// assert_eq!(
+///     Input::decode_message(0, &[0xB0, 84, 127]),
+///     Message::FaderChanged {
+///         template: Template::user(0),
+///         fader: Fader::new(7),
+///         value: 127,
+///         timestamp: 0,
+///     },
+/// );
+/// assert_eq!(
+///     Input::decode_message(0, &[0x90, 89, 127]),
+///     Message::Press {
+///         template: Template::user(0),
+///         button: Button::track_control(4),
+///         timestamp: 0,
+///     },
+/// );
+/// ```
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub enum Message {
+    /// When a button is pressed
+    Press {
+        template: Template,
+        button: Button,
+        timestamp: u64,
+    },
+
+    /// When a button is released
+    Release {
+        template: Template,
+        button: Button,
+        timestamp: u64,
+    },
+
+    /// When a template is changed, either using the template buttons on the Launch Control XL or
+    /// with [`Output::change_template`](super::Output::change_template)
+    TemplateChanged { template: Template, timestamp: u64 },
+
+    /// When a knob has been moved
+    KnobChanged {
+        template: Template,
+        knob: Knob,
+        value: u8,
+        timestamp: u64,
+    },
+
+    /// When a fader has been moved
+    FaderChanged {
+        template: Template,
+        fader: Fader,
+        value: u8,
+        timestamp: u64,
+    },
+
+    /// A message that doesn't match the Launch Control XL protocol, with its raw bytes
+    Verbatim { bytes: Vec<u8> },
+}
+
+/// The Launch Control XL input connection creator.
+pub struct Input;
+
+impl Input {
+    fn decode_short_message(timestamp: u64, data: &[u8]) -> Result<Message, crate::DecodeError> {
+        let status = data[0] & 0xF0;
+        let template = Template(data[0] & 0x0F);
+
+        // The track buttons and the Device, Mute, Solo and Record Arm buttons send notes
+        if let (0x80 | 0x90, Some(button)) = (status, note_button(data[1])) {
+            return Ok(match [status, data[2]] {
+                [0x90, 1..=127] => Message::Press {
+                    template,
+                    button,
+                    timestamp,
+                },
+                _ => Message::Release {
+                    template,
+                    button,
+                    timestamp,
+                },
+            });
+        }
+
+        let msg = match [status, data[1], data[2]] {
+            // Send Select and Track Select buttons
+            [0xB0, number @ 104..=107, value] => {
+                let button = match number {
+                    104 => Button::Up,
+                    105 => Button::Down,
+                    106 => Button::Left,
+                    _ => Button::Right,
+                };
+                match value {
+                    0 => Message::Release {
+                        template,
+                        button,
+                        timestamp,
+                    },
+                    _ => Message::Press {
+                        template,
+                        button,
+                        timestamp,
+                    },
+                }
+            }
+
+            // Knob and fader changes
+            [0xB0, number @ 13..=20, value] => Message::KnobChanged {
+                template,
+                knob: Knob::send_a(number - 13),
+                value,
+                timestamp,
+            },
+            [0xB0, number @ 29..=36, value] => Message::KnobChanged {
+                template,
+                knob: Knob::send_b(number - 29),
+                value,
+                timestamp,
+            },
+            [0xB0, number @ 49..=56, value] => Message::KnobChanged {
+                template,
+                knob: Knob::pan(number - 49),
+                value,
+                timestamp,
+            },
+            [0xB0, number @ 77..=84, value] => Message::FaderChanged {
+                template,
+                fader: Fader::new(number - 77),
+                value,
+                timestamp,
+            },
+
+            _ => return Err(crate::DecodeError::new(data, "unexpected short message")),
+        };
+        Ok(msg)
+    }
+
+    fn decode_sysex_message(timestamp: u64, data: &[u8]) -> Result<Message, crate::DecodeError> {
+        match *data {
+            [240, 0, 32, 41, 2, 17, 119, template @ 0..=15, 247] => Ok(Message::TemplateChanged {
+                template: Template(template),
+                timestamp,
+            }),
+            _ => Err(crate::DecodeError::new(data, "unexpected sysex message")),
+        }
+    }
+}
+
+impl crate::InputDevice for Input {
+    const MIDI_CONNECTION_NAME: &'static str = "Launchy Launch Control XL input";
+    const MIDI_DEVICE_KEYWORD: &'static str = "Launch Control XL";
+    type Message = Message;
+
+    fn verbatim(bytes: Vec<u8>) -> Message {
+        Message::Verbatim { bytes }
+    }
+
+    fn try_decode_message(timestamp: u64, data: &[u8]) -> Result<Message, crate::DecodeError> {
+        match data.len() {
+            3 => Self::decode_short_message(timestamp, data),
+            _ => Self::decode_sysex_message(timestamp, data),
+        }
+    }
+}
+
+fn note_button(note: u8) -> Option<Button> {
+    match note {
+        41..=44 => Some(Button::track_focus(note - 41)),
+        57..=60 => Some(Button::track_focus(note - 57 + 4)),
+        73..=76 => Some(Button::track_control(note - 73)),
+        89..=92 => Some(Button::track_control(note - 89 + 4)),
+        105 => Some(Button::Device),
+        106 => Some(Button::Mute),
+        107 => Some(Button::Solo),
+        108 => Some(Button::RecordArm),
+        _ => None,
+    }
+}
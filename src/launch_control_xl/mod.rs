@@ -0,0 +1,277 @@
+/*!
+# Launch Control XL low-level API
+
+The Launch Control XL has three rows of 8 knobs each (Send A, Send B and Pan/Device), 8 faders
+and two rows of 8 track buttons below them: the track focus buttons and the track control
+buttons. On the right side are the Send Select (up/down) and Track Select (left/right) buttons,
+and the Device, Mute, Solo and Record Arm buttons.
+
+Every knob and every button has a red/green LED. The LEDs are addressed per template: there are
+8 user templates and 8 factory templates, and lighting an LED only affects the template it is
+addressed to. `launchy` decodes input messages according to the factory default mapping, which
+all templates start out with.
+
+The canvas, whose size is 10x5, covers every LED:
+
+```text
+Knob Knob Knob Knob Knob Knob Knob Knob  Up     Down
+Knob Knob Knob Knob Knob Knob Knob Knob  Left   Right
+Knob Knob Knob Knob Knob Knob Knob Knob
+Foc. Foc. Foc. Foc. Foc. Foc. Foc. Foc.  Device Mute
+Ctl. Ctl. Ctl. Ctl. Ctl. Ctl. Ctl. Ctl.  Solo   RecArm
+```
+*/
+
+mod input;
+pub use input::*;
+
+mod output;
+pub use output::*;
+
+pub use crate::launch_control::Template;
+
+/// A button of the Launch Control XL
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Button {
+    /// One of the 8 buttons in the upper row below the faders
+    TrackFocus(u8),
+    /// One of the 8 buttons in the lower row below the faders
+    TrackControl(u8),
+    Device,
+    Mute,
+    Solo,
+    RecordArm,
+    /// Send Select up
+    Up,
+    /// Send Select down
+    Down,
+    /// Track Select left
+    Left,
+    /// Track Select right
+    Right,
+}
+
+impl Button {
+    /// Creates a track focus button, in the upper row. `index` must be below 8
+    pub fn track_focus(index: u8) -> Self {
+        assert!(index < 8);
+        Self::TrackFocus(index)
+    }
+
+    /// Creates a track control button, in the lower row. `index` must be below 8
+    pub fn track_control(index: u8) -> Self {
+        assert!(index < 8);
+        Self::TrackControl(index)
+    }
+
+    /// The index of this button's LED in the LED SysEx messages. The knob LEDs come first, so
+    /// button indices go from 24 to 47.
+    pub fn as_index(self) -> u8 {
+        match self {
+            Self::TrackFocus(index) => 24 + index,
+            Self::TrackControl(index) => 32 + index,
+            Self::Device => 40,
+            Self::Mute => 41,
+            Self::Solo => 42,
+            Self::RecordArm => 43,
+            Self::Up => 44,
+            Self::Down => 45,
+            Self::Left => 46,
+            Self::Right => 47,
+        }
+    }
+
+    /// The inverse of [`Self::as_index`]
+    pub fn from_index(index: u8) -> Self {
+        match index {
+            24..=31 => Self::track_focus(index - 24),
+            32..=39 => Self::track_control(index - 32),
+            40 => Self::Device,
+            41 => Self::Mute,
+            42 => Self::Solo,
+            43 => Self::RecordArm,
+            44 => Self::Up,
+            45 => Self::Down,
+            46 => Self::Left,
+            47 => Self::Right,
+            _ => panic!("Out of bounds index {}", index),
+        }
+    }
+}
+
+/// One of the 24 knobs on the Launch Control XL
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Knob(pub(crate) u8);
+
+impl Knob {
+    /// Create a new knob from the given index, where indices 0-7 correspond to the top row (Send
+    /// A), 8-15 to the middle row (Send B) and 16-23 to the bottom row (Pan/Device)
+    pub fn new(index: u8) -> Self {
+        assert!(index < 24);
+        Self(index)
+    }
+
+    /// Create a new knob in the top row. `index` must be below 8
+    pub fn send_a(index: u8) -> Self {
+        assert!(index < 8);
+        Self(index)
+    }
+
+    /// Create a new knob in the middle row. `index` must be below 8
+    pub fn send_b(index: u8) -> Self {
+        assert!(index < 8);
+        Self(index + 8)
+    }
+
+    /// Create a new knob in the bottom row. `index` must be below 8
+    pub fn pan(index: u8) -> Self {
+        assert!(index < 8);
+        Self(index + 16)
+    }
+
+    /// The index of this knob, see [`Self::new`]. It's also the index of the knob's LED in the
+    /// LED SysEx messages.
+    pub fn index(self) -> u8 {
+        self.0
+    }
+}
+
+/// One of the 8 faders on the Launch Control XL. Faders have no LED.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Fader(pub(crate) u8);
+
+impl Fader {
+    /// Create a new fader from the given index, counting from the left. `index` must be below 8
+    pub fn new(index: u8) -> Self {
+        assert!(index < 8);
+        Self(index)
+    }
+
+    pub fn index(self) -> u8 {
+        self.0
+    }
+}
+
+/// Anything with an LED on the Launch Control XL: a knob or a button
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Led {
+    Knob(Knob),
+    Button(Button),
+}
+
+impl Led {
+    /// The index of this LED in the LED SysEx messages
+    pub fn as_index(self) -> u8 {
+        match self {
+            Self::Knob(knob) => knob.index(),
+            Self::Button(button) => button.as_index(),
+        }
+    }
+
+    fn from_xy(x: u32, y: u32) -> Self {
+        match (x, y) {
+            (0..=7, 0..=2) => Self::Knob(Knob::new((y * 8 + x) as u8)),
+            (0..=7, 3) => Self::Button(Button::track_focus(x as u8)),
+            (0..=7, 4) => Self::Button(Button::track_control(x as u8)),
+            (8, 0) => Self::Button(Button::Up),
+            (9, 0) => Self::Button(Button::Down),
+            (8, 1) => Self::Button(Button::Left),
+            (9, 1) => Self::Button(Button::Right),
+            (8, 3) => Self::Button(Button::Device),
+            (9, 3) => Self::Button(Button::Mute),
+            (8, 4) => Self::Button(Button::Solo),
+            (9, 4) => Self::Button(Button::RecordArm),
+            _ => panic!("Unexpected coordinates ({}|{})", x, y),
+        }
+    }
+}
+
+impl From<Knob> for Led {
+    fn from(knob: Knob) -> Self {
+        Self::Knob(knob)
+    }
+}
+
+impl From<Button> for Led {
+    fn from(button: Button) -> Self {
+        Self::Button(button)
+    }
+}
+
+#[doc(hidden)]
+pub struct Spec;
+
+impl crate::DeviceSpec for Spec {
+    const BOUNDING_BOX_WIDTH: u32 = 10;
+    const BOUNDING_BOX_HEIGHT: u32 = 5;
+    const COLOR_PRECISION: u16 = 4;
+
+    type Input = Input;
+    type Output<S: crate::MidiSink> = Output<S>;
+
+    fn is_valid(x: u32, y: u32) -> bool {
+        x < 10 && y < 5 && !(x >= 8 && y == 2)
+    }
+
+    fn setup<S: crate::MidiSink>(output: &mut Self::Output<S>) -> Result<(), crate::MidiError> {
+        output.change_template(0)
+    }
+
+    fn flush<T: crate::MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
+        changes: &[(u32, u32, (u8, u8, u8))],
+    ) -> Result<(), crate::MidiError> {
+        canvas.output.light_multiple(
+            0,
+            changes.iter().map(|&(x, y, (r, g, _b))| {
+                (
+                    Led::from_xy(x, y),
+                    Color::new(r, g),
+                    DoubleBufferingBehavior::Copy,
+                )
+            }),
+        )
+    }
+
+    fn convert_message(msg: Message) -> Option<crate::CanvasMessage> {
+        fn button_to_xy(button: Button) -> (u32, u32) {
+            match button {
+                Button::TrackFocus(index) => (index as u32, 3),
+                Button::TrackControl(index) => (index as u32, 4),
+                Button::Up => (8, 0),
+                Button::Down => (9, 0),
+                Button::Left => (8, 1),
+                Button::Right => (9, 1),
+                Button::Device => (8, 3),
+                Button::Mute => (9, 3),
+                Button::Solo => (8, 4),
+                Button::RecordArm => (9, 4),
+            }
+        }
+
+        match msg {
+            Message::Press {
+                template: _,
+                button,
+                timestamp,
+            } => {
+                let (x, y) = button_to_xy(button);
+                Some(crate::CanvasMessage::Press { x, y, timestamp })
+            }
+            Message::Release {
+                template: _,
+                button,
+                timestamp,
+            } => {
+                let (x, y) = button_to_xy(button);
+                Some(crate::CanvasMessage::Release { x, y, timestamp })
+            }
+            Message::TemplateChanged { .. }
+            | Message::KnobChanged { .. }
+            | Message::FaderChanged { .. }
+            | Message::Verbatim { .. } => None,
+        }
+    }
+}
+
+pub type Canvas<'a> = crate::DeviceCanvas<Spec>;
@@ -0,0 +1,141 @@
+use super::{Led, Template};
+use crate::midi_io::DefaultSink;
+use crate::{MidiSink, OutputDevice};
+
+#[doc(inline)]
+pub use crate::protocols::double_buffering::*;
+
+/// The Launch Control XL output connection handler.
+///
+/// All LEDs are addressed by SysEx, so they can be lit on any template, not just the currently
+/// selected one.
+///
+/// ```
+/// use launchy::launch_control_xl::{Button, Color, DoubleBufferingBehavior, Knob, Output};
+/// use launchy::OutputDevice as _;
+///
+/// let (sink, sent) = launchy::ChannelSink::new();
+/// let mut output = Output::from_connection(sink)?;
+///
+/// output.light(8, Knob::send_b(2), Color::AMBER, DoubleBufferingBehavior::Copy)?;
+/// assert_eq!(sent.try_recv()?, [240, 0, 32, 41, 2, 17, 120, 8, 10, 55, 247]);
+///
+/// output.light(8, Button::Solo, Color::GREEN, DoubleBufferingBehavior::Copy)?;
+/// assert_eq!(sent.try_recv()?, [240, 0, 32, 41, 2, 17, 120, 8, 42, 52, 247]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct Output<S: MidiSink = DefaultSink> {
+    connection: S,
+}
+
+impl<S: MidiSink> crate::OutputDevice for Output<S> {
+    const MIDI_CONNECTION_NAME: &'static str = "Launchy Launch Control XL output";
+    const MIDI_DEVICE_KEYWORD: &'static str = "Launch Control XL";
+
+    type Sink = S;
+
+    fn from_connection(connection: S) -> Result<Self, crate::MidiError> {
+        Ok(Self { connection })
+    }
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), crate::MidiError> {
+        self.connection.send(bytes)
+    }
+}
+
+impl<S: MidiSink> Output<S> {
+    /// Light multiple knob or button LEDs of the given template with varying colors and double
+    /// buffering behavior.
+    pub fn light_multiple(
+        &mut self,
+        template: impl Into<Template>,
+        leds: impl IntoIterator<Item = impl std::borrow::Borrow<(Led, Color, DoubleBufferingBehavior)>>,
+    ) -> Result<(), crate::MidiError> {
+        let mut bytes = Vec::new();
+        bytes.extend(&[240, 0, 32, 41, 2, 17, 120, template.into().0]);
+        for entry in leds.into_iter() {
+            let &(led, color, dbb) = entry.borrow();
+
+            bytes.extend(&[led.as_index(), make_color_code(color, dbb)]);
+        }
+        bytes.push(247);
+
+        self.send(&bytes)
+    }
+
+    /// Change the template that will be displayed and edited on. The Launch Control XL answers
+    /// with a [`Message::TemplateChanged`](super::Message::TemplateChanged).
+    pub fn change_template(
+        &mut self,
+        template: impl Into<Template>,
+    ) -> Result<(), crate::MidiError> {
+        self.send(&[240, 0, 32, 41, 2, 17, 119, template.into().0, 247])
+    }
+
+    /// Turns on all LEDs of the given template to a certain brightness, dictated by the
+    /// `brightness` parameter.
+    ///
+    /// Like on the Launch Control, this is more of a diagnostic function to check if the device
+    /// is working correctly.
+    pub fn turn_on_all_leds(
+        &mut self,
+        template: impl Into<Template>,
+        brightness: Brightness,
+    ) -> Result<(), crate::MidiError> {
+        let brightness_code = match brightness {
+            Brightness::Off => 0,
+            Brightness::Low => 125,
+            Brightness::Medium => 126,
+            Brightness::Full => 127,
+        };
+
+        self.send(&[0xB0 + template.into().0, 0, brightness_code])
+    }
+
+    /// This method controls the double buffering mode of the given template. It works the same
+    /// way as on the Launchpad S, see
+    /// [`launchpad_s::Output::control_double_buffering`](crate::launchpad_s::Output::control_double_buffering).
+    pub fn control_double_buffering(
+        &mut self,
+        template: impl Into<Template>,
+        d: DoubleBuffering,
+    ) -> Result<(), crate::MidiError> {
+        let last_byte = 0b00100000
+            | ((d.copy as u8) << 4)
+            | ((d.flash as u8) << 3)
+            | ((d.edited_buffer as u8) << 2)
+            | d.displayed_buffer as u8;
+
+        self.send(&[0xB0 + template.into().0, 0, last_byte])
+    }
+
+    // -----------------------------
+    // Shorthand functions:
+    // -----------------------------
+
+    /// All LEDs of the given template are turned off, and the buffer settings are reset to their
+    /// default values.
+    pub fn reset(&mut self, template: impl Into<Template>) -> Result<(), crate::MidiError> {
+        self.turn_on_all_leds(template, Brightness::Off)
+    }
+
+    /// Light a knob or button LED of the given template with a certain color.
+    pub fn light(
+        &mut self,
+        template: impl Into<Template>,
+        led: impl Into<Led>,
+        color: Color,
+        dbb: DoubleBufferingBehavior,
+    ) -> Result<(), crate::MidiError> {
+        self.light_multiple(template, [(led.into(), color, dbb)])
+    }
+
+    /// Turn off a knob or button LED of the given template
+    pub fn turn_off(
+        &mut self,
+        template: impl Into<Template>,
+        led: impl Into<Led>,
+    ) -> Result<(), crate::MidiError> {
+        self.light(template, led, Color::BLACK, DoubleBufferingBehavior::None)
+    }
+}
@@ -0,0 +1,102 @@
+use super::{Button, Knob};
+
+/// A Launchkey Mini MK3 input message, as sent in InControl mode. The `timestamp` fields hold the
+/// MIDI timestamp of the message in microseconds, see [`MidiClock`](crate::MidiClock).
+///
+/// ```
+/// use launchy::launchkey_mini_mk3::{Button, Input, Knob, Message};
+/// use launchy::InputDevice as _;
+///
+/// assert_eq!(
+///     Input::decode_message(0, &[0x90, 113, 80]),
+///     Message::Press { button: Button::pad(1, 1), velocity: 80, timestamp: 0 },
+/// );
+/// assert_eq!(
+///     Input::decode_message(0, &[0xB0, 115, 0]),
+///     Message::Release { button: Button::Play, timestamp: 0 },
+/// );
+/// assert_eq!(
+///     Input::decode_message(0, &[0xB0, 23, 64]),
+///     Message::KnobChanged { knob: Knob::new(2), value: 64, timestamp: 0 },
+/// );
+/// ```
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub enum Message {
+    /// A button was pressed. The Launch, Stop/Solo/Mute and transport buttons always report a
+    /// velocity of 127.
+    Press {
+        button: Button,
+        velocity: u8,
+        timestamp: u64,
+    },
+    /// A button was released
+    Release { button: Button, timestamp: u64 },
+    /// A knob has been moved
+    KnobChanged {
+        knob: Knob,
+        value: u8,
+        timestamp: u64,
+    },
+    /// A message that doesn't match the Launchkey Mini MK3 protocol, with its raw bytes
+    Verbatim { bytes: Vec<u8> },
+}
+
+/// The Launchkey Mini MK3 input connection creator.
+pub struct Input;
+
+impl crate::InputDevice for Input {
+    const MIDI_CONNECTION_NAME: &'static str = "Launchy Launchkey Mini MK3 input";
+    /// Device name.
+    ///
+    /// The Launchkey Mini MK3 has a MIDI port for the keys and a DAW port for InControl mode.
+    /// Only the DAW port is supported. Windows doesn't name it "DAW", but it's recognized as the
+    /// second port of the device there. On platforms where it's named differently, use
+    /// [`InputDevice::select`](crate::InputDevice::select).
+    const MIDI_DEVICE_KEYWORD: &'static str = "Launchkey Mini MK3 DAW";
+    type Message = Message;
+
+    fn verbatim(bytes: Vec<u8>) -> Message {
+        Message::Verbatim { bytes }
+    }
+
+    fn is_device_port(name: &str) -> bool {
+        super::is_daw_port(name)
+    }
+
+    fn try_decode_message(timestamp: u64, data: &[u8]) -> Result<Message, crate::DecodeError> {
+        let unexpected_button =
+            |id: u8| crate::DecodeError::new(data, format!("unexpected button {}", id));
+
+        let msg = match *data {
+            // The pads send notes, the other buttons send control changes
+            [0x90, note, velocity @ 1..=127] => Message::Press {
+                button: Button::from_note(note).ok_or_else(|| unexpected_button(note))?,
+                velocity,
+                timestamp,
+            },
+            [0x80 | 0x90, note, _] => Message::Release {
+                button: Button::from_note(note).ok_or_else(|| unexpected_button(note))?,
+                timestamp,
+            },
+            [0xB0, number @ 21..=28, value] => Message::KnobChanged {
+                knob: Knob::new(number - 21),
+                value,
+                timestamp,
+            },
+            [0xB0, number, value] => {
+                let button =
+                    Button::from_control(number).ok_or_else(|| unexpected_button(number))?;
+                match value {
+                    0 => Message::Release { button, timestamp },
+                    velocity => Message::Press {
+                        button,
+                        velocity,
+                        timestamp,
+                    },
+                }
+            }
+            _ => return Err(crate::DecodeError::new(data, "unexpected message")),
+        };
+        Ok(msg)
+    }
+}
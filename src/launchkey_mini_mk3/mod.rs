@@ -0,0 +1,208 @@
+/*!
+# Launchkey Mini MK3 low-level API
+
+The Launchkey Mini MK3 is a 25 key keyboard with 16 velocity sensitive RGB pads in two rows of 8,
+8 knobs, the round Launch and Stop/Solo/Mute buttons to the right of the pads, and the Play and
+Record transport buttons.
+
+`launchy` talks to the Launchkey Mini MK3 over its DAW port, in InControl mode (also called DAW
+mode), which is enabled upon connecting. In InControl mode, the pads are in the Session layout
+and can be lit with the 128 color palette of the MK3 generation. The keys keep sending notes on
+the other (MIDI) port and are not handled by this module.
+
+The canvas is 8x2 and consists of the pads. The Launchkey has no RGB lighting, so canvas colors
+are shown as the closest palette color.
+*/
+
+mod input;
+pub use input::*;
+
+mod output;
+pub use output::*;
+
+/// Whether a port is the DAW port. Windows names it after the MIDI port instead, as the second
+/// port of the device, e.g. "MIDIIN2 (Launchkey Mini MK3 MIDI)", and cuts the name off after 31
+/// characters
+fn is_daw_port(name: &str) -> bool {
+    match crate::midi_io::parse_windows_port_name(name) {
+        Some((number, device)) => number == 2 && device.starts_with("Launchkey Mini MK3"),
+        None => name.contains("Launchkey Mini MK3 DAW"),
+    }
+}
+
+/// A button of the Launchkey Mini MK3
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum Button {
+    /// One of the 16 pads. `y` is 0 for the upper row and 1 for the lower row
+    Pad {
+        x: u8,
+        y: u8,
+    },
+    /// The round ">" button to the right of the upper pad row
+    Launch,
+    /// The round Stop/Solo/Mute button to the right of the lower pad row
+    StopSoloMute,
+    Play,
+    Record,
+}
+
+impl Button {
+    /// Creates a new pad coordinate. `x` must be below 8 and `y` below 2
+    pub fn pad(x: u8, y: u8) -> Self {
+        assert!(x < 8 && y < 2);
+        Self::Pad { x, y }
+    }
+
+    /// The note number of this button if it's a pad, or the control change number otherwise
+    pub(crate) fn code(self) -> u8 {
+        match self {
+            Self::Pad { x, y: 0 } => 96 + x,
+            Self::Pad { x, .. } => 112 + x,
+            Self::Launch => 104,
+            Self::StopSoloMute => 105,
+            Self::Play => 115,
+            Self::Record => 117,
+        }
+    }
+
+    pub(crate) fn from_note(note: u8) -> Option<Self> {
+        match note {
+            96..=103 => Some(Self::pad(note - 96, 0)),
+            112..=119 => Some(Self::pad(note - 112, 1)),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn from_control(number: u8) -> Option<Self> {
+        match number {
+            104 => Some(Self::Launch),
+            105 => Some(Self::StopSoloMute),
+            115 => Some(Self::Play),
+            117 => Some(Self::Record),
+            _ => None,
+        }
+    }
+}
+
+/// One of the 8 knobs on the Launchkey Mini MK3
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Knob(pub(crate) u8);
+
+impl Knob {
+    /// Create a new knob from the given index, counting from the left. `index` must be below 8
+    pub fn new(index: u8) -> Self {
+        assert!(index < 8);
+        Self(index)
+    }
+
+    pub fn index(self) -> u8 {
+        self.0
+    }
+}
+
+/// The palette color that comes closest to a quantized canvas color
+fn nearest_palette_color(color: (u8, u8, u8)) -> PaletteColor {
+    use crate::DeviceSpec as _;
+
+    let color = crate::protocols::palette::dequantize(color, Spec::COLOR_PRECISION as u8);
+    PaletteColor::nearest(color)
+}
+
+#[doc(hidden)]
+pub struct Spec;
+
+impl crate::DeviceSpec for Spec {
+    const BOUNDING_BOX_WIDTH: u32 = 8;
+    const BOUNDING_BOX_HEIGHT: u32 = 2;
+    const COLOR_PRECISION: u16 = 4;
+
+    type Input = Input;
+    type Output<S: crate::MidiSink> = Output<S>;
+
+    fn is_valid(x: u32, y: u32) -> bool {
+        x < 8 && y < 2
+    }
+
+    fn flush<T: crate::MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
+        changes: &[(u32, u32, (u8, u8, u8))],
+    ) -> Result<(), crate::MidiError> {
+        for &(x, y, color) in changes {
+            let color = nearest_palette_color(color);
+            canvas.output.light(Button::pad(x as u8, y as u8), color)?;
+        }
+        Ok(())
+    }
+
+    fn supports_effect<T: crate::MidiTransport>(
+        _canvas: &crate::DeviceCanvas<Self, T>,
+        _effect: crate::PadEffect,
+    ) -> bool {
+        true
+    }
+
+    fn flush_effects<T: crate::MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
+        changes: &[crate::EffectChange],
+    ) -> Result<(), crate::MidiError> {
+        for &(x, y, color, effect) in changes {
+            let button = Button::pad(x as u8, y as u8);
+            let color = nearest_palette_color(color);
+            match effect {
+                crate::PadEffect::Flash { other } => {
+                    // The flash alternates between the previously shown color and the given one
+                    canvas.output.light(button, color)?;
+                    canvas.output.flash(button, PaletteColor::nearest(other))?;
+                }
+                crate::PadEffect::Pulse => canvas.output.pulse(button, color)?,
+                crate::PadEffect::Steady => canvas.output.light(button, color)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn convert_message(msg: Message) -> Option<crate::CanvasMessage> {
+        match msg {
+            Message::Press {
+                button: Button::Pad { x, y },
+                timestamp,
+                ..
+            } => Some(crate::CanvasMessage::Press {
+                x: x as u32,
+                y: y as u32,
+                timestamp,
+            }),
+            Message::Release {
+                button: Button::Pad { x, y },
+                timestamp,
+            } => Some(crate::CanvasMessage::Release {
+                x: x as u32,
+                y: y as u32,
+                timestamp,
+            }),
+            Message::Press { .. }
+            | Message::Release { .. }
+            | Message::KnobChanged { .. }
+            | Message::Verbatim { .. } => None,
+        }
+    }
+}
+
+pub type Canvas<'a> = crate::DeviceCanvas<Spec>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daw_port_is_found_on_every_platform() {
+        assert!(is_daw_port("Launchkey Mini MK3 DAW Port"));
+        assert!(is_daw_port("MIDIIN2 (Launchkey Mini MK3 MIDI)"));
+        assert!(is_daw_port("MIDIOUT2 (Launchkey Mini MK3 MID"));
+        assert!(is_daw_port("MIDIOUT2 (2- Launchkey Mini MK3 "));
+
+        assert!(!is_daw_port("Launchkey Mini MK3 MIDI Port"));
+        assert!(!is_daw_port("Launchkey Mini MK3 MIDI"));
+        assert!(!is_daw_port("2- Launchkey Mini MK3 MIDI"));
+    }
+}
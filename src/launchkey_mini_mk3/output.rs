@@ -0,0 +1,168 @@
+pub use crate::protocols::mk3::{LightMode, PaletteColor};
+
+use super::Button;
+use crate::midi_io::DefaultSink;
+use crate::{MidiSink, OutputDevice};
+
+/// The Launchkey Mini MK3 output connection handler. Connecting enables InControl mode.
+///
+/// ```
+/// use launchy::launchkey_mini_mk3::{Button, Output, PaletteColor};
+/// use launchy::OutputDevice as _;
+///
+/// let (sink, sent) = launchy::ChannelSink::new();
+/// let mut output = Output::from_connection(sink)?;
+/// assert_eq!(sent.try_recv()?, [0x9F, 12, 127]);
+///
+/// output.light(Button::pad(3, 0), PaletteColor::RED)?;
+/// output.pulse(Button::Launch, PaletteColor::GREEN)?;
+/// assert_eq!(sent.try_recv()?, [0x90, 99, 5]);
+/// assert_eq!(sent.try_recv()?, [0xB2, 104, 21]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct Output<S: MidiSink = DefaultSink> {
+    connection: S,
+}
+
+impl<S: MidiSink> crate::OutputDevice for Output<S> {
+    const MIDI_CONNECTION_NAME: &'static str = "Launchy Launchkey Mini MK3 output";
+    /// Device name.
+    ///
+    /// The Launchkey Mini MK3 has a MIDI port for the keys and a DAW port for InControl mode.
+    /// Only the DAW port is supported. Windows doesn't name it "DAW", but it's recognized as the
+    /// second port of the device there. On platforms where it's named differently, use
+    /// [`OutputDevice::select`](crate::OutputDevice::select).
+    const MIDI_DEVICE_KEYWORD: &'static str = "Launchkey Mini MK3 DAW";
+
+    type Sink = S;
+
+    fn from_connection(connection: S) -> Result<Self, crate::MidiError> {
+        let mut self_ = Self { connection };
+        self_.set_incontrol_mode(true)?;
+        Ok(self_)
+    }
+
+    fn is_device_port(name: &str) -> bool {
+        super::is_daw_port(name)
+    }
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), crate::MidiError> {
+        self.connection.send(bytes)
+    }
+
+    /// Plain LED messages only depend on the latest color
+    fn coalescing_key(bytes: &[u8]) -> Option<u32> {
+        match *bytes {
+            [status @ (0x90 | 0xB0), key, _] => Some(u32::from_be_bytes([0, 0, status, key])),
+            _ => None,
+        }
+    }
+}
+
+impl<S: MidiSink> Output<S> {
+    /// Enables or disables InControl mode. In InControl mode, the pads, knobs and buttons are
+    /// reported on the DAW port and their LEDs can be controlled. When disabled, the Launchkey
+    /// falls back to its standalone behavior and lights the pads itself.
+    pub fn set_incontrol_mode(&mut self, enabled: bool) -> Result<(), crate::MidiError> {
+        self.send(&[0x9F, 12, if enabled { 127 } else { 0 }])
+    }
+
+    /// Set a `button` to a certain palette `color` with a certain `light_mode`.
+    ///
+    /// The pads and the Launch and Stop/Solo/Mute buttons have RGB LEDs. The LEDs of the Play and
+    /// Record buttons can't be controlled.
+    pub fn set_button(
+        &mut self,
+        button: Button,
+        color: PaletteColor,
+        light_mode: LightMode,
+    ) -> Result<(), crate::MidiError> {
+        assert!(color.id <= 127);
+
+        let type_byte = match button {
+            Button::Pad { .. } => 0x90,
+            _ => 0xB0,
+        } + match light_mode {
+            LightMode::Plain => 0,
+            LightMode::Flash => 1,
+            LightMode::Pulse => 2,
+        };
+
+        self.send(&[type_byte, button.code(), color.id])
+    }
+
+    /// Light a button with a palette color. Identical to
+    /// `set_button(<button>, <color>, LightMode::Plain)`.
+    pub fn light(&mut self, button: Button, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.set_button(button, color, LightMode::Plain)
+    }
+
+    /// Starts a flashing motion between the previously shown color on this button and palette
+    /// color `color`. Identical to `set_button(<button>, <color>, LightMode::Flash)`.
+    pub fn flash(&mut self, button: Button, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.set_button(button, color, LightMode::Flash)
+    }
+
+    /// Start a pulse; a rhythmic increase and decreases in brightness. Identical to
+    /// `set_button(<button>, <color>, LightMode::Pulse)`.
+    pub fn pulse(&mut self, button: Button, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.set_button(button, color, LightMode::Pulse)
+    }
+
+    /// Light multiple buttons with varying colors.
+    pub fn light_multiple(
+        &mut self,
+        buttons: impl IntoIterator<Item = impl std::borrow::Borrow<(Button, PaletteColor)>>,
+    ) -> Result<(), crate::MidiError> {
+        for pair in buttons {
+            let (button, color) = *pair.borrow();
+            self.light(button, color)?;
+        }
+        Ok(())
+    }
+
+    /// Start flashing multiple buttons with varying colors.
+    pub fn flash_multiple(
+        &mut self,
+        buttons: impl IntoIterator<Item = impl std::borrow::Borrow<(Button, PaletteColor)>>,
+    ) -> Result<(), crate::MidiError> {
+        for pair in buttons {
+            let (button, color) = *pair.borrow();
+            self.flash(button, color)?;
+        }
+        Ok(())
+    }
+
+    /// Start pulsing multiple buttons with varying colors.
+    pub fn pulse_multiple(
+        &mut self,
+        buttons: impl IntoIterator<Item = impl std::borrow::Borrow<(Button, PaletteColor)>>,
+    ) -> Result<(), crate::MidiError> {
+        for pair in buttons {
+            let (button, color) = *pair.borrow();
+            self.pulse(button, color)?;
+        }
+        Ok(())
+    }
+
+    /// Light all pads with the same palette color
+    pub fn light_all(&mut self, color: PaletteColor) -> Result<(), crate::MidiError> {
+        for y in 0..2 {
+            for x in 0..8 {
+                self.light(Button::pad(x, y), color)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Turn off all pads
+    pub fn clear(&mut self) -> Result<(), crate::MidiError> {
+        self.light_all(PaletteColor::BLACK)
+    }
+
+    /// Sends a MIDI clock tick. The flashing and pulsing speeds follow the tempo of these ticks,
+    /// with 24 ticks per beat.
+    pub fn send_clock_tick(&mut self) -> Result<(), crate::MidiError> {
+        self.send(&[248, 0, 0])
+    }
+}
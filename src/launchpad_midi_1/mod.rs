@@ -20,6 +20,27 @@ pub use output::*;
 
 pub use crate::protocols::Button80 as Button;
 
+/// The port names of the other Launchpads, which all contain "Launchpad" as well. The MK3
+/// generation and the Launchpad X are named by their short names on Windows
+const OTHER_MODEL_KEYWORDS: [&str; 8] = [
+    "Launchpad S",
+    "Launchpad Mini",
+    "Launchpad MK2",
+    "Launchpad X",
+    "Launchpad Pro",
+    "LPMiniMK3",
+    "LPX",
+    "LPProMK3",
+];
+
+/// Whether a port belongs to a Launchpad MIDI 1, whose port is just called "Launchpad"
+fn is_device_port(name: &str) -> bool {
+    name.contains("Launchpad")
+        && !OTHER_MODEL_KEYWORDS
+            .iter()
+            .any(|keyword| name.contains(keyword))
+}
+
 #[doc(hidden)]
 pub struct Spec;
 
@@ -29,7 +50,7 @@ impl crate::DeviceSpec for Spec {
     const COLOR_PRECISION: u16 = 4;
 
     type Input = Input;
-    type Output = Output;
+    type Output<S: crate::MidiSink> = Output<S>;
 
     fn is_valid(x: u32, y: u32) -> bool {
         if x > 8 || y > 8 {
@@ -41,90 +62,73 @@ impl crate::DeviceSpec for Spec {
         true
     }
 
-    fn flush(
-        canvas: &mut crate::DeviceCanvas<Self>,
+    fn flush<T: crate::MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
         changes: &[(u32, u32, (u8, u8, u8))],
     ) -> Result<(), crate::MidiError> {
-        use crate::Canvas;
-
-        let convert_color = |color: crate::Color| {
-            let (r, g, _b) = color.quantize(Self::COLOR_PRECISION as u8);
-            Color::new(r, g)
-        };
-
-        // Because rapid-update mode lets us set 2 LEDs per instruction, if we
-        // have more than 40 updates, it's faster to use rapid-update mode to
-        // re-write the whole canvas
-        if changes.len() > 40 {
-            // Set the main body
-            for y in 1..=8 {
-                for x in (0..=7).step_by(2) {
-                    canvas.output.set_button_rapid(
-                        convert_color(*canvas.low_level_get_pending(x, y).unwrap()),
-                        DoubleBufferingBehavior::Copy,
-                        convert_color(*canvas.low_level_get_pending(x + 1, y).unwrap()),
-                        DoubleBufferingBehavior::Copy,
-                    )?;
-                }
-            }
-
-            // Set the scene launch buttons (x = 8)
-            for y in (1..=8).step_by(2) {
-                canvas.output.set_button_rapid(
-                    convert_color(*canvas.low_level_get_pending(8, y).unwrap()),
-                    DoubleBufferingBehavior::Copy,
-                    convert_color(*canvas.low_level_get_pending(8, y + 1).unwrap()),
-                    DoubleBufferingBehavior::Copy,
-                )?;
-            }
-
-            // Set the Automap/live buttons (y = 0)
-            for x in (0..=7).step_by(2) {
-                canvas.output.set_button_rapid(
-                    convert_color(*canvas.low_level_get_pending(x, 0).unwrap()),
-                    DoubleBufferingBehavior::Copy,
-                    convert_color(*canvas.low_level_get_pending(x + 1, 0).unwrap()),
-                    DoubleBufferingBehavior::Copy,
-                )?;
-            }
-
-            // dummy-light some button just to get out of the rapid update mode
-            canvas.output.light(
-                Button::ControlButton { index: 0 },
-                convert_color(*canvas.low_level_get_pending(0, 0).unwrap()),
-            )?;
-        } else {
-            for &(x, y, (r, g, _b)) in changes {
-                canvas
-                    .output
-                    .light(Button::from_abs(x as u8, y as u8), Color::new(r, g))?;
-            }
-        }
+        crate::protocols::double_buffering::flush(canvas, changes)
+    }
 
-        Ok(())
+    fn supports_effect<T: crate::MidiTransport>(
+        canvas: &crate::DeviceCanvas<Self, T>,
+        effect: crate::PadEffect,
+    ) -> bool {
+        crate::protocols::double_buffering::supports_effect(canvas, effect)
+    }
+
+    fn flush_effects<T: crate::MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
+        changes: &[crate::EffectChange],
+    ) -> Result<(), crate::MidiError> {
+        crate::protocols::double_buffering::flush_effects(canvas, changes)
     }
 
     fn convert_message(msg: Message) -> Option<crate::CanvasMessage> {
         match msg {
-            Message::Press { button } => Some(crate::CanvasMessage::Press {
+            Message::Press { button, timestamp } => Some(crate::CanvasMessage::Press {
                 x: button.abs_x() as u32,
                 y: button.abs_y() as u32,
+                timestamp,
             }),
-            Message::Release { button } => Some(crate::CanvasMessage::Release {
+            Message::Release { button, timestamp } => Some(crate::CanvasMessage::Release {
                 x: button.abs_x() as u32,
                 y: button.abs_y() as u32,
+                timestamp,
             }),
             Message::TextEndedOrLooped
             | Message::UnknownShortMessage { .. }
             | Message::DeviceInquiry(_)
-            | Message::VersionInquiry(_) => None,
+            | Message::VersionInquiry(_)
+            | Message::Verbatim { .. } => None,
         }
     }
 
-    fn setup(output: &mut Self::Output) -> Result<(), crate::MidiError> {
+    fn setup<S: crate::MidiSink>(output: &mut Self::Output<S>) -> Result<(), crate::MidiError> {
         output.reset()?;
         Ok(())
     }
 }
 
 pub type Canvas<'a> = crate::DeviceCanvas<Spec>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn other_launchpad_ports_are_excluded() {
+        assert!(is_device_port("Launchpad"));
+        assert!(is_device_port("Launchpad:Launchpad MIDI 1 20:0"));
+        assert!(is_device_port("2- Launchpad"));
+        assert!(!is_device_port("Launchpad S"));
+        assert!(!is_device_port("Launchpad Mini:Launchpad Mini MIDI 1 20:0"));
+        assert!(!is_device_port("Launchpad MK2"));
+        assert!(!is_device_port(
+            "Launchpad Mini MK3:Launchpad Mini MK3 LPMiniMK3 MI 20:1"
+        ));
+        assert!(!is_device_port("Launchpad X LPX MIDI"));
+        assert!(!is_device_port("MIDIOUT2 (Launchpad Pro)"));
+        assert!(!is_device_port("Launchpad Pro MK3 LPProMK3 MIDI"));
+        assert!(!is_device_port("Launch Control"));
+    }
+}
@@ -12,6 +12,12 @@ pub use output::*;
 
 pub use crate::protocols::Button80 as Button;
 
+/// Whether a port belongs to a Launchpad Mini. The ports of the Launchpad Mini MK3 contain
+/// "Launchpad Mini" as well
+fn is_device_port(name: &str) -> bool {
+    name.contains("Launchpad Mini") && !name.contains("Launchpad Mini MK3")
+}
+
 #[doc(hidden)]
 pub struct Spec;
 
@@ -21,7 +27,7 @@ impl crate::DeviceSpec for Spec {
     const COLOR_PRECISION: u16 = 4;
 
     type Input = Input;
-    type Output = Output;
+    type Output<S: crate::MidiSink> = Output<S>;
 
     fn is_valid(x: u32, y: u32) -> bool {
         if x > 8 || y > 8 {
@@ -33,84 +39,63 @@ impl crate::DeviceSpec for Spec {
         true
     }
 
-    fn flush(
-        canvas: &mut crate::DeviceCanvas<Self>,
+    fn flush<T: crate::MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
         changes: &[(u32, u32, (u8, u8, u8))],
     ) -> Result<(), crate::MidiError> {
-        use crate::Canvas;
-
-        let convert_color = |color: crate::Color| {
-            let (r, g, _b) = color.quantize(Self::COLOR_PRECISION as u8);
-            Color::new(r, g)
-        };
-
-        // Because rapid-update mode lets us set 2 LEDs per instruction, if we
-        // have more than 40 updates, it's faster to use rapid-update mode to
-        // re-write the whole canvas
-        if changes.len() > 40 {
-            // Set the main body
-            for y in 1..=8 {
-                for x in (0..=7).step_by(2) {
-                    canvas.output.set_button_rapid(
-                        convert_color(*canvas.low_level_get_pending(x, y).unwrap()),
-                        DoubleBufferingBehavior::Copy,
-                        convert_color(*canvas.low_level_get_pending(x + 1, y).unwrap()),
-                        DoubleBufferingBehavior::Copy,
-                    )?;
-                }
-            }
-
-            // Set the scene launch buttons (x = 8)
-            for y in (1..=8).step_by(2) {
-                canvas.output.set_button_rapid(
-                    convert_color(*canvas.low_level_get_pending(8, y).unwrap()),
-                    DoubleBufferingBehavior::Copy,
-                    convert_color(*canvas.low_level_get_pending(8, y + 1).unwrap()),
-                    DoubleBufferingBehavior::Copy,
-                )?;
-            }
-
-            // Set the Automap/live buttons (y = 0)
-            for x in (0..=7).step_by(2) {
-                canvas.output.set_button_rapid(
-                    convert_color(*canvas.low_level_get_pending(x, 0).unwrap()),
-                    DoubleBufferingBehavior::Copy,
-                    convert_color(*canvas.low_level_get_pending(x + 1, 0).unwrap()),
-                    DoubleBufferingBehavior::Copy,
-                )?;
-            }
-
-            // dummy-light some button just to get out of the rapid update mode
-            canvas.output.light(
-                Button::ControlButton { index: 0 },
-                convert_color(*canvas.low_level_get_pending(0, 0).unwrap()),
-            )?;
-        } else {
-            for &(x, y, (r, g, _b)) in changes {
-                canvas
-                    .output
-                    .light(Button::from_abs(x as u8, y as u8), Color::new(r, g))?;
-            }
-        }
+        crate::protocols::double_buffering::flush(canvas, changes)
+    }
+
+    fn supports_effect<T: crate::MidiTransport>(
+        canvas: &crate::DeviceCanvas<Self, T>,
+        effect: crate::PadEffect,
+    ) -> bool {
+        crate::protocols::double_buffering::supports_effect(canvas, effect)
+    }
 
-        Ok(())
+    fn flush_effects<T: crate::MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
+        changes: &[crate::EffectChange],
+    ) -> Result<(), crate::MidiError> {
+        crate::protocols::double_buffering::flush_effects(canvas, changes)
     }
 
     fn convert_message(msg: Message) -> Option<crate::CanvasMessage> {
         match msg {
-            Message::Press { button } => Some(crate::CanvasMessage::Press {
+            Message::Press { button, timestamp } => Some(crate::CanvasMessage::Press {
                 x: button.abs_x() as u32,
                 y: button.abs_y() as u32,
+                timestamp,
             }),
-            Message::Release { button } => Some(crate::CanvasMessage::Release {
+            Message::Release { button, timestamp } => Some(crate::CanvasMessage::Release {
                 x: button.abs_x() as u32,
                 y: button.abs_y() as u32,
+                timestamp,
             }),
             Message::TextEndedOrLooped => None,
             Message::DeviceInquiry(_) => None,
             Message::VersionInquiry(_) => None,
+            Message::Verbatim { .. } => None,
         }
     }
 }
 
 pub type Canvas<'a> = crate::DeviceCanvas<Spec>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn launchpad_mini_mk3_ports_are_excluded() {
+        assert!(is_device_port("Launchpad Mini"));
+        assert!(is_device_port("Launchpad Mini:Launchpad Mini MIDI 1 20:0"));
+        assert!(is_device_port("2- Launchpad Mini"));
+        assert!(!is_device_port(
+            "Launchpad Mini MK3:Launchpad Mini MK3 LPMiniMK3 MI 20:1"
+        ));
+        assert!(!is_device_port("Launchpad Mini MK3 LPMiniMK3 DAW"));
+        assert!(!is_device_port("LPMiniMK3 MIDI"));
+        assert!(!is_device_port("Launchpad MK2"));
+    }
+}
@@ -0,0 +1,167 @@
+use super::{Button, DawLayout, Message};
+
+/// The note mapping of the 8x8 grid in one of the custom modes of the Launchpad Mini MK3: the
+/// Drums, Keys and User layouts.
+///
+/// In the custom modes, the grid sends notes on the MIDI port, which are decoded as
+/// [`Message::NotePress`] and [`Message::NoteRelease`]. A `NoteLayout` maps those notes back to
+/// grid buttons, and tells which note lights which pad, see
+/// [`Output::light_custom`](super::Output::light_custom).
+///
+/// The constructors [`NoteLayout::drums`], [`NoteLayout::keys`] and [`NoteLayout::user`] describe
+/// the factory default custom modes. Custom modes that were changed in Novation Components can be
+/// described with [`NoteLayout::new`].
+///
+/// ```
+/// use launchy::mini_mk3::{Button, Input, Message, NoteLayout};
+/// use launchy::InputDevice as _;
+///
+/// let drums = NoteLayout::drums();
+/// let message = Input::decode_message(0, &[0x98, 36, 127]);
+/// assert_eq!(
+///     drums.translate(&message),
+///     Some(Message::Press { button: Button::grid(0, 7), timestamp: 0 }),
+/// );
+/// assert_eq!(drums.note(Button::grid(7, 0)), Some(99));
+/// ```
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct NoteLayout {
+    channel: u8,
+    /// Indexed by `[y][x]`, with `y` counting from the top like [`Button::GridButton`]
+    notes: [[Option<u8>; 8]; 8],
+}
+
+impl NoteLayout {
+    /// Creates a note layout with the MIDI `channel` (1-15) and the note of every grid button,
+    /// indexed by `[y][x]` with `y` counting from the top. Buttons that don't send a note are
+    /// `None`.
+    ///
+    /// Channel 0 (MIDI channel 1) is taken by the programmer mode, so notes on it are decoded as
+    /// programmer mode buttons and can't be translated. Panics if `channel` is 0.
+    pub fn new(channel: u8, notes: [[Option<u8>; 8]; 8]) -> Self {
+        assert!(
+            (1..=15).contains(&channel),
+            "custom modes can't use the channel of the programmer mode"
+        );
+        assert!(notes.iter().flatten().flatten().all(|&note| note <= 127));
+        Self { channel, notes }
+    }
+
+    fn from_fn(channel: u8, note: impl Fn(u8, u8) -> Option<u8>) -> Self {
+        let mut notes = [[None; 8]; 8];
+        for (y, row) in notes.iter_mut().enumerate() {
+            for (x, slot) in row.iter_mut().enumerate() {
+                *slot = note(x as u8, y as u8);
+            }
+        }
+        Self::new(channel, notes)
+    }
+
+    /// The factory default Drums layout: four 4x4 drum racks on MIDI channel 9, with notes 36 to
+    /// 51 in the bottom left quarter, 52 to 67 in the bottom right, 68 to 83 in the top left and
+    /// 84 to 99 in the top right. Within a quarter, notes go up to the right and then upwards.
+    pub fn drums() -> Self {
+        Self::from_fn(8, |x, y| {
+            let quarter_base = match (x / 4, y / 4) {
+                (0, 1) => 36,
+                (1, 1) => 52,
+                (0, 0) => 68,
+                _ => 84,
+            };
+            Some(quarter_base + (3 - y % 4) * 4 + x % 4)
+        })
+    }
+
+    /// The factory default Keys layout: a piano keyboard on MIDI channel 8, with four octaves
+    /// starting at note 36 (C2). Every pair of rows is an octave, from the bottom up, with the
+    /// white keys in the lower and the black keys in the upper row of a pair.
+    pub fn keys() -> Self {
+        const WHITE: [u8; 8] = [0, 2, 4, 5, 7, 9, 11, 12];
+        const BLACK: [Option<u8>; 8] = [
+            None,
+            Some(1),
+            Some(3),
+            None,
+            Some(6),
+            Some(8),
+            Some(10),
+            None,
+        ];
+
+        Self::from_fn(7, |x, y| {
+            let octave_base = 36 + 12 * (3 - y / 2);
+            match y % 2 {
+                1 => Some(octave_base + WHITE[x as usize]),
+                _ => BLACK[x as usize].map(|offset| octave_base + offset),
+            }
+        })
+    }
+
+    /// The factory default User layout: MIDI channel 16, with the same note numbers as the
+    /// programmer mode, i.e. 11 in the bottom left to 88 in the top right.
+    pub fn user() -> Self {
+        Self::from_fn(15, |x, y| Some(10 * (8 - y) + x + 1))
+    }
+
+    /// The factory default note layout of the given layout, if it's one of the custom modes
+    pub fn for_layout(layout: DawLayout) -> Option<Self> {
+        match layout {
+            DawLayout::Drums => Some(Self::drums()),
+            DawLayout::Keys => Some(Self::keys()),
+            DawLayout::User => Some(Self::user()),
+            DawLayout::Session | DawLayout::Faders | DawLayout::Programmer => None,
+        }
+    }
+
+    /// The MIDI channel (0-15) of this layout
+    pub fn channel(&self) -> u8 {
+        self.channel
+    }
+
+    /// The note that the given grid button sends, and that lights it. The side buttons and the
+    /// control buttons have no note.
+    pub fn note(&self, button: Button) -> Option<u8> {
+        match button {
+            Button::GridButton { x: x @ 0..=7, y } => self.notes[y as usize][x as usize],
+            _ => None,
+        }
+    }
+
+    /// The grid button that sends `note` on `channel`, if any. If multiple buttons send the same
+    /// note, the topmost, then leftmost one is returned.
+    pub fn button(&self, channel: u8, note: u8) -> Option<Button> {
+        if channel != self.channel {
+            return None;
+        }
+        self.notes.iter().enumerate().find_map(|(y, row)| {
+            let x = row.iter().position(|&n| n == Some(note))?;
+            Some(Button::grid(x as u8, y as u8))
+        })
+    }
+
+    /// Translates a [`Message::NotePress`] or [`Message::NoteRelease`] of this layout into a
+    /// [`Message::Press`] or [`Message::Release`] of the corresponding grid button. Other
+    /// messages, and notes that aren't part of this layout, return `None`.
+    pub fn translate(&self, message: &Message) -> Option<Message> {
+        match *message {
+            Message::NotePress {
+                channel,
+                note,
+                timestamp,
+                ..
+            } => Some(Message::Press {
+                button: self.button(channel, note)?,
+                timestamp,
+            }),
+            Message::NoteRelease {
+                channel,
+                note,
+                timestamp,
+            } => Some(Message::Release {
+                button: self.button(channel, note)?,
+                timestamp,
+            }),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use super::{Button, ButtonStyle, Fader, FaderOrientation, LightMode, PaletteColor};
+use crate::midi_io::DefaultSink;
+use crate::protocols::mk3::{
+    decode_control_button, decode_grid_button, encode_button, fader_setup_payload, push_led_spec,
+};
+use crate::{MidiSink, OutputDevice};
+
+/// The SysEx header of all Launchpad Mini MK3 specific messages
+const SYSEX_HEADER: [u8; 6] = [240, 0, 32, 41, 2, 13];
+
+/// The layouts of the Launchpad Mini MK3 in DAW mode. Apart from [`DawLayout::Faders`] and
+/// [`DawLayout::Programmer`], the user can switch between them with the Session, Drums, Keys and
+/// User buttons.
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum DawLayout {
+    /// The session grid, which is lit by the DAW
+    Session,
+    /// Custom mode 1, which is a drum rack by default. Its pads are sent on the MIDI port.
+    Drums,
+    /// Custom mode 2, which is a keyboard by default. Its pads are sent on the MIDI port.
+    Keys,
+    /// Custom mode 3, the user layout. Its pads are sent on the MIDI port.
+    User,
+    /// The faders configured with [`DawOutput::designate_faders`]
+    Faders,
+    /// The programmer mode layout
+    Programmer,
+}
+
+impl DawLayout {
+    pub(crate) fn id(self) -> u8 {
+        match self {
+            Self::Session => 0,
+            Self::Drums => 4,
+            Self::Keys => 5,
+            Self::User => 6,
+            Self::Faders => 13,
+            Self::Programmer => 127,
+        }
+    }
+
+    fn from_id(id: u8) -> Option<Self> {
+        Some(match id {
+            0 => Self::Session,
+            4 => Self::Drums,
+            5 => Self::Keys,
+            6 => Self::User,
+            13 => Self::Faders,
+            127 => Self::Programmer,
+            _ => return None,
+        })
+    }
+}
+
+/// A message from the DAW port of the Launchpad Mini MK3.
+///
+/// The `timestamp` fields hold the MIDI timestamp of the message in microseconds, see
+/// [`MidiClock`](crate::MidiClock).
+///
+/// ```
+/// use launchy::mini_mk3::{Button, DawInput, DawLayout, DawMessage};
+/// use launchy::InputDevice as _;
+///
+/// assert_eq!(
+///     DawInput::decode_message(0, &[0x90, 81, 127]),
+///     DawMessage::Press { button: Button::grid(0, 0), timestamp: 0 },
+/// );
+/// assert_eq!(
+///     DawInput::decode_message(0, &[0xB4, 20, 100]),
+///     DawMessage::FaderChange { cc: 20, value: 100, timestamp: 0 },
+/// );
+/// assert_eq!(
+///     DawInput::decode_message(0, &[240, 0, 32, 41, 2, 13, 0, 5, 247]),
+///     DawMessage::LayoutChange { layout: DawLayout::Keys, timestamp: 0 },
+/// );
+/// ```
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub enum DawMessage {
+    /// A button was pressed. The grid only reports presses in [`DawLayout::Session`].
+    Press { button: Button, timestamp: u64 },
+    /// A button was released
+    Release { button: Button, timestamp: u64 },
+    /// A fader in [`DawLayout::Faders`] was moved. `cc` is the control change number that was
+    /// assigned to the fader.
+    FaderChange { cc: u8, value: u8, timestamp: u64 },
+    /// The layout changed, either because the user pressed one of the layout buttons or in
+    /// response to a [layout change](DawOutput::select_layout) or
+    /// [layout request](DawOutput::request_layout)
+    LayoutChange { layout: DawLayout, timestamp: u64 },
+    /// A message that doesn't match the Launchpad Mini MK3 DAW protocol, with its raw bytes
+    Verbatim { bytes: Vec<u8> },
+}
+
+/// The input connection creator for the DAW port of the Launchpad Mini MK3. Use it together with
+/// [`DawOutput`].
+pub struct DawInput;
+
+impl crate::InputDevice for DawInput {
+    /// Device name. See [`Input::MIDI_DEVICE_KEYWORD`](super::Input) for port names seen in the
+    /// wild; this matches both the full and the truncated DAW port name.
+    /// On Windows, the DAW port is called "MIDIIN2 (LPMiniMK3 MIDI)" instead.
+    const MIDI_DEVICE_KEYWORD: &'static str = "Launchpad Mini MK3 LPMiniMK3 DA";
+    const MIDI_CONNECTION_NAME: &'static str = "Launchy Mini Mk3 DAW Input";
+    type Message = DawMessage;
+
+    fn verbatim(bytes: Vec<u8>) -> DawMessage {
+        DawMessage::Verbatim { bytes }
+    }
+
+    fn is_device_port(name: &str) -> bool {
+        super::is_daw_port(name)
+    }
+
+    fn try_decode_message(timestamp: u64, data: &[u8]) -> Result<DawMessage, crate::DecodeError> {
+        let unexpected_button =
+            |button| crate::DecodeError::new(data, format!("unexpected button value {}", button));
+
+        let msg = match *data {
+            // Session grid
+            [status @ (0x80 | 0x90), button, velocity] => {
+                let button = decode_grid_button(button).ok_or_else(|| unexpected_button(button))?;
+                match (status, velocity) {
+                    (0x90, 1..=127) => DawMessage::Press { button, timestamp },
+                    _ => DawMessage::Release { button, timestamp },
+                }
+            }
+            // Control buttons
+            [0xB0, number, velocity] => {
+                let button =
+                    decode_control_button(number).ok_or_else(|| unexpected_button(number))?;
+                match velocity {
+                    0 => DawMessage::Release { button, timestamp },
+                    _ => DawMessage::Press { button, timestamp },
+                }
+            }
+            // Faders report on the fifth MIDI channel
+            [0xB4, cc, value] => DawMessage::FaderChange {
+                cc,
+                value,
+                timestamp,
+            },
+            [240, 0, 32, 41, 2, 13, 0, layout, 247] => DawMessage::LayoutChange {
+                layout: DawLayout::from_id(layout)
+                    .ok_or_else(|| crate::DecodeError::new(data, "unexpected layout"))?,
+                timestamp,
+            },
+            _ => return Err(crate::DecodeError::new(data, "unexpected message")),
+        };
+        Ok(msg)
+    }
+}
+
+/// The DAW faders are identified by their CC number, see [`Fader`]
+impl crate::ControlInput for DawInput {
+    fn control_change(msg: &DawMessage) -> Option<crate::ControlChange> {
+        match *msg {
+            DawMessage::FaderChange {
+                cc,
+                value,
+                timestamp,
+            } => Some(crate::ControlChange {
+                control: crate::ControlId::new(cc),
+                value,
+                timestamp,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// The output connection handler for the DAW port of the Launchpad Mini MK3.
+///
+/// Unlike [`Output`](super::Output), which forces the programmer mode on the MIDI port, this
+/// enables DAW mode and selects [`DawLayout::Session`] upon connecting. The user can then switch
+/// layouts with the device's own Session, Drums, Keys and User buttons, which is reported as
+/// [`DawMessage::LayoutChange`]. The session grid and the control buttons are lit independently
+/// of the programmer mode LEDs.
+///
+/// Don't use it together with an [`Output`](super::Output) connection, since that switches the
+/// device to programmer mode.
+///
+/// ```
+/// use launchy::mini_mk3::{
+///     Button, DawOutput, Fader, FaderOrientation, FaderType, PaletteColor,
+/// };
+/// use launchy::OutputDevice as _;
+///
+/// let (sink, sent) = launchy::ChannelSink::new();
+/// let mut output = DawOutput::from_connection(sink)?;
+/// assert_eq!(sent.try_recv()?, [240, 0, 32, 41, 2, 13, 16, 1, 247]);
+/// assert_eq!(sent.try_recv()?, [240, 0, 32, 41, 2, 13, 0, 0, 247]);
+///
+/// output.light(Button::grid(2, 7), PaletteColor::GREEN)?;
+/// assert_eq!(sent.try_recv()?, [0x90, 13, 21]);
+///
+/// output.designate_faders(
+///     FaderOrientation::Vertical,
+///     &[Fader::new(0, FaderType::Unipolar, 20, PaletteColor::RED)],
+/// )?;
+/// assert_eq!(sent.try_recv()?, [240, 0, 32, 41, 2, 13, 1, 0, 0, 0, 0, 20, 5, 247]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct DawOutput<S: MidiSink = DefaultSink> {
+    connection: S,
+}
+
+impl<S: MidiSink> crate::OutputDevice for DawOutput<S> {
+    const MIDI_CONNECTION_NAME: &'static str = "Launchy Mini Mk3 DAW output";
+    /// Device name. See [`Output::MIDI_DEVICE_KEYWORD`](super::Output) for port names seen in the
+    /// wild; this matches both the full and the truncated DAW port name.
+    /// On Windows, the DAW port is called "MIDIOUT2 (LPMiniMK3 MIDI)" instead.
+    const MIDI_DEVICE_KEYWORD: &'static str = "Launchpad Mini MK3 LPMiniMK3 DA";
+
+    type Sink = S;
+
+    fn from_connection(connection: S) -> Result<Self, crate::MidiError> {
+        let mut self_ = Self { connection };
+        self_.set_daw_mode(true)?;
+        self_.select_layout(DawLayout::Session)?;
+        Ok(self_)
+    }
+
+    fn is_device_port(name: &str) -> bool {
+        super::is_daw_port(name)
+    }
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), crate::MidiError> {
+        self.connection.send(bytes)
+    }
+
+    /// Plain LED messages and fader values only depend on the latest value
+    fn coalescing_key(bytes: &[u8]) -> Option<u32> {
+        match *bytes {
+            [status @ (0x90 | 0xB0 | 0xB4), key, _] => {
+                Some(u32::from_be_bytes([0, 0, status, key]))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<S: MidiSink> DawOutput<S> {
+    fn send_sysex(&mut self, command: u8, payload: &[u8]) -> Result<(), crate::MidiError> {
+        self.send(&[&SYSEX_HEADER[..], &[command], payload, &[247]].concat())
+    }
+
+    /// Enables or disables DAW mode. Outside of DAW mode, the session grid and the faders aren't
+    /// available.
+    pub fn set_daw_mode(&mut self, enabled: bool) -> Result<(), crate::MidiError> {
+        self.send_sysex(16, &[enabled as u8])
+    }
+
+    /// Clears the LED states and fader setup that were sent in DAW mode. `session` clears the
+    /// session grid, `drums` the drum layout and `controls` the control buttons and faders.
+    pub fn clear_daw_state(
+        &mut self,
+        session: bool,
+        drums: bool,
+        controls: bool,
+    ) -> Result<(), crate::MidiError> {
+        self.send_sysex(18, &[session as u8, drums as u8, controls as u8])
+    }
+
+    /// Switches to the given layout
+    pub fn select_layout(&mut self, layout: DawLayout) -> Result<(), crate::MidiError> {
+        self.send_sysex(0, &[layout.id()])
+    }
+
+    /// Requests a [`DawMessage::LayoutChange`] with the current layout
+    pub fn request_layout(&mut self) -> Result<(), crate::MidiError> {
+        self.send_sysex(0, &[])
+    }
+
+    /// Sets up the faders of [`DawLayout::Faders`]. Their movements are reported as
+    /// [`DawMessage::FaderChange`].
+    pub fn designate_faders(
+        &mut self,
+        orientation: FaderOrientation,
+        faders: &[Fader],
+    ) -> Result<(), crate::MidiError> {
+        self.send_sysex(1, &fader_setup_payload(orientation, faders))
+    }
+
+    /// Moves the fader with the control change number `cc` to a specific `value`
+    pub fn set_fader(&mut self, cc: u8, value: u8) -> Result<(), crate::MidiError> {
+        assert!(cc <= 127);
+        assert!(value <= 127);
+
+        self.send(&[0xB4, cc, value])
+    }
+
+    /// Set a session grid or control `button` to a certain `color` with a certain `light_mode`,
+    /// using a short MIDI message on channel 1, 2 or 3.
+    pub fn set_button(
+        &mut self,
+        button: Button,
+        color: PaletteColor,
+        light_mode: LightMode,
+    ) -> Result<(), crate::MidiError> {
+        assert!(color.id <= 127);
+
+        let type_byte = match button {
+            Button::GridButton { x: 0..=7, .. } => 0x90,
+            _ => 0xB0,
+        } + match light_mode {
+            LightMode::Plain => 0,
+            LightMode::Flash => 1,
+            LightMode::Pulse => 2,
+        };
+
+        self.send(&[type_byte, encode_button(button), color.id])
+    }
+
+    /// Light multiple session grid or control buttons in one SysEx message, with palette, RGB,
+    /// flashing or pulsing styles.
+    pub fn set_buttons<I, T>(&mut self, buttons: I) -> Result<(), crate::MidiError>
+    where
+        I: IntoIterator<Item = T>,
+        T: std::borrow::Borrow<(Button, ButtonStyle)>,
+    {
+        let mut payload = Vec::new();
+        for pair in buttons {
+            let (button, style) = pair.borrow();
+            push_led_spec(&mut payload, encode_button(*button), style);
+        }
+        self.send_sysex(3, &payload)
+    }
+
+    /// Light a button with a palette color. Identical to
+    /// `set_button(<button>, <color>, LightMode::Plain)`.
+    pub fn light(&mut self, button: Button, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.set_button(button, color, LightMode::Plain)
+    }
+
+    /// Start flashing a button. Identical to `set_button(<button>, <color>, LightMode::Flash)`.
+    pub fn flash(&mut self, button: Button, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.set_button(button, color, LightMode::Flash)
+    }
+
+    /// Start pulsing a button. Identical to `set_button(<button>, <color>, LightMode::Pulse)`.
+    pub fn pulse(&mut self, button: Button, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.set_button(button, color, LightMode::Pulse)
+    }
+}
@@ -1,16 +1,18 @@
-use core::panic;
-
 pub use crate::protocols::query::*;
 
-use super::{Button, Layout, SleepMode};
+use super::{Button, Layout, SleepMode, Version};
+use crate::protocols::mk3::{decode_control_button, decode_grid_button};
 
 #[derive(Debug, Eq, PartialEq, Hash, Clone)]
 /// A Launchpad Mini MK3 input message
+///
+/// The `timestamp` fields hold the MIDI timestamp of the message in microseconds, see
+/// [`MidiClock`](crate::MidiClock).
 pub enum Message {
     /// A button was pressed
-    Press { button: Button },
+    Press { button: Button, timestamp: u64 },
     /// A button was released
-    Release { button: Button },
+    Release { button: Button, timestamp: u64 },
     /// One of the responses to a [device inquiry request](super::Output::request_device_inquiry)
     ApplicationVersion(Version),
     /// One of the responses to a [device inquiry request](super::Output::request_device_inquiry)
@@ -21,39 +23,27 @@ pub enum Message {
     SleepMode(SleepMode),
     /// The response to a [brigtness request](super::Output::request_brightness).
     Brightness(u8),
-}
-
-/// A version structure
-///
-/// The version is 4 bytes from 0-9.
-#[derive(Debug, Eq, PartialEq, Hash, Clone)]
-pub struct Version {
-    pub bytes: [u8; 4],
+    /// A grid button was pressed in one of the custom modes, which send notes on their own MIDI
+    /// `channel` (0-15). Use a [`NoteLayout`](super::NoteLayout) to find out which button it was.
+    NotePress {
+        channel: u8,
+        note: u8,
+        velocity: u8,
+        timestamp: u64,
+    },
+    /// A grid button was released in one of the custom modes, see [`Message::NotePress`]
+    NoteRelease {
+        channel: u8,
+        note: u8,
+        timestamp: u64,
+    },
+    /// A message that doesn't match the Launchpad Mini MK3 protocol, with its raw bytes
+    Verbatim { bytes: Vec<u8> },
 }
 
 /// The Launchpad Mini MK3 input connection creator.
 pub struct Input;
 
-fn decode_grid_button(btn: u8) -> Button {
-    let x = (btn % 10) - 1;
-    let y = 8 - (btn / 10);
-    Button::GridButton { x, y }
-}
-
-fn decode_control_button(btn: u8) -> Button {
-    // The top control buttons are encoded as 91, 92, 95, 96, 97, 98, while the
-    // right-side control buttons are encoded as 89, 79, 69, 59, 49, 39, 29, 19
-    // (which fits in line with the grid button coordinates).
-    //
-    // In fact, Launchy considers the right-side control buttons as
-    // grid buttons.
-    match btn {
-        91..=98 => Button::ControlButton { index: btn - 91 },
-        19..=89 if btn % 10 == 9 => decode_grid_button(btn),
-        _ => panic!("Unexpected control button value {}", btn),
-    }
-}
-
 impl crate::InputDevice for Input {
     /// Device name.
     ///
@@ -63,65 +53,100 @@ impl crate::InputDevice for Input {
     /// - Launchpad Mini MK3:Launchpad Mini MK3 LPMiniMK3 DA 32:0 (Linux)
     /// - Launchpad Mini MK3:Launchpad Mini MK3 LPMiniMK3 MI 32:1 (Linux)
     ///
-    /// On some platforms, the string is truncated, and the device with "DAW" in its name does not work.
+    /// On some platforms, the string is truncated. The device with "DAW" in its name is used by
+    /// [`DawInput`](super::DawInput) and [`DawOutput`](super::DawOutput).
     const MIDI_DEVICE_KEYWORD: &'static str = "Launchpad Mini MK3 LPMiniMK3 MI";
     const MIDI_CONNECTION_NAME: &'static str = "Launchy Mini Mk3 Input";
     type Message = Message;
 
-    fn decode_message(_timestamp: u64, data: &[u8]) -> Message {
-        match data {
+    fn verbatim(bytes: Vec<u8>) -> Message {
+        Message::Verbatim { bytes }
+    }
+
+    fn is_device_port(name: &str) -> bool {
+        super::is_device_port(name)
+    }
+
+    fn try_decode_message(timestamp: u64, data: &[u8]) -> Result<Message, crate::DecodeError> {
+        let unexpected_button =
+            |button| crate::DecodeError::new(data, format!("unexpected button value {}", button));
+
+        let msg = match *data {
             // Grid button
-            &[0x90, button, velocity] => {
-                let button = decode_grid_button(button);
+            [0x90, button, velocity] => {
+                let button = decode_grid_button(button).ok_or_else(|| unexpected_button(button))?;
 
                 match velocity {
-                    0 => Message::Release { button },
-                    127 => Message::Press { button },
-                    other => panic!("Unexpected grid note-on velocity {}", other),
+                    0 => Message::Release { button, timestamp },
+                    127 => Message::Press { button, timestamp },
+                    other => {
+                        let reason = format!("unexpected grid note-on velocity {}", other);
+                        return Err(crate::DecodeError::new(data, reason));
+                    }
                 }
             }
             // Control button
-            &[0xB0, number, velocity] => {
-                let button = decode_control_button(number);
+            [0xB0, number, velocity] => {
+                let button =
+                    decode_control_button(number).ok_or_else(|| unexpected_button(number))?;
 
                 match velocity {
-                    0 => Message::Release { button },
-                    127 => Message::Press { button },
-                    other => panic!("Unexpected grid note-on velocity {}", other),
+                    0 => Message::Release { button, timestamp },
+                    127 => Message::Press { button, timestamp },
+                    other => {
+                        let reason = format!("unexpected control velocity {}", other);
+                        return Err(crate::DecodeError::new(data, reason));
+                    }
                 }
             }
             // Implement release (actively used)
-            &[0x80, button, extra] => {
+            [0x80, button, extra] => {
                 // TODO: figure out what extra is, appears to be 0x40 for all buttons
                 if extra != 0x40 {
-                    panic!("Unexpected grid note-off extra byte {}", extra);
+                    let reason = format!("unexpected grid note-off extra byte {}", extra);
+                    return Err(crate::DecodeError::new(data, reason));
                 }
 
-                let button = decode_grid_button(button);
+                let button = decode_grid_button(button).ok_or_else(|| unexpected_button(button))?;
 
-                Message::Release { button }
+                Message::Release { button, timestamp }
             }
+            // Notes of the custom modes, which use other channels than the programmer mode
+            [status @ 0x91..=0x9F, note, velocity @ 1..=127] => Message::NotePress {
+                channel: status & 0x0F,
+                note,
+                velocity,
+                timestamp,
+            },
+            [status @ (0x81..=0x8F | 0x91..=0x9F), note, _] => Message::NoteRelease {
+                channel: status & 0x0F,
+                note,
+                timestamp,
+            },
             // Response to a Device Inquiry
-            &[240, 126, 0, 6, 2, 0, 32, 41, 19, 1, 0, 0, v1, v2, v3, v4, 247] => {
+            [240, 126, 0, 6, 2, 0, 32, 41, 19, 1, 0, 0, v1, v2, v3, v4, 247] => {
                 Message::ApplicationVersion(Version {
                     bytes: [v1, v2, v3, v4],
                 })
             }
-            &[240, 126, 0, 6, 2, 0, 32, 41, 19, 17, 0, 0, v1, v2, v3, v4, 247] => {
+            [240, 126, 0, 6, 2, 0, 32, 41, 19, 17, 0, 0, v1, v2, v3, v4, 247] => {
                 Message::BootloaderVersion(Version {
                     bytes: [v1, v2, v3, v4],
                 })
             }
             // Response to sleep mode query
-            &[240, 0, 32, 41, 2, 13, 9, sleep, 247] => Message::SleepMode(match sleep {
+            [240, 0, 32, 41, 2, 13, 9, sleep, 247] => Message::SleepMode(match sleep {
                 0 => SleepMode::Sleep,
                 _ => SleepMode::Wake,
             }),
             // Response to layout command
-            &[240, 0, 32, 41, 2, 13, 14, layout, 247] => Message::ChangeLayout(layout.into()),
+            [240, 0, 32, 41, 2, 13, 14, layout @ 0..=1, 247] => {
+                Message::ChangeLayout(Layout::from(layout))
+            }
             // Response to brightness query
-            &[240, 0, 32, 41, 2, 13, 8, brightness, 247] => Message::Brightness(brightness),
-            other => panic!("Unexpected midi message: {:?}", other),
-        }
+            [240, 0, 32, 41, 2, 13, 8, brightness, 247] => Message::Brightness(brightness),
+            _ => return Err(crate::DecodeError::new(data, "unexpected message")),
+        };
+        Ok(msg)
     }
 }
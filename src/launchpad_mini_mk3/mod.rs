@@ -7,6 +7,11 @@ The Launchpad Mini MK3 has a 9x9 grid with 80 buttons. The top
 row are control buttons, indexed `0..7`. The `launchy` library
 considers the right-hand side buttons to be part of the grid,
 so the grid has a size of 9x8.
+
+The [`Output`] and [`Input`] connect to the MIDI port and use the programmer mode. To use the
+device's own Session, Drums, Keys and User layouts and its faders instead, connect to the DAW
+port with [`DawOutput`] and [`DawInput`]. While the Drums, Keys or User layout is selected, the
+grid sends notes on the MIDI port, which can be mapped to buttons and lit with a [`NoteLayout`].
 */
 
 mod input;
@@ -15,8 +20,29 @@ pub use input::*;
 mod output;
 pub use output::*;
 
+mod daw;
+pub use daw::*;
+
+mod custom;
+pub use custom::*;
+
 pub use crate::protocols::Button80 as Button;
 
+/// Whether a port is the main MIDI port. Windows calls it "LPMiniMK3 MIDI", and names the DAW port
+/// after it, e.g. "MIDIIN2 (LPMiniMK3 MIDI)"
+fn is_device_port(name: &str) -> bool {
+    name.contains("LPMiniMK3 MI") && !crate::midi_io::is_further_windows_port(name, "LPMiniMK3")
+}
+
+/// Whether a port is the DAW port. Windows names it after the MIDI port instead, as the second
+/// port of the device, e.g. "MIDIIN2 (LPMiniMK3 MIDI)"
+fn is_daw_port(name: &str) -> bool {
+    match crate::midi_io::parse_windows_port_name(name) {
+        Some((number, device)) => number == 2 && device.starts_with("LPMiniMK3"),
+        None => name.contains("Launchpad Mini MK3 LPMiniMK3 DA"),
+    }
+}
+
 #[doc(hidden)]
 pub struct Spec;
 
@@ -26,7 +52,7 @@ impl crate::DeviceSpec for Spec {
     const COLOR_PRECISION: u16 = 128;
 
     type Input = Input;
-    type Output = Output;
+    type Output<S: crate::MidiSink> = Output<S>;
 
     fn is_valid(x: u32, y: u32) -> bool {
         if x > 8 || y > 8 {
@@ -38,37 +64,100 @@ impl crate::DeviceSpec for Spec {
         true
     }
 
-    fn flush(
-        canvas: &mut crate::DeviceCanvas<Self>,
+    fn flush<T: crate::MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
         changes: &[(u32, u32, (u8, u8, u8))],
     ) -> Result<(), crate::MidiError> {
-        let changes = changes.iter().map(|&(x, y, (r, g, b))| {
-            let color = RgbColor::new(r, g, b);
+        let strategy = canvas.flush_strategy();
+        let changes = changes.iter().map(|&(x, y, color)| {
+            let style = crate::protocols::mk3::canvas_button_style(color, strategy);
+
+            let button = Button::from_abs(x as u8, y as u8);
+
+            (button, style)
+        });
+        canvas.output.set_buttons(changes)
+    }
+
+    fn supports_effect<T: crate::MidiTransport>(
+        _canvas: &crate::DeviceCanvas<Self, T>,
+        _effect: crate::PadEffect,
+    ) -> bool {
+        true
+    }
+
+    fn flush_effects<T: crate::MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
+        changes: &[crate::EffectChange],
+    ) -> Result<(), crate::MidiError> {
+        let changes = changes.iter().map(|&(x, y, color, effect)| {
+            let style = crate::protocols::mk3::effect_button_style(color, effect);
 
             let button = Button::from_abs(x as u8, y as u8);
 
-            (button, color)
+            (button, style)
         });
-        canvas.output.light_multiple_rgb(changes)
+        canvas.output.set_buttons(changes)
     }
 
     fn convert_message(msg: Message) -> Option<crate::CanvasMessage> {
         match msg {
-            Message::Press { button } => Some(crate::CanvasMessage::Press {
+            Message::Press { button, timestamp } => Some(crate::CanvasMessage::Press {
                 x: button.abs_x() as u32,
                 y: button.abs_y() as u32,
+                timestamp,
             }),
-            Message::Release { button } => Some(crate::CanvasMessage::Release {
+            Message::Release { button, timestamp } => Some(crate::CanvasMessage::Release {
                 x: button.abs_x() as u32,
                 y: button.abs_y() as u32,
+                timestamp,
             }),
             Message::ApplicationVersion(_)
             | Message::BootloaderVersion(_)
             | Message::SleepMode(_)
             | Message::Brightness(_)
-            | Message::ChangeLayout(_) => None,
+            | Message::ChangeLayout(_)
+            | Message::NotePress { .. }
+            | Message::NoteRelease { .. }
+            | Message::Verbatim { .. } => None,
         }
     }
 }
 
 pub type Canvas<'a> = crate::DeviceCanvas<Spec>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn main_port_is_found_on_every_platform() {
+        assert!(is_device_port("Launchpad Mini MK3 LPMiniMK3 MIDI"));
+        assert!(is_device_port(
+            "Launchpad Mini MK3:Launchpad Mini MK3 LPMiniMK3 MI 32:1"
+        ));
+        assert!(is_device_port("LPMiniMK3 MIDI"));
+        assert!(is_device_port("2- LPMiniMK3 MIDI"));
+
+        assert!(!is_device_port("Launchpad Mini MK3 LPMiniMK3 DAW"));
+        assert!(!is_device_port("MIDIIN2 (LPMiniMK3 MIDI)"));
+        assert!(!is_device_port("MIDIOUT2 (2- LPMiniMK3 MIDI)"));
+    }
+
+    #[test]
+    fn daw_port_is_found_on_every_platform() {
+        assert!(is_daw_port("Launchpad Mini MK3 LPMiniMK3 DAW"));
+        assert!(is_daw_port(
+            "Launchpad Mini MK3:Launchpad Mini MK3 LPMiniMK3 DA 32:0"
+        ));
+        assert!(is_daw_port("MIDIIN2 (LPMiniMK3 MIDI)"));
+        assert!(is_daw_port("MIDIOUT2 (2- LPMiniMK3 MIDI)"));
+
+        assert!(!is_daw_port("Launchpad Mini MK3 LPMiniMK3 MIDI"));
+        assert!(!is_daw_port(
+            "Launchpad Mini MK3:Launchpad Mini MK3 LPMiniMK3 MI 32:1"
+        ));
+        assert!(!is_daw_port("LPMiniMK3 MIDI"));
+        assert!(!is_daw_port("MIDIOUT2 (LPX MIDI)"));
+    }
+}
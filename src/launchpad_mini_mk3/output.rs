@@ -1,238 +1,10 @@
-use midir::MidiOutputConnection;
-
+pub use crate::protocols::mk3::*;
 pub use crate::protocols::query::*;
 
-use super::Button;
-use crate::OutputDevice;
-
-/// The maximum value of an RGB LED
-const MAX_RGB: u8 = 127;
-
-/// A color from the Mk3 color palette. See the "Launchpad MK3 Programmers Reference Manual"
-/// to see the palette, or [see here](http://launchpaddr.com/mk3palette/).
-///
-/// Everywhere where a PaletteColor is expected as a funcion argument, you can also directly pass
-/// in the palette index and call `.into()` on it. Example:
-/// ```no_run
-/// # use launchy::mini_mk3::{PaletteColor};
-/// # let output: launchy::mini_mk3::Output = unimplemented!();
-/// // This:
-/// output.light_all(PaletteColor::new(92));
-/// // can also be written as:
-/// output.light_all(92.into());
-/// # Ok::<(), launchy::MidiError>(())
-/// ```
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
-pub struct PaletteColor {
-    pub(crate) id: u8,
-}
-
-impl PaletteColor {
-    pub fn is_valid(&self) -> bool {
-        self.id <= 127
-    }
-
-    pub fn new(id: u8) -> Self {
-        let self_ = Self { id };
-        assert!(self_.is_valid());
-        self_
-    }
-
-    pub fn id(&self) -> u8 {
-        self.id
-    }
-    pub fn set_id(&mut self, id: u8) {
-        self.id = id
-    }
-}
-
-impl From<u8> for PaletteColor {
-    fn from(id: u8) -> Self {
-        Self::new(id)
-    }
-}
-
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
-/// An RGB color. Each component may only go up to 63.
-pub struct RgbColor {
-    r: u8,
-    g: u8,
-    b: u8,
-}
-
-impl RgbColor {
-    /// Create a new RgbColor from the individual component values
-    pub fn new(r: u8, g: u8, b: u8) -> Self {
-        let self_ = Self { r, g, b };
-        assert!(self_.is_valid());
-        self_
-    }
-
-    /// Check whether the rgb color is valid - each component may only go up to MAX_RGB.
-    pub fn is_valid(&self) -> bool {
-        self.r <= MAX_RGB && self.g <= MAX_RGB && self.b <= MAX_RGB
-    }
-
-    pub fn red(&self) -> u8 {
-        self.r
-    }
-    pub fn green(&self) -> u8 {
-        self.g
-    }
-    pub fn blue(&self) -> u8 {
-        self.b
-    }
-    pub fn set_red(&mut self, r: u8) {
-        assert!(r <= MAX_RGB);
-        self.r = r
-    }
-    pub fn set_green(&mut self, g: u8) {
-        assert!(g <= MAX_RGB);
-        self.g = g
-    }
-    pub fn set_blue(&mut self, b: u8) {
-        assert!(b <= MAX_RGB);
-        self.b = b
-    }
-}
-
-/// The button styles supported by the MK3 Mini
-///
-/// Buttons can be in one of 3 states:
-///
-/// - Plain: a constant color, using either a palette color or an RGB color.
-/// - Flashing: flashing between two colors on a 50% duty cycle. For simplicity,
-///   [ButtonStyle::flash] flashes between a given color and black, and [ButtonStyle::flash2]
-///   flashes between 2 colors. Flashing can only use palette colors.
-/// - Pulsing: pulsing between a given (palette) color and black. Pulsing looks more subdued than
-///   flashing.
-///
-/// [PaletteColor] and [RgbColor] are convertible into [ButtonStyle]
-/// using `color.into()`; this will use the plain (non-flashing) button
-/// style.
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
-pub enum ButtonStyle {
-    Palette {
-        color: PaletteColor,
-    },
-    Rgb {
-        color: RgbColor,
-    },
-    Flash {
-        color1: PaletteColor,
-        color2: PaletteColor,
-    },
-    Pulse {
-        color: PaletteColor,
-    },
-}
-
-impl ButtonStyle {
-    /// Create a plain button style from a palette color
-    pub fn palette(color: PaletteColor) -> Self {
-        ButtonStyle::Palette { color }
-    }
-
-    /// Flash between a given color and black
-    pub fn flash(color: PaletteColor) -> Self {
-        Self::flash2(color, PaletteColor::BLACK)
-    }
-
-    /// Flash between a given color and black
-    pub fn flash2(color1: PaletteColor, color2: PaletteColor) -> Self {
-        ButtonStyle::Flash { color1, color2 }
-    }
-
-    /// Pulse the given color (and black)
-    pub fn pulse(color: PaletteColor) -> Self {
-        ButtonStyle::Pulse { color }
-    }
-
-    /// Create a plain button style from an RGB color
-    pub fn rgb(color: RgbColor) -> Self {
-        ButtonStyle::Rgb { color }
-    }
-
-    /// Validate that the button style's colors only use valid numbers
-    pub fn is_valid(&self) -> bool {
-        match self {
-            ButtonStyle::Palette { color } => color.is_valid(),
-            ButtonStyle::Rgb { color } => color.is_valid(),
-            ButtonStyle::Flash { color1, color2 } => color1.is_valid() && color2.is_valid(),
-            ButtonStyle::Pulse { color } => color.is_valid(),
-        }
-    }
-}
-
-impl From<PaletteColor> for ButtonStyle {
-    fn from(color: PaletteColor) -> Self {
-        ButtonStyle::Palette { color }
-    }
-}
-
-impl From<&PaletteColor> for ButtonStyle {
-    fn from(color: &PaletteColor) -> Self {
-        Self::from(*color)
-    }
-}
-
-impl From<RgbColor> for ButtonStyle {
-    fn from(color: RgbColor) -> Self {
-        ButtonStyle::Rgb { color }
-    }
-}
-
-impl From<&RgbColor> for ButtonStyle {
-    fn from(color: &RgbColor) -> Self {
-        Self::from(*color)
-    }
-}
-
-impl PaletteColor {
-    // These are some commonly used colors as palette colors. I don't have Rgb colors as constants
-    // because in the case of rgb colors you can just make your required colors yourself
-
-    // Basic colors, the top row
-    pub const BLACK: PaletteColor = Self { id: 0 };
-    pub const DARK_GRAY: PaletteColor = Self { id: 1 };
-    pub const LIGHT_GRAY: PaletteColor = Self { id: 2 };
-    pub const WHITE: PaletteColor = Self { id: 3 };
-
-    // Third column from the right
-    pub const LIGHT_RED: PaletteColor = Self { id: 4 };
-    pub const RED: PaletteColor = Self { id: 5 };
-    pub const ORANGE: PaletteColor = Self { id: 9 };
-    pub const YELLOW: PaletteColor = Self { id: 13 };
-    pub const LIME_GREEN: PaletteColor = Self { id: 17 };
-    pub const GREEN: PaletteColor = Self { id: 21 };
-    pub const SLIGHTLY_LIGHT_GREEN: PaletteColor = Self { id: 29 };
-    pub const LIGHT_BLUE: PaletteColor = Self { id: 37 };
-    pub const BLUE: PaletteColor = Self { id: 45 };
-    pub const PURPLE: PaletteColor = Self { id: 49 };
-    pub const MAGENTA: PaletteColor = Self { id: 53 };
-    pub const PINK: PaletteColor = Self { id: 57 };
-    pub const BROWN: PaletteColor = Self { id: 61 };
-
-    // This is not belonging to any of the columns/rows but included anyway cuz cyan is important
-    pub const CYAN: PaletteColor = Self { id: 90 };
-}
-
-/// The Mini Mk3 can light a button in different ways
-#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
-pub enum LightMode {
-    /// This is the standard mode. A straight consistent light
-    Plain,
-    /// A flashing motion On->Off->On->Off->...
-    Flash,
-    /// A smooth pulse
-    Pulse,
-}
-
-#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
-pub enum SleepMode {
-    Sleep = 0,
-    Wake = 1,
-}
+use super::{Button, DawLayout, NoteLayout};
+use crate::midi_io::DefaultSink;
+use crate::protocols::mk3::{encode_button, push_led_spec};
+use crate::{MidiSink, OutputDevice};
 
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
 pub enum Layout {
@@ -256,6 +28,7 @@ impl From<u8> for Layout {
 ///
 /// For example:
 /// ```no_run
+/// # #[cfg(feature = "midir")] {
 /// # use launchy::OutputDevice as _;
 /// # use launchy::mini_mk3::{PaletteColor, Button, Output};
 /// let mut output = Output::guess()?;
@@ -270,6 +43,7 @@ impl From<u8> for Layout {
 ///
 /// // light top left button magenta
 /// output.light(Button::GridButton { x: 0, y: 0 }, PaletteColor::MAGENTA);
+/// # }
 /// # Ok::<(), launchy::MidiError>(())
 /// ```
 ///
@@ -279,11 +53,11 @@ impl From<u8> for Layout {
 /// Why would you choose the palette colors when you can just create your required colors yourself?
 /// Well some operations on the Mk3 only support palette colors. Besides, sending palette color midi
 /// messages is simply faster. Therefore you should aim to use the palette colors when possible.
-pub struct Output {
-    connection: MidiOutputConnection,
+pub struct Output<S: MidiSink = DefaultSink> {
+    connection: S,
 }
 
-impl crate::OutputDevice for Output {
+impl<S: MidiSink> crate::OutputDevice for Output<S> {
     const MIDI_CONNECTION_NAME: &'static str = "Launchy Mini Mk3 output";
 
     /// Device name.
@@ -294,22 +68,36 @@ impl crate::OutputDevice for Output {
     /// - Launchpad Mini MK3:Launchpad Mini MK3 LPMiniMK3 DA 32:0 (Linux)
     /// - Launchpad Mini MK3:Launchpad Mini MK3 LPMiniMK3 MI 32:1 (Linux)
     ///
-    /// On some platforms, the string is truncated, and the device with "DAW" in its name does not work.
+    /// On some platforms, the string is truncated. The device with "DAW" in its name is used by
+    /// [`DawInput`](super::DawInput) and [`DawOutput`](super::DawOutput).
     const MIDI_DEVICE_KEYWORD: &'static str = "Launchpad Mini MK3 LPMiniMK3 MI";
 
-    fn from_connection(connection: MidiOutputConnection) -> Result<Self, crate::MidiError> {
+    type Sink = S;
+
+    fn from_connection(connection: S) -> Result<Self, crate::MidiError> {
         let mut self_ = Self { connection };
         self_.change_layout(Layout::Programmer)?;
         Ok(self_)
     }
 
+    fn is_device_port(name: &str) -> bool {
+        super::is_device_port(name)
+    }
+
     fn send(&mut self, bytes: &[u8]) -> Result<(), crate::MidiError> {
-        self.connection.send(bytes)?;
-        Ok(())
+        self.connection.send(bytes)
+    }
+
+    /// Plain LED messages only depend on the latest color
+    fn coalescing_key(bytes: &[u8]) -> Option<u32> {
+        match *bytes {
+            [status @ (0x90 | 0xB0), key, _] => Some(u32::from_be_bytes([0, 0, status, key])),
+            _ => None,
+        }
     }
 }
 
-impl Output {
+impl<S: MidiSink> Output<S> {
     /// Set a `button` to a certain `color` with a certain `light_mode`.
     ///
     /// This uses a direct MIDI message on channel 1, 2 or 3 to set the color,
@@ -344,7 +132,7 @@ impl Output {
             LightMode::Pulse => 2,
         };
 
-        self.send(&[type_byte, Self::encode_button(button), color.id])
+        self.send(&[type_byte, encode_button(button), color.id])
     }
 
     /// Light multiple buttons with varying colors.
@@ -380,27 +168,7 @@ impl Output {
         bytes.extend(&[240, 0, 32, 41, 2, 13, 3]);
         for pair in buttons {
             let (button, style) = pair.borrow();
-            assert!(style.is_valid());
-            match style {
-                ButtonStyle::Palette { color } => {
-                    bytes.extend([0, Self::encode_button(*button), color.id()])
-                }
-                ButtonStyle::Rgb { color } => bytes.extend([
-                    3,
-                    Self::encode_button(*button),
-                    color.red(),
-                    color.green(),
-                    color.blue(),
-                ]),
-                ButtonStyle::Flash { color1, color2 } => {
-                    // Order color2 and color1 such that (1=red, 2=black) looks exactly
-                    // the same as a legacy flash red (as configured by `set_button`).
-                    bytes.extend([1, Self::encode_button(*button), color2.id(), color1.id()])
-                }
-                ButtonStyle::Pulse { color } => {
-                    bytes.extend([2, Self::encode_button(*button), color.id()])
-                }
-            }
+            push_led_spec(&mut bytes, encode_button(*button), style);
         }
         bytes.push(247);
 
@@ -652,26 +420,6 @@ impl Output {
         self.send(&[240, 0, 32, 41, 2, 13, 14, layout as u8, 247])
     }
 
-    fn encode_button(button: Button) -> u8 {
-        match button {
-            Button::GridButton { x, y } => {
-                assert!(x <= 8);
-                assert!(y <= 7);
-
-                10 * (8 - y) + x + 1
-            }
-            Button::ControlButton { index } => {
-                assert!(index <= 15);
-
-                if index <= 7 {
-                    index + 91
-                } else {
-                    (8 - (index - 8)) * 10 + 9
-                }
-            }
-        }
-    }
-
     // --------------------------------------------------------------------------------------------
     // Below this point are shorthand function
     // --------------------------------------------------------------------------------------------
@@ -866,6 +614,100 @@ impl Output {
     pub fn clear(&mut self) -> Result<(), crate::MidiError> {
         self.light_all(PaletteColor::BLACK)
     }
+
+    /// Switches to one of the device's own layouts, like the Drums, Keys or User custom modes.
+    /// This leaves the programmer mode; to return to it, use
+    /// `change_layout(Layout::Programmer)`.
+    ///
+    /// ```
+    /// # use launchy::{ChannelSink, OutputDevice as _};
+    /// use launchy::mini_mk3::{Button, DawLayout, NoteLayout, Output, PaletteColor};
+    ///
+    /// let (sink, sent) = ChannelSink::new();
+    /// let mut output = Output::from_connection(sink)?;
+    /// # sent.try_iter().count(); // discard setup messages
+    ///
+    /// output.select_layout(DawLayout::Drums)?;
+    /// assert_eq!(sent.try_iter().last().unwrap(), [240, 0, 32, 41, 2, 13, 0, 4, 247]);
+    ///
+    /// // Light the bottom left pad of the drum rack
+    /// output.light_custom(&NoteLayout::drums(), Button::grid(0, 7), PaletteColor::RED)?;
+    /// assert_eq!(sent.try_recv().unwrap(), [0x98, 36, 5]);
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    pub fn select_layout(&mut self, layout: DawLayout) -> Result<(), crate::MidiError> {
+        if layout != DawLayout::Programmer {
+            self.change_layout(Layout::Live)?;
+        }
+        self.send(&[240, 0, 32, 41, 2, 13, 0, layout.id(), 247])
+    }
+
+    /// Lights a grid button with a palette color while a custom mode is selected, by sending the
+    /// button's note on the custom mode's channel. The LED keeps its color when the button is
+    /// played.
+    ///
+    /// Returns [`MidiError::NoNote`](crate::MidiError::NoNote) if the button has no note in
+    /// `layout`.
+    pub fn light_custom(
+        &mut self,
+        layout: &NoteLayout,
+        button: Button,
+        color: PaletteColor,
+    ) -> Result<(), crate::MidiError> {
+        let note = custom_note(layout, button)?;
+        self.send(&[0x90 | layout.channel(), note, color.id])
+    }
+
+    /// Lights multiple grid buttons while a custom mode is selected, see [`Self::light_custom`].
+    ///
+    /// If any button has no note in `layout`, nothing is sent and
+    /// [`MidiError::NoNote`](crate::MidiError::NoNote) is returned.
+    ///
+    /// ```
+    /// # use launchy::{ChannelSink, OutputDevice as _};
+    /// use launchy::mini_mk3::{Button, NoteLayout, Output, PaletteColor};
+    /// use launchy::MidiError;
+    ///
+    /// let (sink, sent) = ChannelSink::new();
+    /// let mut output = Output::from_connection(sink)?;
+    /// # sent.try_iter().count(); // discard setup messages
+    ///
+    /// // The side buttons have no note
+    /// let result = output.light_custom_multiple(
+    ///     &NoteLayout::drums(),
+    ///     &[
+    ///         (Button::grid(0, 7), PaletteColor::RED),
+    ///         (Button::grid(8, 7), PaletteColor::RED),
+    ///     ],
+    /// );
+    /// assert!(matches!(result, Err(MidiError::NoNote { .. })));
+    /// assert!(sent.try_recv().is_err());
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    pub fn light_custom_multiple(
+        &mut self,
+        layout: &NoteLayout,
+        buttons: impl IntoIterator<Item = impl std::borrow::Borrow<(Button, PaletteColor)>>,
+    ) -> Result<(), crate::MidiError> {
+        let notes = buttons
+            .into_iter()
+            .map(|pair| {
+                let (button, color) = *pair.borrow();
+                Ok((custom_note(layout, button)?, color))
+            })
+            .collect::<Result<Vec<_>, crate::MidiError>>()?;
+        for (note, color) in notes {
+            self.send(&[0x90 | layout.channel(), note, color.id])?;
+        }
+        Ok(())
+    }
+}
+
+/// The note of a grid button in a custom mode, or an error if it has none
+fn custom_note(layout: &NoteLayout, button: Button) -> Result<u8, crate::MidiError> {
+    layout.note(button).ok_or_else(|| crate::MidiError::NoNote {
+        button: format!("{:?}", button),
+    })
 }
 
 /// Returns an iterator for all buttons in the given column
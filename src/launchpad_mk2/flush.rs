@@ -0,0 +1,350 @@
+use super::{Button, Output, PaletteColor, RgbColor, Spec};
+
+type Rgb = (u8, u8, u8);
+
+/// A quantized pad color, and the palette color that shows it exactly if there is one
+type PadColor = (Rgb, Option<PaletteColor>);
+
+/// A set of pads, indexed by `[y][x]` in canvas coordinates
+type PadSet = [[bool; 9]; 9];
+
+/// The palette color that shows exactly the given canvas color, if any
+fn palette_color(color: Rgb) -> Option<PaletteColor> {
+    use crate::DeviceSpec as _;
+
+    let range = Spec::COLOR_PRECISION as u8;
+    crate::protocols::palette::MK2_PALETTE
+        .exact(color, range)
+        .map(PaletteColor::new)
+}
+
+/// The colors that the pads show after the flush, indexed by `[y][x]`. `None` where there's no
+/// pad
+struct Frame([[Option<PadColor>; 9]; 9]);
+
+impl Frame {
+    /// Build a frame from the quantized color of every pad
+    fn new(color_at: impl Fn(u32, u32) -> Option<Rgb>) -> Self {
+        let mut colors = [[None; 9]; 9];
+        for (y, row) in colors.iter_mut().enumerate() {
+            for (x, color) in row.iter_mut().enumerate() {
+                *color = color_at(x as u32, y as u32).map(|color| (color, palette_color(color)));
+            }
+        }
+        Self(colors)
+    }
+
+    fn from_canvas<T: crate::MidiTransport>(canvas: &crate::DeviceCanvas<Spec, T>) -> Self {
+        Self::new(|x, y| canvas.target_color(x, y))
+    }
+
+    fn pads(&self) -> impl Iterator<Item = (usize, usize, Rgb, Option<PaletteColor>)> + '_ {
+        self.0.iter().enumerate().flat_map(|(y, row)| {
+            row.iter().enumerate().filter_map(move |(x, color)| {
+                let (color, palette_color) = (*color)?;
+                Some((x, y, color, palette_color))
+            })
+        })
+    }
+
+    /// The palette color of the given pads, if they all have the same one
+    fn uniform_palette_color(
+        &self,
+        mut pads: impl Iterator<Item = (usize, usize)>,
+    ) -> Option<PaletteColor> {
+        let (x, y) = pads.next()?;
+        let (color, palette_color) = self.0[y][x]?;
+        match pads.all(|(x, y)| self.0[y][x].map(|(color, _)| color) == Some(color)) {
+            true => palette_color,
+            false => None,
+        }
+    }
+
+    /// The palette color that most pads have, if any pad has a palette color
+    fn most_common_palette_color(&self) -> Option<PaletteColor> {
+        let mut counts = [0u8; 128];
+        for (_, _, _, palette_color) in self.pads() {
+            if let Some(palette_color) = palette_color {
+                counts[palette_color.id() as usize] += 1;
+            }
+        }
+        let (id, &count) = counts.iter().enumerate().max_by_key(|&(_, count)| count)?;
+        match count {
+            0 => None,
+            _ => Some(PaletteColor::new(id as u8)),
+        }
+    }
+}
+
+/// The messages to send for a flush
+struct Plan {
+    /// The color of all pads, sent first
+    fill: Option<PaletteColor>,
+    /// Colors of the grid rows (y = 1 to 8, including the side button)
+    rows: [Option<PaletteColor>; 9],
+    /// Colors of the columns (x = 0 to 8, without the control buttons)
+    columns: [Option<PaletteColor>; 9],
+    /// The pads that are sent one by one
+    pads: PadSet,
+}
+
+impl Plan {
+    fn new(frame: &Frame, fill: Option<PaletteColor>, mut pads: PadSet, use_lines: bool) -> Self {
+        let mut rows = [None; 9];
+        let mut columns = [None; 9];
+
+        if use_lines {
+            for y in 1..9 {
+                if !pads[y].contains(&true) {
+                    continue;
+                }
+                if let Some(color) = frame.uniform_palette_color((0..9).map(|x| (x, y))) {
+                    rows[y] = Some(color);
+                    pads[y] = [false; 9];
+                }
+            }
+            for x in 0..9 {
+                if !(1..9).any(|y| pads[y][x]) {
+                    continue;
+                }
+                if let Some(color) = frame.uniform_palette_color((1..9).map(|y| (x, y))) {
+                    columns[x] = Some(color);
+                    (1..9).for_each(|y| pads[y][x] = false);
+                }
+            }
+        }
+
+        Self {
+            fill,
+            rows,
+            columns,
+            pads,
+        }
+    }
+
+    fn individual_pads<'a>(
+        &'a self,
+        frame: &'a Frame,
+    ) -> impl Iterator<Item = (Button, Rgb, Option<PaletteColor>)> + 'a {
+        frame.pads().filter(move |&(x, y, ..)| self.pads[y][x]).map(
+            |(x, y, color, palette_color)| {
+                (Button::from_abs(x as u8, y as u8), color, palette_color)
+            },
+        )
+    }
+
+    /// The number of bytes of all messages of this plan
+    fn cost(&self, frame: &Frame) -> usize {
+        // Every multi-pad SysEx has 8 bytes of overhead
+        let multi_message_cost = |num_entries: usize, entry_size: usize| match num_entries {
+            0 => 0,
+            _ => 8 + num_entries * entry_size,
+        };
+
+        let num_rows = self.rows.iter().flatten().count();
+        let num_columns = self.columns.iter().flatten().count();
+        let num_palette_pads = self
+            .individual_pads(frame)
+            .filter(|&(.., palette_color)| palette_color.is_some())
+            .count();
+        let num_rgb_pads = self.individual_pads(frame).count() - num_palette_pads;
+
+        self.fill.map_or(0, |_| 9)
+            + multi_message_cost(num_rows, 2)
+            + multi_message_cost(num_columns, 2)
+            + multi_message_cost(num_palette_pads, 2)
+            + multi_message_cost(num_rgb_pads, 4)
+    }
+
+    fn send<S: crate::MidiSink>(
+        &self,
+        frame: &Frame,
+        output: &mut Output<S>,
+    ) -> Result<(), crate::MidiError> {
+        if let Some(color) = self.fill {
+            output.light_all(color)?;
+        }
+
+        let mut lines = [(0, PaletteColor::BLACK); 9];
+        let mut num_lines = 0;
+        for (y, color) in self.rows.iter().enumerate() {
+            if let Some(color) = *color {
+                lines[num_lines] = (y as u8, color);
+                num_lines += 1;
+            }
+        }
+        if num_lines > 0 {
+            output.light_rows(&lines[..num_lines])?;
+        }
+
+        num_lines = 0;
+        for (x, color) in self.columns.iter().enumerate() {
+            if let Some(color) = *color {
+                lines[num_lines] = (x as u8, color);
+                num_lines += 1;
+            }
+        }
+        if num_lines > 0 {
+            output.light_columns(&lines[..num_lines])?;
+        }
+
+        let mut palette_pads = [(Button::GridButton { x: 0, y: 0 }, PaletteColor::BLACK); 81];
+        let mut num_palette_pads = 0;
+        let mut rgb_pads = [(Button::GridButton { x: 0, y: 0 }, RgbColor::new(0, 0, 0)); 81];
+        let mut num_rgb_pads = 0;
+        for (button, color, palette_color) in self.individual_pads(frame) {
+            match palette_color {
+                Some(palette_color) => {
+                    palette_pads[num_palette_pads] = (button, palette_color);
+                    num_palette_pads += 1;
+                }
+                None => {
+                    let (r, g, b) = color;
+                    rgb_pads[num_rgb_pads] = (button, RgbColor::new(r, g, b));
+                    num_rgb_pads += 1;
+                }
+            }
+        }
+        if num_palette_pads > 0 {
+            output.light_multiple(&palette_pads[..num_palette_pads])?;
+        }
+        if num_rgb_pads > 0 {
+            output.light_multiple_rgb(&rgb_pads[..num_rgb_pads])?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Plan the changes with the fewest bytes: either only the changed pads, or a fill of the whole
+/// display with the most common palette color followed by all other pads. Either way, uniform
+/// rows and columns are sent as a whole if that's shorter. While the device flashes or pulses pads
+/// by itself, only the changed pads are sent, because rewriting the others would stop their
+/// effects.
+fn plan(frame: &Frame, changes: &[(u32, u32, Rgb)], has_native_effects: bool) -> Plan {
+    let mut changed_pads = [[false; 9]; 9];
+    for &(x, y, _) in changes {
+        changed_pads[y as usize][x as usize] = true;
+    }
+
+    if has_native_effects {
+        return Plan::new(frame, None, changed_pads, false);
+    }
+
+    let mut plans = vec![
+        Plan::new(frame, None, changed_pads, false),
+        Plan::new(frame, None, changed_pads, true),
+    ];
+    if let Some(fill) = frame.most_common_palette_color() {
+        let mut other_pads = [[false; 9]; 9];
+        for (x, y, _, palette_color) in frame.pads() {
+            other_pads[y][x] = palette_color != Some(fill);
+        }
+        plans.push(Plan::new(frame, Some(fill), other_pads, false));
+        plans.push(Plan::new(frame, Some(fill), other_pads, true));
+    }
+
+    // On a tie, the first plan wins, which sends the fewest messages
+    plans
+        .into_iter()
+        .min_by_key(|plan| plan.cost(frame))
+        .expect("there's always a plan")
+}
+
+/// Send the changes with the fewest bytes, see [`plan`]
+pub(super) fn flush_minimal<T: crate::MidiTransport>(
+    canvas: &mut crate::DeviceCanvas<Spec, T>,
+    changes: &[(u32, u32, Rgb)],
+) -> Result<(), crate::MidiError> {
+    let frame = Frame::from_canvas(canvas);
+    plan(&frame, changes, canvas.has_native_effects()).send(&frame, &mut canvas.output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DeviceSpec as _, OutputDevice as _};
+
+    const GREEN: Rgb = (0, 63, 0);
+    /// A color without an exact palette entry
+    const TEAL: Rgb = (10, 20, 30);
+
+    /// The messages that a minimal flush sends when the display shows `color_at` afterwards and
+    /// the pads in `changed` were changed
+    fn planned_messages(
+        color_at: impl Fn(u32, u32) -> Rgb,
+        changed: impl Fn(u32, u32) -> bool,
+    ) -> Vec<Vec<u8>> {
+        let frame = Frame::new(|x, y| Some(color_at(x, y)).filter(|_| Spec::is_valid(x, y)));
+        let changes: Vec<_> = frame
+            .pads()
+            .map(|(x, y, color, _)| (x as u32, y as u32, color))
+            .filter(|&(x, y, _)| changed(x, y))
+            .collect();
+
+        let (sink, sent) = crate::ChannelSink::new();
+        let mut output = Output::from_connection(sink).unwrap();
+        sent.try_iter().count(); // discard setup messages
+
+        plan(&frame, &changes, false)
+            .send(&frame, &mut output)
+            .unwrap();
+        sent.try_iter().collect()
+    }
+
+    #[test]
+    fn uniform_display_is_filled() {
+        let messages = planned_messages(|_, _| GREEN, |_, _| true);
+        assert_eq!(messages, [vec![240, 0, 32, 41, 2, 24, 14, 21, 247]]);
+    }
+
+    #[test]
+    fn uniform_row_is_sent_as_row() {
+        let messages =
+            planned_messages(|_, y| [(0, 0, 0), GREEN][(y == 3) as usize], |_, y| y == 3);
+        assert_eq!(messages, [vec![240, 0, 32, 41, 2, 24, 13, 5, 21, 247]]);
+    }
+
+    #[test]
+    fn uniform_column_is_sent_as_column() {
+        let messages = planned_messages(
+            |x, y| [(0, 0, 0), GREEN][(x == 2 && y > 0) as usize],
+            |x, y| x == 2 && y > 0,
+        );
+        assert_eq!(messages, [vec![240, 0, 32, 41, 2, 24, 12, 2, 21, 247]]);
+    }
+
+    #[test]
+    fn mixed_changes_are_sent_per_pad() {
+        let color_at = |x, y| match (x, y) {
+            (0, 1) => GREEN,
+            (8, 8) => TEAL,
+            (3, 0) => GREEN,
+            _ => (0, 0, 0),
+        };
+        let messages = planned_messages(color_at, |x, y| color_at(x, y) != (0, 0, 0));
+        assert_eq!(
+            messages,
+            [
+                vec![240, 0, 32, 41, 2, 24, 10, 107, 21, 81, 21, 247],
+                vec![240, 0, 32, 41, 2, 24, 11, 19, 10, 20, 30, 247],
+            ]
+        );
+    }
+
+    #[test]
+    fn colors_outside_the_palette_are_sent_as_rgb() {
+        let messages = planned_messages(|_, _| TEAL, |_, _| true);
+
+        let mut expected = vec![240, 0, 32, 41, 2, 24, 11];
+        for led in (104..112).chain(
+            (1..9)
+                .rev()
+                .flat_map(|row| (1..10).map(move |x| row * 10 + x)),
+        ) {
+            expected.extend([led, 10, 20, 30]);
+        }
+        expected.push(247);
+        assert_eq!(messages, [expected]);
+    }
+}
@@ -4,11 +4,14 @@ use super::Button;
 
 #[derive(Debug, Eq, PartialEq, Hash, Clone)]
 /// A Launchpad MK2 input message
+///
+/// The `timestamp` fields hold the MIDI timestamp of the message in microseconds, see
+/// [`MidiClock`](crate::MidiClock).
 pub enum Message {
     /// A button was pressed
-    Press { button: Button },
+    Press { button: Button, timestamp: u64 },
     /// A button was released
-    Release { button: Button },
+    Release { button: Button, timestamp: u64 },
     /// Emitted after a text scroll was initiated
     TextEndedOrLooped,
     /// The response to a [device inquiry request](super::Output::request_device_inquiry)
@@ -17,16 +20,26 @@ pub enum Message {
     VersionInquiry(VersionInquiry),
     /// Emitted when a fader was changed by the user, in [fader
     /// mode](super::Output::enter_fader_mode)
-    FaderChange { index: u8, value: u8 },
+    FaderChange {
+        index: u8,
+        value: u8,
+        timestamp: u64,
+    },
+    /// A message that doesn't match the Launchpad MK2 protocol, with its raw bytes
+    Verbatim { bytes: Vec<u8> },
 }
 
 /// The Launchpad MK2 input connection creator.
 pub struct Input;
 
-fn decode_grid_button(btn: u8) -> Button {
+fn decode_grid_button(btn: u8) -> Option<Button> {
+    if !(11..=89).contains(&btn) || !(1..=9).contains(&(btn % 10)) {
+        return None;
+    }
+
     let x = (btn % 10) - 1;
     let y = 8 - (btn / 10);
-    Button::GridButton { x, y }
+    Some(Button::GridButton { x, y })
 }
 
 impl crate::InputDevice for Input {
@@ -34,44 +47,76 @@ impl crate::InputDevice for Input {
     const MIDI_CONNECTION_NAME: &'static str = "Launchy Mk2 Input";
     type Message = Message;
 
-    fn decode_message(_timestamp: u64, data: &[u8]) -> Message {
+    fn verbatim(bytes: Vec<u8>) -> Message {
+        Message::Verbatim { bytes }
+    }
+
+    fn try_decode_message(timestamp: u64, data: &[u8]) -> Result<Message, crate::DecodeError> {
         if let Some(device_inquiry) = parse_device_query(data) {
-            return Message::DeviceInquiry(device_inquiry);
+            return Ok(Message::DeviceInquiry(device_inquiry));
         }
 
         if let Some(version_inquiry) = parse_version_query(data) {
-            return Message::VersionInquiry(version_inquiry);
+            return Ok(Message::VersionInquiry(version_inquiry));
         }
 
-        match data {
-            &[0x90, button, velocity] => {
-                let button = decode_grid_button(button);
+        let msg = match *data {
+            [0x90, button, velocity] => {
+                let button = decode_grid_button(button).ok_or_else(|| {
+                    crate::DecodeError::new(data, format!("unexpected grid button {}", button))
+                })?;
 
                 match velocity {
-                    0 => Message::Release { button },
-                    127 => Message::Press { button },
-                    other => panic!("Unexpected grid note-on velocity {}", other),
+                    0 => Message::Release { button, timestamp },
+                    127 => Message::Press { button, timestamp },
+                    other => {
+                        let reason = format!("unexpected grid note-on velocity {}", other);
+                        return Err(crate::DecodeError::new(data, reason));
+                    }
                 }
             }
             // Controller change
-            &[0xB0, number @ 104..=111, velocity] => {
+            [0xB0, number @ 104..=111, velocity] => {
                 let button = Button::ControlButton {
                     index: number - 104,
                 };
 
                 match velocity {
-                    0 => Message::Release { button },
-                    127 => Message::Press { button },
-                    other => panic!("Unexpected control note-on velocity {}", other),
+                    0 => Message::Release { button, timestamp },
+                    127 => Message::Press { button, timestamp },
+                    other => {
+                        let reason = format!("unexpected control note-on velocity {}", other);
+                        return Err(crate::DecodeError::new(data, reason));
+                    }
                 }
             }
             // Fader change
-            &[0xB0, number @ 21..=28, value] => Message::FaderChange {
+            [0xB0, number @ 21..=28, value] => Message::FaderChange {
                 index: number - 21,
                 value,
+                timestamp,
             },
-            &[240, 0, 32, 41, 2, 24, 21, 247] => Message::TextEndedOrLooped,
-            other => panic!("Unexpected midi message: {:?}", other),
+            [240, 0, 32, 41, 2, 24, 21, 247] => Message::TextEndedOrLooped,
+            _ => return Err(crate::DecodeError::new(data, "unexpected message")),
+        };
+        Ok(msg)
+    }
+}
+
+/// The faders of the [fader mode](super::Output::enter_fader_mode) are controls 0 to 7
+impl crate::ControlInput for Input {
+    fn control_change(msg: &Message) -> Option<crate::ControlChange> {
+        match *msg {
+            Message::FaderChange {
+                index,
+                value,
+                timestamp,
+            } => Some(crate::ControlChange {
+                control: crate::ControlId::new(index),
+                value,
+                timestamp,
+            }),
+            _ => None,
         }
     }
 }
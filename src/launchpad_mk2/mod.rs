@@ -10,6 +10,8 @@ pub use input::*;
 mod output;
 pub use output::*;
 
+mod flush;
+
 pub use crate::protocols::Button80 as Button;
 
 #[doc(hidden)]
@@ -21,7 +23,7 @@ impl crate::DeviceSpec for Spec {
     const COLOR_PRECISION: u16 = 64;
 
     type Input = Input;
-    type Output = Output;
+    type Output<S: crate::MidiSink> = Output<S>;
 
     fn is_valid(x: u32, y: u32) -> bool {
         if x > 8 || y > 8 {
@@ -33,10 +35,14 @@ impl crate::DeviceSpec for Spec {
         true
     }
 
-    fn flush(
-        canvas: &mut crate::DeviceCanvas<Self>,
+    fn flush<T: crate::MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
         changes: &[(u32, u32, (u8, u8, u8))],
     ) -> Result<(), crate::MidiError> {
+        if canvas.flush_strategy() == crate::FlushStrategy::Minimal {
+            return flush::flush_minimal(canvas, changes);
+        }
+
         let changes = changes.iter().map(|&(x, y, (r, g, b))| {
             let color = RgbColor::new(r, g, b);
 
@@ -47,20 +53,74 @@ impl crate::DeviceSpec for Spec {
         canvas.output.light_multiple_rgb(changes)
     }
 
+    fn supports_effect<T: crate::MidiTransport>(
+        _canvas: &crate::DeviceCanvas<Self, T>,
+        _effect: crate::PadEffect,
+    ) -> bool {
+        true
+    }
+
+    fn flush_effects<T: crate::MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
+        changes: &[crate::EffectChange],
+    ) -> Result<(), crate::MidiError> {
+        use crate::PadEffect;
+
+        // The effects only work with palette colors
+        let nearest = |color: (u8, u8, u8)| {
+            let color = crate::protocols::palette::dequantize(color, Self::COLOR_PRECISION as u8);
+            PaletteColor::nearest(color)
+        };
+        let button = |x: u32, y: u32| Button::from_abs(x as u8, y as u8);
+
+        // A flash alternates between the previously shown color and the flash color, so the pad
+        // color is lit first
+        let flash_bases = changes
+            .iter()
+            .filter(|&&(.., effect)| matches!(effect, PadEffect::Flash { .. }))
+            .map(|&(x, y, (r, g, b), _)| (button(x, y), RgbColor::new(r, g, b)))
+            .collect::<Vec<_>>();
+        if !flash_bases.is_empty() {
+            canvas.output.light_multiple_rgb(flash_bases)?;
+        }
+
+        let mut flashes = Vec::new();
+        let mut pulses = Vec::new();
+        for &(x, y, color, effect) in changes {
+            match effect {
+                PadEffect::Flash { other } => {
+                    flashes.push((button(x, y), PaletteColor::nearest(other)));
+                }
+                PadEffect::Pulse => pulses.push((button(x, y), nearest(color))),
+                PadEffect::Steady => {}
+            }
+        }
+        if !flashes.is_empty() {
+            canvas.output.flash_multiple(flashes)?;
+        }
+        if !pulses.is_empty() {
+            canvas.output.pulse_multiple(pulses)?;
+        }
+        Ok(())
+    }
+
     fn convert_message(msg: Message) -> Option<crate::CanvasMessage> {
         match msg {
-            Message::Press { button } => Some(crate::CanvasMessage::Press {
+            Message::Press { button, timestamp } => Some(crate::CanvasMessage::Press {
                 x: button.abs_x() as u32,
                 y: button.abs_y() as u32,
+                timestamp,
             }),
-            Message::Release { button } => Some(crate::CanvasMessage::Release {
+            Message::Release { button, timestamp } => Some(crate::CanvasMessage::Release {
                 x: button.abs_x() as u32,
                 y: button.abs_y() as u32,
+                timestamp,
             }),
             Message::TextEndedOrLooped
             | Message::DeviceInquiry(_)
             | Message::VersionInquiry(_)
-            | Message::FaderChange { .. } => None,
+            | Message::FaderChange { .. }
+            | Message::Verbatim { .. } => None,
         }
     }
 }
@@ -1,9 +1,8 @@
-use midir::MidiOutputConnection;
-
 pub use crate::protocols::query::*;
 
 use super::Button;
-use crate::OutputDevice;
+use crate::midi_io::DefaultSink;
+use crate::{MidiSink, OutputDevice};
 
 /// A color from the Mk2 color palette. See the "Launchpad MK2 Programmers Reference Manual"
 /// to see the palette, or [see here](http://launchpaddr.com/mk2palette/).
@@ -41,6 +40,36 @@ impl PaletteColor {
     pub fn set_id(&mut self, id: u8) {
         self.id = id
     }
+
+    /// The approximate color that the device shows for this palette color, in 8-bit sRGB
+    ///
+    /// ```
+    /// # use launchy::mk2::PaletteColor;
+    /// assert_eq!(PaletteColor::BLACK.to_rgb(), (0, 0, 0));
+    /// assert_eq!(PaletteColor::RED.to_rgb(), (254, 10, 0));
+    /// ```
+    pub fn to_rgb(&self) -> (u8, u8, u8) {
+        crate::protocols::palette::MK2_PALETTE.to_rgb(self.id)
+    }
+
+    /// Like [`Self::to_rgb`], but as a canvas color
+    pub fn to_color(&self) -> crate::Color {
+        crate::protocols::palette::MK2_PALETTE.to_color(self.id)
+    }
+
+    /// The palette color that looks the most like the given color. Use this to show colors with
+    /// palette messages, which are shorter than RGB messages, or with flashing and pulsing, which
+    /// only work with palette colors.
+    ///
+    /// ```
+    /// # use launchy::{Color, mk2::PaletteColor};
+    /// assert_eq!(PaletteColor::nearest(Color::BLACK), PaletteColor::BLACK);
+    /// assert_eq!(PaletteColor::nearest(Color::new(0.0, 0.0, 1.0)), PaletteColor::BLUE);
+    /// assert_eq!(PaletteColor::nearest(PaletteColor::CYAN.to_color()), PaletteColor::CYAN);
+    /// ```
+    pub fn nearest(color: crate::Color) -> Self {
+        Self::new(crate::protocols::palette::MK2_PALETTE.nearest(color))
+    }
 }
 
 impl From<u8> for PaletteColor {
@@ -129,7 +158,7 @@ pub enum LightMode {
 }
 
 /// Volume faders light from the bottom up, and pan faders light from the centre out.
-#[derive(Copy, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
 pub enum FaderType {
     Volume,
     Pan,
@@ -182,13 +211,13 @@ enum Layout {
 /// default value.
 ///
 /// For further documentation and examples, see [`Output::enter_fader_mode`].
-pub struct FaderMode {
-    output: Output,
+pub struct FaderMode<S: MidiSink = DefaultSink> {
+    output: Output<S>,
     fader_type: FaderType,
 }
 
-impl FaderMode {
-    fn new(mut output: Output, fader_type: FaderType) -> Result<Self, crate::MidiError> {
+impl<S: MidiSink> FaderMode<S> {
+    fn new(mut output: Output<S>, fader_type: FaderType) -> Result<Self, crate::MidiError> {
         output.change_layout(match fader_type {
             FaderType::Volume => Layout::Volume,
             FaderType::Pan => Layout::Pan,
@@ -198,7 +227,7 @@ impl FaderMode {
 
     /// Exit fader mode by transforming this FaderMode object back into a Output object.
     #[must_use = "You must use the returned object, or the MIDI connection will be dropped"]
-    pub fn exit(mut self) -> Result<Output, crate::MidiError> {
+    pub fn exit(mut self) -> Result<Output<S>, crate::MidiError> {
         self.output.change_layout(Layout::Session)?;
         Ok(self.output)
     }
@@ -242,6 +271,7 @@ impl FaderMode {
 ///
 /// For example:
 /// ```no_run
+/// # #[cfg(feature = "midir")] {
 /// # use launchy::OutputDevice as _;
 /// # use launchy::mk2::{PaletteColor, Button, Output};
 /// let mut output = Output::guess()?;
@@ -256,6 +286,7 @@ impl FaderMode {
 ///
 /// // light top left button magenta
 /// output.light(Button::GridButton { x: 0, y: 0 }, PaletteColor::MAGENTA);
+/// # }
 /// # Ok::<(), launchy::MidiError>(())
 /// ```
 ///
@@ -265,27 +296,36 @@ impl FaderMode {
 /// Why would you choose the palette colors when you can just create your required colors yourself?
 /// Well some operations on the Mk2 only support palette colors. Besides, sending palette color midi
 /// messages is simply faster. Therefore you should aim to use the palette colors when possible.
-pub struct Output {
-    connection: MidiOutputConnection,
+pub struct Output<S: MidiSink = DefaultSink> {
+    connection: S,
 }
 
-impl crate::OutputDevice for Output {
+impl<S: MidiSink> crate::OutputDevice for Output<S> {
     const MIDI_CONNECTION_NAME: &'static str = "Launchy Mk2 output";
     const MIDI_DEVICE_KEYWORD: &'static str = "Launchpad MK2";
 
-    fn from_connection(connection: MidiOutputConnection) -> Result<Self, crate::MidiError> {
+    type Sink = S;
+
+    fn from_connection(connection: S) -> Result<Self, crate::MidiError> {
         let mut self_ = Self { connection };
         self_.change_layout(Layout::Session)?;
         Ok(self_)
     }
 
     fn send(&mut self, bytes: &[u8]) -> Result<(), crate::MidiError> {
-        self.connection.send(bytes)?;
-        Ok(())
+        self.connection.send(bytes)
+    }
+
+    /// Plain LED messages and fader values only depend on the latest value
+    fn coalescing_key(bytes: &[u8]) -> Option<u32> {
+        match *bytes {
+            [status @ (0x90 | 0xB0), key, _] => Some(u32::from_be_bytes([0, 0, status, key])),
+            _ => None,
+        }
     }
 }
 
-impl Output {
+impl<S: MidiSink> Output<S> {
     /// This is a function testing various parts of this API by executing various commands in order
     /// to find issues either in this library or in your device
     pub fn test_api(&mut self) -> Result<(), crate::MidiError> {
@@ -656,7 +696,7 @@ impl Output {
     /// # Ok::<(), launchy::MidiError>(())
     /// ```
     #[must_use = "If you don't use the returned object, the MIDI connection will be dropped immediately"]
-    pub fn enter_fader_mode(self, fader_type: FaderType) -> Result<FaderMode, crate::MidiError> {
+    pub fn enter_fader_mode(self, fader_type: FaderType) -> Result<FaderMode<S>, crate::MidiError> {
         FaderMode::new(self, fader_type)
     }
 
@@ -0,0 +1,152 @@
+pub use crate::protocols::query::*;
+
+use super::{Button, Layout, Mode};
+
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+/// A Launchpad Pro input message
+///
+/// The `timestamp` fields hold the MIDI timestamp of the message in microseconds, see
+/// [`MidiClock`](crate::MidiClock).
+///
+/// ```
+/// use launchy::launchpad_pro::{Button, Input, Message};
+/// use launchy::InputDevice as _;
+///
+/// assert_eq!(
+///     Input::decode_message(0, &[0x90, 11, 87]),
+///     Message::Press { button: Button::grid(0, 7), velocity: 87, timestamp: 0 },
+/// );
+/// assert_eq!(
+///     Input::decode_message(0, &[0xB0, 91, 127]),
+///     Message::Press { button: Button::UP, velocity: 127, timestamp: 0 },
+/// );
+/// ```
+pub enum Message {
+    /// A button was pressed. The control buttons always report a velocity of 127.
+    Press {
+        button: Button,
+        velocity: u8,
+        timestamp: u64,
+    },
+    /// A button was released
+    Release { button: Button, timestamp: u64 },
+    /// The pressure on a pressed pad changed, if the device is set to polyphonic aftertouch
+    Aftertouch {
+        button: Button,
+        pressure: u8,
+        timestamp: u64,
+    },
+    /// The pressure on the pads changed, if the device is set to channel pressure
+    ChannelPressure { pressure: u8, timestamp: u64 },
+    /// Emitted when a fader was changed by the user, in [fader
+    /// mode](super::Output::enter_fader_mode)
+    FaderChange {
+        index: u8,
+        value: u8,
+        timestamp: u64,
+    },
+    /// Emitted after a text scroll was initiated
+    TextEndedOrLooped,
+    /// The response to a [device inquiry request](super::Output::request_device_inquiry)
+    DeviceInquiry(DeviceInquiry),
+    /// Sent when the [mode changed](super::Output::set_mode)
+    Mode(Mode),
+    /// Sent when the [Standalone layout changed](super::Output::select_layout)
+    Layout(Layout),
+    /// The response to a [status request](super::Output::request_status)
+    Status { mode: Mode, layout: Layout },
+    /// A message that doesn't match the Launchpad Pro protocol, with its raw bytes
+    Verbatim { bytes: Vec<u8> },
+}
+
+/// The Launchpad Pro input connection creator.
+pub struct Input;
+
+impl crate::InputDevice for Input {
+    /// Device name. Like for the [`Output`](super::Output), that's the Standalone port, which is
+    /// the only one that sends the presses of the Programmer layout.
+    const MIDI_DEVICE_KEYWORD: &'static str = super::STANDALONE_PORT_KEYWORD;
+    const MIDI_CONNECTION_NAME: &'static str = "Launchy Pro Input";
+    type Message = Message;
+
+    fn verbatim(bytes: Vec<u8>) -> Message {
+        Message::Verbatim { bytes }
+    }
+
+    fn is_device_port(name: &str) -> bool {
+        super::is_standalone_port(name)
+    }
+
+    fn try_decode_message(timestamp: u64, data: &[u8]) -> Result<Message, crate::DecodeError> {
+        if let Some(device_inquiry) = parse_device_query(data) {
+            return Ok(Message::DeviceInquiry(device_inquiry));
+        }
+
+        let decode_button = |id: u8| {
+            Button::from_programmer_id(id)
+                .ok_or_else(|| crate::DecodeError::new(data, format!("unexpected button {}", id)))
+        };
+
+        let msg = match *data {
+            // The grid sends notes, the control buttons send control changes
+            [0x90, id, velocity] => button_message(decode_button(id)?, velocity, timestamp),
+            [0x80, id, _] => Message::Release {
+                button: decode_button(id)?,
+                timestamp,
+            },
+            [0xA0, id, pressure] => Message::Aftertouch {
+                button: decode_button(id)?,
+                pressure,
+                timestamp,
+            },
+            [0xD0, pressure] => Message::ChannelPressure {
+                pressure,
+                timestamp,
+            },
+            // Fader change. The faders use the ids of grid buttons, which otherwise never send
+            // control changes
+            [0xB0, number @ 21..=28, value] => Message::FaderChange {
+                index: number - 21,
+                value,
+                timestamp,
+            },
+            [0xB0, id, value] => match Button::from_programmer_id(id) {
+                Some(button @ Button::ControlButton { .. }) => {
+                    button_message(button, value, timestamp)
+                }
+                _ => {
+                    let reason = format!("unexpected control change {}", id);
+                    return Err(crate::DecodeError::new(data, reason));
+                }
+            },
+            [240, 0, 32, 41, 2, 16, 21, 247] => Message::TextEndedOrLooped,
+            [240, 0, 32, 41, 2, 16, 45, mode, 247] => Message::Mode(
+                Mode::from_id(mode)
+                    .ok_or_else(|| crate::DecodeError::new(data, "unexpected mode"))?,
+            ),
+            [240, 0, 32, 41, 2, 16, 46, layout, 247] => Message::Layout(
+                Layout::from_id(layout)
+                    .ok_or_else(|| crate::DecodeError::new(data, "unexpected layout"))?,
+            ),
+            [240, 0, 32, 41, 2, 16, 47, mode, layout, 247] => Message::Status {
+                mode: Mode::from_id(mode)
+                    .ok_or_else(|| crate::DecodeError::new(data, "unexpected mode"))?,
+                layout: Layout::from_id(layout)
+                    .ok_or_else(|| crate::DecodeError::new(data, "unexpected layout"))?,
+            },
+            _ => return Err(crate::DecodeError::new(data, "unexpected message")),
+        };
+        Ok(msg)
+    }
+}
+
+fn button_message(button: Button, velocity: u8, timestamp: u64) -> Message {
+    match velocity {
+        0 => Message::Release { button, timestamp },
+        velocity => Message::Press {
+            button,
+            velocity,
+            timestamp,
+        },
+    }
+}
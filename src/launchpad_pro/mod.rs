@@ -0,0 +1,404 @@
+/*!
+# Launchpad Pro low-level API
+
+The original Launchpad Pro has an 8x8 grid of velocity and pressure sensitive pads, surrounded
+on all four sides by a ring of 8 control buttons each. That makes for an _almost_ 10x10 layout,
+except that the corners are missing. There's an additional LED on the front side of the device,
+the side LED, which can be lit but not pressed.
+
+`launchy` talks to the Launchpad Pro in the Programmer layout of the Standalone mode, which is
+selected upon connecting.
+*/
+
+mod input;
+pub use input::*;
+
+mod output;
+pub use output::*;
+
+// The name of the port that the Standalone mode uses, the second of the three ports of the device.
+// It's called differently on every platform, see `is_standalone_port`
+#[cfg(target_os = "macos")]
+const STANDALONE_PORT_KEYWORD: &str = "Launchpad Pro Standalone Port";
+#[cfg(windows)]
+const STANDALONE_PORT_KEYWORD: &str = "2 (Launchpad Pro)";
+#[cfg(not(any(target_os = "macos", windows)))]
+const STANDALONE_PORT_KEYWORD: &str = "Launchpad Pro MIDI 2";
+
+/// Whether a port is the Standalone port. macOS names it after the port in the programmer's
+/// reference. ALSA and Windows number the ports of a device instead, and Windows additionally puts
+/// a number in front of the device name for every further Launchpad Pro. None of the names match
+/// a Launchpad Pro MK3.
+fn is_standalone_port(name: &str) -> bool {
+    match crate::midi_io::parse_windows_port_name(name) {
+        Some((number, device)) => number == 2 && device == "Launchpad Pro",
+        None => {
+            name.contains("Launchpad Pro Standalone Port") || name.contains("Launchpad Pro MIDI 2")
+        }
+    }
+}
+
+/// One of the four sides of the control button ring
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum Side {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+/// A button of the Launchpad Pro
+///
+/// The control buttons in the top and bottom row are indexed from left to right, the control
+/// buttons in the left and right column from top to bottom.
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum Button {
+    GridButton { x: u8, y: u8 },
+    ControlButton { side: Side, index: u8 },
+}
+
+impl Button {
+    pub const UP: Self = Self::ControlButton {
+        side: Side::Top,
+        index: 0,
+    };
+    pub const DOWN: Self = Self::ControlButton {
+        side: Side::Top,
+        index: 1,
+    };
+    pub const LEFT: Self = Self::ControlButton {
+        side: Side::Top,
+        index: 2,
+    };
+    pub const RIGHT: Self = Self::ControlButton {
+        side: Side::Top,
+        index: 3,
+    };
+    pub const SESSION: Self = Self::ControlButton {
+        side: Side::Top,
+        index: 4,
+    };
+    pub const NOTE: Self = Self::ControlButton {
+        side: Side::Top,
+        index: 5,
+    };
+    pub const DEVICE: Self = Self::ControlButton {
+        side: Side::Top,
+        index: 6,
+    };
+    pub const USER: Self = Self::ControlButton {
+        side: Side::Top,
+        index: 7,
+    };
+
+    pub const SHIFT: Self = Self::ControlButton {
+        side: Side::Left,
+        index: 0,
+    };
+    pub const CLICK: Self = Self::ControlButton {
+        side: Side::Left,
+        index: 1,
+    };
+    pub const UNDO: Self = Self::ControlButton {
+        side: Side::Left,
+        index: 2,
+    };
+    pub const DELETE: Self = Self::ControlButton {
+        side: Side::Left,
+        index: 3,
+    };
+    pub const QUANTISE: Self = Self::ControlButton {
+        side: Side::Left,
+        index: 4,
+    };
+    pub const DUPLICATE: Self = Self::ControlButton {
+        side: Side::Left,
+        index: 5,
+    };
+    pub const DOUBLE: Self = Self::ControlButton {
+        side: Side::Left,
+        index: 6,
+    };
+    pub const RECORD: Self = Self::ControlButton {
+        side: Side::Left,
+        index: 7,
+    };
+
+    pub const RECORD_ARM: Self = Self::ControlButton {
+        side: Side::Bottom,
+        index: 0,
+    };
+    pub const TRACK_SELECT: Self = Self::ControlButton {
+        side: Side::Bottom,
+        index: 1,
+    };
+    pub const MUTE: Self = Self::ControlButton {
+        side: Side::Bottom,
+        index: 2,
+    };
+    pub const SOLO: Self = Self::ControlButton {
+        side: Side::Bottom,
+        index: 3,
+    };
+    pub const VOLUME: Self = Self::ControlButton {
+        side: Side::Bottom,
+        index: 4,
+    };
+    pub const PAN: Self = Self::ControlButton {
+        side: Side::Bottom,
+        index: 5,
+    };
+    pub const SENDS: Self = Self::ControlButton {
+        side: Side::Bottom,
+        index: 6,
+    };
+    pub const STOP_CLIP: Self = Self::ControlButton {
+        side: Side::Bottom,
+        index: 7,
+    };
+
+    /// Creates a new GridButton coordinate
+    pub fn grid(x: u8, y: u8) -> Self {
+        Self::GridButton { x, y }
+    }
+
+    /// Creates a new ControlButton coordinate
+    pub fn control(side: Side, index: u8) -> Self {
+        Self::ControlButton { side, index }
+    }
+
+    /// Creates the scene launch button (the right column) with the given index
+    pub fn scene(index: u8) -> Self {
+        Self::ControlButton {
+            side: Side::Right,
+            index,
+        }
+    }
+
+    /// Creates a new button out of absolute coordinates, like the ones returned by `abs_x()` and
+    /// `abs_y()`.
+    pub fn from_abs(x: u8, y: u8) -> Self {
+        match (x, y) {
+            (1..=8, 0) => Self::control(Side::Top, x - 1),
+            (1..=8, 9) => Self::control(Side::Bottom, x - 1),
+            (0, 1..=8) => Self::control(Side::Left, y - 1),
+            (9, 1..=8) => Self::control(Side::Right, y - 1),
+            (1..=8, 1..=8) => Self::grid(x - 1, y - 1),
+            _ => panic!("Unexpected coordinates: ({}, {})", x, y),
+        }
+    }
+
+    /// Returns x coordinate assuming coordinate origin in the top left corner, which has no
+    /// button
+    pub fn abs_x(&self) -> u8 {
+        match *self {
+            Self::GridButton { x, .. } => x + 1,
+            Self::ControlButton { side, index } => match side {
+                Side::Top | Side::Bottom => index + 1,
+                Side::Left => 0,
+                Side::Right => 9,
+            },
+        }
+    }
+
+    /// Returns y coordinate assuming coordinate origin in the top left corner, which has no
+    /// button
+    pub fn abs_y(&self) -> u8 {
+        match *self {
+            Self::GridButton { y, .. } => y + 1,
+            Self::ControlButton { side, index } => match side {
+                Side::Left | Side::Right => index + 1,
+                Side::Top => 0,
+                Side::Bottom => 9,
+            },
+        }
+    }
+
+    /// The LED and note number of this button in the Programmer layout. The numbers start at the
+    /// bottom left and go up by 1 to the right and by 10 upwards.
+    pub(crate) fn to_programmer_id(self) -> u8 {
+        match self {
+            Self::GridButton { x, y } => assert!(x <= 7 && y <= 7),
+            Self::ControlButton { index, .. } => assert!(index <= 7),
+        }
+        10 * (9 - self.abs_y()) + self.abs_x()
+    }
+
+    pub(crate) fn from_programmer_id(id: u8) -> Option<Self> {
+        if id > 98 {
+            return None;
+        }
+
+        let (x, y) = (id % 10, 9 - id / 10);
+        match (x, y) {
+            (0 | 9, 0 | 9) => None,
+            _ => Some(Self::from_abs(x, y)),
+        }
+    }
+}
+
+#[doc(hidden)]
+pub struct Spec;
+
+impl crate::DeviceSpec for Spec {
+    const BOUNDING_BOX_WIDTH: u32 = 10;
+    const BOUNDING_BOX_HEIGHT: u32 = 10;
+    const COLOR_PRECISION: u16 = 64;
+
+    type Input = Input;
+    type Output<S: crate::MidiSink> = Output<S>;
+
+    fn is_valid(x: u32, y: u32) -> bool {
+        if x > 9 || y > 9 {
+            return false;
+        }
+        // The corners have no buttons
+        if (x == 0 || x == 9) && (y == 0 || y == 9) {
+            return false;
+        }
+        true
+    }
+
+    fn flush<T: crate::MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
+        changes: &[(u32, u32, (u8, u8, u8))],
+    ) -> Result<(), crate::MidiError> {
+        // A minimal flush sends black pads as palette packets, which are half as long as RGB
+        // packets. That's worth the extra message only if there are enough of them
+        let is_black = |&&(_, _, color): &&(u32, u32, (u8, u8, u8))| color == (0, 0, 0);
+        let num_black = match canvas.flush_strategy() {
+            crate::FlushStrategy::Minimal => changes.iter().filter(is_black).count(),
+            crate::FlushStrategy::PerPad => 0,
+        };
+        let split_black = num_black > 0 && (num_black == changes.len() || num_black > 4);
+
+        if split_black {
+            let black_pads = changes
+                .iter()
+                .filter(is_black)
+                .map(|&(x, y, _)| (Button::from_abs(x as u8, y as u8), PaletteColor::BLACK));
+            canvas.output.set_buttons(black_pads, LightMode::Plain)?;
+        }
+
+        let changes = changes
+            .iter()
+            .filter(|change| !(split_black && is_black(change)))
+            .map(|&(x, y, (r, g, b))| {
+                let color = RgbColor::new(r, g, b);
+
+                let button = Button::from_abs(x as u8, y as u8);
+
+                (button, color)
+            })
+            .collect::<Vec<_>>();
+        if changes.is_empty() {
+            return Ok(());
+        }
+        canvas.output.light_multiple_rgb(changes)
+    }
+
+    fn supports_effect<T: crate::MidiTransport>(
+        _canvas: &crate::DeviceCanvas<Self, T>,
+        _effect: crate::PadEffect,
+    ) -> bool {
+        true
+    }
+
+    fn flush_effects<T: crate::MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
+        changes: &[crate::EffectChange],
+    ) -> Result<(), crate::MidiError> {
+        use crate::PadEffect;
+
+        // The effects only work with palette colors
+        let nearest = |color: (u8, u8, u8)| {
+            let color = crate::protocols::palette::dequantize(color, Self::COLOR_PRECISION as u8);
+            PaletteColor::nearest(color)
+        };
+        let button = |x: u32, y: u32| Button::from_abs(x as u8, y as u8);
+
+        // A flash alternates between the previously shown color and the flash color, so the pad
+        // color is lit first
+        let flash_bases = changes
+            .iter()
+            .filter(|&&(.., effect)| matches!(effect, PadEffect::Flash { .. }))
+            .map(|&(x, y, (r, g, b), _)| (button(x, y), RgbColor::new(r, g, b)))
+            .collect::<Vec<_>>();
+        if !flash_bases.is_empty() {
+            canvas.output.light_multiple_rgb(flash_bases)?;
+        }
+
+        let mut flashes = Vec::new();
+        let mut pulses = Vec::new();
+        for &(x, y, color, effect) in changes {
+            match effect {
+                PadEffect::Flash { other } => {
+                    flashes.push((button(x, y), PaletteColor::nearest(other)));
+                }
+                PadEffect::Pulse => pulses.push((button(x, y), nearest(color))),
+                PadEffect::Steady => {}
+            }
+        }
+        if !flashes.is_empty() {
+            canvas.output.flash_multiple(flashes)?;
+        }
+        if !pulses.is_empty() {
+            canvas.output.pulse_multiple(pulses)?;
+        }
+        Ok(())
+    }
+
+    fn convert_message(msg: Message) -> Option<crate::CanvasMessage> {
+        match msg {
+            Message::Press {
+                button, timestamp, ..
+            } => Some(crate::CanvasMessage::Press {
+                x: button.abs_x() as u32,
+                y: button.abs_y() as u32,
+                timestamp,
+            }),
+            Message::Release { button, timestamp } => Some(crate::CanvasMessage::Release {
+                x: button.abs_x() as u32,
+                y: button.abs_y() as u32,
+                timestamp,
+            }),
+            Message::Aftertouch { .. }
+            | Message::ChannelPressure { .. }
+            | Message::FaderChange { .. }
+            | Message::TextEndedOrLooped
+            | Message::DeviceInquiry(_)
+            | Message::Mode(_)
+            | Message::Layout(_)
+            | Message::Status { .. }
+            | Message::Verbatim { .. } => None,
+        }
+    }
+}
+
+pub type Canvas<'a> = crate::DeviceCanvas<Spec>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standalone_port_is_found_on_every_platform() {
+        assert!(is_standalone_port("Launchpad Pro Standalone Port"));
+        assert!(is_standalone_port(
+            "Launchpad Pro:Launchpad Pro MIDI 2 20:1"
+        ));
+        assert!(is_standalone_port("MIDIIN2 (Launchpad Pro)"));
+        assert!(is_standalone_port("MIDIOUT2 (Launchpad Pro)"));
+        assert!(is_standalone_port("MIDIIN2 (2- Launchpad Pro)"));
+        assert!(is_standalone_port("MIDIOUT2 (2- Launchpad Pro)"));
+
+        assert!(!is_standalone_port("Launchpad Pro Live Port"));
+        assert!(!is_standalone_port(
+            "Launchpad Pro:Launchpad Pro MIDI 1 20:0"
+        ));
+        assert!(!is_standalone_port("Launchpad Pro"));
+        assert!(!is_standalone_port("2- Launchpad Pro"));
+        assert!(!is_standalone_port("MIDIOUT3 (Launchpad Pro)"));
+        assert!(!is_standalone_port("MIDIOUT2 (LPProMK3 MIDI)"));
+    }
+}
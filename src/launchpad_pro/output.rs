@@ -0,0 +1,533 @@
+pub use crate::launchpad_mk2::{FaderType, LightMode, PaletteColor, RgbColor};
+pub use crate::protocols::query::*;
+
+use super::Button;
+use crate::midi_io::DefaultSink;
+use crate::{MidiSink, OutputDevice};
+
+/// The LED index of the side LED on the front of the device
+const SIDE_LED: u8 = 99;
+
+/// Whether the Launchpad Pro is controlled by Ableton Live or by its own layouts
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum Mode {
+    /// Reserved for Ableton Live, shouldn't be used here
+    Ableton,
+    /// The layouts are selected with [`Output::select_layout`]
+    Standalone,
+}
+
+impl Mode {
+    pub(crate) fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::Ableton),
+            1 => Some(Self::Standalone),
+            _ => None,
+        }
+    }
+}
+
+/// The layouts of the Standalone mode
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum Layout {
+    Note,
+    Drum,
+    /// Used by [`FaderMode`]
+    Fader,
+    /// All LEDs are controlled via MIDI, and every button sends its own note or control change
+    Programmer,
+}
+
+impl Layout {
+    pub(crate) fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::Note),
+            1 => Some(Self::Drum),
+            2 => Some(Self::Fader),
+            3 => Some(Self::Programmer),
+            _ => None,
+        }
+    }
+}
+
+/// Specifies information about a fader
+pub struct Fader {
+    index: u8,
+    fader_type: FaderType,
+    color: PaletteColor,
+    initial_value: u8,
+}
+
+impl Fader {
+    pub fn new(index: u8, fader_type: FaderType, color: PaletteColor, initial_value: u8) -> Self {
+        assert!(initial_value <= 127);
+        assert!(index <= 7);
+
+        Self {
+            index,
+            fader_type,
+            color,
+            initial_value,
+        }
+    }
+
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+    pub fn fader_type(&self) -> FaderType {
+        self.fader_type
+    }
+    pub fn color(&self) -> PaletteColor {
+        self.color
+    }
+    pub fn initial_value(&self) -> u8 {
+        self.initial_value
+    }
+}
+
+/// This is the handler object for the Launchpad Pro's fader layout, in which you can utilize the
+/// built-in fader functionality. Unlike on the Launchpad MK2, volume and pan faders can be mixed.
+///
+/// For further documentation and examples, see [`Output::enter_fader_mode`].
+pub struct FaderMode<S: MidiSink = DefaultSink> {
+    output: Output<S>,
+}
+
+impl<S: MidiSink> FaderMode<S> {
+    fn new(mut output: Output<S>) -> Result<Self, crate::MidiError> {
+        output.select_layout(Layout::Fader)?;
+        Ok(Self { output })
+    }
+
+    /// Exit fader mode by transforming this FaderMode object back into a Output object.
+    #[must_use = "You must use the returned object, or the MIDI connection will be dropped"]
+    pub fn exit(mut self) -> Result<Output<S>, crate::MidiError> {
+        self.output.select_layout(Layout::Programmer)?;
+        Ok(self.output)
+    }
+
+    /// Place faders on the screen. The faders' properties are specified using the `&[Fader]` slice.
+    pub fn designate_faders(&mut self, faders: &[Fader]) -> Result<(), crate::MidiError> {
+        assert!(faders.len() <= 8);
+
+        let mut bytes = Vec::with_capacity(8 + 4 * faders.len());
+        bytes.extend(&[240, 0, 32, 41, 2, 16, 43]);
+        for fader in faders {
+            let fader_type = match fader.fader_type {
+                FaderType::Volume => 0,
+                FaderType::Pan => 1,
+            };
+            bytes.extend(&[
+                fader.index,
+                fader_type,
+                fader.color.id(),
+                fader.initial_value,
+            ]);
+        }
+        bytes.push(247);
+
+        self.output.send(&bytes)
+    }
+
+    /// Moves a fader, specified by `index`, to a specific `value`
+    pub fn set_fader(&mut self, index: u8, value: u8) -> Result<(), crate::MidiError> {
+        assert!(index <= 7);
+        assert!(value <= 127);
+
+        self.output.send(&[176, 21 + index, value])
+    }
+}
+
+/// The object handling any messages _to_ the Launchpad Pro. To get started, initialize with
+/// [`Output::guess`](OutputDevice::guess) and then send messages to your liking. The connection
+/// to the launchpad will get closed when this object goes out of scope.
+///
+/// Upon connecting, the Launchpad Pro is switched to the Programmer layout of the Standalone mode.
+///
+/// ```
+/// # use launchy::{ChannelSink, OutputDevice as _};
+/// use launchy::launchpad_pro::{Button, Output, PaletteColor, RgbColor};
+///
+/// let (sink, sent) = ChannelSink::new();
+/// let mut output = Output::from_connection(sink)?;
+/// # sent.try_iter().count(); // discard setup messages
+///
+/// output.light(Button::grid(0, 0), PaletteColor::RED)?;
+/// assert_eq!(sent.try_recv().unwrap(), [0x90, 81, 5]);
+///
+/// // The side LED can only be lit via SysEx
+/// output.light_side_led_rgb(RgbColor::new(0, 0, 63))?;
+/// assert_eq!(sent.try_recv().unwrap(), [240, 0, 32, 41, 2, 16, 11, 99, 0, 0, 63, 247]);
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+///
+/// # Representing color
+/// The Launchpad Pro uses the same color palette and RGB range as the Launchpad MK2. Like there,
+/// flashing and pulsing only work with palette colors.
+pub struct Output<S: MidiSink = DefaultSink> {
+    connection: S,
+}
+
+impl<S: MidiSink> crate::OutputDevice for Output<S> {
+    const MIDI_CONNECTION_NAME: &'static str = "Launchy Pro output";
+    /// Device name.
+    ///
+    /// The Launchpad Pro has three ports, of which the second is used in the Standalone mode. The
+    /// programmer's reference calls it the "Standalone Port", which is its name on macOS. Other
+    /// platforms number the ports of a device instead.
+    const MIDI_DEVICE_KEYWORD: &'static str = super::STANDALONE_PORT_KEYWORD;
+
+    type Sink = S;
+
+    fn from_connection(connection: S) -> Result<Self, crate::MidiError> {
+        let mut self_ = Self { connection };
+        self_.set_mode(Mode::Standalone)?;
+        self_.select_layout(Layout::Programmer)?;
+        Ok(self_)
+    }
+
+    fn is_device_port(name: &str) -> bool {
+        super::is_standalone_port(name)
+    }
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), crate::MidiError> {
+        self.connection.send(bytes)
+    }
+
+    /// Plain LED messages and fader values only depend on the latest value
+    fn coalescing_key(bytes: &[u8]) -> Option<u32> {
+        match *bytes {
+            [status @ (0x90 | 0xB0), key, _] => Some(u32::from_be_bytes([0, 0, status, key])),
+            _ => None,
+        }
+    }
+}
+
+impl<S: MidiSink> Output<S> {
+    /// Set a `button` to a certain `color` with a certain `light_mode`.
+    ///
+    /// For example to start a yellow pulse on the Shift button:
+    /// ```no_run
+    /// # use launchy::launchpad_pro::{PaletteColor, Button, LightMode};
+    /// # let output: launchy::launchpad_pro::Output = unimplemented!();
+    /// output.set_button(Button::SHIFT, PaletteColor::YELLOW, LightMode::Pulse)?;
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    pub fn set_button(
+        &mut self,
+        button: Button,
+        color: PaletteColor,
+        light_mode: LightMode,
+    ) -> Result<(), crate::MidiError> {
+        assert!(color.is_valid());
+
+        let type_byte = match button {
+            Button::GridButton { .. } => 0x90,
+            Button::ControlButton { .. } => 0xB0,
+        } + match light_mode {
+            LightMode::Plain => 0,
+            LightMode::Flash => 1,
+            LightMode::Pulse => 2,
+        };
+
+        self.send(&[type_byte, button.to_programmer_id(), color.id()])
+    }
+
+    /// Like `set_button()`, but for multiple buttons. This method lights multiple buttons with
+    /// varying color. The light mode can't be varied between buttons.
+    pub fn set_buttons(
+        &mut self,
+        buttons: impl IntoIterator<Item = impl std::borrow::Borrow<(Button, PaletteColor)>>,
+        light_mode: LightMode,
+    ) -> Result<(), crate::MidiError> {
+        let msg_type_byte = match light_mode {
+            LightMode::Plain => 10,
+            LightMode::Flash => 35,
+            LightMode::Pulse => 40,
+        };
+
+        // Like on the MK2, flash and pulse packets are prefixed with a null byte
+        let add_null_byte = match light_mode {
+            LightMode::Plain => false,
+            LightMode::Flash | LightMode::Pulse => true,
+        };
+
+        self.send_multiple(
+            msg_type_byte,
+            add_null_byte,
+            buttons.into_iter().map(|pair| {
+                let &(button, color) = pair.borrow();
+                (button.to_programmer_id(), color)
+            }),
+        )
+    }
+
+    /// Light multiple buttons with varying RGB colors.
+    ///
+    /// For example to light the top left button green and the scene launch button next to it
+    /// blue:
+    /// ```no_run
+    /// # use launchy::launchpad_pro::{Button, RgbColor};
+    /// # let output: launchy::launchpad_pro::Output = unimplemented!();
+    /// output.light_multiple_rgb(&[
+    ///     (Button::grid(0, 0), RgbColor::new(0, 63, 0)),
+    ///     (Button::scene(0), RgbColor::new(0, 0, 63)),
+    /// ])?;
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    pub fn light_multiple_rgb<I, T>(&mut self, buttons: I) -> Result<(), crate::MidiError>
+    where
+        I: IntoIterator<Item = T>,
+        T: std::borrow::Borrow<(Button, RgbColor)>,
+    {
+        self.send_multiple_rgb(buttons.into_iter().map(|pair| {
+            let &(button, color) = pair.borrow();
+            (button.to_programmer_id(), color)
+        }))
+    }
+
+    /// Light multiple columns with varying colors. Column 0 is the left column of the control
+    /// ring, columns 1-8 contain the grid with the top and bottom control buttons, and column 9 is
+    /// the right column of the control ring.
+    pub fn light_columns(
+        &mut self,
+        columns: impl IntoIterator<Item = impl std::borrow::Borrow<(u8, PaletteColor)>>,
+    ) -> Result<(), crate::MidiError> {
+        self.send_multiple(12, false, columns)
+    }
+
+    /// Light multiple rows with varying colors. Row 0 is the top row of the control ring, rows 1-8
+    /// contain the grid with the left and right control buttons, and row 9 is the bottom row of
+    /// the control ring.
+    pub fn light_rows(
+        &mut self,
+        rows: impl IntoIterator<Item = impl std::borrow::Borrow<(u8, PaletteColor)>>,
+    ) -> Result<(), crate::MidiError> {
+        self.send_multiple(
+            13,
+            false,
+            rows.into_iter().map(|pair| {
+                let &(row, color) = pair.borrow();
+                assert!(row <= 9);
+                (9 - row, color)
+            }),
+        )
+    }
+
+    /// Light all buttons, including the control ring and the side LED
+    pub fn light_all(&mut self, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.send(&[240, 0, 32, 41, 2, 16, 14, color.id(), 247])
+    }
+
+    /// Light the side LED on the front of the device with a palette color
+    pub fn light_side_led(&mut self, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.send_multiple(10, false, [(SIDE_LED, color)])
+    }
+
+    /// Light the side LED on the front of the device with an RGB color
+    pub fn light_side_led_rgb(&mut self, color: RgbColor) -> Result<(), crate::MidiError> {
+        self.send_multiple_rgb([(SIDE_LED, color)])
+    }
+
+    /// By default, the Launchpad Pro will flash and pulse at 120 BPM. To use a different tempo,
+    /// send 24 of these clock ticks per beat.
+    pub fn send_clock_tick(&mut self) -> Result<(), crate::MidiError> {
+        self.send(&[248, 0, 0])
+    }
+
+    /// Requests the Launchpad Pro to send a so-called device inquiry. The device inquiry contains
+    /// information about the device ID and the firmware revision number.
+    ///
+    /// In order to be able to receive the Launchpad Pro's response to this request,
+    /// you must have a Launchpad Pro input object set up.
+    pub fn request_device_inquiry(&mut self, query: DeviceIdQuery) -> Result<(), crate::MidiError> {
+        request_device_inquiry(self, query)
+    }
+
+    /// Requests a [super::Message::Status] with the current mode and layout
+    pub fn request_status(&mut self) -> Result<(), crate::MidiError> {
+        self.send(&[240, 0, 32, 41, 2, 16, 47, 247])
+    }
+
+    /// Starts a text scroll across the screen. The screen is temporarily cleared. You can specify
+    /// the color of the text and whether the text should loop indefinitely.
+    ///
+    /// Like on the Launchpad MK2, the plain values 1 – 7 in the text are speed commands (where 1
+    /// is the slowest and 7 is fastest). As the text either ends or loops, a
+    /// [super::Message::TextEndedOrLooped] will be sent to the Input.
+    pub fn scroll_text(
+        &mut self,
+        text: &[u8],
+        color: PaletteColor,
+        should_loop: bool,
+    ) -> Result<(), crate::MidiError> {
+        let bytes = &[
+            &[240, 0, 32, 41, 2, 16, 20, color.id(), should_loop as u8],
+            text,
+            &[247],
+        ]
+        .concat();
+
+        self.send(bytes)
+    }
+
+    /// Switch between the Ableton Live mode and the Standalone mode. The device responds with a
+    /// [super::Message::Mode].
+    pub fn set_mode(&mut self, mode: Mode) -> Result<(), crate::MidiError> {
+        let mode = match mode {
+            Mode::Ableton => 0,
+            Mode::Standalone => 1,
+        };
+        self.send(&[240, 0, 32, 41, 2, 16, 33, mode, 247])
+    }
+
+    /// Select one of the layouts of the Standalone mode. The device responds with a
+    /// [super::Message::Layout].
+    ///
+    /// ```
+    /// # use launchy::{ChannelSink, OutputDevice as _};
+    /// use launchy::launchpad_pro::{Layout, Output};
+    ///
+    /// let (sink, sent) = ChannelSink::new();
+    /// let mut output = Output::from_connection(sink)?;
+    /// # sent.try_iter().count(); // discard setup messages
+    ///
+    /// output.select_layout(Layout::Note)?;
+    /// assert_eq!(sent.try_recv().unwrap(), [240, 0, 32, 41, 2, 16, 44, 0, 247]);
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    pub fn select_layout(&mut self, layout: Layout) -> Result<(), crate::MidiError> {
+        let layout = match layout {
+            Layout::Note => 0,
+            Layout::Drum => 1,
+            Layout::Fader => 2,
+            Layout::Programmer => 3,
+        };
+        self.send(&[240, 0, 32, 41, 2, 16, 44, layout, 247])
+    }
+
+    /// Enter the fader layout, in which you can place faders on the grid.
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "midir")] {
+    /// # use launchy::OutputDevice as _;
+    /// use launchy::launchpad_pro::{Fader, FaderType, Output, PaletteColor};
+    ///
+    /// let output = Output::guess()?;
+    /// let mut fader_mode = output.enter_fader_mode()?;
+    /// fader_mode.designate_faders(&[
+    ///     Fader::new(0, FaderType::Volume, PaletteColor::RED, 100),
+    ///     Fader::new(1, FaderType::Pan, PaletteColor::BLUE, 64),
+    /// ])?;
+    ///
+    /// // ...
+    ///
+    /// let output = fader_mode.exit()?;
+    /// # }
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    #[must_use = "If you don't use the returned object, the MIDI connection will be dropped immediately"]
+    pub fn enter_fader_mode(self) -> Result<FaderMode<S>, crate::MidiError> {
+        FaderMode::new(self)
+    }
+
+    // param `insert_null_bytes`: whether every packet should be preceeded by a null byte
+    fn send_multiple(
+        &mut self,
+        msg_type_byte: u8,
+        insert_null_bytes: bool,
+        pair_iterator: impl IntoIterator<Item = impl std::borrow::Borrow<(u8, PaletteColor)>>,
+    ) -> Result<(), crate::MidiError> {
+        let pair_iterator = pair_iterator.into_iter();
+
+        let capacity = 8 + 3 * pair_iterator.size_hint().0;
+        let mut bytes = Vec::with_capacity(capacity);
+
+        bytes.extend(&[240, 0, 32, 41, 2, 16, msg_type_byte]);
+        for pair in pair_iterator {
+            let &(specifier, color) = pair.borrow();
+            assert!(color.is_valid());
+            if insert_null_bytes {
+                bytes.push(0)
+            }
+            bytes.extend(&[specifier, color.id()]);
+        }
+        bytes.push(247);
+
+        self.send(&bytes)
+    }
+
+    fn send_multiple_rgb(
+        &mut self,
+        pairs: impl IntoIterator<Item = (u8, RgbColor)>,
+    ) -> Result<(), crate::MidiError> {
+        let pairs = pairs.into_iter();
+
+        let mut bytes = Vec::with_capacity(8 + 4 * pairs.size_hint().0);
+        bytes.extend(&[240, 0, 32, 41, 2, 16, 11]);
+        for (led, color) in pairs {
+            assert!(color.is_valid());
+            bytes.extend(&[led, color.red(), color.green(), color.blue()]);
+        }
+        bytes.push(247);
+
+        self.send(&bytes)
+    }
+
+    // --------------------------------------------------------------------------------------------
+    // Below this point are shorthand function
+    // --------------------------------------------------------------------------------------------
+
+    pub fn light(&mut self, button: Button, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.set_button(button, color, LightMode::Plain)
+    }
+
+    pub fn flash(&mut self, button: Button, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.set_button(button, color, LightMode::Flash)
+    }
+
+    pub fn pulse(&mut self, button: Button, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.set_button(button, color, LightMode::Pulse)
+    }
+
+    pub fn light_column(
+        &mut self,
+        column: u8,
+        color: PaletteColor,
+    ) -> Result<(), crate::MidiError> {
+        self.light_columns([(column, color)])
+    }
+
+    pub fn light_row(&mut self, row: u8, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.light_rows([(row, color)])
+    }
+
+    pub fn light_rgb(&mut self, button: Button, color: RgbColor) -> Result<(), crate::MidiError> {
+        self.light_multiple_rgb([(button, color)])
+    }
+
+    pub fn light_multiple(
+        &mut self,
+        buttons: impl IntoIterator<Item = impl std::borrow::Borrow<(Button, PaletteColor)>>,
+    ) -> Result<(), crate::MidiError> {
+        self.set_buttons(buttons, LightMode::Plain)
+    }
+
+    pub fn flash_multiple(
+        &mut self,
+        buttons: impl IntoIterator<Item = impl std::borrow::Borrow<(Button, PaletteColor)>>,
+    ) -> Result<(), crate::MidiError> {
+        self.set_buttons(buttons, LightMode::Flash)
+    }
+
+    pub fn pulse_multiple(
+        &mut self,
+        buttons: impl IntoIterator<Item = impl std::borrow::Borrow<(Button, PaletteColor)>>,
+    ) -> Result<(), crate::MidiError> {
+        self.set_buttons(buttons, LightMode::Pulse)
+    }
+
+    pub fn clear(&mut self) -> Result<(), crate::MidiError> {
+        self.light_all(PaletteColor::BLACK)
+    }
+}
@@ -0,0 +1,146 @@
+pub use crate::protocols::query::*;
+
+use super::{Button, Layout, Mode, Version};
+
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+/// A Launchpad Pro MK3 input message
+///
+/// The `timestamp` fields hold the MIDI timestamp of the message in microseconds, see
+/// [`MidiClock`](crate::MidiClock).
+///
+/// ```
+/// use launchy::launchpad_pro_mk3::{Button, Input, Message};
+/// use launchy::InputDevice as _;
+///
+/// assert_eq!(
+///     Input::decode_message(0, &[0xB0, 101, 127]),
+///     Message::Press { button: Button::track(0), velocity: 127, timestamp: 0 },
+/// );
+/// assert_eq!(
+///     Input::decode_message(0, &[0xA0, 88, 30]),
+///     Message::Aftertouch { button: Button::grid(7, 0), pressure: 30, timestamp: 0 },
+/// );
+/// ```
+pub enum Message {
+    /// A button was pressed. The function buttons always report a velocity of 127.
+    Press {
+        button: Button,
+        velocity: u8,
+        timestamp: u64,
+    },
+    /// A button was released
+    Release { button: Button, timestamp: u64 },
+    /// The pressure on a pressed pad changed, if the device is set to polyphonic aftertouch
+    Aftertouch {
+        button: Button,
+        pressure: u8,
+        timestamp: u64,
+    },
+    /// The pressure on the pads changed, if the device is set to channel pressure
+    ChannelPressure { pressure: u8, timestamp: u64 },
+    /// Emitted when a fader was changed by the user, in [fader
+    /// mode](super::Output::enter_fader_mode). `number` is the control change number that was
+    /// assigned to the fader.
+    FaderChange {
+        number: u8,
+        value: u8,
+        timestamp: u64,
+    },
+    /// One of the responses to a [device inquiry request](super::Output::request_device_inquiry)
+    ApplicationVersion(Version),
+    /// One of the responses to a [device inquiry request](super::Output::request_device_inquiry)
+    BootloaderVersion(Version),
+    /// The response to a [mode change](super::Output::set_mode) or
+    /// [mode request](super::Output::request_mode)
+    Mode(Mode),
+    /// The response to a [layout change](super::Output::select_layout) or
+    /// [layout request](super::Output::request_layout)
+    Layout(Layout),
+    /// A message that doesn't match the Launchpad Pro MK3 protocol, with its raw bytes
+    Verbatim { bytes: Vec<u8> },
+}
+
+/// The Launchpad Pro MK3 input connection creator.
+pub struct Input;
+
+impl crate::InputDevice for Input {
+    /// Device name.
+    ///
+    /// The Launchpad Pro MK3 has a "LPProMK3 MIDI", a "LPProMK3 DIN" and a "LPProMK3 DAW" port.
+    /// On some platforms, the name is truncated. The DAW port isn't supported.
+    const MIDI_DEVICE_KEYWORD: &'static str = "LPProMK3 MI";
+    const MIDI_CONNECTION_NAME: &'static str = "Launchy Pro Mk3 Input";
+    type Message = Message;
+
+    fn verbatim(bytes: Vec<u8>) -> Message {
+        Message::Verbatim { bytes }
+    }
+
+    fn is_device_port(name: &str) -> bool {
+        super::is_device_port(name)
+    }
+
+    fn try_decode_message(timestamp: u64, data: &[u8]) -> Result<Message, crate::DecodeError> {
+        let decode_button = |id: u8| {
+            Button::from_programmer_id(id)
+                .ok_or_else(|| crate::DecodeError::new(data, format!("unexpected button {}", id)))
+        };
+
+        let msg = match *data {
+            // The grid sends notes, the function buttons send control changes
+            [0x90 | 0xB0, id, velocity] => button_message(decode_button(id)?, velocity, timestamp),
+            [0x80, id, _] => Message::Release {
+                button: decode_button(id)?,
+                timestamp,
+            },
+            [0xA0, id, pressure] => Message::Aftertouch {
+                button: decode_button(id)?,
+                pressure,
+                timestamp,
+            },
+            [0xD0, pressure] => Message::ChannelPressure {
+                pressure,
+                timestamp,
+            },
+            // Faders report on the fifth MIDI channel
+            [0xB4, number, value] => Message::FaderChange {
+                number,
+                value,
+                timestamp,
+            },
+            // Responses to a device inquiry
+            [240, 126, 0, 6, 2, 0, 32, 41, 35, 1, 0, 0, v1, v2, v3, v4, 247] => {
+                Message::ApplicationVersion(Version {
+                    bytes: [v1, v2, v3, v4],
+                })
+            }
+            [240, 126, 0, 6, 2, 0, 32, 41, 35, 17, 0, 0, v1, v2, v3, v4, 247] => {
+                Message::BootloaderVersion(Version {
+                    bytes: [v1, v2, v3, v4],
+                })
+            }
+            [240, 0, 32, 41, 2, 14, 0, layout, page, 0, 247] => Message::Layout(
+                Layout::from_ids(layout, page)
+                    .ok_or_else(|| crate::DecodeError::new(data, "unexpected layout"))?,
+            ),
+            [240, 0, 32, 41, 2, 14, 14, mode, 247] => Message::Mode(match mode {
+                0 => Mode::Live,
+                1 => Mode::Programmer,
+                _ => return Err(crate::DecodeError::new(data, "unexpected mode")),
+            }),
+            _ => return Err(crate::DecodeError::new(data, "unexpected message")),
+        };
+        Ok(msg)
+    }
+}
+
+fn button_message(button: Button, velocity: u8, timestamp: u64) -> Message {
+    match velocity {
+        0 => Message::Release { button, timestamp },
+        velocity => Message::Press {
+            button,
+            velocity,
+            timestamp,
+        },
+    }
+}
@@ -0,0 +1,347 @@
+/*!
+# Launchpad Pro MK3 low-level API
+
+The Launchpad Pro MK3 has an 8x8 grid of velocity and pressure sensitive pads, surrounded by a
+ring of function buttons like on the original Launchpad Pro: 8 buttons each at the top, left and
+right, with the Shift button in the top left corner. Below the grid are two rows of 8 buttons: the
+track buttons, and below those the bottom row.
+
+In absolute coordinates, this makes a 10x11 bounding box. The top right corner and the corners
+of the two bottom rows have no buttons.
+
+Both the Launchpad Pro MK3 and the Launchpad Mini MK3 belong to the MK3 generation, which is why
+they share their color and lighting types.
+*/
+
+mod input;
+pub use input::*;
+
+mod output;
+pub use output::*;
+
+pub use crate::launchpad_pro::Side;
+
+/// Whether a port is the main MIDI port. On Windows, the DAW and the DIN port are named after it,
+/// e.g. "MIDIIN2 (LPProMK3 MIDI)" and "MIDIIN3 (LPProMK3 MIDI)"
+fn is_device_port(name: &str) -> bool {
+    name.contains("LPProMK3 MI") && !crate::midi_io::is_further_windows_port(name, "LPProMK3")
+}
+
+/// A button of the Launchpad Pro MK3
+///
+/// The control buttons in the top and bottom row are indexed from left to right, the control
+/// buttons in the left and right column from top to bottom. [`Side::Bottom`] refers to the
+/// lowest row; the row between it and the grid are the track buttons.
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum Button {
+    GridButton { x: u8, y: u8 },
+    ControlButton { side: Side, index: u8 },
+    TrackButton { index: u8 },
+    Shift,
+}
+
+impl Button {
+    pub const LEFT: Self = Self::ControlButton {
+        side: Side::Top,
+        index: 0,
+    };
+    pub const RIGHT: Self = Self::ControlButton {
+        side: Side::Top,
+        index: 1,
+    };
+    pub const SESSION: Self = Self::ControlButton {
+        side: Side::Top,
+        index: 2,
+    };
+    pub const NOTE: Self = Self::ControlButton {
+        side: Side::Top,
+        index: 3,
+    };
+    pub const CHORD: Self = Self::ControlButton {
+        side: Side::Top,
+        index: 4,
+    };
+    pub const CUSTOM: Self = Self::ControlButton {
+        side: Side::Top,
+        index: 5,
+    };
+    pub const SEQUENCER: Self = Self::ControlButton {
+        side: Side::Top,
+        index: 6,
+    };
+    pub const PROJECTS: Self = Self::ControlButton {
+        side: Side::Top,
+        index: 7,
+    };
+
+    pub const UP: Self = Self::ControlButton {
+        side: Side::Left,
+        index: 0,
+    };
+    pub const DOWN: Self = Self::ControlButton {
+        side: Side::Left,
+        index: 1,
+    };
+    pub const CLEAR: Self = Self::ControlButton {
+        side: Side::Left,
+        index: 2,
+    };
+    pub const DUPLICATE: Self = Self::ControlButton {
+        side: Side::Left,
+        index: 3,
+    };
+    pub const QUANTISE: Self = Self::ControlButton {
+        side: Side::Left,
+        index: 4,
+    };
+    pub const FIXED_LENGTH: Self = Self::ControlButton {
+        side: Side::Left,
+        index: 5,
+    };
+    pub const PLAY: Self = Self::ControlButton {
+        side: Side::Left,
+        index: 6,
+    };
+    pub const RECORD: Self = Self::ControlButton {
+        side: Side::Left,
+        index: 7,
+    };
+
+    pub const RECORD_ARM: Self = Self::ControlButton {
+        side: Side::Bottom,
+        index: 0,
+    };
+    pub const MUTE: Self = Self::ControlButton {
+        side: Side::Bottom,
+        index: 1,
+    };
+    pub const SOLO: Self = Self::ControlButton {
+        side: Side::Bottom,
+        index: 2,
+    };
+    pub const VOLUME: Self = Self::ControlButton {
+        side: Side::Bottom,
+        index: 3,
+    };
+    pub const PAN: Self = Self::ControlButton {
+        side: Side::Bottom,
+        index: 4,
+    };
+    pub const SENDS: Self = Self::ControlButton {
+        side: Side::Bottom,
+        index: 5,
+    };
+    pub const DEVICE: Self = Self::ControlButton {
+        side: Side::Bottom,
+        index: 6,
+    };
+    pub const STOP_CLIP: Self = Self::ControlButton {
+        side: Side::Bottom,
+        index: 7,
+    };
+
+    /// Creates a new GridButton coordinate
+    pub fn grid(x: u8, y: u8) -> Self {
+        Self::GridButton { x, y }
+    }
+
+    /// Creates a new ControlButton coordinate
+    pub fn control(side: Side, index: u8) -> Self {
+        Self::ControlButton { side, index }
+    }
+
+    /// Creates the scene launch button (the right column) with the given index
+    pub fn scene(index: u8) -> Self {
+        Self::ControlButton {
+            side: Side::Right,
+            index,
+        }
+    }
+
+    /// Creates the track button (the row below the grid) with the given index
+    pub fn track(index: u8) -> Self {
+        Self::TrackButton { index }
+    }
+
+    /// Creates a new button out of absolute coordinates, like the ones returned by `abs_x()` and
+    /// `abs_y()`.
+    pub fn from_abs(x: u8, y: u8) -> Self {
+        match (x, y) {
+            (0, 0) => Self::Shift,
+            (1..=8, 0) => Self::control(Side::Top, x - 1),
+            (0, 1..=8) => Self::control(Side::Left, y - 1),
+            (9, 1..=8) => Self::control(Side::Right, y - 1),
+            (1..=8, 1..=8) => Self::grid(x - 1, y - 1),
+            (1..=8, 9) => Self::track(x - 1),
+            (1..=8, 10) => Self::control(Side::Bottom, x - 1),
+            _ => panic!("Unexpected coordinates: ({}, {})", x, y),
+        }
+    }
+
+    /// Returns x coordinate assuming coordinate origin in the Shift button
+    pub fn abs_x(&self) -> u8 {
+        match *self {
+            Self::GridButton { x, .. } => x + 1,
+            Self::ControlButton { side, index } => match side {
+                Side::Top | Side::Bottom => index + 1,
+                Side::Left => 0,
+                Side::Right => 9,
+            },
+            Self::TrackButton { index } => index + 1,
+            Self::Shift => 0,
+        }
+    }
+
+    /// Returns y coordinate assuming coordinate origin in the Shift button
+    pub fn abs_y(&self) -> u8 {
+        match *self {
+            Self::GridButton { y, .. } => y + 1,
+            Self::ControlButton { side, index } => match side {
+                Side::Left | Side::Right => index + 1,
+                Side::Top => 0,
+                Side::Bottom => 10,
+            },
+            Self::TrackButton { .. } => 9,
+            Self::Shift => 0,
+        }
+    }
+
+    /// The LED and note number of this button in the programmer mode layout. The numbers go up by
+    /// 1 to the right and by 10 upwards, starting with the bottom row at 1. The track buttons are
+    /// the exception, they are numbered 101 to 108.
+    pub(crate) fn to_programmer_id(self) -> u8 {
+        match self {
+            Self::GridButton { x, y } => assert!(x <= 7 && y <= 7),
+            Self::ControlButton { index, .. } | Self::TrackButton { index } => assert!(index <= 7),
+            Self::Shift => {}
+        }
+        match self.abs_y() {
+            9 => 100 + self.abs_x(),
+            10 => self.abs_x(),
+            y => 10 * (9 - y) + self.abs_x(),
+        }
+    }
+
+    pub(crate) fn from_programmer_id(id: u8) -> Option<Self> {
+        let (x, y) = match id {
+            101..=108 => (id - 100, 9),
+            1..=8 => (id, 10),
+            10..=98 => (id % 10, 9 - id / 10),
+            _ => return None,
+        };
+        Some(Self::from_abs(x, y))
+    }
+}
+
+#[doc(hidden)]
+pub struct Spec;
+
+impl crate::DeviceSpec for Spec {
+    const BOUNDING_BOX_WIDTH: u32 = 10;
+    const BOUNDING_BOX_HEIGHT: u32 = 11;
+    const COLOR_PRECISION: u16 = 128;
+
+    type Input = Input;
+    type Output<S: crate::MidiSink> = Output<S>;
+
+    fn is_valid(x: u32, y: u32) -> bool {
+        if x > 9 || y > 10 {
+            return false;
+        }
+        // The top right corner and the corners of the two bottom rows have no buttons
+        if x == 9 && y == 0 {
+            return false;
+        }
+        if (x == 0 || x == 9) && y >= 9 {
+            return false;
+        }
+        true
+    }
+
+    fn flush<T: crate::MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
+        changes: &[(u32, u32, (u8, u8, u8))],
+    ) -> Result<(), crate::MidiError> {
+        let strategy = canvas.flush_strategy();
+        let changes = changes.iter().map(|&(x, y, color)| {
+            let style = crate::protocols::mk3::canvas_button_style(color, strategy);
+
+            let button = Button::from_abs(x as u8, y as u8);
+
+            (button, style)
+        });
+        canvas.output.set_buttons(changes)
+    }
+
+    fn supports_effect<T: crate::MidiTransport>(
+        _canvas: &crate::DeviceCanvas<Self, T>,
+        _effect: crate::PadEffect,
+    ) -> bool {
+        true
+    }
+
+    fn flush_effects<T: crate::MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
+        changes: &[crate::EffectChange],
+    ) -> Result<(), crate::MidiError> {
+        let changes = changes.iter().map(|&(x, y, color, effect)| {
+            let style = crate::protocols::mk3::effect_button_style(color, effect);
+
+            let button = Button::from_abs(x as u8, y as u8);
+
+            (button, style)
+        });
+        canvas.output.set_buttons(changes)
+    }
+
+    fn convert_message(msg: Message) -> Option<crate::CanvasMessage> {
+        match msg {
+            Message::Press {
+                button, timestamp, ..
+            } => Some(crate::CanvasMessage::Press {
+                x: button.abs_x() as u32,
+                y: button.abs_y() as u32,
+                timestamp,
+            }),
+            Message::Release { button, timestamp } => Some(crate::CanvasMessage::Release {
+                x: button.abs_x() as u32,
+                y: button.abs_y() as u32,
+                timestamp,
+            }),
+            Message::Aftertouch { .. }
+            | Message::ChannelPressure { .. }
+            | Message::FaderChange { .. }
+            | Message::ApplicationVersion(_)
+            | Message::BootloaderVersion(_)
+            | Message::Mode(_)
+            | Message::Layout(_)
+            | Message::Verbatim { .. } => None,
+        }
+    }
+}
+
+pub type Canvas<'a> = crate::DeviceCanvas<Spec>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daw_and_din_ports_are_excluded_on_every_platform() {
+        assert!(is_device_port(
+            "Launchpad Pro MK3:Launchpad Pro MK3 LPProMK3 MIDI 20:0"
+        ));
+        assert!(is_device_port("LPProMK3 MIDI"));
+        assert!(is_device_port("2- LPProMK3 MIDI"));
+
+        assert!(!is_device_port(
+            "Launchpad Pro MK3:Launchpad Pro MK3 LPProMK3 DIN 20:1"
+        ));
+        assert!(!is_device_port(
+            "Launchpad Pro MK3:Launchpad Pro MK3 LPProMK3 DAW 20:2"
+        ));
+        assert!(!is_device_port("MIDIOUT2 (LPProMK3 MIDI)"));
+        assert!(!is_device_port("MIDIIN3 (LPProMK3 MIDI)"));
+        assert!(!is_device_port("MIDIOUT3 (2- LPProMK3 MIDI)"));
+    }
+}
@@ -0,0 +1,409 @@
+pub use crate::protocols::mk3::{
+    ButtonStyle, Fader, FaderOrientation, FaderType, LightMode, PaletteColor, RgbColor, Version,
+};
+pub use crate::protocols::query::*;
+
+use super::Button;
+use crate::midi_io::DefaultSink;
+use crate::protocols::mk3::{fader_setup_payload, push_led_spec};
+use crate::{MidiSink, OutputDevice};
+
+/// The SysEx header of all Launchpad Pro MK3 specific messages
+const SYSEX_HEADER: [u8; 6] = [240, 0, 32, 41, 2, 14];
+
+/// Whether the device is controlled by Ableton Live or by you
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum Mode {
+    /// The layouts selected with [`Output::select_layout`] are shown
+    Live,
+    /// All LEDs are controlled via MIDI, and the buttons always send the programmer mode layout
+    Programmer,
+}
+
+/// The layouts of the Launchpad Pro MK3. The sequencer has more layouts, which aren't covered
+/// here.
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum Layout {
+    Session,
+    /// Used by [`FaderMode`]
+    Fader,
+    Chord,
+    /// One of the 8 custom modes (0-7)
+    Custom(u8),
+    /// The note or drum mode, depending on the device settings
+    Note,
+    /// The sequencer steps view
+    Sequencer,
+    /// The programmer mode layout
+    Programmer,
+}
+
+impl Layout {
+    fn ids(self) -> [u8; 2] {
+        match self {
+            Self::Session => [0, 0],
+            Self::Fader => [1, 0],
+            Self::Chord => [2, 0],
+            Self::Custom(page) => {
+                assert!(page <= 7);
+                [3, page]
+            }
+            Self::Note => [4, 0],
+            Self::Sequencer => [7, 0],
+            Self::Programmer => [17, 0],
+        }
+    }
+
+    pub(crate) fn from_ids(layout: u8, page: u8) -> Option<Self> {
+        Some(match layout {
+            0 => Self::Session,
+            1 => Self::Fader,
+            2 => Self::Chord,
+            3 if page <= 7 => Self::Custom(page),
+            4 => Self::Note,
+            7 => Self::Sequencer,
+            17 => Self::Programmer,
+            _ => return None,
+        })
+    }
+}
+
+/// This is the handler object for the Launchpad Pro MK3's fader layout, in which you can utilize
+/// the built-in fader functionality. Fader movements are reported as
+/// [super::Message::FaderChange].
+///
+/// For further documentation and examples, see [`Output::enter_fader_mode`].
+pub struct FaderMode<S: MidiSink = DefaultSink> {
+    output: Output<S>,
+}
+
+impl<S: MidiSink> FaderMode<S> {
+    fn new(mut output: Output<S>) -> Result<Self, crate::MidiError> {
+        output.set_mode(Mode::Live)?;
+        output.select_layout(Layout::Fader)?;
+        Ok(Self { output })
+    }
+
+    /// Exit fader mode by transforming this FaderMode object back into a Output object.
+    #[must_use = "You must use the returned object, or the MIDI connection will be dropped"]
+    pub fn exit(mut self) -> Result<Output<S>, crate::MidiError> {
+        self.output.set_mode(Mode::Programmer)?;
+        Ok(self.output)
+    }
+
+    /// Place faders on the screen. The faders' properties are specified using the `&[Fader]` slice.
+    pub fn designate_faders(
+        &mut self,
+        orientation: FaderOrientation,
+        faders: &[Fader],
+    ) -> Result<(), crate::MidiError> {
+        self.output
+            .send_sysex(1, &fader_setup_payload(orientation, faders))
+    }
+
+    /// Moves the fader with the control change number `cc` to a specific `value`
+    pub fn set_fader(&mut self, cc: u8, value: u8) -> Result<(), crate::MidiError> {
+        assert!(cc <= 127);
+        assert!(value <= 127);
+
+        self.output.send(&[0xB4, cc, value])
+    }
+}
+
+/// The object handling any messages _to_ the Launchpad Pro MK3. To get started, initialize with
+/// [`Output::guess`](OutputDevice::guess) and then send messages to your liking. The connection
+/// to the launchpad will get closed when this object goes out of scope.
+///
+/// Upon connecting, the Launchpad Pro MK3 is switched to [`Mode::Programmer`].
+///
+/// ```
+/// # use launchy::{ChannelSink, OutputDevice as _};
+/// use launchy::launchpad_pro_mk3::{Button, ButtonStyle, Output, PaletteColor};
+///
+/// let (sink, sent) = ChannelSink::new();
+/// let mut output = Output::from_connection(sink)?;
+/// assert_eq!(sent.try_recv().unwrap(), [240, 0, 32, 41, 2, 14, 14, 1, 247]);
+///
+/// output.light(Button::RECORD_ARM, PaletteColor::RED)?;
+/// assert_eq!(sent.try_recv().unwrap(), [0xB0, 1, 5]);
+///
+/// output.set_buttons([(Button::track(0), ButtonStyle::pulse(PaletteColor::BLUE))])?;
+/// assert_eq!(sent.try_recv().unwrap(), [240, 0, 32, 41, 2, 14, 3, 2, 101, 45, 247]);
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+///
+/// # Representing color
+/// Like the other MK3 generation Launchpads, the Launchpad Pro MK3 can either show one of the 128
+/// built-in palette colors, or a custom RGB color. Palette color messages are shorter, and
+/// flashing and pulsing only work with palette colors.
+pub struct Output<S: MidiSink = DefaultSink> {
+    connection: S,
+}
+
+impl<S: MidiSink> crate::OutputDevice for Output<S> {
+    const MIDI_CONNECTION_NAME: &'static str = "Launchy Pro Mk3 output";
+    /// Device name.
+    ///
+    /// The Launchpad Pro MK3 has a "LPProMK3 MIDI", a "LPProMK3 DIN" and a "LPProMK3 DAW" port.
+    /// On some platforms, the name is truncated. The DAW port isn't supported.
+    const MIDI_DEVICE_KEYWORD: &'static str = "LPProMK3 MI";
+
+    type Sink = S;
+
+    fn from_connection(connection: S) -> Result<Self, crate::MidiError> {
+        let mut self_ = Self { connection };
+        self_.set_mode(Mode::Programmer)?;
+        Ok(self_)
+    }
+
+    fn is_device_port(name: &str) -> bool {
+        super::is_device_port(name)
+    }
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), crate::MidiError> {
+        self.connection.send(bytes)
+    }
+
+    /// Plain LED messages and fader values only depend on the latest value
+    fn coalescing_key(bytes: &[u8]) -> Option<u32> {
+        match *bytes {
+            [status @ (0x90 | 0xB0 | 0xB4), key, _] => {
+                Some(u32::from_be_bytes([0, 0, status, key]))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<S: MidiSink> Output<S> {
+    fn send_sysex(&mut self, command: u8, payload: &[u8]) -> Result<(), crate::MidiError> {
+        self.send(&[&SYSEX_HEADER[..], &[command], payload, &[247]].concat())
+    }
+
+    /// Set a `button` to a certain `color` with a certain `light_mode`, using a short MIDI
+    /// message on channel 1, 2 or 3.
+    ///
+    /// For example to start a yellow pulse on the Shift button:
+    /// ```no_run
+    /// # use launchy::launchpad_pro_mk3::{PaletteColor, Button, LightMode};
+    /// # let output: launchy::launchpad_pro_mk3::Output = unimplemented!();
+    /// output.set_button(Button::Shift, PaletteColor::YELLOW, LightMode::Pulse)?;
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    pub fn set_button(
+        &mut self,
+        button: Button,
+        color: PaletteColor,
+        light_mode: LightMode,
+    ) -> Result<(), crate::MidiError> {
+        assert!(color.is_valid());
+
+        let type_byte = match button {
+            Button::GridButton { .. } => 0x90,
+            Button::ControlButton { .. } | Button::TrackButton { .. } | Button::Shift => 0xB0,
+        } + match light_mode {
+            LightMode::Plain => 0,
+            LightMode::Flash => 1,
+            LightMode::Pulse => 2,
+        };
+
+        self.send(&[type_byte, button.to_programmer_id(), color.id()])
+    }
+
+    /// Light multiple buttons with varying styles in a single SysEx message. This supports RGB
+    /// colors as well as flashing between 2 colors.
+    pub fn set_buttons<I, T>(&mut self, buttons: I) -> Result<(), crate::MidiError>
+    where
+        I: IntoIterator<Item = T>,
+        T: std::borrow::Borrow<(Button, ButtonStyle)>,
+    {
+        let mut payload = Vec::with_capacity(5 * 106);
+        for pair in buttons {
+            let (button, style) = pair.borrow();
+            push_led_spec(&mut payload, button.to_programmer_id(), style);
+        }
+
+        self.send_sysex(3, &payload)
+    }
+
+    /// Light multiple buttons with varying RGB colors. Forwards to [`Output::set_buttons`].
+    pub fn light_multiple_rgb<I, T>(&mut self, buttons: I) -> Result<(), crate::MidiError>
+    where
+        I: IntoIterator<Item = T>,
+        T: std::borrow::Borrow<(Button, RgbColor)>,
+    {
+        self.set_buttons(
+            buttons
+                .into_iter()
+                .map(|pair| *pair.borrow())
+                .map(|(button, color)| (button, color.into())),
+        )
+    }
+
+    /// Light multiple buttons with varying palette colors. Forwards to [`Output::set_buttons`].
+    pub fn light_multiple(
+        &mut self,
+        buttons: impl IntoIterator<Item = impl std::borrow::Borrow<(Button, PaletteColor)>>,
+    ) -> Result<(), crate::MidiError> {
+        self.set_buttons(
+            buttons
+                .into_iter()
+                .map(|pair| *pair.borrow())
+                .map(|(button, color)| (button, color.into())),
+        )
+    }
+
+    /// Start flashing multiple buttons with varying colors. Forwards to [`Output::set_buttons`].
+    pub fn flash_multiple(
+        &mut self,
+        buttons: impl IntoIterator<Item = impl std::borrow::Borrow<(Button, PaletteColor)>>,
+    ) -> Result<(), crate::MidiError> {
+        self.set_buttons(
+            buttons
+                .into_iter()
+                .map(|pair| *pair.borrow())
+                .map(|(button, color)| (button, ButtonStyle::flash(color))),
+        )
+    }
+
+    /// Start pulsing multiple buttons with varying colors. Forwards to [`Output::set_buttons`].
+    pub fn pulse_multiple(
+        &mut self,
+        buttons: impl IntoIterator<Item = impl std::borrow::Borrow<(Button, PaletteColor)>>,
+    ) -> Result<(), crate::MidiError> {
+        self.set_buttons(
+            buttons
+                .into_iter()
+                .map(|pair| *pair.borrow())
+                .map(|(button, color)| (button, ButtonStyle::pulse(color))),
+        )
+    }
+
+    /// Light all buttons, including the function buttons
+    pub fn light_all(&mut self, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.light_multiple(all_buttons().map(|button| (button, color)))
+    }
+
+    /// Flashing and pulsing happen at 120 BPM by default. To use a different tempo, send 24 of
+    /// these clock ticks per beat.
+    pub fn send_clock_tick(&mut self) -> Result<(), crate::MidiError> {
+        self.send(&[248, 0, 0])
+    }
+
+    /// Requests the Launchpad Pro MK3 to send a device inquiry, which is answered with both a
+    /// [super::Message::ApplicationVersion] and a [super::Message::BootloaderVersion].
+    ///
+    /// In order to be able to receive the Launchpad Pro MK3's response to this request, you must
+    /// have a Launchpad Pro MK3 input object set up.
+    pub fn request_device_inquiry(&mut self, query: DeviceIdQuery) -> Result<(), crate::MidiError> {
+        request_device_inquiry(self, query)
+    }
+
+    /// Switch between Live and Programmer mode. The device responds with a
+    /// [super::Message::Mode].
+    pub fn set_mode(&mut self, mode: Mode) -> Result<(), crate::MidiError> {
+        let mode = match mode {
+            Mode::Live => 0,
+            Mode::Programmer => 1,
+        };
+        self.send_sysex(14, &[mode])
+    }
+
+    /// Requests a [super::Message::Mode]
+    pub fn request_mode(&mut self) -> Result<(), crate::MidiError> {
+        self.send_sysex(14, &[])
+    }
+
+    /// Select a layout, e.g. one of the custom modes. The layouts are only shown in
+    /// [`Mode::Live`]. The device responds with a [super::Message::Layout].
+    ///
+    /// ```
+    /// # use launchy::{ChannelSink, OutputDevice as _};
+    /// use launchy::launchpad_pro_mk3::{Layout, Mode, Output};
+    ///
+    /// let (sink, sent) = ChannelSink::new();
+    /// let mut output = Output::from_connection(sink)?;
+    /// # sent.try_iter().count(); // discard setup messages
+    ///
+    /// output.set_mode(Mode::Live)?;
+    /// output.select_layout(Layout::Custom(2))?;
+    /// assert_eq!(sent.try_iter().last().unwrap(), [240, 0, 32, 41, 2, 14, 0, 3, 2, 0, 247]);
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    pub fn select_layout(&mut self, layout: Layout) -> Result<(), crate::MidiError> {
+        let [layout, page] = layout.ids();
+        self.send_sysex(0, &[layout, page, 0])
+    }
+
+    /// Requests a [super::Message::Layout]
+    pub fn request_layout(&mut self) -> Result<(), crate::MidiError> {
+        self.send_sysex(0, &[])
+    }
+
+    /// Enter the fader layout, in which you can place faders on the grid.
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "midir")] {
+    /// # use launchy::OutputDevice as _;
+    /// use launchy::launchpad_pro_mk3::{Fader, FaderOrientation, FaderType, Output, PaletteColor};
+    ///
+    /// let output = Output::guess()?;
+    /// let mut fader_mode = output.enter_fader_mode()?;
+    /// fader_mode.designate_faders(
+    ///     FaderOrientation::Vertical,
+    ///     &[
+    ///         Fader::new(0, FaderType::Unipolar, 20, PaletteColor::RED),
+    ///         Fader::new(1, FaderType::Bipolar, 21, PaletteColor::BLUE),
+    ///     ],
+    /// )?;
+    ///
+    /// // ...
+    ///
+    /// let output = fader_mode.exit()?;
+    /// # }
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    #[must_use = "If you don't use the returned object, the MIDI connection will be dropped immediately"]
+    pub fn enter_fader_mode(self) -> Result<FaderMode<S>, crate::MidiError> {
+        FaderMode::new(self)
+    }
+
+    // --------------------------------------------------------------------------------------------
+    // Below this point are shorthand function
+    // --------------------------------------------------------------------------------------------
+
+    /// Light a button with a palette color. Identical to
+    /// `set_button(<button>, <color>, LightMode::Plain)`.
+    pub fn light(&mut self, button: Button, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.set_button(button, color, LightMode::Plain)
+    }
+
+    /// Starts a flashing motion between the previously shown color on this button and palette color
+    /// `color`. Identical to `set_button(<button>, <color>, LightMode::Flash)`.
+    pub fn flash(&mut self, button: Button, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.set_button(button, color, LightMode::Flash)
+    }
+
+    /// Start a pulse. Identical to `set_button(<button>, <color>, LightMode::Pulse)`.
+    pub fn pulse(&mut self, button: Button, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.set_button(button, color, LightMode::Pulse)
+    }
+
+    /// Light a single button with an RGB color
+    pub fn light_rgb(&mut self, button: Button, color: RgbColor) -> Result<(), crate::MidiError> {
+        self.light_multiple_rgb([(button, color)])
+    }
+
+    /// Clears all buttons. Equivalent to `output.light_all(PaletteColor::BLACK)`.
+    pub fn clear(&mut self) -> Result<(), crate::MidiError> {
+        self.light_all(PaletteColor::BLACK)
+    }
+}
+
+/// Returns an iterator over all buttons of the device
+fn all_buttons() -> impl Iterator<Item = Button> {
+    (0..10u8)
+        .flat_map(|x| (0..11u8).map(move |y| (x, y)))
+        .filter(|&(x, y)| <super::Spec as crate::DeviceSpec>::is_valid(x.into(), y.into()))
+        .map(|(x, y)| Button::from_abs(x, y))
+}
@@ -3,12 +3,15 @@ pub use crate::protocols::query::*;
 use super::Button;
 
 /// A Launchpad S input message
+///
+/// The `timestamp` fields hold the MIDI timestamp of the message in microseconds, see
+/// [`MidiClock`](crate::MidiClock).
 #[derive(Debug, Eq, PartialEq, Hash, Clone)]
 pub enum Message {
     /// A button was pressed
-    Press { button: Button },
+    Press { button: Button, timestamp: u64 },
     /// A button was released
-    Release { button: Button },
+    Release { button: Button, timestamp: u64 },
     /// Emitted after a text scroll was initiated
     TextEndedOrLooped,
     /// Every once in a while, the device randomly spews out a weird undocumented MIDI message.
@@ -19,13 +22,16 @@ pub enum Message {
     DeviceInquiry(DeviceInquiry),
     /// The response to a [version inquiry request](super::Output::request_version_inquiry)
     VersionInquiry(VersionInquiry),
+    /// A message that doesn't match the Launchpad S protocol, with its raw bytes
+    Verbatim { bytes: Vec<u8> },
 }
 
-fn decode_grid_button(btn: u8) -> Button {
-    Button::GridButton {
-        x: btn % 16,
-        y: btn / 16,
+fn decode_grid_button(btn: u8) -> Option<Button> {
+    let (x, y) = (btn % 16, btn / 16);
+    if x > 8 || y > 7 {
+        return None;
     }
+    Some(Button::GridButton { x, y })
 }
 
 /// The Launchpad S input connection creator.
@@ -36,44 +42,57 @@ impl crate::InputDevice for Input {
     const MIDI_DEVICE_KEYWORD: &'static str = "Launchpad S";
     type Message = Message;
 
-    fn decode_message(_timestamp: u64, data: &[u8]) -> Message {
+    fn verbatim(bytes: Vec<u8>) -> Message {
+        Message::Verbatim { bytes }
+    }
+
+    fn try_decode_message(timestamp: u64, data: &[u8]) -> Result<Message, crate::DecodeError> {
         if let Some(device_inquiry) = parse_device_query(data) {
-            return Message::DeviceInquiry(device_inquiry);
+            return Ok(Message::DeviceInquiry(device_inquiry));
         }
 
         if let Some(version_inquiry) = parse_version_query(data) {
-            return Message::VersionInquiry(version_inquiry);
+            return Ok(Message::VersionInquiry(version_inquiry));
         }
 
         // first byte of a launchpad midi message is the message type
-        match data {
+        let msg = match *data {
             // Note on
-            &[0x90, button, velocity] => {
-                let button = decode_grid_button(button);
+            [0x90, button, velocity] => {
+                let button = decode_grid_button(button).ok_or_else(|| {
+                    crate::DecodeError::new(data, format!("unexpected grid button {}", button))
+                })?;
 
                 match velocity {
-                    0 => Message::Release { button },
-                    127 => Message::Press { button },
-                    other => panic!("Unexpected grid note-on velocity {}", other),
+                    0 => Message::Release { button, timestamp },
+                    127 => Message::Press { button, timestamp },
+                    other => {
+                        let reason = format!("unexpected grid note-on velocity {}", other);
+                        return Err(crate::DecodeError::new(data, reason));
+                    }
                 }
             }
             // Controller change
-            &[0xB0, number @ 104..=111, velocity] => {
+            [0xB0, number @ 104..=111, velocity] => {
                 let button = Button::ControlButton {
                     index: number - 104,
                 };
 
                 match velocity {
-                    0 => Message::Release { button },
-                    127 => Message::Press { button },
-                    other => panic!("Unexpected control note-on velocity {}", other),
+                    0 => Message::Release { button, timestamp },
+                    127 => Message::Press { button, timestamp },
+                    other => {
+                        let reason = format!("unexpected control note-on velocity {}", other);
+                        return Err(crate::DecodeError::new(data, reason));
+                    }
                 }
             }
-            &[0xB0, 0, 3] => Message::TextEndedOrLooped,
-            &[a, b, c] => Message::UnknownShortMessage { bytes: [a, b, c] },
+            [0xB0, 0, 3] => Message::TextEndedOrLooped,
+            [a, b, c] => Message::UnknownShortMessage { bytes: [a, b, c] },
             // YES we have no note off message handler here because it's not used by the launchpad.
             // It sends zero-velocity note-on messages instead.
-            other => panic!("Unexpected midi message: {:?}", other),
-        }
+            _ => return Err(crate::DecodeError::new(data, "unexpected message")),
+        };
+        Ok(msg)
     }
 }
@@ -1,7 +1,6 @@
-use midir::MidiOutputConnection;
-
 use super::Button;
-use crate::OutputDevice;
+use crate::midi_io::DefaultSink;
+use crate::{MidiSink, OutputDevice};
 
 pub use crate::protocols::double_buffering::*;
 pub use crate::protocols::query::*;
@@ -20,25 +19,32 @@ pre-programmed state, while the pads can again be updated invisibly ready for th
 visible buffer can alternatively be configured to swap automatically at 280ms intervals in order
 to configure LEDs to flash.
 */
-pub struct Output {
-    connection: MidiOutputConnection,
+pub struct Output<S: MidiSink = DefaultSink> {
+    connection: S,
 }
 
-impl crate::OutputDevice for Output {
+impl<S: MidiSink> crate::OutputDevice for Output<S> {
     const MIDI_CONNECTION_NAME: &'static str = "Launchy S output";
     const MIDI_DEVICE_KEYWORD: &'static str = "Launchpad S";
+    /// The older Launchpads start dropping messages when they receive faster than classic MIDI
+    const MAX_BYTES_PER_SECOND: u32 = 3125;
+
+    type Sink = S;
 
-    fn from_connection(connection: MidiOutputConnection) -> Result<Self, crate::MidiError> {
+    fn from_connection(connection: S) -> Result<Self, crate::MidiError> {
         Ok(Self { connection })
     }
 
     fn send(&mut self, bytes: &[u8]) -> Result<(), crate::MidiError> {
-        self.connection.send(bytes)?;
-        Ok(())
+        self.connection.send(bytes)
+    }
+
+    fn coalescing_key(bytes: &[u8]) -> Option<u32> {
+        coalescing_key(bytes)
     }
 }
 
-impl Output {
+impl<S: MidiSink> Output<S> {
     /// Updates the state for a single LED, specified by `button`. The color, as well as the double
     /// buffering attributes, are specified in `light_state`.
     pub fn set_button(
@@ -47,20 +53,7 @@ impl Output {
         color: Color,
         d: DoubleBufferingBehavior,
     ) -> Result<(), crate::MidiError> {
-        let light_code = make_color_code(color, d);
-
-        match button {
-            Button::GridButton { x, y } => {
-                let button_code = y * 16 + x;
-                self.send(&[0x90, button_code, light_code])?;
-            }
-            Button::ControlButton { index } => {
-                let button_code = 104 + index;
-                self.send(&[0xB0, button_code, light_code])?;
-            }
-        }
-
-        Ok(())
+        set_button(self, button, color, d)
     }
 
     /// In order to make maximum use of the original Launchpad's slow midi speeds, a rapid LED
@@ -79,11 +72,7 @@ impl Output {
         color2: Color,
         dbb2: DoubleBufferingBehavior,
     ) -> Result<(), crate::MidiError> {
-        self.send(&[
-            0x92,
-            make_color_code(color1, dbb1),
-            make_color_code(color2, dbb2),
-        ])
+        set_button_rapid(self, color1, dbb1, color2, dbb2)
     }
 
     /// Turns on all LEDs to a certain brightness, dictated by the `brightness` parameter. According
@@ -153,13 +142,7 @@ impl Output {
     /// - `updated`: the new updated buffer
     /// - `displayed`: the new displayed buffer
     pub fn control_double_buffering(&mut self, d: DoubleBuffering) -> Result<(), crate::MidiError> {
-        let last_byte = 0b00100000
-            | ((d.copy as u8) << 4)
-            | ((d.flash as u8) << 3)
-            | ((d.edited_buffer as u8) << 2)
-            | d.displayed_buffer as u8;
-
-        self.send(&[0xB0, 0, last_byte])
+        control_double_buffering(self, d)
     }
 
     pub fn request_device_inquiry(&mut self, query: DeviceIdQuery) -> Result<(), crate::MidiError> {
@@ -0,0 +1,242 @@
+pub use crate::protocols::query::*;
+
+use super::Version;
+use super::{AftertouchMode, AftertouchThreshold, Button, Layout, Mode, SleepMode, VelocityCurve};
+use crate::protocols::mk3::{decode_control_button, decode_grid_button};
+
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+/// A Launchpad X input message
+///
+/// The `timestamp` fields hold the MIDI timestamp of the message in microseconds, see
+/// [`MidiClock`](crate::MidiClock).
+///
+/// The device doesn't say which layout a note comes from. Notes and control changes on the first
+/// MIDI channel that match the programmer mode layout are decoded as button presses and releases,
+/// everything else (e.g. from the note mode or custom modes) as [`Message::NoteOn`],
+/// [`Message::NoteOff`] and [`Message::ControlChange`]. Keep in mind that notes from the note
+/// mode and custom modes can overlap with the programmer mode layout.
+///
+/// ```
+/// use launchy::launchpad_x::{Button, Input, Message};
+/// use launchy::InputDevice as _;
+///
+/// assert_eq!(
+///     Input::decode_message(0, &[0xA0, 81, 42]),
+///     Message::Aftertouch { button: Button::grid(0, 0), pressure: 42, timestamp: 0 },
+/// );
+/// assert_eq!(
+///     Input::decode_message(0, &[0x92, 36, 100]),
+///     Message::NoteOn { channel: 2, note: 36, velocity: 100, timestamp: 0 },
+/// );
+/// ```
+pub enum Message {
+    /// A button was pressed. The control buttons always report a velocity of 127.
+    Press {
+        button: Button,
+        velocity: u8,
+        timestamp: u64,
+    },
+    /// A button was released
+    Release { button: Button, timestamp: u64 },
+    /// The pressure on a pressed pad changed, in polyphonic aftertouch mode
+    Aftertouch {
+        button: Button,
+        pressure: u8,
+        timestamp: u64,
+    },
+    /// The pressure on the pads changed, in channel aftertouch mode
+    ChannelPressure { pressure: u8, timestamp: u64 },
+    /// A note that doesn't belong to the programmer mode layout was started. `channel` is
+    /// zero-based.
+    NoteOn {
+        channel: u8,
+        note: u8,
+        velocity: u8,
+        timestamp: u64,
+    },
+    /// A note that doesn't belong to the programmer mode layout was stopped. `channel` is
+    /// zero-based.
+    NoteOff {
+        channel: u8,
+        note: u8,
+        timestamp: u64,
+    },
+    /// A control change that doesn't belong to the programmer mode layout. `channel` is
+    /// zero-based.
+    ControlChange {
+        channel: u8,
+        number: u8,
+        value: u8,
+        timestamp: u64,
+    },
+    /// One of the responses to a [device inquiry request](super::Output::request_device_inquiry)
+    ApplicationVersion(Version),
+    /// One of the responses to a [device inquiry request](super::Output::request_device_inquiry)
+    BootloaderVersion(Version),
+    /// The response to a [mode change](super::Output::set_mode) or
+    /// [mode request](super::Output::request_mode)
+    Mode(Mode),
+    /// The response to a [layout request](super::Output::request_layout)
+    Layout(Layout),
+    /// The response to a [sleep mode request](super::Output::request_sleep_mode)
+    SleepMode(SleepMode),
+    /// The response to a [brightness request](super::Output::request_brightness)
+    Brightness(u8),
+    /// The response to a [velocity curve request](super::Output::request_velocity_curve)
+    VelocityCurve(VelocityCurve),
+    /// The response to an [aftertouch configuration request](super::Output::request_aftertouch)
+    AftertouchConfig {
+        mode: AftertouchMode,
+        threshold: AftertouchThreshold,
+    },
+    /// A message that doesn't match the Launchpad X protocol, with its raw bytes
+    Verbatim { bytes: Vec<u8> },
+}
+
+/// The Launchpad X input connection creator.
+pub struct Input;
+
+impl crate::InputDevice for Input {
+    /// Device name.
+    ///
+    /// The Launchpad X has a "LPX MIDI" and a "LPX DAW" port. On some platforms, the name is
+    /// truncated. The DAW port isn't supported.
+    const MIDI_DEVICE_KEYWORD: &'static str = "LPX MI";
+    const MIDI_CONNECTION_NAME: &'static str = "Launchy X Input";
+    type Message = Message;
+
+    fn verbatim(bytes: Vec<u8>) -> Message {
+        Message::Verbatim { bytes }
+    }
+
+    fn is_device_port(name: &str) -> bool {
+        super::is_device_port(name)
+    }
+
+    fn try_decode_message(timestamp: u64, data: &[u8]) -> Result<Message, crate::DecodeError> {
+        let unexpected = |reason: &str| crate::DecodeError::new(data, reason);
+
+        let msg = match *data {
+            [0x90, note, velocity] => match decode_grid_button(note) {
+                Some(button) if velocity == 0 => Message::Release { button, timestamp },
+                Some(button) => Message::Press {
+                    button,
+                    velocity,
+                    timestamp,
+                },
+                None => note_message(0, note, velocity, timestamp),
+            },
+            [0x80, note, _] => match decode_grid_button(note) {
+                Some(button) => Message::Release { button, timestamp },
+                None => note_message(0, note, 0, timestamp),
+            },
+            [0xA0, note, pressure] => {
+                let button = decode_grid_button(note)
+                    .ok_or_else(|| unexpected("aftertouch for unexpected button"))?;
+                Message::Aftertouch {
+                    button,
+                    pressure,
+                    timestamp,
+                }
+            }
+            [0xD0, pressure] => Message::ChannelPressure {
+                pressure,
+                timestamp,
+            },
+            [0xB0, number, value] => match decode_control_button(number) {
+                Some(button) if value == 0 => Message::Release { button, timestamp },
+                Some(button) => Message::Press {
+                    button,
+                    velocity: value,
+                    timestamp,
+                },
+                None => Message::ControlChange {
+                    channel: 0,
+                    number,
+                    value,
+                    timestamp,
+                },
+            },
+            [status @ 0x91..=0x9F, note, velocity] => {
+                note_message(status & 0x0F, note, velocity, timestamp)
+            }
+            [status @ 0x81..=0x8F, note, _] => note_message(status & 0x0F, note, 0, timestamp),
+            [status @ 0xB1..=0xBF, number, value] => Message::ControlChange {
+                channel: status & 0x0F,
+                number,
+                value,
+                timestamp,
+            },
+            // Responses to a device inquiry
+            [240, 126, 0, 6, 2, 0, 32, 41, 3, 1, 0, 0, v1, v2, v3, v4, 247] => {
+                Message::ApplicationVersion(Version {
+                    bytes: [v1, v2, v3, v4],
+                })
+            }
+            [240, 126, 0, 6, 2, 0, 32, 41, 3, 17, 0, 0, v1, v2, v3, v4, 247] => {
+                Message::BootloaderVersion(Version {
+                    bytes: [v1, v2, v3, v4],
+                })
+            }
+            [240, 0, 32, 41, 2, 12, command, ref payload @ .., 247] => {
+                decode_sysex(command, payload).ok_or_else(|| unexpected("unexpected SysEx"))?
+            }
+            _ => return Err(unexpected("unexpected message")),
+        };
+        Ok(msg)
+    }
+}
+
+fn note_message(channel: u8, note: u8, velocity: u8, timestamp: u64) -> Message {
+    match velocity {
+        0 => Message::NoteOff {
+            channel,
+            note,
+            timestamp,
+        },
+        velocity => Message::NoteOn {
+            channel,
+            note,
+            velocity,
+            timestamp,
+        },
+    }
+}
+
+fn decode_sysex(command: u8, payload: &[u8]) -> Option<Message> {
+    Some(match (command, payload) {
+        (0, &[layout]) => Message::Layout(Layout::from_id(layout)?),
+        (4, &[curve, fixed_velocity]) => Message::VelocityCurve(match curve {
+            0 => VelocityCurve::Low,
+            1 => VelocityCurve::Medium,
+            2 => VelocityCurve::High,
+            3 => VelocityCurve::Fixed(fixed_velocity),
+            _ => return None,
+        }),
+        (8, &[brightness]) => Message::Brightness(brightness),
+        (9, &[sleep]) => Message::SleepMode(match sleep {
+            0 => SleepMode::Sleep,
+            _ => SleepMode::Wake,
+        }),
+        (11, &[mode, threshold]) => Message::AftertouchConfig {
+            mode: match mode {
+                0 => AftertouchMode::Polyphonic,
+                1 => AftertouchMode::Channel,
+                2 => AftertouchMode::Off,
+                _ => return None,
+            },
+            threshold: match threshold {
+                0 => AftertouchThreshold::Low,
+                1 => AftertouchThreshold::Medium,
+                2 => AftertouchThreshold::High,
+                _ => return None,
+            },
+        },
+        (14, &[mode]) => Message::Mode(match mode {
+            0 => Mode::Live,
+            1 => Mode::Programmer,
+            _ => return None,
+        }),
+        _ => return None,
+    })
+}
@@ -0,0 +1,133 @@
+/*!
+# Launchpad X low-level API
+
+The Launchpad X has the same 9x9 arrangement as the Launchpad Mini MK3: an 8x8 grid of
+velocity and pressure sensitive pads, a row of 8 control buttons at the top and a column of 8
+scene launch buttons at the right. Like with the Mini MK3, `launchy` considers the right-hand side
+buttons to be part of the grid, so the grid has a size of 9x8. The top right corner is the logo
+LED, which can be lit but not pressed.
+
+Both devices speak the same protocol apart from a different device id, which is why they share
+their color and lighting types.
+*/
+
+mod input;
+pub use input::*;
+
+mod output;
+pub use output::*;
+
+pub use crate::protocols::Button80 as Button;
+
+/// Whether a port is the main MIDI port. On Windows, the DAW port is named after it, e.g.
+/// "MIDIIN2 (LPX MIDI)"
+fn is_device_port(name: &str) -> bool {
+    name.contains("LPX MI") && !crate::midi_io::is_further_windows_port(name, "LPX")
+}
+
+#[doc(hidden)]
+pub struct Spec;
+
+impl crate::DeviceSpec for Spec {
+    const BOUNDING_BOX_WIDTH: u32 = 9;
+    const BOUNDING_BOX_HEIGHT: u32 = 9;
+    const COLOR_PRECISION: u16 = 128;
+
+    type Input = Input;
+    type Output<S: crate::MidiSink> = Output<S>;
+
+    fn is_valid(x: u32, y: u32) -> bool {
+        if x > 8 || y > 8 {
+            return false;
+        }
+        if x == 8 && y == 0 {
+            return false;
+        }
+        true
+    }
+
+    fn flush<T: crate::MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
+        changes: &[(u32, u32, (u8, u8, u8))],
+    ) -> Result<(), crate::MidiError> {
+        let strategy = canvas.flush_strategy();
+        let changes = changes.iter().map(|&(x, y, color)| {
+            let style = crate::protocols::mk3::canvas_button_style(color, strategy);
+
+            let button = Button::from_abs(x as u8, y as u8);
+
+            (button, style)
+        });
+        canvas.output.set_buttons(changes)
+    }
+
+    fn supports_effect<T: crate::MidiTransport>(
+        _canvas: &crate::DeviceCanvas<Self, T>,
+        _effect: crate::PadEffect,
+    ) -> bool {
+        true
+    }
+
+    fn flush_effects<T: crate::MidiTransport>(
+        canvas: &mut crate::DeviceCanvas<Self, T>,
+        changes: &[crate::EffectChange],
+    ) -> Result<(), crate::MidiError> {
+        let changes = changes.iter().map(|&(x, y, color, effect)| {
+            let style = crate::protocols::mk3::effect_button_style(color, effect);
+
+            let button = Button::from_abs(x as u8, y as u8);
+
+            (button, style)
+        });
+        canvas.output.set_buttons(changes)
+    }
+
+    fn convert_message(msg: Message) -> Option<crate::CanvasMessage> {
+        match msg {
+            Message::Press {
+                button, timestamp, ..
+            } => Some(crate::CanvasMessage::Press {
+                x: button.abs_x() as u32,
+                y: button.abs_y() as u32,
+                timestamp,
+            }),
+            Message::Release { button, timestamp } => Some(crate::CanvasMessage::Release {
+                x: button.abs_x() as u32,
+                y: button.abs_y() as u32,
+                timestamp,
+            }),
+            Message::Aftertouch { .. }
+            | Message::ChannelPressure { .. }
+            | Message::NoteOn { .. }
+            | Message::NoteOff { .. }
+            | Message::ControlChange { .. }
+            | Message::ApplicationVersion(_)
+            | Message::BootloaderVersion(_)
+            | Message::Mode(_)
+            | Message::Layout(_)
+            | Message::SleepMode(_)
+            | Message::Brightness(_)
+            | Message::VelocityCurve(_)
+            | Message::AftertouchConfig { .. }
+            | Message::Verbatim { .. } => None,
+        }
+    }
+}
+
+pub type Canvas<'a> = crate::DeviceCanvas<Spec>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daw_port_is_excluded_on_every_platform() {
+        assert!(is_device_port("Launchpad X:Launchpad X LPX MIDI 20:1"));
+        assert!(is_device_port("LPX MIDI"));
+        assert!(is_device_port("2- LPX MIDI"));
+
+        assert!(!is_device_port("Launchpad X:Launchpad X LPX DAW 20:0"));
+        assert!(!is_device_port("MIDIIN2 (LPX MIDI)"));
+        assert!(!is_device_port("MIDIOUT2 (2- LPX MIDI)"));
+    }
+}
@@ -0,0 +1,543 @@
+pub use crate::protocols::mk3::*;
+pub use crate::protocols::query::*;
+
+use super::Button;
+use crate::midi_io::DefaultSink;
+use crate::protocols::mk3::{encode_button, push_led_spec};
+use crate::{MidiSink, OutputDevice};
+
+/// The SysEx header of all Launchpad X specific messages
+const SYSEX_HEADER: [u8; 6] = [240, 0, 32, 41, 2, 12];
+
+/// The LED index of the Novation logo in the top right corner
+const LOGO: u8 = 99;
+
+/// Whether the device is controlled by Ableton Live or by you
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum Mode {
+    /// Reserved for Ableton Live, shouldn't be used here
+    Live = 0,
+    /// All LEDs are controlled via MIDI, and the pads always send the programmer mode layout
+    Programmer = 1,
+}
+
+/// The layouts that can be selected in [`Mode::Live`]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum Layout {
+    /// Only available when a DAW is connected to the DAW port
+    Session,
+    /// The pads play notes, according to the note mode settings of the device
+    Note,
+    /// Custom mode 1, a drum rack by default
+    Custom1,
+    /// Custom mode 2, keys by default
+    Custom2,
+    /// Custom mode 3, a lighting mode by default
+    Custom3,
+    /// Custom mode 4, a lighting mode by default
+    Custom4,
+    /// Only available when a DAW is connected to the DAW port
+    DawFaders,
+    /// The programmer mode layout
+    Programmer,
+}
+
+impl Layout {
+    fn id(self) -> u8 {
+        match self {
+            Self::Session => 0,
+            Self::Note => 1,
+            Self::Custom1 => 4,
+            Self::Custom2 => 5,
+            Self::Custom3 => 6,
+            Self::Custom4 => 7,
+            Self::DawFaders => 13,
+            Self::Programmer => 127,
+        }
+    }
+
+    pub(crate) fn from_id(id: u8) -> Option<Self> {
+        Some(match id {
+            0 => Self::Session,
+            1 => Self::Note,
+            4 => Self::Custom1,
+            5 => Self::Custom2,
+            6 => Self::Custom3,
+            7 => Self::Custom4,
+            13 => Self::DawFaders,
+            127 => Self::Programmer,
+            _ => return None,
+        })
+    }
+}
+
+/// How hard a pad must be hit to produce which note velocity
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum VelocityCurve {
+    Low,
+    Medium,
+    High,
+    /// Every hit has the given velocity (1..128)
+    Fixed(u8),
+}
+
+/// Which kind of pressure messages the pads send
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum AftertouchMode {
+    /// A [`Message::Aftertouch`](super::Message::Aftertouch) per pad
+    Polyphonic = 0,
+    /// A single [`Message::ChannelPressure`](super::Message::ChannelPressure) for all pads
+    Channel = 1,
+    /// No pressure messages
+    Off = 2,
+}
+
+/// How much pressure is needed until aftertouch messages are sent
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum AftertouchThreshold {
+    Low = 0,
+    Medium = 1,
+    High = 2,
+}
+
+/// The object handling any messages _to_ the Launchpad X. To get started, initialize with
+/// [`Output::guess`](OutputDevice::guess) and then send messages to your liking. The connection
+/// to the launchpad will get closed when this object goes out of scope.
+///
+/// Upon connecting, the Launchpad X is switched to [`Mode::Programmer`].
+///
+/// ```
+/// # use launchy::{ChannelSink, OutputDevice as _};
+/// use launchy::launchpad_x::{Button, Output, PaletteColor, RgbColor};
+///
+/// let (sink, sent) = ChannelSink::new();
+/// let mut output = Output::from_connection(sink)?;
+/// assert_eq!(sent.try_recv().unwrap(), [240, 0, 32, 41, 2, 12, 14, 1, 247]);
+///
+/// output.light(Button::grid(0, 0), PaletteColor::RED)?;
+/// assert_eq!(sent.try_recv().unwrap(), [0x90, 81, 5]);
+///
+/// output.light_rgb(Button::UP, RgbColor::new(0, 127, 0))?;
+/// assert_eq!(sent.try_recv().unwrap(), [240, 0, 32, 41, 2, 12, 3, 3, 91, 0, 127, 0, 247]);
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+///
+/// # Representing color
+/// Like the other MK3 generation Launchpads, the Launchpad X can either show one of the 128
+/// built-in palette colors, or a custom RGB color. Palette color messages are shorter, and
+/// flashing and pulsing only work with palette colors.
+pub struct Output<S: MidiSink = DefaultSink> {
+    connection: S,
+}
+
+impl<S: MidiSink> crate::OutputDevice for Output<S> {
+    const MIDI_CONNECTION_NAME: &'static str = "Launchy X output";
+    /// Device name.
+    ///
+    /// The Launchpad X has a "LPX MIDI" and a "LPX DAW" port. On some platforms, the name is
+    /// truncated. The DAW port isn't supported.
+    const MIDI_DEVICE_KEYWORD: &'static str = "LPX MI";
+
+    type Sink = S;
+
+    fn from_connection(connection: S) -> Result<Self, crate::MidiError> {
+        let mut self_ = Self { connection };
+        self_.set_mode(Mode::Programmer)?;
+        Ok(self_)
+    }
+
+    fn is_device_port(name: &str) -> bool {
+        super::is_device_port(name)
+    }
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), crate::MidiError> {
+        self.connection.send(bytes)
+    }
+
+    /// Plain LED messages only depend on the latest color
+    fn coalescing_key(bytes: &[u8]) -> Option<u32> {
+        match *bytes {
+            [status @ (0x90 | 0xB0), key, _] => Some(u32::from_be_bytes([0, 0, status, key])),
+            _ => None,
+        }
+    }
+}
+
+impl<S: MidiSink> Output<S> {
+    fn send_sysex(&mut self, command: u8, payload: &[u8]) -> Result<(), crate::MidiError> {
+        self.send(&[&SYSEX_HEADER[..], &[command], payload, &[247]].concat())
+    }
+
+    /// Set a `button` to a certain `color` with a certain `light_mode`, using a short MIDI
+    /// message on channel 1, 2 or 3.
+    ///
+    /// For example to start a yellow pulse on the leftmost control button:
+    /// ```no_run
+    /// # use launchy::launchpad_x::{PaletteColor, Button, LightMode};
+    /// # let output: launchy::launchpad_x::Output = unimplemented!();
+    /// output.set_button(Button::UP, PaletteColor::YELLOW, LightMode::Pulse)?;
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    pub fn set_button(
+        &mut self,
+        button: Button,
+        color: PaletteColor,
+        light_mode: LightMode,
+    ) -> Result<(), crate::MidiError> {
+        assert!(color.is_valid());
+
+        let type_byte = match button {
+            Button::GridButton { .. } => 0x90,
+            Button::ControlButton { .. } => 0xB0,
+        } + match light_mode {
+            LightMode::Plain => 0,
+            LightMode::Flash => 1,
+            LightMode::Pulse => 2,
+        };
+
+        self.send(&[type_byte, encode_button(button), color.id()])
+    }
+
+    /// Light multiple buttons with varying styles in a single SysEx message. This supports RGB
+    /// colors as well as flashing between 2 colors.
+    ///
+    /// ```no_run
+    /// # use launchy::launchpad_x::{Button, ButtonStyle, PaletteColor, RgbColor};
+    /// # let output: launchy::launchpad_x::Output = unimplemented!();
+    /// output.set_buttons(&[
+    ///     (Button::grid(0, 0), ButtonStyle::rgb(RgbColor::new(0, 0, 127))),
+    ///     (Button::grid(7, 0), ButtonStyle::flash2(PaletteColor::RED, PaletteColor::BLUE)),
+    /// ])?;
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    pub fn set_buttons<I, T>(&mut self, buttons: I) -> Result<(), crate::MidiError>
+    where
+        I: IntoIterator<Item = T>,
+        T: std::borrow::Borrow<(Button, ButtonStyle)>,
+    {
+        let mut payload = Vec::with_capacity(5 * 81);
+        for pair in buttons {
+            let (button, style) = pair.borrow();
+            push_led_spec(&mut payload, encode_button(*button), style);
+        }
+
+        self.send_sysex(3, &payload)
+    }
+
+    /// Light multiple buttons with varying RGB colors. Forwards to [`Output::set_buttons`].
+    pub fn light_multiple_rgb<I, T>(&mut self, buttons: I) -> Result<(), crate::MidiError>
+    where
+        I: IntoIterator<Item = T>,
+        T: std::borrow::Borrow<(Button, RgbColor)>,
+    {
+        self.set_buttons(
+            buttons
+                .into_iter()
+                .map(|pair| *pair.borrow())
+                .map(|(button, color)| (button, color.into())),
+        )
+    }
+
+    /// Light multiple buttons with varying palette colors. Forwards to [`Output::set_buttons`].
+    pub fn light_multiple(
+        &mut self,
+        buttons: impl IntoIterator<Item = impl std::borrow::Borrow<(Button, PaletteColor)>>,
+    ) -> Result<(), crate::MidiError> {
+        self.set_buttons(
+            buttons
+                .into_iter()
+                .map(|pair| *pair.borrow())
+                .map(|(button, color)| (button, color.into())),
+        )
+    }
+
+    /// Start flashing multiple buttons with varying colors. Forwards to [`Output::set_buttons`].
+    pub fn flash_multiple(
+        &mut self,
+        buttons: impl IntoIterator<Item = impl std::borrow::Borrow<(Button, PaletteColor)>>,
+    ) -> Result<(), crate::MidiError> {
+        self.set_buttons(
+            buttons
+                .into_iter()
+                .map(|pair| *pair.borrow())
+                .map(|(button, color)| (button, ButtonStyle::flash(color))),
+        )
+    }
+
+    /// Start pulsing multiple buttons with varying colors. Forwards to [`Output::set_buttons`].
+    pub fn pulse_multiple(
+        &mut self,
+        buttons: impl IntoIterator<Item = impl std::borrow::Borrow<(Button, PaletteColor)>>,
+    ) -> Result<(), crate::MidiError> {
+        self.set_buttons(
+            buttons
+                .into_iter()
+                .map(|pair| *pair.borrow())
+                .map(|(button, color)| (button, ButtonStyle::pulse(color))),
+        )
+    }
+
+    /// Light multiple columns (0-8) with varying colors. This method does not light up the control
+    /// buttons.
+    pub fn light_columns(
+        &mut self,
+        columns: impl IntoIterator<Item = impl std::borrow::Borrow<(u8, PaletteColor)>>,
+    ) -> Result<(), crate::MidiError> {
+        self.set_buttons(
+            columns
+                .into_iter()
+                .map(|pair| *pair.borrow())
+                .flat_map(|(x, color)| column_buttons(x).map(move |button| (button, color.into()))),
+        )
+    }
+
+    /// Light multiple grid rows (0-7) with varying colors, including the scene launch buttons
+    pub fn light_rows(
+        &mut self,
+        rows: impl IntoIterator<Item = impl std::borrow::Borrow<(u8, PaletteColor)>>,
+    ) -> Result<(), crate::MidiError> {
+        self.set_buttons(
+            rows.into_iter()
+                .map(|pair| *pair.borrow())
+                .flat_map(|(y, color)| row_buttons(y).map(move |button| (button, color.into()))),
+        )
+    }
+
+    /// Light all buttons and the logo
+    pub fn light_all(&mut self, color: PaletteColor) -> Result<(), crate::MidiError> {
+        let mut payload = Vec::with_capacity(3 * 81);
+        for row in 1..10 {
+            for column in 1..10 {
+                push_led_spec(&mut payload, row * 10 + column, &color.into());
+            }
+        }
+
+        self.send_sysex(3, &payload)
+    }
+
+    /// Set the style of the Novation logo in the top right corner
+    pub fn set_logo(&mut self, style: impl Into<ButtonStyle>) -> Result<(), crate::MidiError> {
+        let mut payload = Vec::with_capacity(5);
+        push_led_spec(&mut payload, LOGO, &style.into());
+
+        self.send_sysex(3, &payload)
+    }
+
+    /// Flashing and pulsing happen at 120 BPM by default. To use a different tempo, send 24 of
+    /// these clock ticks per beat.
+    pub fn send_clock_tick(&mut self) -> Result<(), crate::MidiError> {
+        self.send(&[248, 0, 0])
+    }
+
+    /// Requests the Launchpad X to send a device inquiry, which is answered with both a
+    /// [super::Message::ApplicationVersion] and a [super::Message::BootloaderVersion].
+    ///
+    /// In order to be able to receive the Launchpad X's response to this request, you must have a
+    /// Launchpad X input object set up.
+    pub fn request_device_inquiry(&mut self, query: DeviceIdQuery) -> Result<(), crate::MidiError> {
+        request_device_inquiry(self, query)
+    }
+
+    /// Switch between Live and Programmer mode. The device responds with a
+    /// [super::Message::Mode].
+    pub fn set_mode(&mut self, mode: Mode) -> Result<(), crate::MidiError> {
+        self.send_sysex(14, &[mode as u8])
+    }
+
+    /// Requests a [super::Message::Mode]
+    pub fn request_mode(&mut self) -> Result<(), crate::MidiError> {
+        self.send_sysex(14, &[])
+    }
+
+    /// Select a layout, e.g. the note mode or one of the custom modes. Note that the layout is
+    /// left when switching to [`Mode::Programmer`].
+    ///
+    /// ```
+    /// # use launchy::{ChannelSink, OutputDevice as _};
+    /// use launchy::launchpad_x::{Layout, Mode, Output};
+    ///
+    /// let (sink, sent) = ChannelSink::new();
+    /// let mut output = Output::from_connection(sink)?;
+    /// # sent.try_iter().count(); // discard setup messages
+    ///
+    /// output.set_mode(Mode::Live)?;
+    /// output.select_layout(Layout::Custom1)?;
+    /// assert_eq!(sent.try_iter().last().unwrap(), [240, 0, 32, 41, 2, 12, 0, 4, 247]);
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    pub fn select_layout(&mut self, layout: Layout) -> Result<(), crate::MidiError> {
+        self.send_sysex(0, &[layout.id()])
+    }
+
+    /// Requests a [super::Message::Layout]
+    pub fn request_layout(&mut self) -> Result<(), crate::MidiError> {
+        self.send_sysex(0, &[])
+    }
+
+    /// Set how hit strength translates into note velocity
+    pub fn set_velocity_curve(&mut self, curve: VelocityCurve) -> Result<(), crate::MidiError> {
+        let payload = match curve {
+            VelocityCurve::Low => [0, 0],
+            VelocityCurve::Medium => [1, 0],
+            VelocityCurve::High => [2, 0],
+            VelocityCurve::Fixed(velocity) => {
+                assert!((1..128).contains(&velocity));
+                [3, velocity]
+            }
+        };
+        self.send_sysex(4, &payload)
+    }
+
+    /// Requests a [super::Message::VelocityCurve]
+    pub fn request_velocity_curve(&mut self) -> Result<(), crate::MidiError> {
+        self.send_sysex(4, &[])
+    }
+
+    /// Configure the pressure sensitivity of the pads
+    pub fn set_aftertouch(
+        &mut self,
+        mode: AftertouchMode,
+        threshold: AftertouchThreshold,
+    ) -> Result<(), crate::MidiError> {
+        self.send_sysex(11, &[mode as u8, threshold as u8])
+    }
+
+    /// Requests a [super::Message::AftertouchConfig]
+    pub fn request_aftertouch(&mut self) -> Result<(), crate::MidiError> {
+        self.send_sysex(11, &[])
+    }
+
+    /// Set the LED brightness on a scale of (0..128) (exclusive).
+    pub fn set_brightness(&mut self, brightness: u8) -> Result<(), crate::MidiError> {
+        assert!((0..128).contains(&brightness));
+        self.send_sysex(8, &[brightness])
+    }
+
+    /// Requests a [super::Message::Brightness]
+    pub fn request_brightness(&mut self) -> Result<(), crate::MidiError> {
+        self.send_sysex(8, &[])
+    }
+
+    pub fn send_sleep(&mut self, sleep_mode: SleepMode) -> Result<(), crate::MidiError> {
+        self.send_sysex(9, &[sleep_mode as u8])
+    }
+
+    /// Requests a [super::Message::SleepMode]
+    pub fn request_sleep_mode(&mut self) -> Result<(), crate::MidiError> {
+        self.send_sysex(9, &[])
+    }
+
+    /// Starts a text scroll across the screen in a palette color. The speed is given in
+    /// pads/second (0..128); values of 64 and above scroll from left to right. An empty text
+    /// changes the attributes of the ongoing scroll instead.
+    ///
+    /// ```no_run
+    /// # use launchy::launchpad_x::PaletteColor;
+    /// # let output: launchy::launchpad_x::Output = unimplemented!();
+    /// output.scroll_text(b"Hello, world!", PaletteColor::BLUE, 16, false)?;
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
+    pub fn scroll_text(
+        &mut self,
+        text: &[u8],
+        color: PaletteColor,
+        speed: u8,
+        should_loop: bool,
+    ) -> Result<(), crate::MidiError> {
+        assert!((0..128).contains(&speed));
+        let payload = [&[should_loop as u8, speed, 0, color.id()][..], text].concat();
+        self.send_sysex(7, &payload)
+    }
+
+    /// Like [`Output::scroll_text`], but in an RGB color
+    pub fn scroll_text_rgb(
+        &mut self,
+        text: &[u8],
+        color: RgbColor,
+        speed: u8,
+        should_loop: bool,
+    ) -> Result<(), crate::MidiError> {
+        assert!((0..128).contains(&speed));
+        let header = [
+            should_loop as u8,
+            speed,
+            1,
+            color.red(),
+            color.green(),
+            color.blue(),
+        ];
+        self.send_sysex(7, &[&header[..], text].concat())
+    }
+
+    /// Stop the ongoing text scroll immediately
+    pub fn stop_scroll(&mut self) -> Result<(), crate::MidiError> {
+        self.send_sysex(7, &[])
+    }
+
+    // --------------------------------------------------------------------------------------------
+    // Below this point are shorthand function
+    // --------------------------------------------------------------------------------------------
+
+    /// Put the Launchpad X to sleep
+    pub fn sleep(&mut self) -> Result<(), crate::MidiError> {
+        self.send_sleep(SleepMode::Sleep)
+    }
+
+    /// Wake the device up from sleep mode
+    pub fn wake(&mut self) -> Result<(), crate::MidiError> {
+        self.send_sleep(SleepMode::Wake)
+    }
+
+    /// Light a button with a palette color. Identical to
+    /// `set_button(<button>, <color>, LightMode::Plain)`.
+    pub fn light(&mut self, button: Button, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.set_button(button, color, LightMode::Plain)
+    }
+
+    /// Starts a flashing motion between the previously shown color on this button and palette color
+    /// `color`. Identical to `set_button(<button>, <color>, LightMode::Flash)`.
+    pub fn flash(&mut self, button: Button, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.set_button(button, color, LightMode::Flash)
+    }
+
+    /// Start a pulse. Identical to `set_button(<button>, <color>, LightMode::Pulse)`.
+    pub fn pulse(&mut self, button: Button, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.set_button(button, color, LightMode::Pulse)
+    }
+
+    /// Light a single column (0-8)
+    pub fn light_column(
+        &mut self,
+        column: u8,
+        color: PaletteColor,
+    ) -> Result<(), crate::MidiError> {
+        self.light_columns([(column, color)])
+    }
+
+    /// Light a single grid row (0-7), including its scene launch button
+    pub fn light_row(&mut self, row: u8, color: PaletteColor) -> Result<(), crate::MidiError> {
+        self.light_rows([(row, color)])
+    }
+
+    /// Light a single button with an RGB color
+    pub fn light_rgb(&mut self, button: Button, color: RgbColor) -> Result<(), crate::MidiError> {
+        self.light_multiple_rgb([(button, color)])
+    }
+
+    /// Clears all buttons and the logo. Equivalent to `output.light_all(PaletteColor::BLACK)`.
+    pub fn clear(&mut self) -> Result<(), crate::MidiError> {
+        self.light_all(PaletteColor::BLACK)
+    }
+}
+
+/// Returns an iterator for all buttons in the given column
+fn column_buttons(x: u8) -> impl Iterator<Item = Button> {
+    (0..8).map(move |y| Button::GridButton { x, y })
+}
+
+/// Returns an iterator for all buttons in the given row
+///
+/// Includes the scene launch button on the right-hand side
+fn row_buttons(y: u8) -> impl Iterator<Item = Button> {
+    (0..9).map(move |x| Button::GridButton { x, y })
+}
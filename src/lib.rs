@@ -9,6 +9,7 @@ LEDs that can be written to, and consequently flushed to the actual hardware. A
 utilities make the Canvas API a pleasure to use.
 
 ```no_run
+# #[cfg(feature = "midir")] {
 use launchy::{CanvasMessage, Color, Canvas as _, MsgPollingWrapper as _};
 
 let (mut canvas, input_poller) = launchy::s::Canvas::guess_polling()?;
@@ -20,6 +21,7 @@ for msg in input_poller.iter() {
     }
     canvas.flush()?;
 }
+# }
 # Ok::<(), launchy::MidiError>(())
 ```
 The above `match` statement could also be written in a more concise way:
@@ -45,6 +47,7 @@ user has fine control over the data that's actually being sent.
 
 ## Using double-buffering to produce a continuous red flash
 ```no_run
+# #[cfg(feature = "midir")] {
 use launchy::{OutputDevice as _};
 use launchy::s::{Color, DoubleBuffering, DoubleBufferingBehavior, Buffer};
 
@@ -74,6 +77,7 @@ output.control_double_buffering(DoubleBuffering {
     edited_buffer: Buffer::A,
     displayed_buffer: Buffer::A,
 });
+# }
 # Ok::<(), launchy::MidiError>(())
 ```
 */
@@ -92,6 +96,25 @@ pub use midi_io::*;
 mod errors;
 pub use errors::*;
 
+#[cfg(feature = "midir")]
+mod discovery;
+#[cfg(feature = "midir")]
+pub use discovery::*;
+
+mod model;
+pub use model::*;
+
+mod paired;
+pub use paired::*;
+
+mod throttle;
+pub use throttle::*;
+
+mod controls;
+pub use controls::*;
+
+pub mod emulator;
+
 pub mod launchpad_s;
 pub use launchpad_s as s;
 
@@ -104,13 +127,26 @@ pub use launchpad_mk2 as mk2;
 pub mod launchpad_mini_mk3;
 pub use launchpad_mini_mk3 as mini_mk3;
 
+pub mod launchpad_x;
+pub use launchpad_x as x;
+
+pub mod launchpad_pro;
+pub use launchpad_pro as pro;
+
+pub mod launchpad_pro_mk3;
+pub use launchpad_pro_mk3 as pro_mk3;
+
 pub mod launchpad_midi_1;
 pub use launchpad_midi_1 as midi1;
 pub mod launch_control;
 pub use launch_control as control;
-/// The MIDI API of the classic Launch Control and the Launch Control XL is identical
-pub use launch_control as launch_control_xl;
-pub use launch_control as control_xl;
+
+pub mod launch_control_xl;
+pub use launch_control_xl as control_xl;
+
+pub mod launchkey_mini_mk3;
+
+pub mod apc_mini;
 
 pub mod prelude {
     pub use crate::canvas::{Canvas, Color, Pad};
@@ -118,4 +154,5 @@ pub mod prelude {
 }
 
 /// Identifier used for e.g. the midi port names etc.
+#[cfg(feature = "midir")]
 const APPLICATION_NAME: &str = "Launchy";
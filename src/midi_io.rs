@@ -1,14 +1,23 @@
+#[cfg(any(feature = "midir", test))]
 use log::debug;
-use midir::{MidiInput, MidiInputConnection, MidiInputPort, MidiOutput, MidiOutputConnection};
+#[cfg(feature = "midir")]
+use midir::{
+    MidiInput, MidiInputConnection, MidiInputPort, MidiOutput, MidiOutputConnection, MidiOutputPort,
+};
 
-fn guess_port<T: midir::MidiIO>(midi_io: &T, keyword: &str) -> Option<T::Port> {
+#[cfg(feature = "midir")]
+fn guess_port<T: midir::MidiIO>(
+    midi_io: &T,
+    keyword: &str,
+    is_device_port: fn(&str) -> bool,
+) -> Option<T::Port> {
     for port in midi_io.ports() {
         let name = match midi_io.port_name(&port) {
             Ok(name) => name,
             Err(_) => continue,
         };
         debug!("Considering MIDI port: '{}'", name);
-        if name.contains(keyword) {
+        if is_device_port(&name) {
             debug!("Found matching MIDI port: '{}'", name);
             return Some(port);
         }
@@ -17,6 +26,424 @@ fn guess_port<T: midir::MidiIO>(midi_io: &T, keyword: &str) -> Option<T::Port> {
     None
 }
 
+/// Splits a name that Windows gives to a further port of a device, like "MIDIOUT2 (LPX MIDI)", into
+/// the number of the port and the name of the device, here 2 and "LPX MIDI". The "2- " prefix of
+/// further devices of the same type is removed from the device name. Windows cuts port names off
+/// after 31 characters, so the closing parenthesis and the end of the device name may be missing.
+pub(crate) fn parse_windows_port_name(name: &str) -> Option<(u32, &str)> {
+    let rest = name
+        .strip_prefix("MIDIIN")
+        .or_else(|| name.strip_prefix("MIDIOUT"))?;
+    let (number, device) = rest.split_once(" (")?;
+    let number = number.parse().ok()?;
+
+    let device = device.strip_suffix(')').unwrap_or(device);
+    let device = match device.split_once("- ") {
+        Some((index, rest)) if index.parse::<u32>().is_ok() => rest,
+        _ => device,
+    };
+    Some((number, device))
+}
+
+/// Whether a port is one of the further ports that Windows names after the main port of a device,
+/// e.g. "MIDIIN2 (LPX MIDI)" for the DAW port of the Launchpad X. `device` is the start of the main
+/// port's name. These ports contain the keyword of the main port, so they have to be ruled out
+/// explicitly.
+pub(crate) fn is_further_windows_port(name: &str, device: &str) -> bool {
+    match parse_windows_port_name(name) {
+        Some((_, name)) => name.starts_with(device),
+        None => false,
+    }
+}
+
+/// Chooses one of the MIDI ports of the system, as an alternative to guessing the port by name.
+/// Used with [`OutputDevice::select`], [`InputDevice::select`] and
+/// [`DeviceCanvas::select`](crate::DeviceCanvas::select).
+///
+/// ```no_run
+/// use launchy::{OutputDevice as _, PortSelector};
+///
+/// // Connect to the second Launchpad MK2
+/// let second = launchy::mk2::Output::select(PortSelector::Nth(1))?;
+/// // Connect to a port by its exact name
+/// let named = launchy::mk2::Output::select(PortSelector::Name("Launchpad MK2 MIDI 1"))?;
+/// // Connect to the first port whose name ends with a certain number
+/// let custom = launchy::mk2::Output::select(PortSelector::Predicate(&|name| name.ends_with("3")))?;
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+#[cfg(feature = "midir")]
+#[derive(Clone, Copy)]
+pub enum PortSelector<'a> {
+    /// The port at this position in the list of all input or output ports of the system
+    Index(usize),
+    /// The port that has exactly this name
+    Name(&'a str),
+    /// The first port whose name fulfills this predicate
+    Predicate(&'a dyn Fn(&str) -> bool),
+    /// The `n`th port (zero-indexed) that belongs to the device type, judging by its name. See
+    /// [`OutputDevice::is_device_port`] and [`InputDevice::is_device_port`].
+    ///
+    /// The operating system may list input and output ports in different orders, so the input and
+    /// output port with the same `n` don't necessarily belong to the same physical device. To
+    /// connect to both ports of the `n`th device, use
+    /// [`DeviceCanvas::guess_nth`](crate::DeviceCanvas::guess_nth).
+    Nth(usize),
+}
+
+#[cfg(feature = "midir")]
+impl std::fmt::Debug for PortSelector<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Index(index) => f.debug_tuple("Index").field(index).finish(),
+            Self::Name(name) => f.debug_tuple("Name").field(name).finish(),
+            Self::Predicate(_) => f.debug_tuple("Predicate").finish(),
+            Self::Nth(n) => f.debug_tuple("Nth").field(n).finish(),
+        }
+    }
+}
+
+#[cfg(feature = "midir")]
+impl PortSelector<'_> {
+    fn describe(&self, keyword: &str) -> String {
+        match self {
+            Self::Index(index) => format!("port with index {}", index),
+            Self::Name(name) => format!("port named {:?}", name),
+            Self::Predicate(_) => "port matching the predicate".to_owned(),
+            Self::Nth(n) => format!("port number {} for {:?}", n, keyword),
+        }
+    }
+}
+
+#[cfg(feature = "midir")]
+fn select_port<T: midir::MidiIO>(
+    midi_io: &T,
+    selector: PortSelector<'_>,
+    keyword: &str,
+    is_device_port: fn(&str) -> bool,
+) -> Result<T::Port, crate::MidiError> {
+    let mut ports = midi_io
+        .ports()
+        .into_iter()
+        .filter_map(|port| Some((midi_io.port_name(&port).ok()?, port)));
+
+    let port = match selector {
+        PortSelector::Index(index) => midi_io.ports().into_iter().nth(index),
+        PortSelector::Name(wanted) => ports.find(|(name, _)| name == wanted).map(|(_, port)| port),
+        PortSelector::Predicate(predicate) => ports
+            .find(|(name, _)| predicate(name))
+            .map(|(_, port)| port),
+        PortSelector::Nth(n) => ports
+            .filter(|(name, _)| is_device_port(name))
+            .nth(n)
+            .map(|(_, port)| port),
+    };
+
+    match port {
+        Some(port) => {
+            debug!("Selected MIDI port {:?}", midi_io.port_name(&port));
+            Ok(port)
+        }
+        None => Err(crate::MidiError::NoMatchingPort {
+            selector: selector.describe(keyword),
+        }),
+    }
+}
+
+/// The names of the input and output ports of every connected device of the given types. See
+/// [`pair_port_names`] for how the ports of a device are found.
+#[cfg(feature = "midir")]
+pub(crate) fn device_port_names<I: InputDevice, O: OutputDevice>(
+) -> Result<Vec<(String, String)>, crate::MidiError> {
+    fn port_names<T: midir::MidiIO>(midi_io: &T, is_device_port: fn(&str) -> bool) -> Vec<String> {
+        midi_io
+            .ports()
+            .iter()
+            .filter_map(|port| midi_io.port_name(port).ok())
+            .filter(|name| is_device_port(name))
+            .collect()
+    }
+
+    let inputs = port_names(&MidiInput::new(crate::APPLICATION_NAME)?, I::is_device_port);
+    let outputs = port_names(
+        &MidiOutput::new(crate::APPLICATION_NAME)?,
+        O::is_device_port,
+    );
+    Ok(pair_port_names(inputs, outputs))
+}
+
+/// Pairs every output port with the input port of the same physical device, in the order of the
+/// output ports. The operating systems give both ports of a device the same name, so that's the
+/// input port with the same name. Windows names the further ports of a device `MIDIIN2 (…)` and
+/// `MIDIOUT2 (…)`, which count as the same name.
+///
+/// Ports without a counterpart of the same name are left out rather than paired with the ports of
+/// another device, which would mix up the buttons and LEDs of different devices.
+#[cfg(any(feature = "midir", test))]
+pub(crate) fn pair_port_names(
+    mut inputs: Vec<String>,
+    outputs: Vec<String>,
+) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    for output in outputs {
+        let matching_input = inputs
+            .iter()
+            .position(|input| input.replacen("MIDIIN", "MIDIOUT", 1) == output);
+        match matching_input {
+            Some(i) => pairs.push((inputs.remove(i), output)),
+            None => debug!("Found no input port for the MIDI output port '{}'", output),
+        }
+    }
+    pairs
+}
+
+/// Something that outgoing MIDI messages can be written to. Every [`OutputDevice`] sends its bytes
+/// through a [`MidiSink`].
+///
+/// The sink used by default is a real [midir](midir::MidiOutputConnection) OS MIDI port. For
+/// testing without hardware, see [`ChannelSink`].
+pub trait MidiSink: Send {
+    /// Send a single MIDI message
+    fn send(&mut self, bytes: &[u8]) -> Result<(), crate::MidiError>;
+
+    /// The monitor of the queue, if this sink queues the messages and sends them in the
+    /// background like [`ThrottledSink`](crate::ThrottledSink) does. `None` by default.
+    fn queue_monitor(&self) -> Option<crate::QueueMonitor> {
+        None
+    }
+}
+
+#[cfg(feature = "midir")]
+impl MidiSink for MidiOutputConnection {
+    fn send(&mut self, bytes: &[u8]) -> Result<(), crate::MidiError> {
+        MidiOutputConnection::send(self, bytes)?;
+        Ok(())
+    }
+}
+
+/// Something that incoming MIDI messages can be received from. Every [`InputDevice`] can be
+/// connected to a [`MidiSource`].
+///
+/// The source used by default is a real OS MIDI port, see [`MidirSource`]. For testing without
+/// hardware, see [`ChannelSource`].
+pub trait MidiSource {
+    /// The connection handle. Messages are delivered to the callback for as long as this handle is
+    /// kept alive
+    type Connection: Send + 'static;
+
+    /// Start listening for messages. The callback is called with a timestamp in microseconds and
+    /// the raw message bytes for every incoming MIDI message.
+    fn connect<F>(
+        self,
+        connection_name: &str,
+        callback: F,
+    ) -> Result<Self::Connection, crate::MidiError>
+    where
+        F: FnMut(u64, &[u8]) + Send + 'static;
+}
+
+/// A MIDI backend, bundling the [`MidiSink`] and [`MidiSource`] types that belong together.
+///
+/// [`DeviceCanvas`](crate::DeviceCanvas) is generic over this trait, so that a canvas can be driven
+/// by something other than real OS MIDI ports.
+pub trait MidiTransport {
+    /// The output connection type
+    type Sink: MidiSink;
+    /// The input connection type
+    type Source: MidiSource;
+}
+
+/// The default [`MidiTransport`], which talks to real OS MIDI ports using [midir](midir). Requires
+/// the `midir` feature, which is enabled by default.
+#[cfg(feature = "midir")]
+pub struct Midir;
+
+#[cfg(feature = "midir")]
+impl MidiTransport for Midir {
+    type Sink = MidiOutputConnection;
+    type Source = MidirSource;
+}
+
+/// An OS MIDI input port that has not been connected yet
+#[cfg(feature = "midir")]
+pub struct MidirSource {
+    midi_input: MidiInput,
+    port: MidiInputPort,
+}
+
+#[cfg(feature = "midir")]
+impl MidirSource {
+    pub fn new(midi_input: MidiInput, port: MidiInputPort) -> Self {
+        Self { midi_input, port }
+    }
+}
+
+#[cfg(feature = "midir")]
+impl MidiSource for MidirSource {
+    type Connection = MidiInputConnection<()>;
+
+    fn connect<F>(
+        self,
+        connection_name: &str,
+        mut callback: F,
+    ) -> Result<Self::Connection, crate::MidiError>
+    where
+        F: FnMut(u64, &[u8]) + Send + 'static,
+    {
+        let midir_callback = move |timestamp: u64, data: &[u8], _: &mut _| {
+            (callback)(timestamp, data);
+        };
+
+        Ok(self
+            .midi_input
+            .connect(&self.port, connection_name, midir_callback, ())?)
+    }
+}
+
+/// The [`MidiSink`] that device outputs and canvases use unless told otherwise: an OS MIDI port, or
+/// a [`ChannelSink`] without the `midir` feature
+#[cfg(feature = "midir")]
+pub(crate) type DefaultSink = MidiOutputConnection;
+#[cfg(not(feature = "midir"))]
+pub(crate) type DefaultSink = ChannelSink;
+
+/// The [`MidiTransport`] that canvases use unless told otherwise, see [`DefaultSink`]
+#[cfg(feature = "midir")]
+pub(crate) type DefaultTransport = Midir;
+#[cfg(not(feature = "midir"))]
+pub(crate) type DefaultTransport = ChannelTransport;
+
+/// The connection that input handlers hold unless told otherwise, see [`DefaultSink`]
+type DefaultInputConnection =
+    <<DefaultTransport as MidiTransport>::Source as MidiSource>::Connection;
+
+/// An in-memory [`MidiTransport`]. Useful to test Launchpad code without a device attached.
+///
+/// See [`ChannelSink`] and [`ChannelSource`].
+pub struct ChannelTransport;
+
+impl MidiTransport for ChannelTransport {
+    type Sink = ChannelSink;
+    type Source = ChannelSource;
+}
+
+/// An in-memory [`MidiSink`]. Every sent message is forwarded into a channel, where it can be
+/// inspected.
+///
+/// ```
+/// # use launchy::{ChannelSink, OutputDevice as _};
+/// let (sink, sent) = ChannelSink::new();
+/// let mut output = launchy::mk2::Output::from_connection(sink)?;
+/// # sent.try_iter().count(); // discard setup messages
+///
+/// output.light(launchy::mk2::Button::UP, launchy::mk2::PaletteColor::RED)?;
+/// assert_eq!(sent.try_recv().unwrap(), [0xB0, 104, 5]);
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+pub struct ChannelSink {
+    sender: std::sync::mpsc::Sender<Vec<u8>>,
+}
+
+impl ChannelSink {
+    /// Create a new sink, plus the receiver where all sent messages will arrive
+    pub fn new() -> (Self, std::sync::mpsc::Receiver<Vec<u8>>) {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        (Self { sender }, receiver)
+    }
+}
+
+impl MidiSink for ChannelSink {
+    fn send(&mut self, bytes: &[u8]) -> Result<(), crate::MidiError> {
+        self.sender
+            .send(bytes.to_vec())
+            .map_err(|_| crate::MidiError::ChannelClosed)
+    }
+}
+
+type SourceCallback = Box<dyn FnMut(u64, &[u8]) + Send>;
+type SharedSourceCallback = std::sync::Arc<std::sync::Mutex<Option<SourceCallback>>>;
+
+/// An in-memory [`MidiSource`]. Messages injected via the accompanying [`ChannelSourceSender`] are
+/// delivered synchronously to whoever is connected to this source.
+///
+/// ```
+/// # use launchy::{ChannelSource, InputDevice as _, MsgPollingWrapper as _};
+/// use launchy::mk2::{Button, Input, Message};
+///
+/// let (source, sender) = ChannelSource::new();
+/// let input = Input::from_source_polling(source)?;
+///
+/// sender.send(0, &[0xB0, 104, 127]);
+/// assert_eq!(input.try_recv(), Some(Message::Press { button: Button::UP, timestamp: 0 }));
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+pub struct ChannelSource {
+    callback: SharedSourceCallback,
+}
+
+impl ChannelSource {
+    /// Create a new source, plus the sender that can be used to inject messages
+    pub fn new() -> (Self, ChannelSourceSender) {
+        let callback = SharedSourceCallback::default();
+        let sender = ChannelSourceSender {
+            callback: callback.clone(),
+        };
+        (Self { callback }, sender)
+    }
+}
+
+impl MidiSource for ChannelSource {
+    type Connection = ChannelSourceConnection;
+
+    fn connect<F>(
+        self,
+        _connection_name: &str,
+        callback: F,
+    ) -> Result<Self::Connection, crate::MidiError>
+    where
+        F: FnMut(u64, &[u8]) + Send + 'static,
+    {
+        *self.callback.lock().unwrap() = Some(Box::new(callback));
+        Ok(ChannelSourceConnection {
+            callback: self.callback,
+        })
+    }
+}
+
+/// Injects messages into a [`ChannelSource`], as if they were sent by a device
+#[derive(Clone)]
+pub struct ChannelSourceSender {
+    callback: SharedSourceCallback,
+}
+
+impl ChannelSourceSender {
+    /// Deliver a message to the connected input. If nobody is connected (yet or anymore), the
+    /// message is discarded - just like a real device would do it.
+    ///
+    /// Returns whether the message was delivered.
+    pub fn send(&self, timestamp: u64, bytes: &[u8]) -> bool {
+        match &mut *self.callback.lock().unwrap() {
+            Some(callback) => {
+                (callback)(timestamp, bytes);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// The connection handle of a [`ChannelSource`]. Disconnects when dropped.
+pub struct ChannelSourceConnection {
+    callback: SharedSourceCallback,
+}
+
+impl Drop for ChannelSourceConnection {
+    fn drop(&mut self) {
+        // take() the callback out before dropping it, so that it's not dropped while locked
+        let callback = self.callback.lock().unwrap().take();
+        drop(callback);
+    }
+}
+
 pub trait OutputDevice
 where
     Self: Sized,
@@ -24,50 +451,149 @@ where
     const MIDI_CONNECTION_NAME: &'static str;
     const MIDI_DEVICE_KEYWORD: &'static str;
 
-    /// Initiate from an existing midir connection.
-    fn from_connection(connection: MidiOutputConnection) -> Result<Self, crate::MidiError>;
+    /// The connection type that MIDI messages are sent through
+    type Sink: MidiSink;
+
+    /// Initiate from an existing connection, for example a midir connection or a [`ChannelSink`].
+    fn from_connection(connection: Self::Sink) -> Result<Self, crate::MidiError>;
+
+    /// Whether the output port with this name belongs to this device type. Used to guess the
+    /// port, and by [`PortSelector::Nth`]. By default, that's every port whose name contains
+    /// [`Self::MIDI_DEVICE_KEYWORD`].
+    fn is_device_port(name: &str) -> bool {
+        name.contains(Self::MIDI_DEVICE_KEYWORD)
+    }
 
     fn send(&mut self, bytes: &[u8]) -> Result<(), crate::MidiError>;
 
-    fn guess() -> Result<Self, crate::MidiError> {
-        debug!(
-            "Attempting to guess output device with keyword: '{}'",
-            Self::MIDI_DEVICE_KEYWORD
-        );
+    /// The number of bytes per second that the device can receive without dropping messages. Used
+    /// by [`ThrottledSink::for_device`](crate::ThrottledSink::for_device). Unlimited by default.
+    /// Must not be 0.
+    const MAX_BYTES_PER_SECOND: u32 = u32::MAX;
+
+    /// If `bytes` is a message that sets an LED to a color independent of its previous state,
+    /// returns a key that identifies the LED. A [`ThrottledSink`](crate::ThrottledSink) drops a
+    /// queued message when a newer message with the same key arrives. By default, no message is
+    /// coalesced.
+    fn coalescing_key(bytes: &[u8]) -> Option<u32> {
+        let _ = bytes;
+        None
+    }
+
+    #[cfg(feature = "midir")]
+    fn guess() -> Result<Self, crate::MidiError>
+    where
+        Self: OutputDevice<Sink = MidiOutputConnection>,
+    {
+        let connection = guess_output_connection::<Self>()?;
+        Self::from_connection(connection)
+    }
+
+    /// Connect to a specific output port
+    #[cfg(feature = "midir")]
+    fn from_port(midi_output: MidiOutput, port: &MidiOutputPort) -> Result<Self, crate::MidiError>
+    where
+        Self: OutputDevice<Sink = MidiOutputConnection>,
+    {
+        let connection = midi_output.connect(port, Self::MIDI_CONNECTION_NAME)?;
+        Self::from_connection(connection)
+    }
+
+    /// Connect to the output port chosen by `selector`. See [`PortSelector`] for an example.
+    #[cfg(feature = "midir")]
+    fn select(selector: PortSelector<'_>) -> Result<Self, crate::MidiError>
+    where
+        Self: OutputDevice<Sink = MidiOutputConnection>,
+    {
         let midi_output = MidiOutput::new(crate::APPLICATION_NAME)?;
-        let port = guess_port(&midi_output, Self::MIDI_DEVICE_KEYWORD).ok_or(
-            crate::MidiError::NoPortFound {
-                keyword: Self::MIDI_DEVICE_KEYWORD,
-            },
+        let port = select_port(
+            &midi_output,
+            selector,
+            Self::MIDI_DEVICE_KEYWORD,
+            Self::is_device_port,
         )?;
-        let connection = midi_output.connect(&port, Self::MIDI_CONNECTION_NAME)?;
-        debug!(
-            "Successfully connected to output device: '{}'",
-            Self::MIDI_DEVICE_KEYWORD
-        );
-        Self::from_connection(connection)
+        Self::from_port(midi_output, &port)
     }
 }
 
+/// Connect to the output port of the device type `O` by guessing the port by its name
+#[cfg(feature = "midir")]
+pub(crate) fn guess_output_connection<O: OutputDevice>(
+) -> Result<MidiOutputConnection, crate::MidiError> {
+    debug!(
+        "Attempting to guess output device with keyword: '{}'",
+        O::MIDI_DEVICE_KEYWORD
+    );
+    let midi_output = MidiOutput::new(crate::APPLICATION_NAME)?;
+    let port = guess_port(&midi_output, O::MIDI_DEVICE_KEYWORD, O::is_device_port).ok_or(
+        crate::MidiError::NoPortFound {
+            keyword: O::MIDI_DEVICE_KEYWORD,
+        },
+    )?;
+    let connection = midi_output.connect(&port, O::MIDI_CONNECTION_NAME)?;
+    debug!(
+        "Successfully connected to output device: '{}'",
+        O::MIDI_DEVICE_KEYWORD
+    );
+    Ok(connection)
+}
+
 /// A handler for a Launchpad input connection. This variant is used when an input connection is
 /// initiated with callback
-pub struct InputDeviceHandler {
-    _connection: MidiInputConnection<()>,
+pub struct InputDeviceHandler<C = DefaultInputConnection> {
+    _connection: C,
 }
 
 /// A handler for a Launchpad input connection that can be polled for new messages. The actual
 /// polling methods are implemented inside [MsgPollingWrapper](crate::MsgPollingWrapper). Look there
 /// for documentation on how to poll messages.
-pub struct InputDeviceHandlerPolling<Message> {
-    _connection: MidiInputConnection<()>,
-    receiver: std::sync::mpsc::Receiver<Message>,
+///
+/// With the `async` feature enabled, this is also a `futures::Stream` of messages, just like the
+/// canvas pollers:
+///
+/// ```
+/// # #[cfg(feature = "async")] {
+/// use futures::StreamExt as _;
+/// use launchy::emulator::{Emulator, Mk2};
+/// use launchy::mk2::{Button, Input, Message};
+/// use launchy::InputDevice as _;
+///
+/// let emulator = std::sync::Arc::new(Emulator::new(Mk2::new()));
+/// let mut input = Input::from_source_polling(emulator.source())?;
+///
+/// let device = emulator.clone();
+/// std::thread::spawn(move || {
+///     std::thread::sleep(std::time::Duration::from_millis(10));
+///     device.press(Button::UP);
+/// });
+///
+/// let msg = futures::executor::block_on(input.next());
+/// assert!(matches!(msg, Some(Message::Press { button: Button::UP, .. })));
+/// # }
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+pub struct InputDeviceHandlerPolling<Message, C = DefaultInputConnection> {
+    _connection: C,
+    receiver: PollerReceiver<Message>,
 }
 
-impl<Message> crate::MsgPollingWrapper for InputDeviceHandlerPolling<Message> {
+impl<Message, C> crate::MsgPollingWrapper for InputDeviceHandlerPolling<Message, C> {
     type Message = Message;
 
     fn receiver(&self) -> &std::sync::mpsc::Receiver<Self::Message> {
-        &self.receiver
+        self.receiver.receiver()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Message, C> futures_core::Stream for InputDeviceHandlerPolling<Message, C> {
+    type Item = Message;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Message>> {
+        self.receiver.poll_next(cx)
     }
 }
 
@@ -76,53 +602,117 @@ pub trait InputDevice {
     const MIDI_DEVICE_KEYWORD: &'static str;
     type Message;
 
-    fn decode_message(timestamp: u64, data: &[u8]) -> Self::Message;
+    /// Decode a message sent by the device. Messages that don't match the device's protocol are
+    /// returned as the `Verbatim` variant of [`Self::Message`], so this function never panics.
+    ///
+    /// ```
+    /// use launchy::mk2::{Input, Message};
+    /// use launchy::InputDevice as _;
+    ///
+    /// // Button presses always have a velocity of 127
+    /// assert_eq!(
+    ///     Input::decode_message(0, &[0x90, 11, 64]),
+    ///     Message::Verbatim { bytes: vec![0x90, 11, 64] },
+    /// );
+    /// ```
+    fn decode_message(timestamp: u64, data: &[u8]) -> Self::Message {
+        Self::try_decode_message(timestamp, data)
+            .unwrap_or_else(|error| Self::verbatim(error.bytes))
+    }
+
+    /// Like [`Self::decode_message`], but returns an error for messages that don't match the
+    /// device's protocol.
+    fn try_decode_message(timestamp: u64, data: &[u8])
+        -> Result<Self::Message, crate::DecodeError>;
 
+    /// Wraps the raw bytes of a message that doesn't match the device's protocol in the
+    /// `Verbatim` variant of [`Self::Message`]
+    fn verbatim(bytes: Vec<u8>) -> Self::Message;
+
+    /// Whether the input port with this name belongs to this device type. Used to guess the port,
+    /// and by [`PortSelector::Nth`]. By default, that's every port whose name contains
+    /// [`Self::MIDI_DEVICE_KEYWORD`].
+    fn is_device_port(name: &str) -> bool {
+        name.contains(Self::MIDI_DEVICE_KEYWORD)
+    }
+
+    /// Connect to any [`MidiSource`], for example a [`MidirSource`] or a [`ChannelSource`].
     #[must_use = "If not saved, the connection will be immediately dropped"]
-    fn from_port<F>(
-        midi_input: MidiInput,
-        port: &MidiInputPort,
+    fn from_source<S, F>(
+        source: S,
         mut user_callback: F,
-    ) -> Result<InputDeviceHandler, crate::MidiError>
+    ) -> Result<InputDeviceHandler<S::Connection>, crate::MidiError>
     where
+        S: MidiSource,
         F: FnMut(Self::Message) + Send + 'static,
     {
-        let midir_callback = move |timestamp: u64, data: &[u8], _: &mut _| {
+        let connection = source.connect(Self::MIDI_CONNECTION_NAME, move |timestamp, data| {
             let msg = Self::decode_message(timestamp, data);
             (user_callback)(msg);
-        };
-
-        let connection =
-            midi_input.connect(port, Self::MIDI_CONNECTION_NAME, midir_callback, ())?;
+        })?;
 
         Ok(InputDeviceHandler {
             _connection: connection,
         })
     }
 
+    /// Like [`Self::from_source`], but in strict mode: messages that don't match the device's
+    /// protocol aren't passed to the callback as `Verbatim` messages. Instead, they are reported
+    /// through the returned receiver.
+    ///
+    /// ```
+    /// use launchy::{ChannelSource, InputDevice as _};
+    ///
+    /// let (source, sender) = ChannelSource::new();
+    /// let (_input, errors) = launchy::mk2::Input::from_source_strict(source, |_msg| {})?;
+    ///
+    /// sender.send(0, &[0x90, 11, 64]);
+    /// assert_eq!(errors.try_recv().unwrap().bytes, vec![0x90, 11, 64]);
+    /// # Ok::<(), launchy::MidiError>(())
+    /// ```
     #[must_use = "If not saved, the connection will be immediately dropped"]
-    fn from_port_polling(
-        midi_input: MidiInput,
-        port: &MidiInputPort,
-    ) -> Result<InputDeviceHandlerPolling<Self::Message>, crate::MidiError>
+    fn from_source_strict<S, F>(
+        source: S,
+        mut user_callback: F,
+    ) -> Result<
+        (
+            InputDeviceHandler<S::Connection>,
+            std::sync::mpsc::Receiver<crate::DecodeError>,
+        ),
+        crate::MidiError,
+    >
     where
-        Self::Message: Send + 'static,
+        S: MidiSource,
+        F: FnMut(Self::Message) + Send + 'static,
     {
-        let (sender, receiver) = std::sync::mpsc::channel();
-        let midir_callback = move |timestamp: u64, data: &[u8], _: &mut _| {
-            let msg = Self::decode_message(timestamp, data);
-            // The following statement can only panic when the receiver was dropped but the
-            // connection is still alive. This can't happen by accident I think, because the
-            // user would have to destructure the input device handler in order to get the
-            // connection and the receiver seperately, in order to drop one but not the other -
-            // but if he does that it's his fault that he gets a panic /shrug
-            sender
-                .send(msg)
-                .expect("Message receiver has hung up (this shouldn't happen)");
+        let (error_sender, error_receiver) = std::sync::mpsc::channel();
+        let connection = source.connect(Self::MIDI_CONNECTION_NAME, move |timestamp, data| {
+            match Self::try_decode_message(timestamp, data) {
+                Ok(msg) => (user_callback)(msg),
+                // If the error receiver was dropped, the user isn't interested in errors anymore
+                Err(error) => drop(error_sender.send(error)),
+            }
+        })?;
+
+        let handler = InputDeviceHandler {
+            _connection: connection,
         };
+        Ok((handler, error_receiver))
+    }
 
-        let connection =
-            midi_input.connect(port, Self::MIDI_CONNECTION_NAME, midir_callback, ())?;
+    /// Like [`Self::from_source`], but returns a handler that can be polled for messages
+    #[must_use = "If not saved, the connection will be immediately dropped"]
+    fn from_source_polling<S>(
+        source: S,
+    ) -> Result<InputDeviceHandlerPolling<Self::Message, S::Connection>, crate::MidiError>
+    where
+        S: MidiSource,
+        Self::Message: Send + 'static,
+    {
+        let (sender, receiver) = poller_channel(None);
+        let connection = source.connect(Self::MIDI_CONNECTION_NAME, move |timestamp, data| {
+            sender.send(Self::decode_message(timestamp, data));
+        })?;
 
         Ok(InputDeviceHandlerPolling {
             _connection: connection,
@@ -130,7 +720,53 @@ pub trait InputDevice {
         })
     }
 
+    #[cfg(feature = "midir")]
+    #[must_use = "If not saved, the connection will be immediately dropped"]
+    fn from_port<F>(
+        midi_input: MidiInput,
+        port: &MidiInputPort,
+        user_callback: F,
+    ) -> Result<InputDeviceHandler, crate::MidiError>
+    where
+        F: FnMut(Self::Message) + Send + 'static,
+    {
+        Self::from_source(MidirSource::new(midi_input, port.clone()), user_callback)
+    }
+
+    /// Like [`Self::from_port`], but in strict mode. See [`Self::from_source_strict`].
+    #[cfg(feature = "midir")]
+    #[must_use = "If not saved, the connection will be immediately dropped"]
+    fn from_port_strict<F>(
+        midi_input: MidiInput,
+        port: &MidiInputPort,
+        user_callback: F,
+    ) -> Result<
+        (
+            InputDeviceHandler,
+            std::sync::mpsc::Receiver<crate::DecodeError>,
+        ),
+        crate::MidiError,
+    >
+    where
+        F: FnMut(Self::Message) + Send + 'static,
+    {
+        Self::from_source_strict(MidirSource::new(midi_input, port.clone()), user_callback)
+    }
+
+    #[cfg(feature = "midir")]
+    #[must_use = "If not saved, the connection will be immediately dropped"]
+    fn from_port_polling(
+        midi_input: MidiInput,
+        port: &MidiInputPort,
+    ) -> Result<InputDeviceHandlerPolling<Self::Message>, crate::MidiError>
+    where
+        Self::Message: Send + 'static,
+    {
+        Self::from_source_polling(MidirSource::new(midi_input, port.clone()))
+    }
+
     /// Search the midi devices and choose the first midi device matching the wanted Launchpad type.
+    #[cfg(feature = "midir")]
     #[must_use = "If not saved, the connection will be immediately dropped"]
     fn guess<F>(user_callback: F) -> Result<InputDeviceHandler, crate::MidiError>
     where
@@ -142,7 +778,7 @@ pub trait InputDevice {
         );
         let midi_input = MidiInput::new(crate::APPLICATION_NAME)?;
 
-        let port = guess_port(&midi_input, Self::MIDI_DEVICE_KEYWORD).ok_or(
+        let port = guess_port(&midi_input, Self::MIDI_DEVICE_KEYWORD, Self::is_device_port).ok_or(
             crate::MidiError::NoPortFound {
                 keyword: Self::MIDI_DEVICE_KEYWORD,
             },
@@ -155,6 +791,7 @@ pub trait InputDevice {
     }
 
     /// Search the midi devices and choose the first midi device matching the wanted Launchpad type.
+    #[cfg(feature = "midir")]
     #[must_use = "If not saved, the connection will be immediately dropped"]
     fn guess_polling() -> Result<InputDeviceHandlerPolling<Self::Message>, crate::MidiError>
     where
@@ -166,7 +803,7 @@ pub trait InputDevice {
         );
         let midi_input = MidiInput::new(crate::APPLICATION_NAME)?;
 
-        let port = guess_port(&midi_input, Self::MIDI_DEVICE_KEYWORD).ok_or(
+        let port = guess_port(&midi_input, Self::MIDI_DEVICE_KEYWORD, Self::is_device_port).ok_or(
             crate::MidiError::NoPortFound {
                 keyword: Self::MIDI_DEVICE_KEYWORD,
             },
@@ -177,6 +814,45 @@ pub trait InputDevice {
         );
         Self::from_port_polling(midi_input, &port)
     }
+
+    /// Connect to the input port chosen by `selector`. See [`PortSelector`] for an example.
+    #[cfg(feature = "midir")]
+    #[must_use = "If not saved, the connection will be immediately dropped"]
+    fn select<F>(
+        selector: PortSelector<'_>,
+        user_callback: F,
+    ) -> Result<InputDeviceHandler, crate::MidiError>
+    where
+        F: FnMut(Self::Message) + Send + 'static,
+    {
+        let midi_input = MidiInput::new(crate::APPLICATION_NAME)?;
+        let port = select_port(
+            &midi_input,
+            selector,
+            Self::MIDI_DEVICE_KEYWORD,
+            Self::is_device_port,
+        )?;
+        Self::from_port(midi_input, &port, user_callback)
+    }
+
+    /// Like [`Self::select`], but returns a poller instead of taking a callback
+    #[cfg(feature = "midir")]
+    #[must_use = "If not saved, the connection will be immediately dropped"]
+    fn select_polling(
+        selector: PortSelector<'_>,
+    ) -> Result<InputDeviceHandlerPolling<Self::Message>, crate::MidiError>
+    where
+        Self::Message: Send + 'static,
+    {
+        let midi_input = MidiInput::new(crate::APPLICATION_NAME)?;
+        let port = select_port(
+            &midi_input,
+            selector,
+            Self::MIDI_DEVICE_KEYWORD,
+            Self::is_device_port,
+        )?;
+        Self::from_port_polling(midi_input, &port)
+    }
 }
 
 /// An iterator that yields canvas input messages for some user-defined time duration. For more
@@ -202,6 +878,158 @@ impl<M> Iterator for IterFor<'_, M> {
     }
 }
 
+/// Converts MIDI timestamps, like the `timestamp` fields of input messages, into [`Instant`]s.
+///
+/// MIDI timestamps count microseconds since a starting point that depends on the MIDI backend, but
+/// stays the same for a connection. The clock estimates that starting point from the timestamps it
+/// is given. Because messages can only arrive late, never early, the estimate gets better with
+/// every message, as long as you convert each timestamp right when its message arrives, e.g. in the
+/// input callback. Use one clock per input connection.
+///
+/// ```
+/// # use launchy::{DeviceCanvas, MidiClock};
+/// use launchy::emulator::{Emulator, EmulatorTransport, Mk2};
+///
+/// let emulator = Emulator::new(Mk2::new());
+/// let (sender, receiver) = std::sync::mpsc::channel();
+/// let mut clock = MidiClock::new();
+/// let _canvas = DeviceCanvas::<launchy::mk2::Spec, EmulatorTransport>::from_connections(
+///     emulator.source(),
+///     emulator.sink(),
+///     move |msg| {
+///         let instant = clock.instant(msg.timestamp());
+///         sender.send((msg, instant)).unwrap();
+///     },
+/// )?;
+///
+/// emulator.press(launchy::mk2::Button::UP);
+/// let (msg, pressed_at) = receiver.recv().unwrap();
+/// assert!(msg.is_press());
+/// assert!(pressed_at <= std::time::Instant::now());
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+///
+/// [`Instant`]: std::time::Instant
+#[derive(Debug, Clone, Default)]
+pub struct MidiClock {
+    epoch: Option<std::time::Instant>,
+}
+
+impl MidiClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The instant at which the message with the given timestamp was received by the MIDI
+    /// backend. Call this as soon as the message arrives.
+    pub fn instant(&mut self, timestamp: u64) -> std::time::Instant {
+        let now = std::time::Instant::now();
+        let offset = std::time::Duration::from_micros(timestamp);
+
+        // The message may have been delayed on its way to us, but never sent in the future. So the
+        // earliest estimate of the starting point is the most accurate one
+        let epoch = match (now.checked_sub(offset), self.epoch) {
+            (Some(estimate), Some(epoch)) => estimate.min(epoch),
+            (Some(estimate), None) => estimate,
+            (None, Some(epoch)) => epoch,
+            (None, None) => return now,
+        };
+        self.epoch = Some(epoch);
+
+        epoch + offset
+    }
+}
+
+enum ChannelSender<M> {
+    Unbounded(std::sync::mpsc::Sender<M>),
+    Bounded(std::sync::mpsc::SyncSender<M>),
+}
+
+/// Slot for the waker of the async task that is waiting for the next message of a poller
+type WakerSlot = std::sync::Arc<std::sync::Mutex<Option<std::task::Waker>>>;
+
+/// The sending half of the channel behind a poller
+pub(crate) struct PollerSender<M> {
+    sender: ChannelSender<M>,
+    waker: WakerSlot,
+}
+
+impl<M> PollerSender<M> {
+    pub(crate) fn send(&self, msg: M) {
+        // This can only panic if the poller was dropped while the connection is still alive, which
+        // can't happen because the poller owns the connection
+        assert!(
+            self.try_send(msg),
+            "Message receiver has hung up (this shouldn't happen)"
+        );
+    }
+
+    /// Like [`Self::send`], but returns false instead of panicking if the receiver was dropped
+    pub(crate) fn try_send(&self, msg: M) -> bool {
+        let sent = match &self.sender {
+            ChannelSender::Unbounded(sender) => sender.send(msg).is_ok(),
+            ChannelSender::Bounded(sender) => sender.send(msg).is_ok(),
+        };
+
+        if let Some(waker) = self.waker.lock().unwrap().take() {
+            waker.wake();
+        }
+        sent
+    }
+}
+
+/// The receiving half of the channel behind a poller. Besides the blocking methods of
+/// [`MsgPollingWrapper`], it can be awaited as a stream when the `async` feature is enabled.
+pub(crate) struct PollerReceiver<M> {
+    receiver: std::sync::mpsc::Receiver<M>,
+    // Only read by the `Stream` implementation
+    #[cfg_attr(not(feature = "async"), allow(dead_code))]
+    waker: WakerSlot,
+}
+
+impl<M> PollerReceiver<M> {
+    pub(crate) fn receiver(&self) -> &std::sync::mpsc::Receiver<M> {
+        &self.receiver
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn poll_next(&self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Option<M>> {
+        use std::sync::mpsc::TryRecvError;
+        use std::task::Poll;
+
+        // Register before checking the channel, so that a message arriving in between isn't missed
+        *self.waker.lock().unwrap() = Some(cx.waker().clone());
+        match self.receiver.try_recv() {
+            Ok(msg) => Poll::Ready(Some(msg)),
+            Err(TryRecvError::Empty) => Poll::Pending,
+            Err(TryRecvError::Disconnected) => Poll::Ready(None),
+        }
+    }
+}
+
+/// Creates the channel behind a poller, optionally with a maximum number of queued messages
+pub(crate) fn poller_channel<M>(bound: Option<usize>) -> (PollerSender<M>, PollerReceiver<M>) {
+    let (sender, receiver) = match bound {
+        None => {
+            let (sender, receiver) = std::sync::mpsc::channel();
+            (ChannelSender::Unbounded(sender), receiver)
+        }
+        Some(bound) => {
+            let (sender, receiver) = std::sync::mpsc::sync_channel(bound);
+            (ChannelSender::Bounded(sender), receiver)
+        }
+    };
+    let waker = WakerSlot::default();
+
+    (
+        PollerSender {
+            sender,
+            waker: waker.clone(),
+        },
+        PollerReceiver { receiver, waker },
+    )
+}
+
 // I have no idea what I'm doing
 pub trait MsgPollingWrapper {
     /// The type of message that is yielded
@@ -298,3 +1126,141 @@ pub trait MsgPollingWrapper {
         self.iter_pending().count()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(input, output)| (input.to_string(), output.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn windows_port_names_are_parsed() {
+        assert_eq!(
+            parse_windows_port_name("MIDIIN2 (LPX MIDI)"),
+            Some((2, "LPX MIDI"))
+        );
+        assert_eq!(
+            parse_windows_port_name("MIDIOUT3 (2- Launchpad Pro)"),
+            Some((3, "Launchpad Pro"))
+        );
+        assert_eq!(
+            parse_windows_port_name("MIDIIN2 (Launchkey Mini MK3 MID"),
+            Some((2, "Launchkey Mini MK3 MID"))
+        );
+        assert_eq!(parse_windows_port_name("LPX MIDI"), None);
+        assert_eq!(parse_windows_port_name("2- Launchpad Pro"), None);
+        assert_eq!(parse_windows_port_name("MIDIIN (LPX MIDI)"), None);
+    }
+
+    #[test]
+    fn further_windows_ports_are_recognized() {
+        assert!(is_further_windows_port("MIDIIN2 (LPX MIDI)", "LPX"));
+        assert!(is_further_windows_port("MIDIOUT2 (2- LPX MIDI)", "LPX"));
+        assert!(!is_further_windows_port("LPX MIDI", "LPX"));
+        assert!(!is_further_windows_port("2- LPX MIDI", "LPX"));
+        assert!(!is_further_windows_port("MIDIOUT2 (Launchpad Pro)", "LPX"));
+    }
+
+    #[test]
+    fn identical_devices_are_paired_by_port_name() {
+        // ALSA tells identical devices apart by the client number at the end. The second device
+        // is listed first here
+        let inputs = names(&[
+            "Launchpad MK2:Launchpad MK2 MIDI 1 24:0",
+            "Launchpad MK2:Launchpad MK2 MIDI 1 20:0",
+        ]);
+        let outputs = names(&[
+            "Launchpad MK2:Launchpad MK2 MIDI 1 20:0",
+            "Launchpad MK2:Launchpad MK2 MIDI 1 24:0",
+        ]);
+        assert_eq!(
+            pair_port_names(inputs, outputs),
+            pairs(&[
+                (
+                    "Launchpad MK2:Launchpad MK2 MIDI 1 20:0",
+                    "Launchpad MK2:Launchpad MK2 MIDI 1 20:0",
+                ),
+                (
+                    "Launchpad MK2:Launchpad MK2 MIDI 1 24:0",
+                    "Launchpad MK2:Launchpad MK2 MIDI 1 24:0",
+                ),
+            ])
+        );
+
+        // Windows numbers the ports of the second device
+        let inputs = names(&["2- Launchpad MK2", "Launchpad MK2"]);
+        let outputs = names(&["Launchpad MK2", "2- Launchpad MK2"]);
+        assert_eq!(
+            pair_port_names(inputs, outputs),
+            pairs(&[
+                ("Launchpad MK2", "Launchpad MK2"),
+                ("2- Launchpad MK2", "2- Launchpad MK2"),
+            ])
+        );
+    }
+
+    #[test]
+    fn windows_second_ports_are_paired() {
+        let inputs = names(&["MIDIIN2 (LPMiniMK3 MIDI)", "LPMiniMK3 MIDI"]);
+        let outputs = names(&["LPMiniMK3 MIDI", "MIDIOUT2 (LPMiniMK3 MIDI)"]);
+        assert_eq!(
+            pair_port_names(inputs, outputs),
+            pairs(&[
+                ("LPMiniMK3 MIDI", "LPMiniMK3 MIDI"),
+                ("MIDIIN2 (LPMiniMK3 MIDI)", "MIDIOUT2 (LPMiniMK3 MIDI)"),
+            ])
+        );
+    }
+
+    #[test]
+    fn output_without_input_is_left_unpaired() {
+        // The input port of the first device is missing
+        let inputs = names(&["2- Launchpad MK2"]);
+        let outputs = names(&["Launchpad MK2", "2- Launchpad MK2"]);
+        assert_eq!(
+            pair_port_names(inputs, outputs),
+            pairs(&[("2- Launchpad MK2", "2- Launchpad MK2")])
+        );
+
+        // The input port of the first device is named differently
+        let inputs = names(&["MIDIIN2 (Launchpad MK2)", "Launchpad MK2 In"]);
+        let outputs = names(&["Launchpad MK2", "MIDIOUT2 (Launchpad MK2)"]);
+        assert_eq!(
+            pair_port_names(inputs, outputs),
+            pairs(&[("MIDIIN2 (Launchpad MK2)", "MIDIOUT2 (Launchpad MK2)")])
+        );
+    }
+
+    #[test]
+    fn input_without_output_is_ignored() {
+        let inputs = names(&[
+            "Launchpad Pro:Launchpad Pro MIDI 1 20:0",
+            "Launchpad Pro:Launchpad Pro MIDI 2 20:1",
+            "Launchpad Pro:Launchpad Pro MIDI 3 20:2",
+        ]);
+        let outputs = names(&["Launchpad Pro:Launchpad Pro MIDI 2 20:1"]);
+        assert_eq!(
+            pair_port_names(inputs, outputs),
+            pairs(&[(
+                "Launchpad Pro:Launchpad Pro MIDI 2 20:1",
+                "Launchpad Pro:Launchpad Pro MIDI 2 20:1",
+            )])
+        );
+
+        let inputs = names(&["MIDIIN3 (Launchpad Pro)", "MIDIIN2 (Launchpad Pro)"]);
+        let outputs = names(&["MIDIOUT2 (Launchpad Pro)"]);
+        assert_eq!(
+            pair_port_names(inputs, outputs),
+            pairs(&[("MIDIIN2 (Launchpad Pro)", "MIDIOUT2 (Launchpad Pro)")])
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use crate::protocols::query::DeviceInquiry;
+
+/// The Launchpad models that can be identified via a device inquiry
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum Model {
+    S,
+    Mini,
+    Mk2,
+    MiniMk3,
+    X,
+    Pro,
+    ProMk3,
+}
+
+impl Model {
+    const ALL: [Model; 7] = [
+        Model::S,
+        Model::Mini,
+        Model::Mk2,
+        Model::MiniMk3,
+        Model::X,
+        Model::Pro,
+        Model::ProMk3,
+    ];
+
+    /// The family code that this model reports in its device inquiry response
+    pub fn family_code(self) -> u16 {
+        match self {
+            Self::S => 0x2000,
+            Self::Mini => 0x3600,
+            Self::Mk2 => 0x6900,
+            // The Mini MK3 bootloader identifies itself as 0x1311
+            Self::MiniMk3 => 0x1301,
+            // The Launchpad X bootloader identifies itself as 0x0311
+            Self::X => 0x0301,
+            Self::Pro => 0x5100,
+            // The Launchpad Pro MK3 bootloader identifies itself as 0x2311
+            Self::ProMk3 => 0x2301,
+        }
+    }
+
+    /// The family member code that this model reports in its device inquiry response
+    pub fn family_member_code(self) -> u16 {
+        0
+    }
+
+    /// Identify the model that sent the given device inquiry response. Returns None for unknown
+    /// devices, and for devices that are in their bootloader.
+    ///
+    /// ```
+    /// # use launchy::Model;
+    /// let inquiry = launchy::mk2::DeviceInquiry {
+    ///     device_id: 0,
+    ///     family_code: 0x6900,
+    ///     family_member_code: 0,
+    ///     firmware_revision: 178,
+    /// };
+    /// assert_eq!(Model::from_device_inquiry(&inquiry), Some(Model::Mk2));
+    /// ```
+    pub fn from_device_inquiry(inquiry: &DeviceInquiry) -> Option<Self> {
+        Self::ALL.iter().copied().find(|model| {
+            model.family_code() == inquiry.family_code
+                && model.family_member_code() == inquiry.family_member_code
+        })
+    }
+
+    /// Whether a MIDI output port is the main port of this model, as decided by the
+    /// [`OutputDevice::is_device_port`](crate::OutputDevice::is_device_port) of its `Output`
+    #[cfg(any(feature = "midir", test))]
+    pub(crate) fn is_device_port(self, output_port_name: &str) -> bool {
+        fn is_spec_port<Spec: crate::DeviceSpec>(name: &str) -> bool {
+            <Spec::Output<crate::midi_io::DefaultSink> as crate::OutputDevice>::is_device_port(name)
+        }
+
+        match self {
+            Self::S => is_spec_port::<crate::launchpad_s::Spec>(output_port_name),
+            Self::Mini => is_spec_port::<crate::launchpad_mini::Spec>(output_port_name),
+            Self::Mk2 => is_spec_port::<crate::launchpad_mk2::Spec>(output_port_name),
+            Self::MiniMk3 => is_spec_port::<crate::launchpad_mini_mk3::Spec>(output_port_name),
+            Self::X => is_spec_port::<crate::launchpad_x::Spec>(output_port_name),
+            Self::Pro => is_spec_port::<crate::launchpad_pro::Spec>(output_port_name),
+            Self::ProMk3 => is_spec_port::<crate::launchpad_pro_mk3::Spec>(output_port_name),
+        }
+    }
+
+    /// The family code and family member code bytes, as sent in a device inquiry response
+    pub(crate) fn device_inquiry_code(self) -> [u8; 4] {
+        let [fc1, fc2] = self.family_code().to_be_bytes();
+        let [fmc1, fmc2] = self.family_member_code().to_be_bytes();
+        [fc1, fc2, fmc1, fmc2]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_main_ports_are_accepted() {
+        // The DAW ports answer device inquiries just like the main ports
+        assert!(Model::MiniMk3
+            .is_device_port("Launchpad Mini MK3:Launchpad Mini MK3 LPMiniMK3 MI 20:1"));
+        assert!(Model::MiniMk3.is_device_port("LPMiniMK3 MIDI"));
+        assert!(!Model::MiniMk3
+            .is_device_port("Launchpad Mini MK3:Launchpad Mini MK3 LPMiniMK3 DA 20:0"));
+        assert!(!Model::MiniMk3.is_device_port("MIDIIN2 (LPMiniMK3 MIDI)"));
+        assert!(Model::X.is_device_port("2- LPX MIDI"));
+        assert!(!Model::X.is_device_port("Launchpad X LPX DAW"));
+        assert!(!Model::X.is_device_port("MIDIOUT2 (2- LPX MIDI)"));
+        assert!(!Model::ProMk3.is_device_port("MIDIOUT3 (LPProMK3 MIDI)"));
+
+        // The Live port of the original Launchpad Pro answers device inquiries too
+        assert!(!Model::Pro.is_device_port("Launchpad Pro Live Port"));
+        assert!(!Model::Pro.is_device_port("Launchpad Pro:Launchpad Pro MIDI 1 20:0"));
+        assert!(!Model::Pro.is_device_port("Launchpad Pro"));
+        assert!(Model::Pro.is_device_port("Launchpad Pro Standalone Port"));
+        assert!(Model::Pro.is_device_port("Launchpad Pro:Launchpad Pro MIDI 2 20:1"));
+        assert!(Model::Pro.is_device_port("MIDIOUT2 (Launchpad Pro)"));
+
+        assert!(Model::Mk2.is_device_port("Launchpad MK2"));
+        assert!(!Model::Mk2.is_device_port("Launchpad Mini"));
+    }
+}
@@ -0,0 +1,816 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "midir")]
+use midir::MidiOutputConnection;
+
+use crate::midi_io::{poller_channel, PollerReceiver, PollerSender};
+use crate::{InputDevice, MidiSink, MidiSource, OutputDevice};
+
+/// What happened when a message was offered to a waiting query
+enum Delivery {
+    /// The message isn't the response the query is waiting for
+    Ignored,
+    /// The message was the response, and was handed to the query
+    Delivered,
+    /// The query isn't waiting anymore, for example because it timed out
+    Abandoned,
+}
+
+type Waiter<M> = Box<dyn FnMut(&M) -> Delivery + Send>;
+type Waiters<M> = Arc<Mutex<Vec<(u64, Waiter<M>)>>>;
+
+/// An input and an output connection to the same device, which makes it possible to send a
+/// request and wait for the device's response to it.
+///
+/// All messages from the device that aren't a response to an ongoing query are passed on as
+/// usual: a `PairedDevice` can be polled for them just like an
+/// [`InputDeviceHandlerPolling`](crate::InputDeviceHandlerPolling). That includes responses that
+/// arrive after their query timed out. The output is available via [`Self::output`].
+///
+/// Which queries are available depends on the device: the Launchpad S, Mini, MK2 and MIDI 1
+/// support `query_device` and `query_version`, the Launchpad Mini MK3 supports `query_version`,
+/// `query_bootloader_version`, `query_brightness` and `query_sleep_state`. With the `async`
+/// feature, every query has an `_async` variant that returns a `QueryFuture` instead of blocking.
+///
+/// ```
+/// use launchy::emulator::{Emulator, MiniMk3};
+/// use launchy::mini_mk3::{Button, Input, Message, Output};
+/// use launchy::{MsgPollingWrapper as _, PairedDevice};
+/// use std::time::Duration;
+///
+/// let emulator = Emulator::new(MiniMk3::new());
+/// let mut device =
+///     PairedDevice::<Input, Output<_>>::from_connections(emulator.source(), emulator.sink())?;
+///
+/// device.output().set_brightness(100)?;
+/// assert_eq!(device.query_brightness(Duration::from_secs(1))?, 100);
+///
+/// // Unrelated messages arrive as usual
+/// emulator.press(Button::grid(0, 0));
+/// assert!(matches!(device.try_recv(), Some(Message::Press { .. })));
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+pub struct PairedDevice<I: InputDevice, O> {
+    output: O,
+    _input: Box<dyn std::any::Any + Send>,
+    waiters: Waiters<I::Message>,
+    next_waiter_id: u64,
+    receiver: PollerReceiver<I::Message>,
+}
+
+impl<I, O> PairedDevice<I, O>
+where
+    I: InputDevice,
+    I::Message: Send + 'static,
+    O: OutputDevice,
+{
+    /// Pair the given input and output connections, which must belong to the same device
+    pub fn from_connections<S: MidiSource>(
+        source: S,
+        sink: O::Sink,
+    ) -> Result<Self, crate::MidiError> {
+        let output = O::from_connection(sink)?;
+        Self::with_input(output, |callback| I::from_source(source, callback))
+    }
+
+    fn with_input<H: Send + 'static>(
+        output: O,
+        connect: impl FnOnce(Box<dyn FnMut(I::Message) + Send>) -> Result<H, crate::MidiError>,
+    ) -> Result<Self, crate::MidiError> {
+        let waiters = Waiters::<I::Message>::default();
+        let (sender, receiver) = poller_channel(None);
+
+        let callback_waiters = waiters.clone();
+        let input = connect(Box::new(move |msg| {
+            // The first query waiting for this message gets it. Queries that aren't waiting
+            // anymore are cleaned up along the way
+            let mut delivered = false;
+            callback_waiters
+                .lock()
+                .unwrap()
+                .retain_mut(|(_, waiter)| match delivered {
+                    true => true,
+                    false => match waiter(&msg) {
+                        Delivery::Ignored => true,
+                        Delivery::Delivered => {
+                            delivered = true;
+                            false
+                        }
+                        Delivery::Abandoned => false,
+                    },
+                });
+
+            if !delivered {
+                sender.send(msg);
+            }
+        }))?;
+
+        Ok(Self {
+            output,
+            _input: Box::new(input),
+            waiters,
+            next_waiter_id: 0,
+            receiver,
+        })
+    }
+
+    /// The output connection of the device
+    pub fn output(&mut self) -> &mut O {
+        &mut self.output
+    }
+
+    /// Register a query that waits for the first message that `extract` returns a value for.
+    /// After the deadline, the query doesn't take messages anymore, so that a late response is
+    /// passed on like any other message.
+    fn wait_for<R: Send + 'static>(
+        &mut self,
+        extract: fn(&I::Message) -> Option<R>,
+        deadline: Option<Instant>,
+    ) -> (u64, PollerReceiver<R>) {
+        let (sender, receiver): (PollerSender<R>, _) = poller_channel(None);
+        let waiter: Waiter<I::Message> = Box::new(move |msg| {
+            // This runs under the waiters lock, like the removal of the waiter after a timeout
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return Delivery::Abandoned;
+            }
+            match extract(msg) {
+                Some(response) => match sender.try_send(response) {
+                    true => Delivery::Delivered,
+                    false => Delivery::Abandoned,
+                },
+                None => Delivery::Ignored,
+            }
+        });
+
+        let id = self.next_waiter_id;
+        self.next_waiter_id += 1;
+        self.waiters.lock().unwrap().push((id, waiter));
+
+        (id, receiver)
+    }
+
+    /// Send a request, and block until the response arrives or the timeout expires
+    fn query<R: Send + 'static>(
+        &mut self,
+        request: impl FnOnce(&mut O) -> Result<(), crate::MidiError>,
+        extract: fn(&I::Message) -> Option<R>,
+        timeout: Duration,
+    ) -> Result<R, crate::MidiError> {
+        // Register before sending, so that a quick response isn't missed
+        let deadline = Instant::now() + timeout;
+        let (id, receiver) = self.wait_for(extract, Some(deadline));
+        if let Err(e) = request(&mut self.output) {
+            remove_waiter(&self.waiters, id);
+            return Err(e);
+        }
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if let Ok(response) = receiver.receiver().recv_timeout(remaining) {
+            return Ok(response);
+        }
+
+        // The response may have been delivered between the timeout and taking the lock. Once the
+        // waiter is removed, nothing is delivered anymore, and later responses are passed on
+        let mut waiters = self.waiters.lock().unwrap();
+        waiters.retain(|&(waiter_id, _)| waiter_id != id);
+        receiver
+            .receiver()
+            .try_recv()
+            .map_err(|_| crate::MidiError::NoResponse)
+    }
+
+    /// Send a request, and return a future that resolves to the response
+    #[cfg(feature = "async")]
+    fn query_async<R: Send + 'static>(
+        &mut self,
+        request: impl FnOnce(&mut O) -> Result<(), crate::MidiError>,
+        extract: fn(&I::Message) -> Option<R>,
+    ) -> QueryFuture<R> {
+        let (id, receiver) = self.wait_for(extract, None);
+        let waiters = self.waiters.clone();
+        let cancel = Box::new(move || remove_waiter(&waiters, id));
+
+        match request(&mut self.output) {
+            Ok(()) => QueryFuture {
+                state: Ok(receiver),
+                cancel: Some(cancel),
+            },
+            Err(e) => {
+                cancel();
+                QueryFuture {
+                    state: Err(Some(e)),
+                    cancel: None,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "midir")]
+impl<I, O> PairedDevice<I, O>
+where
+    I: InputDevice,
+    I::Message: Send + 'static,
+    O: OutputDevice<Sink = MidiOutputConnection>,
+{
+    /// Pair the first input and output ports of this device type, found by guessing the MIDI
+    /// ports by their name
+    pub fn guess() -> Result<Self, crate::MidiError> {
+        let output = O::guess()?;
+        Self::with_input(output, I::guess)
+    }
+}
+
+fn remove_waiter<M>(waiters: &Waiters<M>, id: u64) {
+    waiters
+        .lock()
+        .unwrap()
+        .retain(|&(waiter_id, _)| waiter_id != id);
+}
+
+impl<I: InputDevice, O> crate::MsgPollingWrapper for PairedDevice<I, O> {
+    type Message = I::Message;
+
+    fn receiver(&self) -> &std::sync::mpsc::Receiver<Self::Message> {
+        self.receiver.receiver()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<I: InputDevice, O: Unpin> futures_core::Stream for PairedDevice<I, O> {
+    type Item = I::Message;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<I::Message>> {
+        self.receiver.poll_next(cx)
+    }
+}
+
+/// The future returned by the `_async` queries of [`PairedDevice`]. It doesn't time out on its
+/// own; combine it with the timeout facility of your async runtime. Dropping the future cancels
+/// the query.
+///
+/// ```
+/// # #[cfg(feature = "async")] {
+/// use launchy::emulator::{Emulator, MiniMk3};
+/// use launchy::mini_mk3::{Input, Output};
+/// use launchy::PairedDevice;
+///
+/// let emulator = Emulator::new(MiniMk3::new());
+/// let mut device =
+///     PairedDevice::<Input, Output<_>>::from_connections(emulator.source(), emulator.sink())?;
+///
+/// let brightness = futures::executor::block_on(device.query_brightness_async())?;
+/// assert_eq!(brightness, 127);
+/// # }
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+#[cfg(feature = "async")]
+pub struct QueryFuture<R> {
+    state: Result<PollerReceiver<R>, Option<crate::MidiError>>,
+    cancel: Option<Box<dyn FnOnce() + Send>>,
+}
+
+#[cfg(feature = "async")]
+impl<R> std::future::Future for QueryFuture<R> {
+    type Output = Result<R, crate::MidiError>;
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        use std::task::Poll;
+
+        let result = match &mut self.state {
+            Ok(receiver) => match receiver.poll_next(cx) {
+                Poll::Ready(Some(response)) => Ok(response),
+                // The device was dropped while the query was ongoing
+                Poll::Ready(None) => Err(crate::MidiError::NoResponse),
+                Poll::Pending => return Poll::Pending,
+            },
+            Err(e) => Err(e.take().expect("QueryFuture polled after completion")),
+        };
+
+        // The waiter is already gone, no need to cancel anymore
+        self.cancel = None;
+        Poll::Ready(result)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R> Drop for QueryFuture<R> {
+    fn drop(&mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            cancel();
+        }
+    }
+}
+
+/// Queries of the devices that support device inquiries and version inquiries
+macro_rules! impl_inquiry_queries {
+    ($($device:ident),*) => { $(
+        impl<S: MidiSink> PairedDevice<crate::$device::Input, crate::$device::Output<S>> {
+            /// Request a device inquiry and wait for the response
+            pub fn query_device(
+                &mut self,
+                timeout: Duration,
+            ) -> Result<crate::$device::DeviceInquiry, crate::MidiError> {
+                self.query(
+                    |output| output.request_device_inquiry(crate::$device::DeviceIdQuery::Any),
+                    extract_device_inquiry,
+                    timeout,
+                )
+            }
+
+            /// Request a version inquiry and wait for the response
+            pub fn query_version(
+                &mut self,
+                timeout: Duration,
+            ) -> Result<crate::$device::VersionInquiry, crate::MidiError> {
+                self.query(
+                    |output| output.request_version_inquiry(),
+                    extract_version_inquiry,
+                    timeout,
+                )
+            }
+
+            /// Like [`Self::query_device`], but asynchronous
+            #[cfg(feature = "async")]
+            pub fn query_device_async(
+                &mut self,
+            ) -> QueryFuture<crate::$device::DeviceInquiry> {
+                self.query_async(
+                    |output| output.request_device_inquiry(crate::$device::DeviceIdQuery::Any),
+                    extract_device_inquiry,
+                )
+            }
+
+            /// Like [`Self::query_version`], but asynchronous
+            #[cfg(feature = "async")]
+            pub fn query_version_async(
+                &mut self,
+            ) -> QueryFuture<crate::$device::VersionInquiry> {
+                self.query_async(|output| output.request_version_inquiry(), extract_version_inquiry)
+            }
+        }
+
+        impl ExtractInquiry for crate::$device::Message {
+            fn device_inquiry(&self) -> Option<crate::$device::DeviceInquiry> {
+                match *self {
+                    Self::DeviceInquiry(ref inquiry) => Some(inquiry.clone()),
+                    _ => None,
+                }
+            }
+
+            fn version_inquiry(&self) -> Option<crate::$device::VersionInquiry> {
+                match *self {
+                    Self::VersionInquiry(ref inquiry) => Some(inquiry.clone()),
+                    _ => None,
+                }
+            }
+        }
+    )* };
+}
+
+/// Access to the inquiry responses of the device messages that have them
+trait ExtractInquiry {
+    fn device_inquiry(&self) -> Option<crate::protocols::query::DeviceInquiry>;
+    fn version_inquiry(&self) -> Option<crate::protocols::query::VersionInquiry>;
+}
+
+fn extract_device_inquiry<M: ExtractInquiry>(
+    msg: &M,
+) -> Option<crate::protocols::query::DeviceInquiry> {
+    msg.device_inquiry()
+}
+
+fn extract_version_inquiry<M: ExtractInquiry>(
+    msg: &M,
+) -> Option<crate::protocols::query::VersionInquiry> {
+    msg.version_inquiry()
+}
+
+impl_inquiry_queries!(s, mini, mk2, midi1);
+
+/// Queries of the MK3 generation devices. They respond to a device inquiry with both their
+/// application and their bootloader version, so those are queried separately; the other response
+/// is passed on like any unrelated message.
+macro_rules! impl_mk3_queries {
+    ($($device:ident),*) => { $(
+        impl<S: MidiSink> PairedDevice<crate::$device::Input, crate::$device::Output<S>> {
+            /// Request a device inquiry and wait for the application version in the response
+            pub fn query_version(
+                &mut self,
+                timeout: Duration,
+            ) -> Result<crate::$device::Version, crate::MidiError> {
+                self.query(
+                    |output| output.request_device_inquiry(crate::$device::DeviceIdQuery::Any),
+                    extract_application_version,
+                    timeout,
+                )
+            }
+
+            /// Request a device inquiry and wait for the bootloader version in the response
+            pub fn query_bootloader_version(
+                &mut self,
+                timeout: Duration,
+            ) -> Result<crate::$device::Version, crate::MidiError> {
+                self.query(
+                    |output| output.request_device_inquiry(crate::$device::DeviceIdQuery::Any),
+                    extract_bootloader_version,
+                    timeout,
+                )
+            }
+
+            /// Like [`Self::query_version`], but asynchronous
+            #[cfg(feature = "async")]
+            pub fn query_version_async(&mut self) -> QueryFuture<crate::$device::Version> {
+                self.query_async(
+                    |output| output.request_device_inquiry(crate::$device::DeviceIdQuery::Any),
+                    extract_application_version,
+                )
+            }
+
+            /// Like [`Self::query_bootloader_version`], but asynchronous
+            #[cfg(feature = "async")]
+            pub fn query_bootloader_version_async(
+                &mut self,
+            ) -> QueryFuture<crate::$device::Version> {
+                self.query_async(
+                    |output| output.request_device_inquiry(crate::$device::DeviceIdQuery::Any),
+                    extract_bootloader_version,
+                )
+            }
+        }
+
+        impl ExtractMk3 for crate::$device::Message {
+            fn application_version(&self) -> Option<crate::protocols::mk3::Version> {
+                match *self {
+                    Self::ApplicationVersion(ref version) => Some(version.clone()),
+                    _ => None,
+                }
+            }
+
+            fn bootloader_version(&self) -> Option<crate::protocols::mk3::Version> {
+                match *self {
+                    Self::BootloaderVersion(ref version) => Some(version.clone()),
+                    _ => None,
+                }
+            }
+        }
+    )* };
+}
+
+/// Access to the responses that all MK3 generation device messages have
+trait ExtractMk3 {
+    fn application_version(&self) -> Option<crate::protocols::mk3::Version>;
+    fn bootloader_version(&self) -> Option<crate::protocols::mk3::Version>;
+}
+
+fn extract_application_version<M: ExtractMk3>(msg: &M) -> Option<crate::protocols::mk3::Version> {
+    msg.application_version()
+}
+
+fn extract_bootloader_version<M: ExtractMk3>(msg: &M) -> Option<crate::protocols::mk3::Version> {
+    msg.bootloader_version()
+}
+
+impl_mk3_queries!(mini_mk3, x, pro_mk3);
+
+/// Queries of the brightness and sleep settings, which the Launchpad Pro MK3 doesn't have
+macro_rules! impl_display_settings_queries {
+    ($($device:ident),*) => { $(
+        impl<S: MidiSink> PairedDevice<crate::$device::Input, crate::$device::Output<S>> {
+            /// Request the LED brightness and wait for the response
+            pub fn query_brightness(&mut self, timeout: Duration) -> Result<u8, crate::MidiError> {
+                self.query(|output| output.request_brightness(), extract_brightness, timeout)
+            }
+
+            /// Request the sleep state and wait for the response
+            pub fn query_sleep_state(
+                &mut self,
+                timeout: Duration,
+            ) -> Result<crate::$device::SleepMode, crate::MidiError> {
+                self.query(|output| output.request_sleep_mode(), extract_sleep_state, timeout)
+            }
+
+            /// Like [`Self::query_brightness`], but asynchronous
+            #[cfg(feature = "async")]
+            pub fn query_brightness_async(&mut self) -> QueryFuture<u8> {
+                self.query_async(|output| output.request_brightness(), extract_brightness)
+            }
+
+            /// Like [`Self::query_sleep_state`], but asynchronous
+            #[cfg(feature = "async")]
+            pub fn query_sleep_state_async(&mut self) -> QueryFuture<crate::$device::SleepMode> {
+                self.query_async(|output| output.request_sleep_mode(), extract_sleep_state)
+            }
+        }
+
+        impl ExtractDisplaySettings for crate::$device::Message {
+            fn brightness(&self) -> Option<u8> {
+                match *self {
+                    Self::Brightness(brightness) => Some(brightness),
+                    _ => None,
+                }
+            }
+
+            fn sleep_state(&self) -> Option<crate::protocols::mk3::SleepMode> {
+                match *self {
+                    Self::SleepMode(sleep_mode) => Some(sleep_mode),
+                    _ => None,
+                }
+            }
+        }
+    )* };
+}
+
+/// Access to the brightness and sleep state responses
+trait ExtractDisplaySettings {
+    fn brightness(&self) -> Option<u8>;
+    fn sleep_state(&self) -> Option<crate::protocols::mk3::SleepMode>;
+}
+
+fn extract_brightness<M: ExtractDisplaySettings>(msg: &M) -> Option<u8> {
+    msg.brightness()
+}
+
+fn extract_sleep_state<M: ExtractDisplaySettings>(
+    msg: &M,
+) -> Option<crate::protocols::mk3::SleepMode> {
+    msg.sleep_state()
+}
+
+impl_display_settings_queries!(mini_mk3, x);
+
+/// The queries of the Launchpad X settings that the Launchpad Mini MK3 doesn't have
+impl<S: MidiSink> PairedDevice<crate::x::Input, crate::x::Output<S>> {
+    /// Request the current mode and wait for the response
+    pub fn query_mode(&mut self, timeout: Duration) -> Result<crate::x::Mode, crate::MidiError> {
+        self.query(|output| output.request_mode(), extract_x_mode, timeout)
+    }
+
+    /// Request the selected layout and wait for the response
+    pub fn query_layout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<crate::x::Layout, crate::MidiError> {
+        self.query(|output| output.request_layout(), extract_x_layout, timeout)
+    }
+
+    /// Request the velocity curve and wait for the response
+    pub fn query_velocity_curve(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<crate::x::VelocityCurve, crate::MidiError> {
+        self.query(
+            |output| output.request_velocity_curve(),
+            extract_x_velocity_curve,
+            timeout,
+        )
+    }
+
+    /// Request the aftertouch configuration and wait for the response
+    pub fn query_aftertouch(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<(crate::x::AftertouchMode, crate::x::AftertouchThreshold), crate::MidiError> {
+        self.query(
+            |output| output.request_aftertouch(),
+            extract_x_aftertouch,
+            timeout,
+        )
+    }
+
+    /// Like [`Self::query_mode`], but asynchronous
+    #[cfg(feature = "async")]
+    pub fn query_mode_async(&mut self) -> QueryFuture<crate::x::Mode> {
+        self.query_async(|output| output.request_mode(), extract_x_mode)
+    }
+
+    /// Like [`Self::query_layout`], but asynchronous
+    #[cfg(feature = "async")]
+    pub fn query_layout_async(&mut self) -> QueryFuture<crate::x::Layout> {
+        self.query_async(|output| output.request_layout(), extract_x_layout)
+    }
+
+    /// Like [`Self::query_velocity_curve`], but asynchronous
+    #[cfg(feature = "async")]
+    pub fn query_velocity_curve_async(&mut self) -> QueryFuture<crate::x::VelocityCurve> {
+        self.query_async(
+            |output| output.request_velocity_curve(),
+            extract_x_velocity_curve,
+        )
+    }
+
+    /// Like [`Self::query_aftertouch`], but asynchronous
+    #[cfg(feature = "async")]
+    pub fn query_aftertouch_async(
+        &mut self,
+    ) -> QueryFuture<(crate::x::AftertouchMode, crate::x::AftertouchThreshold)> {
+        self.query_async(|output| output.request_aftertouch(), extract_x_aftertouch)
+    }
+}
+
+fn extract_x_mode(msg: &crate::x::Message) -> Option<crate::x::Mode> {
+    match *msg {
+        crate::x::Message::Mode(mode) => Some(mode),
+        _ => None,
+    }
+}
+
+fn extract_x_layout(msg: &crate::x::Message) -> Option<crate::x::Layout> {
+    match *msg {
+        crate::x::Message::Layout(layout) => Some(layout),
+        _ => None,
+    }
+}
+
+fn extract_x_velocity_curve(msg: &crate::x::Message) -> Option<crate::x::VelocityCurve> {
+    match *msg {
+        crate::x::Message::VelocityCurve(curve) => Some(curve),
+        _ => None,
+    }
+}
+
+fn extract_x_aftertouch(
+    msg: &crate::x::Message,
+) -> Option<(crate::x::AftertouchMode, crate::x::AftertouchThreshold)> {
+    match *msg {
+        crate::x::Message::AftertouchConfig { mode, threshold } => Some((mode, threshold)),
+        _ => None,
+    }
+}
+
+/// The queries of the Launchpad Pro. It doesn't support version inquiries.
+impl<S: MidiSink> PairedDevice<crate::pro::Input, crate::pro::Output<S>> {
+    /// Request a device inquiry and wait for the response
+    pub fn query_device(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<crate::pro::DeviceInquiry, crate::MidiError> {
+        self.query(
+            |output| output.request_device_inquiry(crate::pro::DeviceIdQuery::Any),
+            extract_pro_device_inquiry,
+            timeout,
+        )
+    }
+
+    /// Request the current mode and layout and wait for the response
+    pub fn query_status(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<(crate::pro::Mode, crate::pro::Layout), crate::MidiError> {
+        self.query(
+            |output| output.request_status(),
+            extract_pro_status,
+            timeout,
+        )
+    }
+
+    /// Like [`Self::query_device`], but asynchronous
+    #[cfg(feature = "async")]
+    pub fn query_device_async(&mut self) -> QueryFuture<crate::pro::DeviceInquiry> {
+        self.query_async(
+            |output| output.request_device_inquiry(crate::pro::DeviceIdQuery::Any),
+            extract_pro_device_inquiry,
+        )
+    }
+
+    /// Like [`Self::query_status`], but asynchronous
+    #[cfg(feature = "async")]
+    pub fn query_status_async(&mut self) -> QueryFuture<(crate::pro::Mode, crate::pro::Layout)> {
+        self.query_async(|output| output.request_status(), extract_pro_status)
+    }
+}
+
+fn extract_pro_device_inquiry(msg: &crate::pro::Message) -> Option<crate::pro::DeviceInquiry> {
+    match *msg {
+        crate::pro::Message::DeviceInquiry(ref inquiry) => Some(inquiry.clone()),
+        _ => None,
+    }
+}
+
+fn extract_pro_status(msg: &crate::pro::Message) -> Option<(crate::pro::Mode, crate::pro::Layout)> {
+    match *msg {
+        crate::pro::Message::Status { mode, layout } => Some((mode, layout)),
+        _ => None,
+    }
+}
+
+/// The queries of the Launchpad Pro MK3 mode and layout
+impl<S: MidiSink> PairedDevice<crate::pro_mk3::Input, crate::pro_mk3::Output<S>> {
+    /// Request the current mode and wait for the response
+    pub fn query_mode(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<crate::pro_mk3::Mode, crate::MidiError> {
+        self.query(
+            |output| output.request_mode(),
+            extract_pro_mk3_mode,
+            timeout,
+        )
+    }
+
+    /// Request the selected layout and wait for the response
+    pub fn query_layout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<crate::pro_mk3::Layout, crate::MidiError> {
+        self.query(
+            |output| output.request_layout(),
+            extract_pro_mk3_layout,
+            timeout,
+        )
+    }
+
+    /// Like [`Self::query_mode`], but asynchronous
+    #[cfg(feature = "async")]
+    pub fn query_mode_async(&mut self) -> QueryFuture<crate::pro_mk3::Mode> {
+        self.query_async(|output| output.request_mode(), extract_pro_mk3_mode)
+    }
+
+    /// Like [`Self::query_layout`], but asynchronous
+    #[cfg(feature = "async")]
+    pub fn query_layout_async(&mut self) -> QueryFuture<crate::pro_mk3::Layout> {
+        self.query_async(|output| output.request_layout(), extract_pro_mk3_layout)
+    }
+}
+
+fn extract_pro_mk3_mode(msg: &crate::pro_mk3::Message) -> Option<crate::pro_mk3::Mode> {
+    match *msg {
+        crate::pro_mk3::Message::Mode(mode) => Some(mode),
+        _ => None,
+    }
+}
+
+fn extract_pro_mk3_layout(msg: &crate::pro_mk3::Message) -> Option<crate::pro_mk3::Layout> {
+    match *msg {
+        crate::pro_mk3::Message::Layout(layout) => Some(layout),
+        _ => None,
+    }
+}
+
+impl<S: MidiSink> PairedDevice<crate::mini_mk3::DawInput, crate::mini_mk3::DawOutput<S>> {
+    /// Request the current DAW mode layout and wait for the response
+    pub fn query_layout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<crate::mini_mk3::DawLayout, crate::MidiError> {
+        self.query(
+            |output| output.request_layout(),
+            extract_mini_mk3_daw_layout,
+            timeout,
+        )
+    }
+
+    /// Like [`Self::query_layout`], but asynchronous
+    #[cfg(feature = "async")]
+    pub fn query_layout_async(&mut self) -> QueryFuture<crate::mini_mk3::DawLayout> {
+        self.query_async(
+            |output| output.request_layout(),
+            extract_mini_mk3_daw_layout,
+        )
+    }
+}
+
+fn extract_mini_mk3_daw_layout(
+    msg: &crate::mini_mk3::DawMessage,
+) -> Option<crate::mini_mk3::DawLayout> {
+    match *msg {
+        crate::mini_mk3::DawMessage::LayoutChange { layout, .. } => Some(layout),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emulator::{Emulator, MiniMk3};
+    use crate::mini_mk3::{Input, Message, Output};
+    use crate::MsgPollingWrapper as _;
+
+    #[test]
+    fn late_response_is_passed_on() {
+        let emulator = Emulator::new(MiniMk3::new());
+        let mut device =
+            PairedDevice::<Input, Output<_>>::from_connections(emulator.source(), emulator.sink())
+                .unwrap();
+
+        // Without any time to respond, the response arrives after the deadline
+        assert!(matches!(
+            device.query_brightness(Duration::ZERO),
+            Err(crate::MidiError::NoResponse)
+        ));
+        assert!(matches!(device.try_recv(), Some(Message::Brightness(127))));
+        assert!(device.waiters.lock().unwrap().is_empty());
+
+        assert_eq!(
+            device.query_brightness(Duration::from_secs(1)).unwrap(),
+            127
+        );
+    }
+}
@@ -108,3 +108,322 @@ pub(crate) fn make_color_code_loopable(color: Color, should_loop: bool) -> u8 {
 
     ((should_loop as u8) << 6) | (color.green() << 4) | color.red()
 }
+
+/// The [`OutputDevice::coalescing_key`](crate::OutputDevice::coalescing_key) of the Launchpads
+/// with double buffering. LED messages only replace each other if their double buffering
+/// behavior matches. Rapid LED updates depend on their order and are never coalesced.
+pub(crate) fn coalescing_key(bytes: &[u8]) -> Option<u32> {
+    match *bytes {
+        [status @ (0x90 | 0xB0), key, code] if status == 0x90 || (104..=111).contains(&key) => {
+            Some(u32::from_be_bytes([0, status, key, code & 0b1100]))
+        }
+        _ => None,
+    }
+}
+
+/// The position of a pad in the rapid LED update order: first the grid row by row, then the scene
+/// launch buttons (x = 8), then the Automap/Live buttons (y = 0)
+pub(crate) fn rapid_update_index(x: u32, y: u32) -> usize {
+    match (x, y) {
+        (0..=7, 1..=8) => ((y - 1) * 8 + x) as usize,
+        (8, 1..=8) => (64 + y - 1) as usize,
+        (0..=7, 0) => (72 + x) as usize,
+        _ => panic!("({}, {}) is not a pad", x, y),
+    }
+}
+
+/// The pad at the given position in the rapid LED update order
+pub(crate) fn rapid_update_pad(index: usize) -> (u32, u32) {
+    let index = index as u32;
+    match index {
+        0..=63 => (index % 8, index / 8 + 1),
+        64..=71 => (8, index - 64 + 1),
+        72..=79 => (index - 72, 0),
+        _ => panic!("{} is out of the rapid update range", index),
+    }
+}
+
+/// Plans a flush of the given changes for the Launchpads with double buffering. Returns how many
+/// rapid LED updates should be sent first, starting at the first pad in the rapid update order.
+/// Each of them sets two pads. The changes that aren't covered are then sent one by one.
+///
+/// All messages have three bytes. If rapid updates are sent but nothing after them, an extra
+/// message is needed to leave rapid update mode, which is accounted for here.
+pub(crate) fn plan_rapid_updates(changes: &[(u32, u32, (u8, u8, u8))]) -> usize {
+    // `num_changes_from[i]`: how many changes are at position `i` or later in the rapid order
+    let mut num_changes_from = [0usize; 81];
+    for &(x, y, _) in changes {
+        num_changes_from[rapid_update_index(x, y)] += 1;
+    }
+    for i in (0..80).rev() {
+        num_changes_from[i] += num_changes_from[i + 1];
+    }
+
+    let cost = |num_rapid_updates: usize| {
+        let num_remaining = num_changes_from[num_rapid_updates * 2];
+        let exit_rapid_mode = num_rapid_updates > 0 && num_remaining == 0;
+        num_rapid_updates + num_remaining + exit_rapid_mode as usize
+    };
+    (0..=40).min_by_key(|&n| cost(n)).unwrap()
+}
+
+pub(crate) fn set_button<O: crate::OutputDevice>(
+    output: &mut O,
+    button: super::Button80,
+    color: Color,
+    dbb: DoubleBufferingBehavior,
+) -> Result<(), crate::MidiError> {
+    let light_code = make_color_code(color, dbb);
+
+    match button {
+        super::Button80::GridButton { x, y } => output.send(&[0x90, y * 16 + x, light_code]),
+        super::Button80::ControlButton { index } => output.send(&[0xB0, 104 + index, light_code]),
+    }
+}
+
+pub(crate) fn set_button_rapid<O: crate::OutputDevice>(
+    output: &mut O,
+    color1: Color,
+    dbb1: DoubleBufferingBehavior,
+    color2: Color,
+    dbb2: DoubleBufferingBehavior,
+) -> Result<(), crate::MidiError> {
+    output.send(&[
+        0x92,
+        make_color_code(color1, dbb1),
+        make_color_code(color2, dbb2),
+    ])
+}
+
+pub(crate) fn control_double_buffering<O: crate::OutputDevice>(
+    output: &mut O,
+    d: DoubleBuffering,
+) -> Result<(), crate::MidiError> {
+    let last_byte = 0b00100000
+        | ((d.copy as u8) << 4)
+        | ((d.flash as u8) << 3)
+        | ((d.edited_buffer as u8) << 2)
+        | d.displayed_buffer as u8;
+
+    output.send(&[0xB0, 0, last_byte])
+}
+
+fn convert_color((r, g, _b): (u8, u8, u8)) -> Color {
+    Color::new(r, g)
+}
+
+/// Flushes canvas changes to one of the Launchpads with double buffering, using rapid LED updates
+/// where they save messages.
+///
+/// When tear-free, the changes are written into the hidden buffer only, which is displayed at the
+/// end. Both buffers have the same content before and after the flush.
+pub(crate) fn flush<Spec: crate::DeviceSpec, T: crate::MidiTransport>(
+    canvas: &mut crate::DeviceCanvas<Spec, T>,
+    changes: &[(u32, u32, (u8, u8, u8))],
+) -> Result<(), crate::MidiError> {
+    let tear_free = canvas.tear_free();
+    let dbb = match tear_free {
+        true => DoubleBufferingBehavior::None,
+        false => DoubleBufferingBehavior::Copy,
+    };
+    if tear_free {
+        let start = DoubleBuffering {
+            copy: false,
+            flash: false,
+            edited_buffer: Buffer::B,
+            displayed_buffer: Buffer::A,
+        };
+        control_double_buffering(&mut canvas.output, start)?;
+    }
+
+    // Rapid-update mode sets 2 LEDs per message, but always starts at the first pad. So it's
+    // worth rewriting the beginning of the canvas when enough of it changed. Not while pads
+    // flash though, because rewriting them would stop the flashing
+    let num_rapid_updates = match canvas.flush_strategy() {
+        crate::FlushStrategy::Minimal if !canvas.has_native_effects() => {
+            plan_rapid_updates(changes)
+        }
+        _ => 0,
+    };
+    for i in 0..num_rapid_updates {
+        let (x1, y1) = rapid_update_pad(i * 2);
+        let (x2, y2) = rapid_update_pad(i * 2 + 1);
+        let color1 = convert_color(canvas.target_color(x1, y1).unwrap());
+        let color2 = convert_color(canvas.target_color(x2, y2).unwrap());
+        set_button_rapid(&mut canvas.output, color1, dbb, color2, dbb)?;
+    }
+
+    let mut in_rapid_update_mode = num_rapid_updates > 0;
+    for &(x, y, color) in changes {
+        if rapid_update_index(x, y) < num_rapid_updates * 2 {
+            continue;
+        }
+        let button = super::Button80::from_abs(x as u8, y as u8);
+        set_button(&mut canvas.output, button, convert_color(color), dbb)?;
+        in_rapid_update_mode = false;
+    }
+
+    if tear_free {
+        // Display the written buffer, and copy it into the other one. This also ends the rapid
+        // update mode
+        let end = DoubleBuffering {
+            copy: true,
+            flash: false,
+            edited_buffer: Buffer::A,
+            displayed_buffer: Buffer::B,
+        };
+        control_double_buffering(&mut canvas.output, end)?;
+    } else if in_rapid_update_mode {
+        // dummy-light some button just to get out of the rapid update mode
+        let color = convert_color(canvas.target_color(0, 0).unwrap());
+        let button = super::Button80::ControlButton { index: 0 };
+        set_button(
+            &mut canvas.output,
+            button,
+            color,
+            DoubleBufferingBehavior::Copy,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Flashing is done with the double buffering of the device, which isn't available while flushing
+/// tear-free
+pub(crate) fn supports_effect<Spec: crate::DeviceSpec, T: crate::MidiTransport>(
+    canvas: &crate::DeviceCanvas<Spec, T>,
+    effect: crate::PadEffect,
+) -> bool {
+    matches!(effect, crate::PadEffect::Flash { .. }) && !canvas.tear_free()
+}
+
+/// Sends the native effects of one of the Launchpads with double buffering, which can only flash.
+///
+/// Buffer A gets the pad color and buffer B the other color, and the device flips between them.
+/// All other pads are written to both buffers, so they don't flash. The flashing stays enabled
+/// after the last flashing pad is gone, which is invisible for that reason.
+pub(crate) fn flush_effects<Spec: crate::DeviceSpec, T: crate::MidiTransport>(
+    canvas: &mut crate::DeviceCanvas<Spec, T>,
+    changes: &[crate::EffectChange],
+) -> Result<(), crate::MidiError> {
+    for &(x, y, color, _) in changes {
+        let button = super::Button80::from_abs(x as u8, y as u8);
+        let color = convert_color(color);
+        set_button(
+            &mut canvas.output,
+            button,
+            color,
+            DoubleBufferingBehavior::Copy,
+        )?;
+    }
+    let edit_other_color = DoubleBuffering {
+        copy: false,
+        flash: false,
+        edited_buffer: Buffer::B,
+        displayed_buffer: Buffer::A,
+    };
+    control_double_buffering(&mut canvas.output, edit_other_color)?;
+    for &(x, y, _, effect) in changes {
+        let crate::PadEffect::Flash { other } = effect else {
+            continue;
+        };
+        let button = super::Button80::from_abs(x as u8, y as u8);
+        let other = convert_color(other.quantize(Spec::COLOR_PRECISION as u8));
+        set_button(
+            &mut canvas.output,
+            button,
+            other,
+            DoubleBufferingBehavior::None,
+        )?;
+    }
+    let flash = DoubleBuffering {
+        copy: false,
+        flash: true,
+        edited_buffer: Buffer::A,
+        displayed_buffer: Buffer::A,
+    };
+    control_double_buffering(&mut canvas.output, flash)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Canvas as _, ChannelSink, ChannelSource, ChannelTransport, Color, Pad};
+
+    const RED: u8 = 0b000111;
+    const OFF: u8 = 0b000100;
+
+    /// The messages that a minimal flush of a Launchpad S canvas sends after `draw` lit some pads
+    /// red on a dark display
+    fn flushed_messages(draw: impl Fn(&mut dyn FnMut(u32, u32))) -> Vec<Vec<u8>> {
+        let (sink, sent) = ChannelSink::new();
+        let (source, _device) = ChannelSource::new();
+        let mut canvas = crate::DeviceCanvas::<crate::s::Spec, ChannelTransport>::from_connections(
+            source,
+            sink,
+            |_msg| {},
+        )
+        .unwrap();
+        canvas.set_flush_strategy(crate::FlushStrategy::Minimal);
+        sent.try_iter().count(); // discard setup messages
+
+        draw(&mut |x, y| {
+            canvas[Pad {
+                x: x as i32,
+                y: y as i32,
+            }] = Color::RED
+        });
+        canvas.flush().unwrap();
+        sent.try_iter().collect()
+    }
+
+    #[test]
+    fn few_changes_are_sent_per_pad() {
+        let messages = flushed_messages(|light| light(0, 1));
+        assert_eq!(messages, [[0x90, 0, RED]]);
+    }
+
+    #[test]
+    fn odd_number_of_pads_ends_with_single_pad() {
+        let messages = flushed_messages(|light| (0..3).for_each(|x| light(x, 1)));
+        assert_eq!(messages, [[0x92, RED, RED], [0x90, 2, RED]]);
+    }
+
+    #[test]
+    fn rapid_updates_rewrite_unchanged_pads() {
+        // The first 9 pads in rapid update order, except for the fifth
+        let messages = flushed_messages(|light| {
+            (0..8).filter(|&x| x != 4).for_each(|x| light(x, 1));
+            light(0, 2);
+        });
+        assert_eq!(
+            messages,
+            [
+                [0x92, RED, RED],
+                [0x92, RED, RED],
+                [0x92, OFF, RED],
+                [0x92, RED, RED],
+                [0x90, 16, RED],
+            ]
+        );
+    }
+
+    #[test]
+    fn full_display_is_sent_in_rapid_updates() {
+        let messages = flushed_messages(|light| {
+            for y in 0..9 {
+                for x in 0..9 {
+                    if (x, y) != (8, 0) {
+                        light(x, y);
+                    }
+                }
+            }
+        });
+
+        // Sending the last two pads one by one costs the same as the rapid update plus the
+        // message that leaves rapid update mode, and the fewer rapid updates win the tie
+        let mut expected = vec![[0x92, RED, RED]; 39];
+        expected.push([0xB0, 110, RED]);
+        expected.push([0xB0, 111, RED]);
+        assert_eq!(messages, expected);
+    }
+}
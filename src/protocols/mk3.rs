@@ -0,0 +1,436 @@
+//! Types and encodings shared by the Launchpads of the MK3 generation, which all speak a variant of
+//! the same protocol
+
+use super::Button80;
+
+/// The maximum value of an RGB LED
+const MAX_RGB: u8 = 127;
+
+/// A color from the Mk3 color palette. See the "Launchpad MK3 Programmers Reference Manual"
+/// to see the palette, or [see here](http://launchpaddr.com/mk3palette/).
+///
+/// Everywhere where a PaletteColor is expected as a funcion argument, you can also directly pass
+/// in the palette index and call `.into()` on it. Example:
+/// ```no_run
+/// # use launchy::mini_mk3::{PaletteColor};
+/// # let output: launchy::mini_mk3::Output = unimplemented!();
+/// // This:
+/// output.light_all(PaletteColor::new(92));
+/// // can also be written as:
+/// output.light_all(92.into());
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub struct PaletteColor {
+    pub(crate) id: u8,
+}
+
+impl PaletteColor {
+    pub fn is_valid(&self) -> bool {
+        self.id <= 127
+    }
+
+    pub fn new(id: u8) -> Self {
+        let self_ = Self { id };
+        assert!(self_.is_valid());
+        self_
+    }
+
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+    pub fn set_id(&mut self, id: u8) {
+        self.id = id
+    }
+
+    /// The approximate color that the device shows for this palette color, in 8-bit sRGB
+    ///
+    /// ```
+    /// # use launchy::mini_mk3::PaletteColor;
+    /// assert_eq!(PaletteColor::BLACK.to_rgb(), (0, 0, 0));
+    /// assert_eq!(PaletteColor::RED.to_rgb(), (255, 0, 0));
+    /// ```
+    pub fn to_rgb(&self) -> (u8, u8, u8) {
+        crate::protocols::palette::MK3_PALETTE.to_rgb(self.id)
+    }
+
+    /// Like [`Self::to_rgb`], but as a canvas color
+    pub fn to_color(&self) -> crate::Color {
+        crate::protocols::palette::MK3_PALETTE.to_color(self.id)
+    }
+
+    /// The palette color that looks the most like the given color. Use this to show colors with
+    /// palette messages, which are shorter than RGB messages, or with flashing and pulsing, which
+    /// only work with palette colors.
+    ///
+    /// ```
+    /// # use launchy::{Color, mini_mk3::PaletteColor};
+    /// assert_eq!(PaletteColor::nearest(Color::BLACK), PaletteColor::BLACK);
+    /// assert_eq!(PaletteColor::nearest(Color::new(0.0, 0.0, 1.0)), PaletteColor::BLUE);
+    /// assert_eq!(PaletteColor::nearest(PaletteColor::CYAN.to_color()), PaletteColor::CYAN);
+    /// ```
+    pub fn nearest(color: crate::Color) -> Self {
+        Self::new(crate::protocols::palette::MK3_PALETTE.nearest(color))
+    }
+}
+
+impl From<u8> for PaletteColor {
+    fn from(id: u8) -> Self {
+        Self::new(id)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+/// An RGB color. Each component may only go up to 127.
+pub struct RgbColor {
+    r: u8,
+    g: u8,
+    b: u8,
+}
+
+impl RgbColor {
+    /// Create a new RgbColor from the individual component values
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
+        let self_ = Self { r, g, b };
+        assert!(self_.is_valid());
+        self_
+    }
+
+    /// Check whether the rgb color is valid - each component may only go up to MAX_RGB.
+    pub fn is_valid(&self) -> bool {
+        self.r <= MAX_RGB && self.g <= MAX_RGB && self.b <= MAX_RGB
+    }
+
+    pub fn red(&self) -> u8 {
+        self.r
+    }
+    pub fn green(&self) -> u8 {
+        self.g
+    }
+    pub fn blue(&self) -> u8 {
+        self.b
+    }
+    pub fn set_red(&mut self, r: u8) {
+        assert!(r <= MAX_RGB);
+        self.r = r
+    }
+    pub fn set_green(&mut self, g: u8) {
+        assert!(g <= MAX_RGB);
+        self.g = g
+    }
+    pub fn set_blue(&mut self, b: u8) {
+        assert!(b <= MAX_RGB);
+        self.b = b
+    }
+}
+
+/// The button styles supported by the MK3 generation Launchpads
+///
+/// Buttons can be in one of 3 states:
+///
+/// - Plain: a constant color, using either a palette color or an RGB color.
+/// - Flashing: flashing between two colors on a 50% duty cycle. For simplicity,
+///   [ButtonStyle::flash] flashes between a given color and black, and [ButtonStyle::flash2]
+///   flashes between 2 colors. Flashing can only use palette colors.
+/// - Pulsing: pulsing between a given (palette) color and black. Pulsing looks more subdued than
+///   flashing.
+///
+/// [PaletteColor] and [RgbColor] are convertible into [ButtonStyle]
+/// using `color.into()`; this will use the plain (non-flashing) button
+/// style.
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum ButtonStyle {
+    Palette {
+        color: PaletteColor,
+    },
+    Rgb {
+        color: RgbColor,
+    },
+    Flash {
+        color1: PaletteColor,
+        color2: PaletteColor,
+    },
+    Pulse {
+        color: PaletteColor,
+    },
+}
+
+impl ButtonStyle {
+    /// Create a plain button style from a palette color
+    pub fn palette(color: PaletteColor) -> Self {
+        ButtonStyle::Palette { color }
+    }
+
+    /// Flash between a given color and black
+    pub fn flash(color: PaletteColor) -> Self {
+        Self::flash2(color, PaletteColor::BLACK)
+    }
+
+    /// Flash between a given color and black
+    pub fn flash2(color1: PaletteColor, color2: PaletteColor) -> Self {
+        ButtonStyle::Flash { color1, color2 }
+    }
+
+    /// Pulse the given color (and black)
+    pub fn pulse(color: PaletteColor) -> Self {
+        ButtonStyle::Pulse { color }
+    }
+
+    /// Create a plain button style from an RGB color
+    pub fn rgb(color: RgbColor) -> Self {
+        ButtonStyle::Rgb { color }
+    }
+
+    /// Validate that the button style's colors only use valid numbers
+    pub fn is_valid(&self) -> bool {
+        match self {
+            ButtonStyle::Palette { color } => color.is_valid(),
+            ButtonStyle::Rgb { color } => color.is_valid(),
+            ButtonStyle::Flash { color1, color2 } => color1.is_valid() && color2.is_valid(),
+            ButtonStyle::Pulse { color } => color.is_valid(),
+        }
+    }
+}
+
+impl From<PaletteColor> for ButtonStyle {
+    fn from(color: PaletteColor) -> Self {
+        ButtonStyle::Palette { color }
+    }
+}
+
+impl From<&PaletteColor> for ButtonStyle {
+    fn from(color: &PaletteColor) -> Self {
+        Self::from(*color)
+    }
+}
+
+impl From<RgbColor> for ButtonStyle {
+    fn from(color: RgbColor) -> Self {
+        ButtonStyle::Rgb { color }
+    }
+}
+
+impl From<&RgbColor> for ButtonStyle {
+    fn from(color: &RgbColor) -> Self {
+        Self::from(*color)
+    }
+}
+
+impl PaletteColor {
+    // These are some commonly used colors as palette colors. I don't have Rgb colors as constants
+    // because in the case of rgb colors you can just make your required colors yourself
+
+    // Basic colors, the top row
+    pub const BLACK: PaletteColor = Self { id: 0 };
+    pub const DARK_GRAY: PaletteColor = Self { id: 1 };
+    pub const LIGHT_GRAY: PaletteColor = Self { id: 2 };
+    pub const WHITE: PaletteColor = Self { id: 3 };
+
+    // Third column from the right
+    pub const LIGHT_RED: PaletteColor = Self { id: 4 };
+    pub const RED: PaletteColor = Self { id: 5 };
+    pub const ORANGE: PaletteColor = Self { id: 9 };
+    pub const YELLOW: PaletteColor = Self { id: 13 };
+    pub const LIME_GREEN: PaletteColor = Self { id: 17 };
+    pub const GREEN: PaletteColor = Self { id: 21 };
+    pub const SLIGHTLY_LIGHT_GREEN: PaletteColor = Self { id: 29 };
+    pub const LIGHT_BLUE: PaletteColor = Self { id: 37 };
+    pub const BLUE: PaletteColor = Self { id: 45 };
+    pub const PURPLE: PaletteColor = Self { id: 49 };
+    pub const MAGENTA: PaletteColor = Self { id: 53 };
+    pub const PINK: PaletteColor = Self { id: 57 };
+    pub const BROWN: PaletteColor = Self { id: 61 };
+
+    // This is not belonging to any of the columns/rows but included anyway cuz cyan is important
+    pub const CYAN: PaletteColor = Self { id: 90 };
+}
+
+/// The MK3 generation Launchpads can light a button in different ways
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum LightMode {
+    /// This is the standard mode. A straight consistent light
+    Plain,
+    /// A flashing motion On->Off->On->Off->...
+    Flash,
+    /// A smooth pulse
+    Pulse,
+}
+
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum SleepMode {
+    Sleep = 0,
+    Wake = 1,
+}
+
+/// A version structure
+///
+/// The version is 4 bytes from 0-9.
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub struct Version {
+    pub bytes: [u8; 4],
+}
+
+/// Unipolar faders light from the bottom up, and bipolar faders light from the centre out.
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum FaderType {
+    Unipolar,
+    Bipolar,
+}
+
+/// Whether the faders are laid out as columns or as rows
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum FaderOrientation {
+    Vertical,
+    Horizontal,
+}
+
+/// Specifies information about a fader
+pub struct Fader {
+    index: u8,
+    fader_type: FaderType,
+    cc: u8,
+    color: PaletteColor,
+}
+
+impl Fader {
+    /// Create a fader at position `index` (0-7) that reports its value as control change `cc`
+    pub fn new(index: u8, fader_type: FaderType, cc: u8, color: PaletteColor) -> Self {
+        assert!(index <= 7);
+        assert!(cc <= 127);
+
+        Self {
+            index,
+            fader_type,
+            cc,
+            color,
+        }
+    }
+
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+    pub fn fader_type(&self) -> FaderType {
+        self.fader_type
+    }
+    pub fn cc(&self) -> u8 {
+        self.cc
+    }
+    pub fn color(&self) -> PaletteColor {
+        self.color
+    }
+}
+
+/// The payload of the fader setup SysEx message, which is the same on all MK3 generation
+/// Launchpads with faders
+pub(crate) fn fader_setup_payload(orientation: FaderOrientation, faders: &[Fader]) -> Vec<u8> {
+    assert!(faders.len() <= 8);
+
+    let orientation = match orientation {
+        FaderOrientation::Vertical => 0,
+        FaderOrientation::Horizontal => 1,
+    };
+
+    let mut payload = Vec::with_capacity(2 + 4 * faders.len());
+    // Only a single bank of faders is used
+    payload.extend([0, orientation]);
+    for fader in faders {
+        let fader_type = match fader.fader_type {
+            FaderType::Unipolar => 0,
+            FaderType::Bipolar => 1,
+        };
+        payload.extend([fader.index, fader_type, fader.cc, fader.color.id()]);
+    }
+    payload
+}
+
+/// Appends the lighting spec of a single LED to a lighting SysEx message
+pub(crate) fn push_led_spec(bytes: &mut Vec<u8>, index: u8, style: &ButtonStyle) {
+    assert!(style.is_valid());
+    match style {
+        ButtonStyle::Palette { color } => bytes.extend([0, index, color.id()]),
+        ButtonStyle::Rgb { color } => {
+            bytes.extend([3, index, color.red(), color.green(), color.blue()])
+        }
+        ButtonStyle::Flash { color1, color2 } => {
+            // Order color2 and color1 such that (1=red, 2=black) looks exactly
+            // the same as a legacy flash red (as configured by `set_button`).
+            bytes.extend([1, index, color2.id(), color1.id()])
+        }
+        ButtonStyle::Pulse { color } => bytes.extend([2, index, color.id()]),
+    }
+}
+
+/// The plain button style for a quantized canvas color, according to the flush strategy. A
+/// minimal flush uses a palette spec (3 bytes) instead of an RGB spec (5 bytes) for colors that a
+/// palette entry shows exactly.
+pub(crate) fn canvas_button_style(
+    (r, g, b): (u8, u8, u8),
+    strategy: crate::FlushStrategy,
+) -> ButtonStyle {
+    let palette_color = match strategy {
+        crate::FlushStrategy::Minimal => super::palette::MK3_PALETTE.exact((r, g, b), MAX_RGB + 1),
+        crate::FlushStrategy::PerPad => None,
+    };
+    match palette_color {
+        Some(id) => ButtonStyle::palette(PaletteColor::new(id)),
+        None => ButtonStyle::rgb(RgbColor::new(r, g, b)),
+    }
+}
+
+/// The button style for a quantized canvas color with an effect. The effects only work with
+/// palette colors, so the nearest palette colors are used.
+pub(crate) fn effect_button_style(color: (u8, u8, u8), effect: crate::PadEffect) -> ButtonStyle {
+    let color = PaletteColor::nearest(super::palette::dequantize(color, MAX_RGB + 1));
+    match effect {
+        crate::PadEffect::Steady => ButtonStyle::palette(color),
+        crate::PadEffect::Flash { other } => {
+            ButtonStyle::flash2(PaletteColor::nearest(other), color)
+        }
+        crate::PadEffect::Pulse => ButtonStyle::pulse(color),
+    }
+}
+
+/// Encodes a button of the 9x9 layout of the Launchpad Mini MK3 and the Launchpad X
+pub(crate) fn encode_button(button: Button80) -> u8 {
+    match button {
+        Button80::GridButton { x, y } => {
+            assert!(x <= 8);
+            assert!(y <= 7);
+
+            10 * (8 - y) + x + 1
+        }
+        Button80::ControlButton { index } => {
+            assert!(index <= 15);
+
+            if index <= 7 {
+                index + 91
+            } else {
+                (8 - (index - 8)) * 10 + 9
+            }
+        }
+    }
+}
+
+pub(crate) fn decode_grid_button(btn: u8) -> Option<Button80> {
+    if !(11..=89).contains(&btn) || !(1..=9).contains(&(btn % 10)) {
+        return None;
+    }
+
+    let x = (btn % 10) - 1;
+    let y = 8 - (btn / 10);
+    Some(Button80::GridButton { x, y })
+}
+
+pub(crate) fn decode_control_button(btn: u8) -> Option<Button80> {
+    // The top control buttons are encoded as 91, 92, 95, 96, 97, 98, while the
+    // right-side control buttons are encoded as 89, 79, 69, 59, 49, 39, 29, 19
+    // (which fits in line with the grid button coordinates).
+    //
+    // In fact, Launchy considers the right-side control buttons as
+    // grid buttons.
+    match btn {
+        91..=98 => Some(Button80::ControlButton { index: btn - 91 }),
+        19..=89 if btn % 10 == 9 => decode_grid_button(btn),
+        _ => None,
+    }
+}
@@ -1,4 +1,6 @@
 pub(crate) mod double_buffering;
+pub(crate) mod mk3;
+pub(crate) mod palette;
 pub(crate) mod query;
 
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
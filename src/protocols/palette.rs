@@ -0,0 +1,182 @@
+//! The 128 color palettes of the Launchpads with palette colors
+
+use std::sync::OnceLock;
+
+/// A device palette, with the approximate 8-bit sRGB value of every palette color, as it appears on
+/// the device
+pub(crate) struct Palette {
+    rgb: [u32; 128],
+    /// The colors in Oklab, computed on first use
+    oklab: OnceLock<[[f32; 3]; 128]>,
+}
+
+/// The palette of the Launchpad MK2 and Pro
+#[rustfmt::skip]
+pub(crate) static MK2_PALETTE: Palette = Palette::new([
+    0x000000, 0x1C1C1C, 0x7C7C7C, 0xFCFCFC, 0xFF4E48, 0xFE0A00, 0x5A0000, 0x180002,
+    0xFFBC63, 0xFF5700, 0x5A1D00, 0x241802, 0xFDFD21, 0xFDFD00, 0x585800, 0x181800,
+    0x81FD2B, 0x40FD01, 0x165800, 0x132801, 0x35FD2B, 0x00FE00, 0x005801, 0x001800,
+    0x35FC47, 0x00FE00, 0x005801, 0x001800, 0x32FD7F, 0x00FD3A, 0x015814, 0x001C0E,
+    0x2FFCB1, 0x00FB91, 0x015732, 0x011810, 0x39BEFF, 0x00A7FF, 0x014051, 0x001018,
+    0x4186FF, 0x0050FF, 0x011A5A, 0x010619, 0x4747FF, 0x0000FE, 0x00005A, 0x000018,
+    0x8347FF, 0x5000FF, 0x160067, 0x0B0032, 0xFF48FE, 0xFF00FE, 0x5A005A, 0x180018,
+    0xFB4E83, 0xFF0753, 0x5A021B, 0x210110, 0xFF1901, 0x9A3500, 0x7A5101, 0x3E6500,
+    0x013800, 0x005432, 0x00537F, 0x0000FE, 0x01444D, 0x1A00D1, 0x7C7C7C, 0x202020,
+    0xFF0A00, 0xBAFD00, 0xAAED01, 0x56FD00, 0x008800, 0x01FC7B, 0x00A7FF, 0x021AFF,
+    0x3500FF, 0x7800FF, 0xB4177E, 0x412000, 0xFF4A01, 0x82E100, 0x66FD00, 0x00FE00,
+    0x00FE00, 0x45FD61, 0x01FBCB, 0x5086FF, 0x274DC9, 0x827AED, 0xD30CFF, 0xFF065A,
+    0xFF7D01, 0xB8B100, 0x8AFD00, 0x815D00, 0x3A2802, 0x0D4C05, 0x005037, 0x131429,
+    0x101F5A, 0x6A3C18, 0xAC0401, 0xE15136, 0xDC6900, 0xFEE100, 0x99E101, 0x60B500,
+    0x1B1C31, 0xDCFD54, 0x76FBB9, 0x9698FF, 0x8B62FF, 0x404040, 0x747474, 0xDEFCFC,
+    0xA20401, 0x340100, 0x00D201, 0x004101, 0xB8B100, 0x3C3000, 0xB45D00, 0x4C1300,
+]);
+
+/// The palette of the MK3 generation, as published in their programmer's references. Most entries
+/// match the MK2 palette, but not all.
+#[rustfmt::skip]
+pub(crate) static MK3_PALETTE: Palette = Palette::new([
+    0x000000, 0x1E1E1E, 0x7F7F7F, 0xFFFFFF, 0xFF4C4C, 0xFF0000, 0x590000, 0x190000,
+    0xFFBD6C, 0xFF5400, 0x591D00, 0x271B00, 0xFFFF4C, 0xFFFF00, 0x595900, 0x191900,
+    0x88FF4C, 0x54FF00, 0x1D5900, 0x142B00, 0x4CFF4C, 0x00FF00, 0x005900, 0x001900,
+    0x4CFF5E, 0x00FF19, 0x00590D, 0x001902, 0x4CFF88, 0x00FF55, 0x00591D, 0x001F12,
+    0x4CFFB7, 0x00FF99, 0x005935, 0x001912, 0x4CC3FF, 0x00A9FF, 0x004152, 0x001019,
+    0x4C88FF, 0x0055FF, 0x001D59, 0x000819, 0x4C4CFF, 0x0000FF, 0x000059, 0x000019,
+    0x874CFF, 0x5400FF, 0x190064, 0x0F0030, 0xFF4CFF, 0xFF00FF, 0x590059, 0x190019,
+    0xFF4C87, 0xFF0054, 0x59001D, 0x220013, 0xFF1500, 0x993500, 0x795100, 0x436400,
+    0x033900, 0x005735, 0x00547F, 0x0000FF, 0x00454F, 0x2500CC, 0x7F7F7F, 0x202020,
+    0xFF0000, 0xBDFF2D, 0xAFED06, 0x64FF09, 0x108B00, 0x00FF87, 0x00A9FF, 0x002AFF,
+    0x3F00FF, 0x7A00FF, 0xB21A7D, 0x402100, 0xFF4A00, 0x88E106, 0x72FF15, 0x00FF00,
+    0x3BFF26, 0x59FF71, 0x38FFCC, 0x5B8AFF, 0x3151C6, 0x877FE9, 0xD31DFF, 0xFF005D,
+    0xFF7F00, 0xB9B000, 0x90FF00, 0x835D07, 0x392B00, 0x144C10, 0x0D5038, 0x15152A,
+    0x16205A, 0x693C1C, 0xA8000A, 0xDE513D, 0xD86A1C, 0xFFE126, 0x9EE12F, 0x67B50F,
+    0x1E1E30, 0xDCFF6B, 0x80FFBD, 0x9A99FF, 0x8E66FF, 0x404040, 0x757575, 0xE0FFFF,
+    0xA00000, 0x350000, 0x1AD000, 0x074200, 0xB9B000, 0x3F3100, 0xB35F00, 0x4B1502,
+]);
+
+impl Palette {
+    const fn new(rgb: [u32; 128]) -> Self {
+        Self {
+            rgb,
+            oklab: OnceLock::new(),
+        }
+    }
+
+    pub(crate) fn to_rgb(&self, id: u8) -> (u8, u8, u8) {
+        let rgb = self.rgb[id as usize];
+        ((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+    }
+
+    pub(crate) fn to_color(&self, id: u8) -> crate::Color {
+        let (r, g, b) = self.to_rgb(id);
+        crate::Color::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0)
+    }
+
+    /// The palette color that looks the most like the given color. On a tie, the lowest id wins.
+    pub(crate) fn nearest(&self, color: crate::Color) -> u8 {
+        let palette = self
+            .oklab
+            .get_or_init(|| std::array::from_fn(|id| to_oklab(self.to_color(id as u8))));
+
+        let target = to_oklab(color);
+        let distance = |lab: &[f32; 3]| (0..3).map(|i| (lab[i] - target[i]).powi(2)).sum::<f32>();
+
+        let mut nearest = 0;
+        for (id, lab) in palette.iter().enumerate() {
+            if distance(lab) < distance(&palette[nearest]) {
+                nearest = id;
+            }
+        }
+        nearest as u8
+    }
+
+    /// The palette color that shows exactly the given canvas color, quantized to `range` levels
+    /// per component, if any
+    pub(crate) fn exact(&self, color: (u8, u8, u8), range: u8) -> Option<u8> {
+        (0..128).find(|&id| self.to_color(id).quantize(range) == color)
+    }
+}
+
+/// Converts a color to the Oklab color space, in which euclidean distances match perceived color
+/// differences well. The color components are taken as sRGB values, like the palette colors.
+fn to_oklab(color: crate::Color) -> [f32; 3] {
+    let crate::Color { r, g, b } = color.clamp();
+    let linear = |c: f32| match c <= 0.04045 {
+        true => c / 12.92,
+        false => ((c + 0.055) / 1.055).powf(2.4),
+    };
+    let (r, g, b) = (linear(r), linear(g), linear(b));
+
+    let l = (0.41222146 * r + 0.53633255 * g + 0.051445995 * b).cbrt();
+    let m = (0.2119035 * r + 0.6806995 * g + 0.10739696 * b).cbrt();
+    let s = (0.08830246 * r + 0.28171885 * g + 0.6299787 * b).cbrt();
+
+    [
+        0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+        1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+        0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
+    ]
+}
+
+/// The inverse of [`Color::quantize`](crate::Color::quantize), for picking palette colors for
+/// quantized canvas colors
+pub(crate) fn dequantize((r, g, b): (u8, u8, u8), range: u8) -> crate::Color {
+    let max = (range - 1) as f32;
+    crate::Color::new(r as f32 / max, g as f32 / max, b as f32 / max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Both palettes, with the number of levels per component that their devices take
+    static PALETTES: [(&Palette, u8); 2] = [(&MK2_PALETTE, 64), (&MK3_PALETTE, 128)];
+
+    /// The lowest id with the same RGB value as `id`. Some colors appear more than once
+    fn first_id_of(palette: &Palette, id: u8) -> u8 {
+        (0..=id)
+            .find(|&other| palette.rgb[other as usize] == palette.rgb[id as usize])
+            .unwrap()
+    }
+
+    #[test]
+    fn palettes_have_128_entries() {
+        assert_eq!(MK2_PALETTE.rgb.len(), 128);
+        assert_eq!(MK3_PALETTE.rgb.len(), 128);
+    }
+
+    #[test]
+    fn exact_round_trips_every_entry() {
+        for (palette, range) in PALETTES {
+            for id in 0..128 {
+                let color = palette.to_color(id).quantize(range);
+                let found = palette.exact(color, range).unwrap();
+                assert!(found <= id);
+                assert_eq!(palette.to_color(found).quantize(range), color);
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_of_entry_is_entry() {
+        for (palette, _) in PALETTES {
+            for id in 0..128 {
+                assert_eq!(
+                    palette.nearest(palette.to_color(id)),
+                    first_id_of(palette, id)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_picks_perceptually_closer_entry() {
+        // Halfway between the grays 0x404040 (117) and 0x747474 (118) in sRGB, but lightness
+        // isn't linear in sRGB, so it looks closer to the lighter one
+        let gray = crate::Color::new(
+            0x5A as f32 / 255.0,
+            0x5A as f32 / 255.0,
+            0x5A as f32 / 255.0,
+        );
+        assert_eq!(MK2_PALETTE.nearest(gray), 118);
+    }
+}
@@ -0,0 +1,309 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::midi_io::DefaultSink;
+use crate::{MidiSink, MidiTransport, OutputDevice};
+
+/// Returns a key identifying the LED that a message sets, if the message may replace a queued
+/// message with the same key. See [`OutputDevice::coalescing_key`].
+pub type CoalescingKeyFn = fn(&[u8]) -> Option<u32>;
+
+struct QueueState {
+    /// The pending messages, together with their coalescing key
+    messages: VecDeque<(Option<u32>, Vec<u8>)>,
+    /// Whether the worker is currently sending a message
+    sending: bool,
+    /// Number of messages that were replaced by a newer message for the same LED
+    coalesced: u64,
+    /// A send error that hasn't been reported yet
+    error: Option<crate::MidiError>,
+    /// Set when the [`ThrottledSink`] is dropped. The worker sends the remaining messages and exits
+    closed: bool,
+    /// The tasks waiting for all messages to be sent, see [`QueueMonitor::until_empty`]
+    #[cfg(feature = "async")]
+    wakers: Vec<std::task::Waker>,
+}
+
+struct Queue {
+    state: Mutex<QueueState>,
+    changed: Condvar,
+}
+
+impl Queue {
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap()
+    }
+}
+
+/// A [`MidiSink`] that paces the messages to a device, for devices that drop messages when they
+/// receive too many at once. This affects the older Launchpads in particular.
+///
+/// Messages are queued and sent in the background at no more than the configured number of bytes
+/// per second. While a message that sets an LED waits in the queue, a newer message for the same
+/// LED replaces it, so that only the latest color is sent. That way, an animation that produces
+/// more data than the device can take is shown at a lower frame rate instead of getting corrupted.
+///
+/// The [`QueueMonitor`] returned on construction reports how full the queue is.
+///
+/// ```
+/// use launchy::s::{Button, Color, Output};
+/// use launchy::{ChannelSink, OutputDevice as _, ThrottledSink};
+///
+/// let (sink, sent) = ChannelSink::new();
+/// let (sink, queue) = ThrottledSink::for_device::<Output<ThrottledSink<ChannelSink>>>(sink);
+/// let mut output = Output::from_connection(sink)?;
+///
+/// // The rate limit holds most of these back, so repeated writes to the same LED replace each
+/// // other in the queue
+/// for _ in 0..100 {
+///     output.light(Button::grid(0, 0), Color::RED)?;
+///     output.light(Button::grid(0, 0), Color::GREEN)?;
+/// }
+/// assert!(queue.len() <= 1);
+/// assert!(queue.coalesced() > 0);
+///
+/// // Only the latest color is guaranteed to be sent
+/// queue.wait_until_empty();
+/// let sent: Vec<_> = sent.try_iter().collect();
+/// assert_eq!(sent.last().unwrap(), &[0x90, 0, 0b110100]);
+/// # Ok::<(), launchy::MidiError>(())
+/// ```
+pub struct ThrottledSink<S: MidiSink = DefaultSink> {
+    queue: Arc<Queue>,
+    coalescing_key: CoalescingKeyFn,
+    _sink: std::marker::PhantomData<S>,
+}
+
+impl<S: MidiSink + 'static> ThrottledSink<S> {
+    /// Wrap `sink`, sending at most `bytes_per_second` bytes per second. Queued messages for which
+    /// `coalescing_key` returns the same key replace each other. Panics if `bytes_per_second` is 0.
+    pub fn new(
+        sink: S,
+        bytes_per_second: u32,
+        coalescing_key: CoalescingKeyFn,
+    ) -> (Self, QueueMonitor) {
+        assert!(bytes_per_second > 0, "bytes_per_second must not be 0");
+
+        let queue = Arc::new(Queue {
+            state: Mutex::new(QueueState {
+                messages: VecDeque::new(),
+                sending: false,
+                coalesced: 0,
+                error: None,
+                closed: false,
+                #[cfg(feature = "async")]
+                wakers: Vec::new(),
+            }),
+            changed: Condvar::new(),
+        });
+
+        let worker_queue = queue.clone();
+        std::thread::spawn(move || run_worker(sink, &worker_queue, bytes_per_second));
+
+        let throttled_sink = Self {
+            queue: queue.clone(),
+            coalescing_key,
+            _sink: std::marker::PhantomData,
+        };
+        (throttled_sink, QueueMonitor { queue })
+    }
+
+    /// Wrap `sink` with the rate limit and coalescing rules of the output device `O`
+    pub fn for_device<O: OutputDevice>(sink: S) -> (Self, QueueMonitor) {
+        Self::new(sink, O::MAX_BYTES_PER_SECOND, O::coalescing_key)
+    }
+}
+
+impl<S: MidiSink> MidiSink for ThrottledSink<S> {
+    /// Queue a message. Fails if sending one of the previously queued messages failed.
+    fn send(&mut self, bytes: &[u8]) -> Result<(), crate::MidiError> {
+        let key = (self.coalescing_key)(bytes);
+
+        let mut state = self.queue.lock();
+        if let Some(error) = state.error.take() {
+            return Err(error);
+        }
+
+        // Only coalesce with messages queued after the last message that can't be coalesced, so
+        // that the order relative to e.g. mode changes stays intact
+        if let Some(key) = key {
+            let start = state
+                .messages
+                .iter()
+                .rposition(|&(other_key, _)| other_key.is_none())
+                .map_or(0, |i| i + 1);
+            let position = state
+                .messages
+                .iter()
+                .skip(start)
+                .position(|&(other_key, _)| other_key == Some(key));
+            if let Some(position) = position {
+                state.messages.remove(start + position);
+                state.coalesced += 1;
+            }
+        }
+
+        state.messages.push_back((key, bytes.to_vec()));
+        drop(state);
+        self.queue.changed.notify_all();
+
+        Ok(())
+    }
+
+    fn queue_monitor(&self) -> Option<QueueMonitor> {
+        Some(QueueMonitor {
+            queue: self.queue.clone(),
+        })
+    }
+}
+
+impl<S: MidiSink> Drop for ThrottledSink<S> {
+    fn drop(&mut self) {
+        self.queue.lock().closed = true;
+        self.queue.changed.notify_all();
+    }
+}
+
+fn run_worker<S: MidiSink>(mut sink: S, queue: &Queue, bytes_per_second: u32) {
+    let mut next_send = Instant::now();
+
+    loop {
+        // Wait for the budget before taking the next message, so that it can still be replaced
+        // by a newer message in the meantime
+        if let Some(delay) = next_send.checked_duration_since(Instant::now()) {
+            std::thread::sleep(delay);
+        }
+
+        let bytes = {
+            let mut state = queue.lock();
+            loop {
+                if let Some((_, bytes)) = state.messages.pop_front() {
+                    state.sending = true;
+                    break bytes;
+                }
+                if state.closed {
+                    return;
+                }
+                state = queue.changed.wait(state).unwrap();
+            }
+        };
+
+        let result = sink.send(&bytes);
+        next_send = Instant::max(next_send, Instant::now())
+            + Duration::from_secs_f64(bytes.len() as f64 / bytes_per_second as f64);
+
+        let mut state = queue.lock();
+        state.sending = false;
+        if let Err(error) = result {
+            log::debug!("Sending a throttled message failed: {}", error);
+            state.error.get_or_insert(error);
+        }
+        #[cfg(feature = "async")]
+        if state.messages.is_empty() {
+            for waker in state.wakers.drain(..) {
+                waker.wake();
+            }
+        }
+        drop(state);
+        queue.changed.notify_all();
+    }
+}
+
+/// Reports the state of the queue of a [`ThrottledSink`]
+#[derive(Clone)]
+pub struct QueueMonitor {
+    queue: Arc<Queue>,
+}
+
+impl QueueMonitor {
+    /// The number of messages waiting to be sent
+    pub fn len(&self) -> usize {
+        self.queue.lock().messages.len()
+    }
+
+    /// Whether all messages were sent
+    pub fn is_empty(&self) -> bool {
+        let state = self.queue.lock();
+        state.messages.is_empty() && !state.sending
+    }
+
+    /// The number of messages that were dropped because a newer message for the same LED
+    /// replaced them
+    pub fn coalesced(&self) -> u64 {
+        self.queue.lock().coalesced
+    }
+
+    /// Block until all queued messages were sent
+    pub fn wait_until_empty(&self) {
+        let mut state = self.queue.lock();
+        while !state.messages.is_empty() || state.sending {
+            state = self.queue.changed.wait(state).unwrap();
+        }
+    }
+
+    /// Like [`Self::wait_until_empty`], but returns a future instead of blocking. Requires the
+    /// `async` feature.
+    #[cfg(feature = "async")]
+    pub fn until_empty(&self) -> UntilEmpty {
+        UntilEmpty {
+            queue: self.queue.clone(),
+        }
+    }
+
+    /// Takes the error of a failed send that wasn't reported by [`ThrottledSink::send`] yet
+    #[cfg(feature = "async")]
+    pub(crate) fn take_error(&self) -> Option<crate::MidiError> {
+        self.queue.lock().error.take()
+    }
+}
+
+/// The future returned by [`QueueMonitor::until_empty`]
+#[cfg(feature = "async")]
+pub struct UntilEmpty {
+    queue: Arc<Queue>,
+}
+
+#[cfg(feature = "async")]
+impl std::future::Future for UntilEmpty {
+    type Output = ();
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<()> {
+        let mut state = self.queue.lock();
+        if state.messages.is_empty() && !state.sending {
+            return std::task::Poll::Ready(());
+        }
+
+        if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            state.wakers.push(cx.waker().clone());
+        }
+        std::task::Poll::Pending
+    }
+}
+
+impl std::fmt::Debug for QueueMonitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueueMonitor")
+            .field("len", &self.len())
+            .field("coalesced", &self.coalesced())
+            .finish()
+    }
+}
+
+/// A [`MidiTransport`] that sends through a [`ThrottledSink`]. Use it with
+/// [`DeviceCanvas::guess_throttled`](crate::DeviceCanvas::guess_throttled) to pace canvas
+/// flushes.
+pub struct Throttled<T: MidiTransport = crate::midi_io::DefaultTransport>(
+    std::marker::PhantomData<T>,
+);
+
+impl<T: MidiTransport> MidiTransport for Throttled<T>
+where
+    T::Sink: 'static,
+{
+    type Sink = ThrottledSink<T::Sink>;
+    type Source = T::Source;
+}
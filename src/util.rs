@@ -40,3 +40,42 @@ impl<T: Default + Copy> Array2d<T> {
         self.height
     }
 }
+
+/// The set of cells of a 2d grid that were changed since the last flush. Marking a cell and
+/// clearing the set don't allocate once the set has grown to its working size.
+#[derive(Clone, Debug)]
+pub(crate) struct DirtySet {
+    is_dirty: Array2d<bool>,
+    cells: Vec<(u32, u32)>,
+}
+
+impl DirtySet {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            is_dirty: Array2d::new(width, height),
+            cells: Vec::new(),
+        }
+    }
+
+    /// Mark a cell as changed. Out of bounds coordinates are ignored
+    pub fn mark(&mut self, x: u32, y: u32) {
+        if let Some(is_dirty) = self.is_dirty.get_mut(x, y) {
+            if !*is_dirty {
+                *is_dirty = true;
+                self.cells.push((x, y));
+            }
+        }
+    }
+
+    /// The changed cells, in the order they were first marked
+    pub fn cells(&self) -> &[(u32, u32)] {
+        &self.cells
+    }
+
+    pub fn clear(&mut self) {
+        for &(x, y) in &self.cells {
+            *self.is_dirty.get_mut(x, y).unwrap() = false;
+        }
+        self.cells.clear();
+    }
+}